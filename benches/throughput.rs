@@ -0,0 +1,110 @@
+// Сквозной бенчмарк генератора нагрузки: локальный минимальный JSON-RPC сервер
+// и собранный бинарник против него без пауз между запросами.
+//
+//   cargo bench --bench throughput
+//
+// BENCH_WORKERS (256) и BENCH_DURATION (10 сек) меняют параметры прогона.
+// С BENCH_MIN_RPS бенчмарк завершается с ошибкой, если достигнутый RPS ниже этой границы
+
+use std::process::Command;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+// ID запроса из тела {"jsonrpc":"2.0","id":N,...}: ответ должен его повторить
+fn request_id(body: &[u8]) -> &[u8] {
+    let Some(start) = find(body, b"\"id\":").map(|i| i + 5) else {
+        return b"null";
+    };
+    let end = body[start..]
+        .iter()
+        .position(|&b| b == b',' || b == b'}')
+        .map_or(body.len(), |i| start + i);
+    &body[start..end]
+}
+
+fn content_length(headers: &[u8]) -> usize {
+    let text = String::from_utf8_lossy(headers).to_ascii_lowercase();
+    text.lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+// Keep-alive соединение: читает запросы подряд и отвечает {"result":1}
+async fn serve(mut stream: TcpStream) {
+    let _ = stream.set_nodelay(true);
+    let mut buf = Vec::with_capacity(4096);
+    let mut chunk = [0u8; 4096];
+    loop {
+        while let Some(header_end) = find(&buf, b"\r\n\r\n") {
+            let body_start = header_end + 4;
+            let body_end = body_start + content_length(&buf[..header_end]);
+            if buf.len() < body_end {
+                break;
+            }
+            let body = format!(
+                "{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":1}}",
+                String::from_utf8_lossy(request_id(&buf[body_start..body_end]))
+            );
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if stream.write_all(response.as_bytes()).await.is_err() {
+                return;
+            }
+            buf.drain(..body_end);
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+fn main() {
+    let workers: usize = env_or("BENCH_WORKERS", 256);
+    let duration: u64 = env_or("BENCH_DURATION", 10);
+    let min_rps: Option<f64> = std::env::var("BENCH_MIN_RPS").ok().and_then(|v| v.parse().ok());
+
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to start runtime");
+    let listener = runtime
+        .block_on(TcpListener::bind("127.0.0.1:0"))
+        .expect("Failed to bind");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    runtime.spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve(stream));
+        }
+    });
+
+    let output = Command::new(env!("CARGO_BIN_EXE_solana-rpc-stress-test"))
+        .args(["--url", &url, "--method", "getSlot", "--timeout-ms", "0", "--parser", "fast", "--quiet", "--yes"])
+        .args(["--workers", &workers.to_string(), "--duration", &duration.to_string()])
+        .output()
+        .expect("Failed to run the generator");
+    let verdict = String::from_utf8_lossy(&output.stdout);
+    let total: u64 = verdict
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("total="))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| panic!("Unexpected generator output: {}", verdict));
+    let rps = total as f64 / duration as f64;
+
+    println!("workers: {}, duration: {} s", workers, duration);
+    println!("generator: {}", verdict.trim());
+    println!("throughput: {:.0} requests/s", rps);
+    if let Some(min_rps) = min_rps.filter(|&min_rps| rps < min_rps) {
+        eprintln!("throughput below BENCH_MIN_RPS ({:.0})", min_rps);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,106 @@
+// Чтение аккаунтов по кодировке: getAccountInfo и getMultipleAccounts с base64 и base64+zstd для больших
+// аккаунтов (lookup-таблицы, рынки, большие sysvar) - отдельный режим нагрузки. base64+zstd экономит канал,
+// но нода тратит время на сжатие; по кодировкам видно, сколько весит ответ, сколько в нем данных аккаунтов
+// и сколько клиент тратит на разбор
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+const METHODS: [&str; 2] = ["getAccountInfo", "getMultipleAccounts"];
+// Кодировка по умолчанию у обоих методов
+const DEFAULT_ENCODING: &str = "base58";
+
+// Кодировка запроса из params; None - метод не читает аккаунты
+pub fn encoding(method: &str, params: &[Value]) -> Option<String> {
+    if !METHODS.contains(&method) {
+        return None;
+    }
+    let encoding = params.get(1).and_then(|options| options.get("encoding")).and_then(Value::as_str);
+    Some(encoding.unwrap_or(DEFAULT_ENCODING).to_string())
+}
+
+// Данные аккаунтов в result: длина закодированных данных и размер аккаунтов (space, если нода его отдает)
+pub fn data_sizes(result: &Value) -> (u64, u64) {
+    let accounts: Vec<&Value> = match result.get("value") {
+        Some(Value::Array(accounts)) => accounts.iter().collect(),
+        Some(account) => vec![account],
+        None => Vec::new(),
+    };
+    accounts.iter().fold((0, 0), |(data, space), account| {
+        // ["<данные>", "<кодировка>"]; jsonParsed - объект, его размер не считаем
+        let encoded = account
+            .get("data")
+            .and_then(|data| data.get(0))
+            .and_then(Value::as_str)
+            .map_or(0, |data| data.len() as u64);
+        (data + encoded, space + account.get("space").and_then(Value::as_u64).unwrap_or(0))
+    })
+}
+
+// Один успешный ответ: латентность, тело на проводе и после распаковки, данные аккаунтов, разбор на клиенте
+pub struct AccountRead {
+    pub encoding: String,
+    pub micros: u64,
+    pub wire_bytes: u64,
+    pub decoded_bytes: u64,
+    pub data_bytes: u64,
+    pub space_bytes: u64,
+    pub decode_micros: u64,
+}
+
+#[derive(Default)]
+pub struct AccountReadCounters {
+    responses: u64,
+    micros: u64,
+    wire_bytes: u64,
+    decoded_bytes: u64,
+    data_bytes: u64,
+    space_bytes: u64,
+    decode_micros: u64,
+}
+
+impl AccountReadCounters {
+    pub fn record(&mut self, read: &AccountRead) {
+        self.responses += 1;
+        self.micros += read.micros;
+        self.wire_bytes += read.wire_bytes;
+        self.decoded_bytes += read.decoded_bytes;
+        self.data_bytes += read.data_bytes;
+        self.space_bytes += read.space_bytes;
+        self.decode_micros += read.decode_micros;
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct AccountReadSummary {
+    pub encoding: String,
+    pub responses: u64,
+    pub average_ms: f64,
+    pub average_wire_bytes: f64,
+    pub average_decoded_bytes: f64,
+    pub average_data_bytes: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_account_bytes: Option<f64>, // None - нода не отдает space
+    pub average_decode_ms: f64,
+}
+
+pub fn summarize(counters: BTreeMap<String, AccountReadCounters>) -> Vec<AccountReadSummary> {
+    counters
+        .into_iter()
+        .filter(|(_, counters)| counters.responses > 0)
+        .map(|(encoding, counters)| {
+            let average = |value: u64| value as f64 / counters.responses as f64;
+            AccountReadSummary {
+                encoding,
+                responses: counters.responses,
+                average_ms: average(counters.micros) / 1000.0,
+                average_wire_bytes: average(counters.wire_bytes),
+                average_decoded_bytes: average(counters.decoded_bytes),
+                average_data_bytes: average(counters.data_bytes),
+                average_account_bytes: (counters.space_bytes > 0).then(|| average(counters.space_bytes)),
+                average_decode_ms: average(counters.decode_micros) / 1000.0,
+            }
+        })
+        .collect()
+}
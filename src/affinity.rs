@@ -0,0 +1,157 @@
+// Привязка сессии (--affinity): воркер запоминает cookie или заголовок из первого ответа, где он есть,
+// и повторяет его во всех следующих запросах. С привязкой многие провайдеры маршрутизируют иначе: запросы
+// воркера идут на одну ноду. С --affinity-compare привязку получают только четные воркеры, нечетные шлют
+// запросы без нее, и отчет сравнивает две группы
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, COOKIE, SET_COOKIE};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::aggregator::WorkerCounters;
+use crate::discovery::NodeStats;
+
+#[derive(Clone, Debug)]
+pub enum AffinitySource {
+    Cookie(String),     // Set-Cookie в ответе, Cookie в запросе
+    Header(HeaderName), // один и тот же заголовок в ответе и в запросе
+}
+
+#[derive(Clone, Debug)]
+pub struct AffinitySettings {
+    pub source: AffinitySource,
+    pub compare: bool, // половина воркеров без привязки
+}
+
+impl AffinitySettings {
+    // spec - cookie:<имя> или header:<имя>
+    pub fn new(spec: Option<String>, compare: bool) -> Result<Option<Self>, String> {
+        let Some(spec) = spec else {
+            if compare {
+                return Err("--affinity-compare needs --affinity".to_string());
+            }
+            return Ok(None);
+        };
+        let source = match spec.split_once(':') {
+            Some(("cookie", name)) if !name.is_empty() && !name.contains(['=', ';', ' ']) => {
+                AffinitySource::Cookie(name.to_string())
+            }
+            Some(("header", name)) => AffinitySource::Header(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid affinity header name '{}'", name))?,
+            ),
+            _ => return Err(format!("Invalid --affinity '{}': expected cookie:<name> or header:<name>", spec)),
+        };
+        Ok(Some(Self { source, compare }))
+    }
+
+    pub fn spec(&self) -> String {
+        match &self.source {
+            AffinitySource::Cookie(name) => format!("cookie:{}", name),
+            AffinitySource::Header(name) => format!("header:{}", name),
+        }
+    }
+
+    // Получает ли воркер привязку
+    pub fn pinned(&self, worker_id: usize) -> bool {
+        !self.compare || worker_id.is_multiple_of(2)
+    }
+}
+
+// Сессия воркера: последнее значение привязки, полученное от endpoint
+pub struct Session {
+    source: AffinitySource,
+    value: Option<HeaderValue>,
+    group: Arc<WorkerCounters<GroupStats>>,
+}
+
+impl Session {
+    pub fn new(source: AffinitySource, group: Arc<WorkerCounters<GroupStats>>) -> Self {
+        Self { source, value: None, group }
+    }
+
+    // Заголовок для очередного запроса: до первого ответа с привязкой его нет
+    pub fn request_header(&self) -> Option<(HeaderName, HeaderValue)> {
+        let value = self.value.as_ref()?;
+        match &self.source {
+            AffinitySource::Header(name) => Some((name.clone(), value.clone())),
+            AffinitySource::Cookie(name) => {
+                let cookie = format!("{}={}", name, value.to_str().ok()?);
+                Some((COOKIE, HeaderValue::from_str(&cookie).ok()?))
+            }
+        }
+    }
+
+    // Endpoint может выдать новое значение (нода сменилась): дальше повторяется оно
+    pub fn observe(&mut self, headers: &HeaderMap) {
+        let Some(value) = extract(&self.source, headers) else {
+            return;
+        };
+        match &self.value {
+            Some(current) if *current == value => return,
+            Some(_) => self.group.reassignments.fetch_add(1, Ordering::Relaxed),
+            None => self.group.pinned_sessions.fetch_add(1, Ordering::Relaxed),
+        };
+        self.value = Some(value);
+    }
+}
+
+fn extract(source: &AffinitySource, headers: &HeaderMap) -> Option<HeaderValue> {
+    match source {
+        AffinitySource::Header(name) => headers.get(name).cloned(),
+        // Set-Cookie: <имя>=<значение>; атрибуты
+        AffinitySource::Cookie(name) => headers.get_all(SET_COOKIE).iter().find_map(|header| {
+            let (cookie, value) = header.to_str().ok()?.split(';').next()?.split_once('=')?;
+            (cookie.trim() == name).then(|| HeaderValue::from_str(value.trim()).ok()).flatten()
+        }),
+    }
+}
+
+// Счетчики группы воркеров (с привязкой или без): те же, что у endpoint, плюс судьба сессий
+#[derive(Default)]
+pub struct GroupStats {
+    counters: NodeStats,
+    pinned_sessions: AtomicU64, // сессий, получивших привязку
+    reassignments: AtomicU64,   // endpoint выдал сессии другое значение
+}
+
+#[derive(Serialize, Debug)]
+pub struct GroupSummary {
+    pub pinned: bool,
+    pub requests: u64,
+    pub successful: u64,
+    pub success_rate: f64,
+    pub average_ms: f64,
+    pub max_ms: f64,
+    pub pinned_sessions: u64,
+    pub reassignments: u64,
+}
+
+impl GroupStats {
+    pub fn record_attempts(&self, count: u64) {
+        self.counters.record_attempts(count);
+    }
+
+    pub fn record_success(&self, response_time_micros: u64, count: u64) {
+        self.counters.record_success(response_time_micros, count);
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.counters.merge(other.counters);
+        *self.pinned_sessions.get_mut() += other.pinned_sessions.into_inner();
+        *self.reassignments.get_mut() += other.reassignments.into_inner();
+    }
+
+    pub fn summarize(&self, pinned: bool) -> GroupSummary {
+        let summary = self.counters.summarize("");
+        GroupSummary {
+            pinned,
+            requests: summary.requests,
+            successful: summary.successful,
+            success_rate: summary.success_rate,
+            average_ms: summary.average_ms,
+            max_ms: summary.max_ms,
+            pinned_sessions: self.pinned_sessions.load(Ordering::Relaxed),
+            reassignments: self.reassignments.load(Ordering::Relaxed),
+        }
+    }
+}
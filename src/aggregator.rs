@@ -0,0 +1,815 @@
+// Конвейер статистики: воркеры отправляют компактные события в канал, а единственная
+// задача-агрегатор владеет всеми счетчиками. На горячем пути нет мьютексов, а потоковые
+// вычисления (скользящие окна) живут в одном месте. Очередь событий ограничена (EVENT_BACKLOG):
+// если агрегатор отстает, воркеры ждут перед следующим запросом
+
+use std::collections::{btree_map, BTreeMap, VecDeque};
+use std::net::IpAddr;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, oneshot, Notify};
+
+use crate::accounts::{AccountRead, AccountReadCounters};
+use crate::affinity::GroupStats;
+use crate::apdex::MethodApdex;
+use crate::breaker::{Breaker, BreakerTrip};
+use crate::report::{utc_millis, ConnectionSummary, PeriodSummary, RequestCompressionSummary, ResponseSizeSummary, RollingSummary};
+use crate::compression::AcceptEncoding;
+use crate::discovery::NodeStats;
+use crate::incidents::{Incident, IncidentDetector};
+use crate::keys::KeyStats;
+use crate::live::{LiveStatus, LIVE_WINDOW_SECS};
+use crate::neterror::{NetworkErrorKind, NetworkErrors};
+use crate::region::AddressCounters;
+use crate::scenario::ScenarioStats;
+use crate::sidechannel::{ClientWindow, PollSample};
+
+// Длина скользящего окна пропускной способности и доли ошибок
+pub const ROLLING_WINDOW_SECS: usize = 10;
+
+// Сколько событий может ждать агрегатора; воркеры продолжают, когда очередь опустеет наполовину
+pub const EVENT_BACKLOG: usize = 64 * 1024;
+
+pub enum StatEvent {
+    Success { micros: u64, started_micros: u64 }, // started_micros - начало запроса от старта теста
+    HttpError { status: u16, reason: &'static str },
+    HttpTimeout { waited_micros: u64 },
+    ConnectTimeout,
+    JsonParseError,
+    NetworkError { kind: NetworkErrorKind },
+    SocketExhaustion, // лимит открытых файлов или эфемерных портов клиента
+    RpcError,
+    IdMismatch,
+    NearTimeout,
+    // Ответ пришел по новому соединению (new) или по переиспользованному; tls - соединение с TLS
+    Connection { new: bool, tls: bool },
+    ConnectionFailure { reused: bool }, // сетевая ошибка по переиспользованному или новому соединению
+    ConnectionClosedByPeer,
+    ConnectionReset,
+    LateResponses(u64),
+    WorkerCrash,
+    // Служебный запрос (getSlot для getLatestBlock): в статистику нагрузки не входит
+    ControlRequest { ok: bool, micros: u64 },
+    Batch,
+    BatchMissing,
+    BatchDuplicate,
+    BatchUnexpected,
+    BatchOutOfOrder,
+    Credits(u64),
+    BodySize { wire_bytes: u64, decoded_bytes: u64 },
+    DecodeTime(u64),
+    AccountRead(AccountRead), // успешный getAccountInfo или getMultipleAccounts
+    RequestBody { raw_bytes: u64, sent_bytes: u64 },
+    CompressionRejected,
+    Header(HeaderSample),
+    // IP, с которого пришел ответ, и время до его заголовков (только с --endpoint-ips)
+    RemoteAddress { ip: IpAddr, micros: u64 },
+    // Ответ служебного URL (--poll-url); агрегатор добавляет к нему клиентское окно
+    Poll(PollSample),
+    // Счетчики завершившегося воркера по endpoint, ключу, группе --affinity, методу или сценарию
+    Totals(Box<WorkerTotals>),
+    // Строка состояния --live-interval: накопленные итоги и окно LIVE_WINDOW_SECS
+    Live(oneshot::Sender<LiveStatus>),
+    // Забрать накопленное состояние (счетчики при этом обнуляются)
+    Collect(oneshot::Sender<StatsState>),
+}
+
+// Значение отслеживаемого заголовка ответа в начале интервала
+pub struct HeaderSample {
+    pub interval_start_secs: u64, // от старта прогона
+    pub interval_start_unix: Option<u64>, // только с --align-buckets
+    pub name: String,
+    pub value: String,
+}
+
+pub enum WorkerTotals {
+    Node(String, NodeStats), // по URL endpoint
+    Key(usize, KeyStats),    // по номеру ключа в пуле
+    Affinity(bool, GroupStats), // по признаку привязки сессии
+    Apdex(String, MethodApdex),
+    Scenario(String, Box<ScenarioStats>),
+}
+
+// Счетчики одного воркера: у каждого воркера свои, поэтому они не делятся между потоками.
+// Когда воркер завершается (в том числе паникой), они уходят агрегатору одним событием Totals
+pub struct WorkerCounters<T> {
+    counters: Option<T>, // None только внутри drop
+    totals: Option<Box<dyn FnOnce(T) -> WorkerTotals + Send + Sync>>,
+    events: EventSender,
+}
+
+impl<T> WorkerCounters<T> {
+    pub fn new(events: &EventSender, counters: T, totals: impl FnOnce(T) -> WorkerTotals + Send + Sync + 'static) -> Self {
+        Self {
+            counters: Some(counters),
+            totals: Some(Box::new(totals)),
+            events: events.clone(),
+        }
+    }
+}
+
+impl<T> Deref for WorkerCounters<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.counters.as_ref().expect("worker counters are taken only on drop")
+    }
+}
+
+impl<T> Drop for WorkerCounters<T> {
+    fn drop(&mut self) {
+        if let (Some(counters), Some(totals)) = (self.counters.take(), self.totals.take()) {
+            self.events.send(StatEvent::Totals(Box::new(totals(counters))));
+        }
+    }
+}
+
+// Отправитель событий с учетом очереди: send не ждет, а ready ждет, пока очередь не освободится
+#[derive(Clone)]
+pub struct EventSender {
+    sender: mpsc::UnboundedSender<StatEvent>,
+    backlog: Arc<Backlog>,
+}
+
+#[derive(Default)]
+struct Backlog {
+    pending: AtomicUsize,
+    drained: Notify,
+}
+
+impl EventSender {
+    // Агрегатор живет, пока жив хотя бы один отправитель, поэтому отправка не может не удаться
+    pub fn send(&self, event: StatEvent) {
+        self.backlog.pending.fetch_add(1, Ordering::Relaxed);
+        let _ = self.sender.send(event);
+    }
+
+    // Ждет, пока в очереди есть место; воркер вызывает его перед каждым запросом
+    pub async fn ready(&self) {
+        while self.backlog.pending.load(Ordering::Relaxed) >= EVENT_BACKLOG {
+            let drained = self.backlog.drained.notified();
+            if self.backlog.pending.load(Ordering::Relaxed) < EVENT_BACKLOG {
+                break;
+            }
+            drained.await;
+        }
+    }
+}
+
+impl Backlog {
+    fn received(&self) {
+        if self.pending.fetch_sub(1, Ordering::Relaxed) == EVENT_BACKLOG / 2 + 1 {
+            self.drained.notify_waiters();
+        }
+    }
+}
+
+// Вливает счетчики воркера в счетчики того же ключа
+fn merge<K: Ord, V>(map: &mut BTreeMap<K, V>, key: K, value: V, merge: impl FnOnce(&mut V, V)) {
+    match map.entry(key) {
+        btree_map::Entry::Vacant(entry) => {
+            entry.insert(value);
+        }
+        btree_map::Entry::Occupied(mut entry) => merge(entry.get_mut(), value),
+    }
+}
+
+#[derive(Default)]
+pub struct StatsState {
+    pub total_requests: u64,
+    pub successful_requests: u64,
+    pub http_errors: BTreeMap<String, u64>,
+    pub http_timeouts: u64,
+    pub connect_timeouts: u64,
+    pub json_parse_errors: u64,
+    pub network_errors: u64,
+    pub network_kinds: NetworkErrors,
+    pub socket_exhaustion_errors: u64,
+    pub rpc_errors: u64,
+    pub id_mismatches: u64,
+    pub batches: u64,
+    pub batch_missing: u64,
+    pub batch_duplicates: u64,
+    pub batch_unexpected: u64,
+    pub batch_out_of_order: u64,
+    pub response_times: Vec<u64>, // микросекунды
+    pub response_starts: Vec<u64>, // начало каждого запроса из response_times, микросекунды от старта
+    pub decode_times: Vec<u64>, // микросекунды распаковки и разбора JSON
+    pub timeout_waits: Vec<u64>, // микросекунды до таймаута
+    pub header_samples: Vec<HeaderSample>,
+    pub polls: Vec<PollSample>, // только с --poll-url
+    pub remote_addresses: BTreeMap<IpAddr, AddressCounters>,
+    pub credits_used: u64,
+    pub bodies: u64,
+    pub wire_bytes: u64, // тела ответов как пришли (возможно, сжатые)
+    pub decoded_bytes: u64, // после распаковки
+    pub account_reads: BTreeMap<String, AccountReadCounters>, // по кодировке запроса
+    pub compressed_requests: u64,
+    pub request_raw_bytes: u64, // тела запросов до сжатия
+    pub request_sent_bytes: u64, // после сжатия
+    pub compression_rejections: u64,
+    pub near_timeout_successes: u64,
+    pub connections: ConnectionSummary,
+    pub late_responses: u64, // успешные ответы после объявленного дедлайна
+    pub worker_crashes: u64,
+    pub control_requests: u64,
+    pub control_errors: u64,
+    pub control_micros: u64, // суммарное время служебных запросов
+    pub rolling: Option<RollingSummary>, // None - прогон короче одного окна
+    pub periods: Vec<PeriodSummary>, // только с --summary-window
+    pub breaker_trips: Vec<BreakerTrip>,
+    pub incidents: Vec<Incident>,
+    pub nodes: BTreeMap<String, NodeStats>, // по URL endpoint
+    pub api_keys: BTreeMap<usize, KeyStats>, // по номеру ключа в пуле
+    pub affinity: BTreeMap<bool, GroupStats>, // по признаку привязки сессии
+    pub apdex: BTreeMap<String, MethodApdex>,
+    pub scenarios: BTreeMap<String, ScenarioStats>,
+}
+
+impl StatsState {
+    pub fn response_size_summary(&self, accept_encoding: AcceptEncoding) -> ResponseSizeSummary {
+        let per_response = |bytes: u64| {
+            if self.bodies > 0 {
+                bytes as f64 / self.bodies as f64
+            } else {
+                0.0
+            }
+        };
+        ResponseSizeSummary {
+            accept_encoding,
+            responses: self.bodies,
+            wire_bytes: self.wire_bytes,
+            decoded_bytes: self.decoded_bytes,
+            average_wire_bytes: per_response(self.wire_bytes),
+            average_decoded_bytes: per_response(self.decoded_bytes),
+            compression_ratio: if self.wire_bytes > 0 {
+                self.decoded_bytes as f64 / self.wire_bytes as f64
+            } else {
+                0.0
+            },
+        }
+    }
+
+    pub fn request_compression_summary(&self) -> RequestCompressionSummary {
+        RequestCompressionSummary {
+            requests: self.compressed_requests,
+            raw_bytes: self.request_raw_bytes,
+            sent_bytes: self.request_sent_bytes,
+            compression_ratio: if self.request_sent_bytes > 0 {
+                self.request_raw_bytes as f64 / self.request_sent_bytes as f64
+            } else {
+                0.0
+            },
+            rejected: self.compression_rejections,
+        }
+    }
+}
+
+// Запросы и ошибки за одну секунду (по времени приема события агрегатором)
+#[derive(Default, Clone, Copy)]
+struct SecondBucket {
+    requests: u64,
+    errors: u64,
+}
+
+// Часы корзин статистики: секунды от начала отсчета. Без --align-buckets начало отсчета - старт прогона,
+// с ним - полночь UTC дня старта: границы секунд, периодов и интервалов заголовков совпадают с настенными
+// часами, и ряды прогона ложатся на ряды Prometheus сервера. Первая корзина тогда начинается до старта.
+// Старт прогона по UTC запоминается всегда: по нему время событий переводится в абсолютное
+#[derive(Clone, Copy, Debug)]
+pub struct BucketClock {
+    started_at: Instant,
+    started_unix_micros: u64,
+    offset_millis: u64, // от начала отсчета до старта
+    origin_unix: Option<u64>, // None - без выравнивания
+}
+
+impl BucketClock {
+    pub fn new(started_at: Instant, aligned: bool) -> Self {
+        const DAY_MILLIS: u64 = 86_400_000;
+        let started_unix_micros = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_micros() as u64);
+        let unix_millis = started_unix_micros / 1000;
+        match aligned {
+            true => Self {
+                started_at,
+                started_unix_micros,
+                offset_millis: unix_millis % DAY_MILLIS,
+                origin_unix: Some(unix_millis / DAY_MILLIS * 86_400),
+            },
+            false => Self {
+                started_at,
+                started_unix_micros,
+                offset_millis: 0,
+                origin_unix: None,
+            },
+        }
+    }
+
+    // Микросекунды от старта прогона -> Unix время в микросекундах
+    pub fn unix_micros(&self, run_micros: u64) -> u64 {
+        self.started_unix_micros + run_micros
+    }
+
+    // Секунды от старта прогона -> время UTC с миллисекундами (2026-10-15T10:37:00.125Z)
+    pub fn utc(&self, run_secs: f64) -> String {
+        utc_millis(self.unix_micros((run_secs.max(0.0) * 1_000_000.0) as u64))
+    }
+
+    // Текущий момент по UTC
+    pub fn utc_now(&self) -> String {
+        self.utc(self.elapsed_secs())
+    }
+
+    pub fn elapsed_secs(&self) -> f64 {
+        self.started_at.elapsed().as_secs_f64()
+    }
+
+    pub fn now_secs(&self) -> u64 {
+        (self.offset_millis + self.started_at.elapsed().as_millis() as u64) / 1000
+    }
+
+    fn exact_secs(&self) -> f64 {
+        self.offset_secs() + self.started_at.elapsed().as_secs_f64()
+    }
+
+    // Старт прогона по часам корзин
+    fn offset_secs(&self) -> f64 {
+        self.offset_millis as f64 / 1000.0
+    }
+
+    // Секунда часов корзин -> секунды от старта прогона (корзина, начатая до старта, - с 0)
+    pub fn run_secs(&self, secs: u64) -> f64 {
+        (secs as f64 - self.offset_secs()).max(0.0)
+    }
+
+    // Секунда часов корзин -> Unix время; None - без выравнивания
+    pub fn unix(&self, secs: u64) -> Option<u64> {
+        self.origin_unix.map(|origin| origin + secs)
+    }
+}
+
+// Последние len завершенных секунд и суммы запросов и ошибок по ним
+struct SecondsWindow {
+    len: usize,
+    seconds: VecDeque<SecondBucket>,
+    requests: u64,
+    errors: u64,
+}
+
+impl SecondsWindow {
+    fn new(len: usize) -> Self {
+        Self {
+            len,
+            seconds: VecDeque::new(),
+            requests: 0,
+            errors: 0,
+        }
+    }
+
+    fn push(&mut self, bucket: SecondBucket) {
+        self.seconds.push_back(bucket);
+        self.requests += bucket.requests;
+        self.errors += bucket.errors;
+        if self.seconds.len() > self.len {
+            let old = self.seconds.pop_front().unwrap_or_default();
+            self.requests -= old.requests;
+            self.errors -= old.errors;
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.seconds.len() == self.len
+    }
+
+    fn clear(&mut self) {
+        self.seconds.clear();
+        self.requests = 0;
+        self.errors = 0;
+    }
+
+    // Пропускная способность и доля ошибок по закрытым секундам окна (их может быть меньше длины окна)
+    fn client_window(&self) -> Option<ClientWindow> {
+        (!self.seconds.is_empty()).then(|| ClientWindow {
+            window_secs: self.seconds.len() as u64,
+            rps: self.requests as f64 / self.seconds.len() as f64,
+            error_rate_percent: if self.requests > 0 { self.errors as f64 / self.requests as f64 * 100.0 } else { 0.0 },
+        })
+    }
+}
+
+// Скользящее окно из последних ROLLING_WINDOW_SECS завершенных секунд; рядом - окно строки состояния
+struct RollingWindow {
+    clock: BucketClock,
+    second: u64,
+    current: SecondBucket,
+    window: SecondsWindow,
+    live: SecondsWindow, // LIVE_WINDOW_SECS для --live-interval; предохранитель его не сбрасывает
+    peak_rps: f64,
+    lowest_rps: Option<f64>,
+    worst_error_rate_percent: f64,
+    breaker: Option<Breaker>,
+    trips: Vec<BreakerTrip>,
+    periods: Option<Periods>,
+    incidents: IncidentDetector,
+}
+
+// Итоги по последовательным периодам прогона (--summary-window): запросы и ошибки берутся из закрытых секунд
+// скользящего окна, латентность - по времени приема успешного ответа
+struct Periods {
+    clock: BucketClock,
+    period_secs: u64,
+    closed: Vec<PeriodSummary>,
+    current: PeriodBucket,
+}
+
+#[derive(Default)]
+struct PeriodBucket {
+    index: u64,
+    requests: u64,
+    errors: u64,
+    latencies: Vec<u64>, // микросекунды успешных ответов
+}
+
+impl PeriodBucket {
+    // start_secs и end_secs - по часам корзин; итог - от старта прогона и только та часть периода, что шел прогон
+    fn summarize(&mut self, clock: &BucketClock, start_secs: u64, end_secs: f64) -> PeriodSummary {
+        self.latencies.sort_unstable();
+        let p95_index = ((self.latencies.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+        let run_start = clock.run_secs(start_secs);
+        let length_secs = end_secs - (start_secs as f64).max(clock.offset_secs());
+        PeriodSummary {
+            start_secs: run_start as u64,
+            start_utc: clock.utc(run_start),
+            start_unix: clock.unix(start_secs),
+            length_secs,
+            requests: self.requests,
+            rps: self.requests as f64 / length_secs.max(0.001),
+            error_rate_percent: if self.requests > 0 {
+                self.errors as f64 / self.requests as f64 * 100.0
+            } else {
+                0.0
+            },
+            p95_ms: self.latencies.get(p95_index).map(|&micros| micros as f64 / 1000.0),
+        }
+    }
+}
+
+impl Periods {
+    fn new(period_secs: u64, clock: BucketClock) -> Self {
+        let period_secs = period_secs.max(1);
+        Self {
+            clock,
+            period_secs,
+            closed: Vec::new(),
+            current: PeriodBucket {
+                index: clock.now_secs() / period_secs,
+                ..PeriodBucket::default()
+            },
+        }
+    }
+
+    // Закрывает периоды, закончившиеся до секунды second (пустые тоже попадают в отчет)
+    fn roll(&mut self, second: u64) {
+        while self.current.index < second / self.period_secs {
+            let start_secs = self.current.index * self.period_secs;
+            let summary = self.current.summarize(&self.clock, start_secs, (start_secs + self.period_secs) as f64);
+            self.closed.push(summary);
+            self.current = PeriodBucket {
+                index: self.current.index + 1,
+                ..PeriodBucket::default()
+            };
+        }
+    }
+
+    fn add_second(&mut self, second: u64, bucket: SecondBucket) {
+        self.roll(second);
+        self.current.requests += bucket.requests;
+        self.current.errors += bucket.errors;
+    }
+
+    fn add_latency(&mut self, now_secs: u64, micros: u64) {
+        self.roll(now_secs);
+        self.current.latencies.push(micros);
+    }
+
+    // Все периоды; последний незавершенный - вместе с текущей секундой и своей фактической длиной
+    fn finish(&mut self, current_second: u64, last: SecondBucket) -> Vec<PeriodSummary> {
+        self.add_second(current_second, last);
+        let start_secs = self.current.index * self.period_secs;
+        let end_secs = self.clock.exact_secs();
+        let mut periods = std::mem::take(&mut self.closed);
+        // Хвост короче секунды без запросов (тест кончился ровно на границе периода) не показываем
+        if end_secs - (start_secs as f64).max(self.clock.offset_secs()) >= 1.0 || self.current.requests > 0 {
+            periods.push(self.current.summarize(&self.clock, start_secs, end_secs));
+        }
+        periods
+    }
+}
+
+impl RollingWindow {
+    fn new(clock: BucketClock, breaker: Option<Breaker>, period_secs: Option<u64>) -> Self {
+        Self {
+            clock,
+            second: clock.now_secs(),
+            current: SecondBucket::default(),
+            window: SecondsWindow::new(ROLLING_WINDOW_SECS),
+            live: SecondsWindow::new(LIVE_WINDOW_SECS),
+            peak_rps: 0.0,
+            lowest_rps: None,
+            worst_error_rate_percent: 0.0,
+            breaker,
+            trips: Vec::new(),
+            periods: period_secs.map(|period_secs| Periods::new(period_secs, clock)),
+            incidents: IncidentDetector::new(clock),
+        }
+    }
+
+    // Закрывает секунды до now_secs (пустые секунды без событий тоже попадают в окно)
+    fn advance(&mut self, now_secs: u64) {
+        while self.second < now_secs {
+            let bucket = std::mem::take(&mut self.current);
+            if let Some(periods) = &mut self.periods {
+                periods.add_second(self.second, bucket);
+            }
+            self.incidents.close_second(self.second, bucket.requests, bucket.errors);
+            self.window.push(bucket);
+            self.live.push(bucket);
+            if self.window.is_full() {
+                let (requests, errors) = (self.window.requests, self.window.errors);
+                let rps = requests as f64 / ROLLING_WINDOW_SECS as f64;
+                self.peak_rps = self.peak_rps.max(rps);
+                self.lowest_rps = Some(self.lowest_rps.map_or(rps, |lowest| lowest.min(rps)));
+                if requests > 0 {
+                    let rate = errors as f64 / requests as f64 * 100.0;
+                    self.worst_error_rate_percent = self.worst_error_rate_percent.max(rate);
+                }
+                // Предохранитель считает время от старта прогона
+                let window_end = self.clock.run_secs(self.second + 1) as u64;
+                if let Some(trip) = self.breaker.as_ref().and_then(|b| b.check(window_end, requests, errors)) {
+                    self.trips.push(trip);
+                    // Ошибки до паузы не должны снова разомкнуть предохранитель сразу после нее
+                    self.window.clear();
+                }
+            }
+            self.second += 1;
+        }
+    }
+
+    // error - тип ошибки для инцидентов; None - успешный запрос
+    fn record(&mut self, now_secs: u64, error: Option<String>) {
+        self.advance(now_secs);
+        self.current.requests += 1;
+        if let Some(kind) = error {
+            self.current.errors += 1;
+            self.incidents.add_error(kind);
+        }
+    }
+
+    // Клиентская картина для опроса --poll-url
+    fn client_window(&self) -> Option<ClientWindow> {
+        self.window.client_window()
+    }
+
+    // То же за LIVE_WINDOW_SECS для строки состояния
+    fn live_window(&self) -> Option<ClientWindow> {
+        self.live.client_window()
+    }
+
+    fn summary(&self) -> Option<RollingSummary> {
+        self.lowest_rps.map(|lowest_rps| RollingSummary {
+            window_secs: ROLLING_WINDOW_SECS as u64,
+            peak_rps: self.peak_rps,
+            lowest_rps,
+            worst_error_rate_percent: self.worst_error_rate_percent,
+        })
+    }
+}
+
+// Запускает агрегатор; он завершается, когда закрыты все отправители.
+// period_secs - длина периода итогов --summary-window
+pub fn spawn(clock: BucketClock, breaker: Option<Breaker>, period_secs: Option<u64>) -> EventSender {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let backlog = Arc::new(Backlog::default());
+    tokio::spawn(run(receiver, backlog.clone(), clock, breaker, period_secs));
+    EventSender { sender, backlog }
+}
+
+async fn run(
+    mut receiver: mpsc::UnboundedReceiver<StatEvent>,
+    backlog: Arc<Backlog>,
+    clock: BucketClock,
+    breaker: Option<Breaker>,
+    period_secs: Option<u64>,
+) {
+    let mut state = StatsState::default();
+    let mut rolling = RollingWindow::new(clock, breaker, period_secs);
+    while let Some(event) = receiver.recv().await {
+        backlog.received();
+        let now_secs = clock.now_secs();
+        match event {
+            StatEvent::Success { micros, started_micros } => {
+                state.total_requests += 1;
+                state.successful_requests += 1;
+                state.response_times.push(micros);
+                state.response_starts.push(started_micros);
+                rolling.record(now_secs, None);
+                if let Some(periods) = &mut rolling.periods {
+                    periods.add_latency(now_secs, micros);
+                }
+            }
+            StatEvent::HttpError { status, reason } => {
+                state.total_requests += 1;
+                let kind = format!("{} {}", status, reason);
+                *state.http_errors.entry(kind.clone()).or_insert(0) += 1;
+                rolling.record(now_secs, Some(format!("HTTP {}", kind)));
+            }
+            StatEvent::HttpTimeout { waited_micros } => {
+                state.total_requests += 1;
+                state.http_timeouts += 1;
+                state.timeout_waits.push(waited_micros);
+                rolling.record(now_secs, Some("HTTP timeout".to_string()));
+            }
+            StatEvent::ConnectTimeout => {
+                state.total_requests += 1;
+                state.connect_timeouts += 1;
+                rolling.record(now_secs, Some("connect timeout".to_string()));
+            }
+            StatEvent::JsonParseError => {
+                state.total_requests += 1;
+                state.json_parse_errors += 1;
+                rolling.record(now_secs, Some("JSON parse error".to_string()));
+            }
+            StatEvent::NetworkError { kind } => {
+                state.total_requests += 1;
+                state.network_errors += 1;
+                state.network_kinds.record(kind);
+                rolling.record(now_secs, Some("network error".to_string()));
+            }
+            StatEvent::SocketExhaustion => {
+                state.total_requests += 1;
+                state.socket_exhaustion_errors += 1;
+                rolling.record(now_secs, Some("client socket limit".to_string()));
+            }
+            StatEvent::RpcError => {
+                state.total_requests += 1;
+                state.rpc_errors += 1;
+                rolling.record(now_secs, Some("RPC error".to_string()));
+            }
+            StatEvent::IdMismatch => {
+                state.total_requests += 1;
+                state.id_mismatches += 1;
+                rolling.record(now_secs, Some("ID mismatch".to_string()));
+            }
+            StatEvent::BatchMissing => {
+                state.total_requests += 1;
+                state.batch_missing += 1;
+                rolling.record(now_secs, Some("missing batch response".to_string()));
+            }
+            StatEvent::NearTimeout => state.near_timeout_successes += 1,
+            StatEvent::Connection { new, tls } => {
+                if new {
+                    state.connections.opened += 1;
+                    if tls {
+                        state.connections.tls_handshakes += 1;
+                    }
+                } else {
+                    state.connections.reused += 1;
+                }
+            }
+            StatEvent::ConnectionFailure { reused: true } => state.connections.errors_on_reused += 1,
+            StatEvent::ConnectionFailure { reused: false } => state.connections.errors_on_new += 1,
+            StatEvent::ConnectionClosedByPeer => state.connections.closed_by_peer += 1,
+            StatEvent::ConnectionReset => state.connections.resets += 1,
+            StatEvent::LateResponses(count) => state.late_responses += count,
+            StatEvent::WorkerCrash => state.worker_crashes += 1,
+            StatEvent::ControlRequest { ok, micros } => {
+                state.control_requests += 1;
+                state.control_errors += u64::from(!ok);
+                state.control_micros += micros;
+            }
+            StatEvent::Batch => state.batches += 1,
+            StatEvent::BatchDuplicate => state.batch_duplicates += 1,
+            StatEvent::BatchUnexpected => state.batch_unexpected += 1,
+            StatEvent::BatchOutOfOrder => state.batch_out_of_order += 1,
+            StatEvent::Credits(credits) => state.credits_used += credits,
+            StatEvent::BodySize { wire_bytes, decoded_bytes } => {
+                state.bodies += 1;
+                state.wire_bytes += wire_bytes;
+                state.decoded_bytes += decoded_bytes;
+            }
+            StatEvent::DecodeTime(micros) => state.decode_times.push(micros),
+            StatEvent::AccountRead(read) => state.account_reads.entry(read.encoding.clone()).or_default().record(&read),
+            StatEvent::RequestBody { raw_bytes, sent_bytes } => {
+                state.compressed_requests += 1;
+                state.request_raw_bytes += raw_bytes;
+                state.request_sent_bytes += sent_bytes;
+            }
+            StatEvent::CompressionRejected => state.compression_rejections += 1,
+            StatEvent::Header(sample) => state.header_samples.push(sample),
+            StatEvent::RemoteAddress { ip, micros } => state.remote_addresses.entry(ip).or_default().record(micros),
+            StatEvent::Poll(mut sample) => {
+                rolling.advance(now_secs);
+                sample.client = rolling.client_window();
+                state.polls.push(sample);
+            }
+            StatEvent::Totals(totals) => match *totals {
+                WorkerTotals::Node(url, counters) => merge(&mut state.nodes, url, counters, NodeStats::merge),
+                WorkerTotals::Key(index, counters) => merge(&mut state.api_keys, index, counters, KeyStats::merge),
+                WorkerTotals::Affinity(pinned, counters) => merge(&mut state.affinity, pinned, counters, GroupStats::merge),
+                WorkerTotals::Apdex(method, counters) => merge(&mut state.apdex, method, counters, MethodApdex::merge),
+                WorkerTotals::Scenario(name, counters) => merge(&mut state.scenarios, name, *counters, ScenarioStats::merge),
+            },
+            StatEvent::Live(reply) => {
+                rolling.advance(now_secs);
+                let _ = reply.send(LiveStatus {
+                    total_requests: state.total_requests,
+                    successful_requests: state.successful_requests,
+                    window: rolling.live_window(),
+                });
+            }
+            StatEvent::Collect(reply) => {
+                // Текущая секунда не завершена и в окна не попадает
+                rolling.advance(now_secs);
+                let mut collected = std::mem::take(&mut state);
+                collected.rolling = rolling.summary();
+                collected.breaker_trips = std::mem::take(&mut rolling.trips);
+                let last = rolling.current;
+                if let Some(periods) = &mut rolling.periods {
+                    collected.periods = periods.finish(now_secs, last);
+                }
+                collected.incidents = rolling.incidents.finish(now_secs, last.requests, last.errors);
+                let _ = reply.send(collected);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Часы прогона, начатого secs секунд назад
+    fn clock_started(secs: u64) -> BucketClock {
+        BucketClock::new(Instant::now() - Duration::from_secs(secs), false)
+    }
+
+    fn periods(clock: BucketClock, period_secs: u64) -> Periods {
+        Periods {
+            clock,
+            period_secs,
+            closed: Vec::new(),
+            current: PeriodBucket::default(),
+        }
+    }
+
+    fn second(requests: u64, errors: u64) -> SecondBucket {
+        SecondBucket { requests, errors }
+    }
+
+    #[test]
+    fn roll_closes_finished_periods_including_empty_ones() {
+        let mut periods = periods(clock_started(0), 10);
+        periods.add_second(0, second(10, 5));
+        periods.add_latency(0, 4000);
+        periods.add_second(35, second(1, 0));
+        let starts: Vec<u64> = periods.closed.iter().map(|period| period.start_secs).collect();
+        assert_eq!(starts, [0, 10, 20]);
+        let first = &periods.closed[0];
+        assert_eq!(first.requests, 10);
+        assert_eq!(first.rps, 1.0);
+        assert_eq!(first.error_rate_percent, 50.0);
+        assert_eq!(first.p95_ms, Some(4.0));
+        let empty = &periods.closed[1];
+        assert_eq!((empty.requests, empty.error_rate_percent, empty.p95_ms), (0, 0.0, None));
+        assert_eq!(periods.current.index, 3);
+        assert_eq!(periods.current.requests, 1);
+    }
+
+    #[test]
+    fn finish_adds_the_last_partial_period_with_its_actual_length() {
+        let mut periods = periods(clock_started(25), 10);
+        for s in 0..25 {
+            periods.add_second(s, second(10, u64::from(s < 10)));
+        }
+        let all = periods.finish(25, second(4, 0));
+        assert_eq!(all.len(), 3);
+        assert_eq!((all[0].requests, all[0].error_rate_percent), (100, 10.0));
+        assert_eq!((all[1].requests, all[1].error_rate_percent), (100, 0.0));
+        let last = &all[2];
+        assert_eq!((last.start_secs, last.requests), (20, 54));
+        assert!((last.length_secs - 5.0).abs() < 0.5, "{}", last.length_secs);
+    }
+
+    #[test]
+    fn finish_drops_an_empty_tail_shorter_than_a_second() {
+        let mut periods = periods(clock_started(20), 10);
+        for s in 0..20 {
+            periods.add_second(s, second(1, 0));
+        }
+        let all = periods.finish(20, SecondBucket::default());
+        assert_eq!(all.len(), 2);
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Пороги Apdex: ответ до satisfied_ms - "доволен", до tolerating_ms - "терпит", иначе (и при ошибке) - "недоволен"
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ApdexThresholds {
+    pub satisfied_ms: u64,
+    pub tolerating_ms: u64,
+}
+
+// Счетчики Apdex одного метода; frustrated вычисляется как attempts - satisfied - tolerating
+#[derive(Default)]
+pub struct MethodApdex {
+    attempts: AtomicU64,
+    satisfied: AtomicU64,
+    tolerating: AtomicU64,
+}
+
+impl MethodApdex {
+    pub fn record_attempts(&self, count: u64) {
+        self.attempts.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn record_success(&self, thresholds: &ApdexThresholds, response_time_micros: u64, count: u64) {
+        let ms = response_time_micros / 1000;
+        if ms <= thresholds.satisfied_ms {
+            self.satisfied.fetch_add(count, Ordering::Relaxed);
+        } else if ms <= thresholds.tolerating_ms {
+            self.tolerating.fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        *self.attempts.get_mut() += other.attempts.into_inner();
+        *self.satisfied.get_mut() += other.satisfied.into_inner();
+        *self.tolerating.get_mut() += other.tolerating.into_inner();
+    }
+
+    pub fn summarize(&self, method: &str) -> ApdexSummary {
+        let attempts = self.attempts.load(Ordering::Relaxed);
+        let satisfied = self.satisfied.load(Ordering::Relaxed);
+        let tolerating = self.tolerating.load(Ordering::Relaxed);
+        let frustrated = attempts.saturating_sub(satisfied + tolerating);
+        let score = if attempts > 0 {
+            (satisfied as f64 + tolerating as f64 / 2.0) / attempts as f64
+        } else {
+            0.0
+        };
+        ApdexSummary {
+            method: method.to_string(),
+            score,
+            satisfied,
+            tolerating,
+            frustrated,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ApdexSummary {
+    pub method: String,
+    pub score: f64,
+    pub satisfied: u64,
+    pub tolerating: u64,
+    pub frustrated: u64,
+}
@@ -0,0 +1,257 @@
+use crossbeam::queue::SegQueue;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+use crate::{spawn_supervised, worker, MethodConfig, Stats, WorkerSettings};
+
+// Минимум ответов за интервал, чтобы p95 был осмысленным
+const MIN_SAMPLES: usize = 20;
+
+// Связь воркера с автотюнером: флаг остановки и очередь времен ответов метода
+#[derive(Clone)]
+pub struct TuneHandle {
+    pub stop: Arc<AtomicBool>,
+    pub samples: Arc<SegQueue<u64>>,
+}
+
+impl TuneHandle {
+    pub fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn record(&self, response_time_micros: u64) {
+        self.samples.push(response_time_micros);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AutoTuneSettings {
+    pub target_p95_ms: f64,
+    pub interval: Duration,
+    pub max_workers: usize,
+}
+
+// Найденная конкурентность для метода
+#[derive(Serialize, Debug, Clone)]
+pub struct TuneResult {
+    pub method: String,
+    pub optimal_workers: Option<usize>,
+    pub p95_ms: Option<f64>,
+    pub target_p95_ms: f64,
+    pub steps: Vec<TuneStep>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TuneStep {
+    pub elapsed_secs: u64,
+    pub workers: usize,
+    pub p95_ms: f64,
+}
+
+// Бинарный поиск числа воркеров для одного метода: lo - максимум, при котором p95 в норме,
+// hi - минимум, при котором p95 превысил цель
+struct MethodTuner {
+    config: MethodConfig,
+    samples: Arc<SegQueue<u64>>,
+    stops: Vec<Arc<AtomicBool>>,
+    lo: Option<(usize, f64)>,
+    hi: Option<usize>,
+    steps: Vec<TuneStep>,
+}
+
+impl MethodTuner {
+    fn next_workers(&mut self, p95_ms: f64, target_p95_ms: f64, max_workers: usize) -> usize {
+        let current = self.stops.len();
+        if p95_ms <= target_p95_ms {
+            if self.lo.is_none_or(|(lo, _)| current >= lo) {
+                self.lo = Some((current, p95_ms));
+            }
+        } else {
+            self.hi = Some(self.hi.map_or(current, |hi| hi.min(current)));
+        }
+
+        let next = match (self.lo, self.hi) {
+            (Some((lo, _)), Some(hi)) => (lo + hi) / 2,
+            (Some((lo, _)), None) => lo * 2,
+            (None, Some(hi)) => hi / 2,
+            (None, None) => current,
+        };
+        next.clamp(1, max_workers.max(1))
+    }
+}
+
+fn p95_micros(samples: &mut [u64]) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let index = ((samples.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+    Some(samples[index.min(samples.len() - 1)])
+}
+
+fn spawn_worker(
+    tuner: &mut MethodTuner,
+    worker_id: usize,
+    settings: &WorkerSettings,
+    stats: &Stats,
+) -> JoinHandle<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    tuner.stops.push(stop.clone());
+    let handle = TuneHandle {
+        stop,
+        samples: tuner.samples.clone(),
+    };
+    let method_config = tuner.config.clone();
+    let worker_stats = stats.clone();
+    spawn_supervised(worker_id, settings.clone(), stats.clone(), move |settings| {
+        worker(
+            worker_id,
+            method_config.clone(),
+            settings,
+            worker_stats.clone(),
+            Some(handle.clone()),
+        )
+    })
+}
+
+// Запускает воркеры методов и каждые interval подстраивает их количество под целевой p95.
+// Возвращает хэндлы всех запущенных воркеров и найденную конкурентность
+pub async fn run(
+    methods: &[MethodConfig],
+    settings: &WorkerSettings,
+    stats: &Stats,
+    tune: AutoTuneSettings,
+) -> (Vec<JoinHandle<()>>, Vec<TuneResult>) {
+    let started = Instant::now();
+    let mut handles = Vec::new();
+    let mut next_worker_id = 0;
+    let mut tuners: Vec<MethodTuner> = Vec::new();
+
+    for method_config in methods {
+        let mut tuner = MethodTuner {
+            config: method_config.clone(),
+            samples: Arc::new(SegQueue::new()),
+            stops: Vec::new(),
+            lo: None,
+            hi: None,
+            steps: Vec::new(),
+        };
+        for _ in 0..method_config.workers.max(1) {
+            handles.push(spawn_worker(&mut tuner, next_worker_id, settings, stats));
+            next_worker_id += 1;
+        }
+        tuners.push(tuner);
+    }
+
+    loop {
+        // Не спим дольше, чем осталось до конца теста (его могут продлить, поэтому конец берется каждый раз)
+        let sleep_for = match stats.end() {
+            Some(end) => tune.interval.min(end.saturating_duration_since(Instant::now())),
+            None => tune.interval,
+        };
+        tokio::time::sleep(sleep_for).await;
+        let elapsed = started.elapsed();
+        if !stats.running() || stats.budget_exhausted() {
+            break;
+        }
+
+        for tuner in &mut tuners {
+            let mut samples = Vec::new();
+            while let Some(sample) = tuner.samples.pop() {
+                samples.push(sample);
+            }
+            if samples.len() < MIN_SAMPLES {
+                continue;
+            }
+            let p95_ms = p95_micros(&mut samples).unwrap_or(0) as f64 / 1000.0;
+            let current = tuner.stops.len();
+            tuner.steps.push(TuneStep {
+                elapsed_secs: elapsed.as_secs(),
+                workers: current,
+                p95_ms,
+            });
+
+            let next = tuner.next_workers(p95_ms, tune.target_p95_ms, tune.max_workers);
+            if settings.debug.is_some() {
+                println!(
+                    "[Auto-tune] {}: {} workers, p95 {:.2} ms -> {} workers",
+                    tuner.config.method, current, p95_ms, next
+                );
+            }
+
+            if next > current {
+                // Новые воркеры работают до общего конца теста и стартуют сразу
+                let mut added = settings.clone();
+                added.stagger_start = Duration::ZERO;
+                for _ in current..next {
+                    handles.push(spawn_worker(tuner, next_worker_id, &added, stats));
+                    next_worker_id += 1;
+                }
+            } else {
+                for stop in tuner.stops.drain(next..) {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    let results = tuners
+        .into_iter()
+        .map(|tuner| TuneResult {
+            method: tuner.config.method,
+            optimal_workers: tuner.lo.map(|(workers, _)| workers),
+            p95_ms: tuner.lo.map(|(_, p95)| p95),
+            target_p95_ms: tune.target_p95_ms,
+            steps: tuner.steps,
+        })
+        .collect();
+    (handles, results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuner(workers: usize) -> MethodTuner {
+        MethodTuner {
+            config: serde_json::from_value(serde_json::json!({ "method": "getSlot", "workers": workers })).unwrap(),
+            samples: Arc::new(SegQueue::new()),
+            stops: (0..workers).map(|_| Arc::new(AtomicBool::new(false))).collect(),
+            lo: None,
+            hi: None,
+            steps: Vec::new(),
+        }
+    }
+
+    // Как spawn_worker и остановка воркеров в run: число флагов - текущее число воркеров
+    fn set_workers(tuner: &mut MethodTuner, workers: usize) {
+        tuner.stops.resize_with(workers, || Arc::new(AtomicBool::new(false)));
+    }
+
+    #[test]
+    fn next_workers_doubles_until_p95_exceeds_the_target_then_bisects() {
+        let mut tuner = tuner(4);
+        assert_eq!(tuner.next_workers(50.0, 100.0, 64), 8);
+        set_workers(&mut tuner, 8);
+        assert_eq!(tuner.next_workers(80.0, 100.0, 64), 16);
+        set_workers(&mut tuner, 16);
+        // Превысили цель: между 8 (в норме) и 16
+        assert_eq!(tuner.next_workers(150.0, 100.0, 64), 12);
+        set_workers(&mut tuner, 12);
+        assert_eq!(tuner.next_workers(90.0, 100.0, 64), 14);
+        set_workers(&mut tuner, 14);
+        assert_eq!(tuner.next_workers(120.0, 100.0, 64), 13);
+        assert_eq!(tuner.lo.map(|(lo, _)| lo), Some(12));
+        assert_eq!(tuner.hi, Some(14));
+    }
+
+    #[test]
+    fn next_workers_halves_when_over_target_from_the_start_and_stays_in_bounds() {
+        assert_eq!(tuner(8).next_workers(500.0, 100.0, 64), 4);
+        assert_eq!(tuner(1).next_workers(500.0, 100.0, 64), 1);
+        assert_eq!(tuner(40).next_workers(10.0, 100.0, 64), 64);
+    }
+}
@@ -0,0 +1,98 @@
+// Предохранитель по доле ошибок: если в скользящем окне агрегатора ошибок слишком много,
+// все воркеры останавливаются на время остывания, а затем продолжают.
+// Решение о срабатывании принимает агрегатор, воркеры только читают момент окончания паузы
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Меньше запросов в окне - доля ошибок не показательна
+pub const MIN_WINDOW_REQUESTS: u64 = 20;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BreakerSettings {
+    pub error_rate_percent: f64,
+    pub cooldown: Duration,
+}
+
+// Срабатывание предохранителя
+#[derive(Serialize, Debug, Clone)]
+pub struct BreakerTrip {
+    pub at_secs: u64, // от старта теста
+    pub error_rate_percent: f64,
+    pub window_requests: u64,
+}
+
+#[derive(Clone)]
+pub struct Breaker {
+    pub settings: BreakerSettings,
+    open_until_micros: Arc<AtomicU64>, // от старта теста; 0 - не срабатывал
+}
+
+impl Breaker {
+    pub fn new(settings: BreakerSettings) -> Self {
+        Self {
+            settings,
+            open_until_micros: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    // Сколько еще ждать до конца паузы (None - предохранитель замкнут)
+    pub fn remaining(&self, started_at: Instant) -> Option<Duration> {
+        let open_until = self.open_until_micros.load(Ordering::Relaxed);
+        let now = started_at.elapsed().as_micros() as u64;
+        (now < open_until).then(|| Duration::from_micros(open_until - now))
+    }
+
+    // Проверяет окно из завершенных секунд; при срабатывании открывает паузу с конца окна
+    pub fn check(&self, window_end_secs: u64, requests: u64, errors: u64) -> Option<BreakerTrip> {
+        // Во время паузы приходят только ответы на запросы, отправленные до нее
+        let open_until = self.open_until_micros.load(Ordering::Relaxed);
+        if requests < MIN_WINDOW_REQUESTS || window_end_secs * 1_000_000 < open_until {
+            return None;
+        }
+        let error_rate_percent = errors as f64 / requests as f64 * 100.0;
+        if error_rate_percent < self.settings.error_rate_percent {
+            return None;
+        }
+        let open_until = Duration::from_secs(window_end_secs) + self.settings.cooldown;
+        self.open_until_micros.store(open_until.as_micros() as u64, Ordering::Relaxed);
+        Some(BreakerTrip {
+            at_secs: window_end_secs,
+            error_rate_percent,
+            window_requests: requests,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> Breaker {
+        Breaker::new(BreakerSettings {
+            error_rate_percent: 50.0,
+            cooldown: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn check_ignores_small_windows_and_low_error_rates() {
+        let breaker = breaker();
+        assert!(breaker.check(10, MIN_WINDOW_REQUESTS - 1, MIN_WINDOW_REQUESTS - 1).is_none());
+        assert!(breaker.check(10, 100, 49).is_none());
+    }
+
+    #[test]
+    fn check_trips_and_stays_open_for_the_cooldown() {
+        let breaker = breaker();
+        let trip = breaker.check(10, 100, 50).unwrap();
+        assert_eq!(trip.at_secs, 10);
+        assert_eq!(trip.error_rate_percent, 50.0);
+        assert_eq!(trip.window_requests, 100);
+        // Окна, закончившиеся во время паузы, не проверяются
+        assert!(breaker.check(39, 100, 100).is_none());
+        assert!(breaker.check(40, 100, 100).is_some());
+    }
+}
@@ -0,0 +1,319 @@
+// Встроенный каталог методов Solana JSON-RPC: используется для проверки конфига перед стартом
+
+use serde_json::{json, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamKind {
+    Pubkey,
+    Signature,
+    Text,
+    U64,
+    Array,
+    Object,
+    Any,
+}
+
+impl ParamKind {
+    fn name(self) -> &'static str {
+        match self {
+            ParamKind::Pubkey => "base58 pubkey",
+            ParamKind::Signature => "base58 signature",
+            ParamKind::Text => "string",
+            ParamKind::U64 => "unsigned integer",
+            ParamKind::Array => "array",
+            ParamKind::Object => "object",
+            ParamKind::Any => "any value",
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            ParamKind::Pubkey => value.as_str().is_some_and(|s| is_base58(s, 32, 44)),
+            ParamKind::Signature => value.as_str().is_some_and(|s| is_base58(s, 64, 88)),
+            ParamKind::Text => value.is_string(),
+            ParamKind::U64 => value.is_u64() || value.as_str().is_some_and(|s| SLOT_PLACEHOLDERS.contains(&s)),
+            ParamKind::Array => value.is_array(),
+            ParamKind::Object => value.is_object(),
+            ParamKind::Any => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub required: bool,
+}
+
+#[derive(Debug)]
+pub struct MethodSpec {
+    pub name: &'static str,
+    pub params: &'static [ParamSpec],
+}
+
+const fn req(name: &'static str, kind: ParamKind) -> ParamSpec {
+    ParamSpec { name, kind, required: true }
+}
+
+const fn opt(name: &'static str, kind: ParamKind) -> ParamSpec {
+    ParamSpec { name, kind, required: false }
+}
+
+const CONFIG: ParamSpec = opt("config", ParamKind::Object);
+
+use ParamKind::*;
+
+pub const METHODS: &[MethodSpec] = &[
+    MethodSpec { name: "getAccountInfo", params: &[req("pubkey", Pubkey), CONFIG] },
+    MethodSpec { name: "getBalance", params: &[req("pubkey", Pubkey), CONFIG] },
+    MethodSpec { name: "getBlock", params: &[req("slot", U64), CONFIG] },
+    MethodSpec { name: "getBlockCommitment", params: &[req("slot", U64)] },
+    MethodSpec { name: "getBlockHeight", params: &[CONFIG] },
+    MethodSpec { name: "getBlockProduction", params: &[CONFIG] },
+    MethodSpec { name: "getBlockTime", params: &[req("slot", U64)] },
+    MethodSpec { name: "getBlocks", params: &[req("start_slot", U64), opt("end_slot", Any), CONFIG] },
+    MethodSpec { name: "getBlocksWithLimit", params: &[req("start_slot", U64), req("limit", U64), CONFIG] },
+    MethodSpec { name: "getClusterNodes", params: &[] },
+    MethodSpec { name: "getEpochInfo", params: &[CONFIG] },
+    MethodSpec { name: "getEpochSchedule", params: &[] },
+    MethodSpec { name: "getFeeForMessage", params: &[req("message", Text), CONFIG] },
+    MethodSpec { name: "getFirstAvailableBlock", params: &[] },
+    MethodSpec { name: "getGenesisHash", params: &[] },
+    MethodSpec { name: "getHealth", params: &[] },
+    MethodSpec { name: "getHighestSnapshotSlot", params: &[] },
+    MethodSpec { name: "getIdentity", params: &[] },
+    MethodSpec { name: "getInflationGovernor", params: &[CONFIG] },
+    MethodSpec { name: "getInflationRate", params: &[] },
+    MethodSpec { name: "getInflationReward", params: &[req("addresses", Array), CONFIG] },
+    MethodSpec { name: "getLargestAccounts", params: &[CONFIG] },
+    MethodSpec { name: "getLatestBlockhash", params: &[CONFIG] },
+    MethodSpec { name: "getLeaderSchedule", params: &[opt("slot", Any), CONFIG] },
+    MethodSpec { name: "getMaxRetransmitSlot", params: &[] },
+    MethodSpec { name: "getMaxShredInsertSlot", params: &[] },
+    MethodSpec { name: "getMinimumBalanceForRentExemption", params: &[req("data_length", U64), CONFIG] },
+    MethodSpec { name: "getMultipleAccounts", params: &[req("pubkeys", Array), CONFIG] },
+    MethodSpec { name: "getProgramAccounts", params: &[req("program_id", Pubkey), CONFIG] },
+    MethodSpec { name: "getRecentPerformanceSamples", params: &[opt("limit", U64)] },
+    MethodSpec { name: "getRecentPrioritizationFees", params: &[opt("addresses", Array)] },
+    MethodSpec { name: "getSignatureStatuses", params: &[req("signatures", Array), CONFIG] },
+    MethodSpec { name: "getSignaturesForAddress", params: &[req("address", Pubkey), CONFIG] },
+    MethodSpec { name: "getSlot", params: &[CONFIG] },
+    MethodSpec { name: "getSlotLeader", params: &[CONFIG] },
+    MethodSpec { name: "getSlotLeaders", params: &[req("start_slot", U64), req("limit", U64)] },
+    MethodSpec { name: "getStakeMinimumDelegation", params: &[CONFIG] },
+    MethodSpec { name: "getSupply", params: &[CONFIG] },
+    MethodSpec { name: "getTokenAccountBalance", params: &[req("pubkey", Pubkey), CONFIG] },
+    MethodSpec { name: "getTokenAccountsByDelegate", params: &[req("delegate", Pubkey), req("filter", Object), CONFIG] },
+    MethodSpec { name: "getTokenAccountsByOwner", params: &[req("owner", Pubkey), req("filter", Object), CONFIG] },
+    MethodSpec { name: "getTokenLargestAccounts", params: &[req("mint", Pubkey), CONFIG] },
+    MethodSpec { name: "getTokenSupply", params: &[req("mint", Pubkey), CONFIG] },
+    MethodSpec { name: "getTransaction", params: &[req("signature", Signature), CONFIG] },
+    MethodSpec { name: "getTransactionCount", params: &[CONFIG] },
+    MethodSpec { name: "getVersion", params: &[] },
+    MethodSpec { name: "getVoteAccounts", params: &[CONFIG] },
+    MethodSpec { name: "isBlockhashValid", params: &[req("blockhash", Text), CONFIG] },
+    MethodSpec { name: "minimumLedgerSlot", params: &[] },
+    MethodSpec { name: "requestAirdrop", params: &[req("pubkey", Pubkey), req("lamports", U64), CONFIG] },
+    MethodSpec { name: "sendTransaction", params: &[req("transaction", Text), CONFIG] },
+    MethodSpec { name: "simulateTransaction", params: &[req("transaction", Text), CONFIG] },
+    // Синтетический метод этого инструмента: getSlot + getBlock; слот в params игнорируется
+    MethodSpec { name: "getLatestBlock", params: &[opt("slot_or_config", Any), CONFIG] },
+    // DAS API (Metaplex Digital Asset Standard): есть не у всех провайдеров, params - объект с именованными полями
+    MethodSpec { name: "getAsset", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getAssetBatch", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getAssetProof", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getAssetProofBatch", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getAssetsByAuthority", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getAssetsByCreator", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getAssetsByGroup", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getAssetsByOwner", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getSignaturesForAsset", params: &[DAS_REQUEST] },
+    MethodSpec { name: "getTokenAccounts", params: &[DAS_REQUEST] },
+    MethodSpec { name: "searchAssets", params: &[DAS_REQUEST] },
+];
+
+// В конфиге params DAS метода - массив из одного объекта ([{ id = "..." }]), в запрос уходит сам объект
+const DAS_REQUEST: ParamSpec = req("request", Object);
+
+// Метод с именованными params: JSON-RPC запрос несет объект вместо массива
+pub fn named_params(method: &str) -> bool {
+    find(method).is_some_and(|spec| matches!(spec.params, [param] if param.name == DAS_REQUEST.name))
+}
+
+// Заглушка слота в параметрах по умолчанию: перед стартом заменяется на актуальный слот
+pub const LATEST_SLOT: &str = "<latest slot>";
+// Заглушка самого старого блока в хранилище ноды (getFirstAvailableBlock), для архивных профилей
+pub const FIRST_AVAILABLE_BLOCK: &str = "<first available block>";
+
+const SLOT_PLACEHOLDERS: &[&str] = &[LATEST_SLOT, FIRST_AVAILABLE_BLOCK];
+
+// Аккаунты, существующие в любом кластере (mainnet, devnet, testnet, localnet)
+const CLOCK_SYSVAR: &str = "SysvarC1ock11111111111111111111111111111111";
+const RENT_SYSVAR: &str = "SysvarRent111111111111111111111111111111111";
+const VOTE_PROGRAM: &str = "Vote111111111111111111111111111111111111111";
+
+// Разумные params по умолчанию для методов, которые без них не работают или работают не так,
+// как обычно вызываются клиентами. None - метод вызывается без params
+pub fn default_params(method: &str) -> Option<Vec<Value>> {
+    let finalized = json!({ "commitment": "finalized" });
+    let params = match method {
+        "getAccountInfo" => vec![json!(CLOCK_SYSVAR), json!({ "commitment": "finalized", "encoding": "base64" })],
+        "getBalance" => vec![json!(CLOCK_SYSVAR), finalized],
+        "getBlock" => vec![json!(LATEST_SLOT), block_options()],
+        "getBlockCommitment" | "getBlockTime" => vec![json!(LATEST_SLOT)],
+        "getMinimumBalanceForRentExemption" => vec![json!(165)],
+        "getMultipleAccounts" => vec![
+            json!([CLOCK_SYSVAR, RENT_SYSVAR]),
+            json!({ "commitment": "finalized", "encoding": "base64" }),
+        ],
+        "getRecentPerformanceSamples" => vec![json!(5)],
+        "getSignaturesForAddress" => vec![json!(VOTE_PROGRAM), json!({ "commitment": "finalized", "limit": 10 })],
+        "getSupply" => vec![json!({ "commitment": "finalized", "excludeNonCirculatingAccountsList": true })],
+        "getBlockHeight" | "getEpochInfo" | "getLargestAccounts" | "getLatestBlockhash" | "getSlot"
+        | "getSlotLeader" | "getTransactionCount" | "getVoteAccounts" => vec![finalized],
+        _ => return None,
+    };
+    Some(params)
+}
+
+// Опции getBlock по умолчанию (используются и для getLatestBlock)
+pub fn block_options() -> Value {
+    json!({
+        "commitment": "finalized",
+        "encoding": "json",
+        "transactionDetails": "full",
+        "maxSupportedTransactionVersion": 0,
+        "rewards": false
+    })
+}
+
+// Методы, result которых без context следует за вершиной цепи (слот, высота, лидер, недавние транзакции
+// и голоса): разные тела у одного запроса ожидаемы, --hash-responses их не сравнивает
+pub fn follows_tip(method: &str) -> bool {
+    matches!(
+        method,
+        "getSlot"
+            | "getBlockHeight"
+            | "getEpochInfo"
+            | "getTransactionCount"
+            | "getSlotLeader"
+            | "getMaxRetransmitSlot"
+            | "getMaxShredInsertSlot"
+            | "getHighestSnapshotSlot"
+            | "getRecentPerformanceSamples"
+            | "getRecentPrioritizationFees"
+            | "getSignaturesForAddress"
+            | "getVoteAccounts"
+            | "getLatestBlock"
+    )
+}
+
+pub fn uses_placeholder(params: &[Value], placeholder: &str) -> bool {
+    params.iter().any(|param| param.as_str() == Some(placeholder))
+}
+
+// Подставляет слот вместо заглушки
+pub fn fill_placeholder(params: &mut [Value], placeholder: &str, slot: u64) {
+    for param in params.iter_mut().filter(|param| param.as_str() == Some(placeholder)) {
+        *param = json!(slot);
+    }
+}
+
+// Сигнатура метода для `methods list`: обязательные параметры без скобок, необязательные в []
+pub fn signature(spec: &MethodSpec) -> String {
+    let params: Vec<String> = spec
+        .params
+        .iter()
+        .map(|param| {
+            if param.required {
+                param.name.to_string()
+            } else {
+                format!("[{}]", param.name)
+            }
+        })
+        .collect();
+    format!("{}({})", spec.name, params.join(", "))
+}
+
+pub fn find(method: &str) -> Option<&'static MethodSpec> {
+    METHODS.iter().find(|spec| spec.name == method)
+}
+
+fn is_base58(s: &str, min_len: usize, max_len: usize) -> bool {
+    const ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    (min_len..=max_len).contains(&s.len()) && s.chars().all(|c| ALPHABET.contains(c))
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut cur = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+// Ближайшее по написанию имя метода (для подсказки при опечатке)
+fn suggest(method: &str) -> Option<&'static str> {
+    METHODS
+        .iter()
+        .map(|spec| (edit_distance(method, spec.name), spec.name))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+// Ищет метод в каталоге; для неизвестного имени возвращает ошибку с подсказкой
+pub fn lookup(method: &str) -> Result<&'static MethodSpec, String> {
+    find(method).ok_or_else(|| match suggest(method) {
+        Some(name) => format!("unknown method '{}' (did you mean '{}'?)", method, name),
+        None => format!("unknown method '{}'", method),
+    })
+}
+
+// Проверяет имя метода и правдоподобность params; возвращает описание проблемы
+pub fn validate(method: &str, params: &[Value]) -> Result<(), String> {
+    let spec = lookup(method)?;
+
+    if params.len() > spec.params.len() {
+        return Err(format!(
+            "{}: expected at most {} params, got {}",
+            method,
+            spec.params.len(),
+            params.len()
+        ));
+    }
+    for (index, param) in spec.params.iter().enumerate() {
+        match params.get(index) {
+            None if param.required => {
+                return Err(format!(
+                    "{}: missing required param #{} '{}' ({})",
+                    method,
+                    index + 1,
+                    param.name,
+                    param.kind.name()
+                ))
+            }
+            // null допустим на месте необязательного параметра
+            Some(value) if (param.required || !value.is_null()) && !param.kind.matches(value) => {
+                return Err(format!(
+                    "{}: param #{} '{}' should be {}, got {}",
+                    method,
+                    index + 1,
+                    param.name,
+                    param.kind.name(),
+                    value
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
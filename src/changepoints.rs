@@ -0,0 +1,122 @@
+// Смена режима латентности: ряд медиан по секундам (по времени отправки) делится бинарной сегментацией -
+// ищется точка, где разбиение на два участка сильнее всего уменьшает сумму квадратов отклонений от средних.
+// Разбиение принимается, если выигрыш больше штрафа (как в BIC: шум ряда * ln длины) и медианы участков
+// отличаются заметно; затем так же делятся оба участка. Так видно, например, когда у провайдера сработало
+// автомасштабирование или трафик ушел на другой кластер
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::aggregator::BucketClock;
+
+// Участок короче стольких секунд с ответами не выделяется
+const MIN_SEGMENT_SECS: usize = 10;
+// Выигрыш разбиения должен быть больше PENALTY * дисперсия шума * ln(длина ряда)
+const PENALTY: f64 = 4.0;
+// И медианы участков должны отличаться хотя бы на столько процентов
+const MIN_CHANGE_PERCENT: f64 = 15.0;
+const MAX_CHANGE_POINTS: usize = 8;
+
+#[derive(Serialize, Debug)]
+pub struct LatencyShift {
+    pub at_secs: u64, // от старта теста
+    pub at_utc: String,
+    pub before_ms: f64, // медиана посекундных медиан участка до точки
+    pub after_ms: f64,
+    pub change_percent: f64,
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_unstable_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+// Лучшая точка разбиения series[start..end]: индекс начала второго участка и выигрыш
+fn best_split(series: &[f64], start: usize, end: usize) -> Option<(usize, f64)> {
+    if end - start < 2 * MIN_SEGMENT_SECS {
+        return None;
+    }
+    let values = &series[start..end];
+    let total: f64 = values.iter().sum();
+    let mean = total / values.len() as f64;
+    let total_cost: f64 = values.iter().map(|value| (value - mean).powi(2)).sum();
+    let squares: f64 = values.iter().map(|value| value * value).sum();
+    let (mut left_sum, mut left_squares) = (0.0, 0.0);
+    let mut best: Option<(usize, f64)> = None;
+    for (i, value) in values.iter().enumerate().take(values.len() - MIN_SEGMENT_SECS) {
+        left_sum += value;
+        left_squares += value * value;
+        let left = (i + 1) as f64;
+        if i + 1 < MIN_SEGMENT_SECS {
+            continue;
+        }
+        let right = values.len() as f64 - left;
+        let right_sum = total - left_sum;
+        let cost = (left_squares - left_sum * left_sum / left) + (squares - left_squares - right_sum * right_sum / right);
+        let gain = total_cost - cost;
+        if best.is_none_or(|(_, best_gain)| gain > best_gain) {
+            best = Some((start + i + 1, gain));
+        }
+    }
+    best
+}
+
+// latencies_micros и starts_micros (от старта теста) - парами, как в статистике агрегатора
+pub fn detect(latencies_micros: &[u64], starts_micros: &[u64], clock: &BucketClock) -> Vec<LatencyShift> {
+    let mut by_second: BTreeMap<u64, Vec<f64>> = BTreeMap::new();
+    for (&micros, &start) in latencies_micros.iter().zip(starts_micros) {
+        by_second.entry(start / 1_000_000).or_default().push(micros as f64 / 1000.0);
+    }
+    let seconds: Vec<u64> = by_second.keys().copied().collect();
+    let series: Vec<f64> = by_second.into_values().map(|mut values| median(&mut values)).collect();
+    if series.len() < 2 * MIN_SEGMENT_SECS {
+        return Vec::new();
+    }
+    // Шум ряда - по соседним секундам (MAD разностей), чтобы сами сдвиги его не завышали
+    let mut differences: Vec<f64> = series.windows(2).map(|pair| (pair[1] - pair[0]).abs()).collect();
+    let sigma = median(&mut differences) / (0.6745 * std::f64::consts::SQRT_2);
+    let threshold = PENALTY * sigma.max(0.001).powi(2) * (series.len() as f64).ln();
+
+    let mut splits = Vec::new();
+    let mut segments = vec![(0, series.len())];
+    while let Some((start, end)) = segments.pop() {
+        if splits.len() >= MAX_CHANGE_POINTS {
+            break;
+        }
+        let Some((split, gain)) = best_split(&series, start, end) else {
+            continue;
+        };
+        let before = median(&mut series[start..split].to_vec());
+        let after = median(&mut series[split..end].to_vec());
+        if gain < threshold || ((after - before) / before.max(0.001) * 100.0).abs() < MIN_CHANGE_PERCENT {
+            continue;
+        }
+        splits.push(split);
+        segments.push((start, split));
+        segments.push((split, end));
+    }
+    splits.sort_unstable();
+
+    // Уровни до и после - по соседним точкам, а не по всему участку, который делился
+    let bounds: Vec<usize> = std::iter::once(0).chain(splits.iter().copied()).chain(std::iter::once(series.len())).collect();
+    bounds
+        .windows(3)
+        .map(|window| {
+            let before = median(&mut series[window[0]..window[1]].to_vec());
+            let after = median(&mut series[window[1]..window[2]].to_vec());
+            let at_secs = seconds[window[1]];
+            LatencyShift {
+                at_secs,
+                at_utc: clock.utc(at_secs as f64),
+                before_ms: before,
+                after_ms: after,
+                change_percent: (after - before) / before.max(0.001) * 100.0,
+            }
+        })
+        .collect()
+}
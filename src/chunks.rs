@@ -0,0 +1,191 @@
+// Размер пачки getMultipleAccounts (подкоманда chunks): аккаунты из файла читаются пачками разного размера
+// (1, 10, 50, 100 по умолчанию), каждый размер - отдельный этап заданной длительности. Большая пачка
+// экономит запросы, но медленнее отвечает; оптимум для индексатора - размер с наибольшим числом аккаунтов в секунду
+
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{secrets, send_rpc_request, HeaderCapture, Stats};
+
+pub const DEFAULT_CHUNKS: [usize; 4] = [1, 10, 50, 100];
+// Размер с большей долей ошибок не предлагается как оптимальный
+const MAX_ERROR_PERCENT: f64 = 1.0;
+
+pub struct ChunkOptions {
+    pub url: String,
+    pub accounts: Vec<String>,
+    pub chunks: Vec<usize>,
+    pub duration: Duration, // на каждый размер пачки
+    pub concurrency: usize,
+    pub encoding: String,
+    pub http_timeout: Duration,
+}
+
+// Адреса аккаунтов: по одному на строку, пустые строки и комментарии (#) пропускаются
+pub fn load_accounts(path: &Path) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read accounts file {}: {}", path.display(), e))?;
+    let accounts: Vec<String> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    if accounts.is_empty() {
+        return Err(format!("Accounts file {} has no accounts", path.display()));
+    }
+    Ok(accounts)
+}
+
+#[derive(Debug)]
+pub struct ChunkResult {
+    pub chunk: usize,
+    pub requests: usize,
+    pub errors: usize,
+    pub missing_accounts: u64, // null в ответе: аккаунта нет в кластере
+    pub error_percent: f64,
+    pub average_ms: Option<f64>,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub requests_per_sec: f64,
+    pub accounts_per_sec: f64, // только успешные запросы
+}
+
+// Итоги одного воркера этапа
+#[derive(Default)]
+struct WorkerTotals {
+    latencies: Vec<u64>, // микросекунды успешных запросов
+    errors: usize,
+    missing_accounts: u64,
+}
+
+async fn run_worker(
+    client: reqwest::Client,
+    options: Arc<ChunkOptions>,
+    chunk: usize,
+    next: Arc<AtomicUsize>,
+    deadline: Instant,
+) -> WorkerTotals {
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+    let mut totals = WorkerTotals::default();
+    let config = json!({ "commitment": "confirmed", "encoding": options.encoding });
+    while Instant::now() < deadline {
+        // Пачки идут по файлу по кругу: соседние запросы не повторяют одни и те же аккаунты
+        let start = next.fetch_add(chunk, Ordering::Relaxed);
+        let accounts: Vec<&String> = (start..start + chunk).map(|i| &options.accounts[i % options.accounts.len()]).collect();
+        let request_start = Instant::now();
+        let params = vec![json!(accounts), config.clone()];
+        let response = send_rpc_request(&client, &options.url, "getMultipleAccounts", params, start as u64, &mut capture).await;
+        let micros = capture.network_micros(request_start);
+        match response.ok().and_then(|response| response.result) {
+            Some(result) => {
+                totals.latencies.push(micros);
+                if let Some(Value::Array(values)) = result.get("value") {
+                    totals.missing_accounts += values.iter().filter(|value| value.is_null()).count() as u64;
+                }
+            }
+            None => totals.errors += 1,
+        }
+    }
+    totals
+}
+
+fn percentile_ms(sorted: &[u64], p: f64) -> Option<f64> {
+    let index = ((sorted.len() as f64 * p).ceil() as usize).saturating_sub(1);
+    (!sorted.is_empty()).then(|| sorted[index.min(sorted.len() - 1)] as f64 / 1000.0)
+}
+
+pub async fn run(options: ChunkOptions) -> Result<Vec<ChunkResult>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(options.http_timeout)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let options = Arc::new(options);
+    let next = Arc::new(AtomicUsize::new(0));
+    let mut results = Vec::new();
+    for &chunk in &options.chunks {
+        println!(
+            "Chunk size {}: {}s with {} requests in flight...",
+            chunk,
+            options.duration.as_secs(),
+            options.concurrency
+        );
+        let stage_start = Instant::now();
+        let deadline = stage_start + options.duration;
+        let handles: Vec<_> = (0..options.concurrency.max(1))
+            .map(|_| tokio::spawn(run_worker(client.clone(), options.clone(), chunk, next.clone(), deadline)))
+            .collect();
+        let mut latencies = Vec::new();
+        let (mut errors, mut missing_accounts) = (0, 0);
+        for handle in handles {
+            let Ok(totals) = handle.await else {
+                continue;
+            };
+            latencies.extend(totals.latencies);
+            errors += totals.errors;
+            missing_accounts += totals.missing_accounts;
+        }
+        latencies.sort_unstable();
+        let elapsed = stage_start.elapsed().as_secs_f64().max(0.001);
+        let requests = latencies.len() + errors;
+        results.push(ChunkResult {
+            chunk,
+            requests,
+            errors,
+            missing_accounts,
+            error_percent: if requests > 0 { errors as f64 / requests as f64 * 100.0 } else { 0.0 },
+            average_ms: (!latencies.is_empty())
+                .then(|| latencies.iter().sum::<u64>() as f64 / latencies.len() as f64 / 1000.0),
+            p50_ms: percentile_ms(&latencies, 0.50),
+            p95_ms: percentile_ms(&latencies, 0.95),
+            requests_per_sec: requests as f64 / elapsed,
+            accounts_per_sec: (latencies.len() * chunk) as f64 / elapsed,
+        });
+    }
+    Ok(results)
+}
+
+pub fn print_result(url: &str, encoding: &str, accounts: usize, results: &[ChunkResult]) {
+    let ms = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |ms| format!("{:.2}", ms));
+    println!("\n=== getMultipleAccounts Chunk Size: {} ===", secrets::redact(url));
+    println!("{} accounts from the file, encoding {}", accounts, encoding);
+    println!(
+        "\n{:>6}  {:>8}  {:>6}  {:>7}  {:>9}  {:>9}  {:>9}  {:>9}  {:>11}  {:>13}",
+        "Chunk", "Requests", "Errors", "Errors%", "Avg ms", "p50 ms", "p95 ms", "Req/s", "Accounts/s", "ms per account"
+    );
+    for result in results {
+        println!(
+            "{:>6}  {:>8}  {:>6}  {:>7.1}  {:>9}  {:>9}  {:>9}  {:>9.1}  {:>11.1}  {:>13}",
+            result.chunk,
+            result.requests,
+            result.errors,
+            result.error_percent,
+            ms(result.average_ms),
+            ms(result.p50_ms),
+            ms(result.p95_ms),
+            result.requests_per_sec,
+            result.accounts_per_sec,
+            ms(result.average_ms.map(|average| average / result.chunk as f64))
+        );
+    }
+    let missing: u64 = results.iter().map(|result| result.missing_accounts).sum();
+    if missing > 0 {
+        println!("\nWarning: {} accounts came back null (not found in the cluster); check the accounts file", missing);
+    }
+    let best = results
+        .iter()
+        .filter(|result| result.requests > result.errors && result.error_percent <= MAX_ERROR_PERCENT)
+        .max_by(|a, b| a.accounts_per_sec.total_cmp(&b.accounts_per_sec));
+    match best {
+        Some(result) => println!(
+            "\nOptimal chunk size: {} ({:.1} accounts/s, p95 {} ms)",
+            result.chunk,
+            result.accounts_per_sec,
+            ms(result.p95_ms)
+        ),
+        None => println!("\nOptimal chunk size: none, every chunk size had more than {:.0}% errors", MAX_ERROR_PERCENT),
+    }
+}
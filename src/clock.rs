@@ -0,0 +1,159 @@
+// Проверка часов (--clock-check): сравнение локального времени с заголовком Date ответов endpoint
+// и (с --clock-check-block-time) с getBlockTime последнего подтвержденного слота.
+// Расхождение часов дает «отрицательную латентность» при сопоставлении логов клиента и сервера
+
+use serde::Serialize;
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep;
+
+use crate::{send_rpc_request, HeaderCapture, Stats};
+
+// Date имеет точность в секунду: пробы с шагом в долю секунды попадают на разные границы секунд
+// и сужают интервал, в котором лежит расхождение
+const DATE_PROBES: u32 = 10;
+const DATE_PROBE_INTERVAL: Duration = Duration::from_millis(230);
+
+// Предупреждаем, если расхождение заведомо (с учетом погрешности) больше порога
+pub const DATE_SKEW_WARN_MS: f64 = 500.0;
+// Время блока - взвешенная по стейку оценка валидаторов, она сама отстает от реального на секунды
+pub const BLOCK_TIME_SKEW_WARN_SECS: i64 = 10;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ClockCheck {
+    pub block_time: bool, // сверять и с getBlockTime
+}
+
+impl ClockCheck {
+    // Сверка с временем блока включает и проверку по Date
+    pub fn new(enabled: bool, block_time: bool) -> Option<Self> {
+        (enabled || block_time).then_some(Self { block_time })
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ClockSummary {
+    // Сдвиг часов endpoint относительно локальных по Date, мс: плюс - локальные часы отстают
+    pub date_offset_ms: Option<f64>, // None - ответы без Date
+    pub date_uncertainty_ms: Option<f64>,
+    pub date_probes: u32,
+    pub block_slot: Option<u64>,
+    pub block_time_offset_secs: Option<i64>, // getBlockTime - локальное время получения ответа
+}
+
+impl ClockSummary {
+    // Расхождение по Date заведомо больше порога
+    pub fn date_skewed(&self) -> bool {
+        match (self.date_offset_ms, self.date_uncertainty_ms) {
+            (Some(offset), Some(uncertainty)) => offset.abs() - uncertainty > DATE_SKEW_WARN_MS,
+            _ => false,
+        }
+    }
+
+    pub fn block_time_skewed(&self) -> bool {
+        self.block_time_offset_secs
+            .is_some_and(|offset| offset.abs() > BLOCK_TIME_SKEW_WARN_SECS)
+    }
+}
+
+fn unix_secs_f64(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64())
+}
+
+// Номер дня от 1970-01-01 (алгоритм days_from_civil)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// Date в формате IMF-fixdate (RFC 7231): "Thu, 15 Oct 2026 11:55:24 GMT"
+fn parse_http_date(value: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let mut parts = value.split_whitespace().skip(1);
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut time = parts.next()?.split(':').map(|part| part.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+    if parts.next() != Some("GMT") {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86_400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+// Один запрос getHealth: интервал, в котором лежит сдвиг часов endpoint, в секундах.
+// Сервер поставил Date в момент между отправкой и получением, а Date округлена вниз до секунды
+async fn date_probe(client: &reqwest::Client, url: &str, request_id: u64) -> Option<(f64, f64)> {
+    let body = json!({ "jsonrpc": "2.0", "id": request_id, "method": "getHealth" });
+    let sent = unix_secs_f64(SystemTime::now());
+    let response = client.post(url).json(&body).send().await.ok()?;
+    let received = unix_secs_f64(SystemTime::now());
+    let date = response.headers().get(reqwest::header::DATE)?.to_str().ok()?;
+    let server = parse_http_date(date)? as f64;
+    Some((server - received, server + 1.0 - sent))
+}
+
+async fn date_offset(client: &reqwest::Client, url: &str) -> (Option<f64>, Option<f64>) {
+    let mut bounds = Vec::new();
+    for i in 0..DATE_PROBES {
+        if let Some(bound) = date_probe(client, url, i as u64 + 1).await {
+            bounds.push(bound);
+        }
+        sleep(DATE_PROBE_INTERVAL).await;
+    }
+    if bounds.is_empty() {
+        return (None, None);
+    }
+    // Пересечение интервалов всех проб; если они несовместны (Date кэшируется прокси и т.п.) -
+    // медиана середин с погрешностью в полсекунды
+    let low = bounds.iter().map(|&(low, _)| low).fold(f64::MIN, f64::max);
+    let high = bounds.iter().map(|&(_, high)| high).fold(f64::MAX, f64::min);
+    let (offset, uncertainty) = if low <= high {
+        ((low + high) / 2.0, (high - low) / 2.0)
+    } else {
+        let mut mids: Vec<f64> = bounds.iter().map(|&(low, high)| (low + high) / 2.0).collect();
+        mids.sort_by(f64::total_cmp);
+        (mids[mids.len() / 2], 0.5)
+    };
+    (Some(offset * 1000.0), Some(uncertainty * 1000.0))
+}
+
+// Время последнего подтвержденного блока относительно локального времени, в секундах
+async fn block_time_offset(client: &reqwest::Client, url: &str) -> (Option<u64>, Option<i64>) {
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+    let confirmed = vec![json!({ "commitment": "confirmed" })];
+    let Some(slot) = send_rpc_request(client, url, "getSlot", confirmed, 1, &mut capture)
+        .await
+        .ok()
+        .and_then(|response| response.result?.as_u64())
+    else {
+        return (None, None);
+    };
+    let block_time = send_rpc_request(client, url, "getBlockTime", vec![json!(slot)], 2, &mut capture)
+        .await
+        .ok()
+        .and_then(|response| response.result?.as_i64());
+    let now = unix_secs_f64(SystemTime::now()).round() as i64;
+    (Some(slot), block_time.map(|block_time| block_time - now))
+}
+
+pub async fn check(client: &reqwest::Client, url: &str, block_time: bool) -> ClockSummary {
+    let (date_offset_ms, date_uncertainty_ms) = date_offset(client, url).await;
+    let (block_slot, block_time_offset_secs) = if block_time {
+        block_time_offset(client, url).await
+    } else {
+        (None, None)
+    };
+    ClockSummary {
+        date_offset_ms,
+        date_uncertainty_ms,
+        date_probes: DATE_PROBES,
+        block_slot,
+        block_time_offset_secs,
+    }
+}
@@ -0,0 +1,259 @@
+// Холодный и теплый кэш (cold-warm): один и тот же набор запросов с уникальными params отправляется дважды.
+// Первый проход попадает в холодный кэш провайдера, второй повторяет те же запросы в том же порядке;
+// разница латентности показывает, насколько провайдер кэширует ответы
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::catalog::{self, LATEST_SLOT};
+use crate::{send_rpc_request, HeaderCapture, Rng, Stats};
+
+// Повтор быстрее холодного запроса не меньше чем в 1.5 раза (по медиане пар) - кэш заметен,
+// меньше чем в 1.1 раза - эффекта нет
+const STRONG_SPEEDUP: f64 = 1.5;
+const WEAK_SPEEDUP: f64 = 1.1;
+
+pub struct ColdWarmOptions {
+    pub url: String,
+    pub method: String,
+    pub count: usize,
+    pub concurrency: usize,
+    pub http_timeout: Duration,
+    pub params_file: Option<String>,
+    pub slot_range: u64,
+    pub seed: u64,
+}
+
+// Набор params из файла: по JSON массиву на строку, пустые строки и комментарии (#) пропускаются.
+// Повторы убираются, иначе первый проход уже не был бы холодным
+fn load_params_file(path: &Path) -> Result<Vec<Vec<Value>>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read params file {}: {}", path.display(), e))?;
+    let mut seen = BTreeSet::new();
+    let mut params_set = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let params: Vec<Value> = serde_json::from_str(line).map_err(|e| {
+            format!("{}:{}: params must be a JSON array: {}", path.display(), line_number + 1, e)
+        })?;
+        if seen.insert(serde_json::to_string(&params).unwrap_or_default()) {
+            params_set.push(params);
+        }
+    }
+    Ok(params_set)
+}
+
+// Набор params из params по умолчанию метода: заглушка текущего слота заменяется на count разных
+// прошлых слотов из последних slot_range (старые блоки реже оказываются в кэше из-за чужих запросов)
+async fn slot_params(client: &reqwest::Client, options: &ColdWarmOptions) -> Result<Vec<Vec<Value>>, String> {
+    let template = catalog::default_params(&options.method)
+        .filter(|params| catalog::uses_placeholder(params, LATEST_SLOT))
+        .ok_or_else(|| {
+            format!(
+                "{} has no slot parameter to vary; pass unique params with --params-file",
+                options.method
+            )
+        })?;
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+    let current_slot = send_rpc_request(client, &options.url, "getSlot", Vec::new(), 1, &mut capture)
+        .await
+        .map_err(|e| format!("Failed to get the current slot: {}", e))?
+        .result
+        .and_then(|slot| slot.as_u64())
+        .ok_or("Failed to get the current slot: getSlot returned no slot")?;
+    let range = options.slot_range.min(current_slot);
+    if (range as usize) < options.count {
+        return Err(format!(
+            "Cannot pick {} distinct slots from the last {} slots",
+            options.count, range
+        ));
+    }
+    let mut rng = Rng::new(options.seed);
+    let mut slots = BTreeSet::new();
+    while slots.len() < options.count {
+        slots.insert(current_slot - 1 - rng.up_to(range - 1));
+    }
+    Ok(slots
+        .into_iter()
+        .map(|slot| {
+            let mut params = template.clone();
+            catalog::fill_placeholder(&mut params, LATEST_SLOT, slot);
+            params
+        })
+        .collect())
+}
+
+// Один проход по набору: латентность каждого запроса в порядке набора (None - ошибка)
+async fn run_pass(
+    client: &reqwest::Client,
+    options: &ColdWarmOptions,
+    params_set: &[Vec<Value>],
+    first_id: u64,
+) -> Vec<Option<u64>> {
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let handles: Vec<_> = params_set
+        .iter()
+        .enumerate()
+        .map(|(index, params)| {
+            let semaphore = semaphore.clone();
+            let client = client.clone();
+            let url = options.url.clone();
+            let method = options.method.clone();
+            let params = params.clone();
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+                let request_start = Instant::now();
+                let ok = send_rpc_request(&client, &url, &method, params, first_id + index as u64, &mut capture)
+                    .await
+                    .is_ok_and(|response| response.error.is_none());
+                ok.then(|| capture.network_micros(request_start))
+            })
+        })
+        .collect();
+
+    let mut latencies = Vec::with_capacity(handles.len());
+    for handle in handles {
+        latencies.push(handle.await.ok().flatten());
+    }
+    latencies
+}
+
+#[derive(Debug)]
+pub struct PassSummary {
+    pub requests: usize,
+    pub successful: usize,
+    pub average_ms: Option<f64>,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+}
+
+fn percentile_ms(sorted: &[u64], p: f64) -> Option<f64> {
+    let index = ((sorted.len() as f64 * p).ceil() as usize).saturating_sub(1);
+    (!sorted.is_empty()).then(|| sorted[index.min(sorted.len() - 1)] as f64 / 1000.0)
+}
+
+fn summarize_pass(latencies: &[Option<u64>]) -> PassSummary {
+    let mut sorted: Vec<u64> = latencies.iter().flatten().copied().collect();
+    sorted.sort_unstable();
+    PassSummary {
+        requests: latencies.len(),
+        successful: sorted.len(),
+        average_ms: (!sorted.is_empty()).then(|| sorted.iter().sum::<u64>() as f64 / sorted.len() as f64 / 1000.0),
+        p50_ms: percentile_ms(&sorted, 0.50),
+        p95_ms: percentile_ms(&sorted, 0.95),
+        p99_ms: percentile_ms(&sorted, 0.99),
+    }
+}
+
+#[derive(Debug)]
+pub struct ColdWarmResult {
+    pub method: String,
+    pub cold: PassSummary,
+    pub warm: PassSummary,
+    pub pairs: usize,                 // запросов, успешных в обоих проходах
+    pub median_saved_ms: Option<f64>, // медиана (холодный - теплый) по парам
+    pub faster_percent: Option<f64>,  // доля пар, где повтор быстрее
+    pub median_speedup: Option<f64>,  // медиана отношения холодный / теплый по парам
+}
+
+// Сравнение по парам: один и тот же запрос в холодном и теплом проходе
+fn compare(method: &str, cold: &[Option<u64>], warm: &[Option<u64>]) -> ColdWarmResult {
+    let pairs: Vec<(u64, u64)> = cold
+        .iter()
+        .zip(warm)
+        .filter_map(|(&cold, &warm)| Some((cold?, warm?)))
+        .collect();
+    let median = |mut values: Vec<f64>| {
+        values.sort_by(f64::total_cmp);
+        (!values.is_empty()).then(|| values[values.len() / 2])
+    };
+    ColdWarmResult {
+        method: method.to_string(),
+        cold: summarize_pass(cold),
+        warm: summarize_pass(warm),
+        pairs: pairs.len(),
+        median_saved_ms: median(
+            pairs
+                .iter()
+                .map(|&(cold, warm)| (cold as f64 - warm as f64) / 1000.0)
+                .collect(),
+        ),
+        faster_percent: (!pairs.is_empty())
+            .then(|| pairs.iter().filter(|(cold, warm)| warm < cold).count() as f64 / pairs.len() as f64 * 100.0),
+        median_speedup: median(
+            pairs
+                .iter()
+                .map(|&(cold, warm)| cold as f64 / warm.max(1) as f64)
+                .collect(),
+        ),
+    }
+}
+
+pub async fn run(options: ColdWarmOptions) -> Result<ColdWarmResult, String> {
+    let client = reqwest::Client::builder()
+        .timeout(options.http_timeout)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let params_set = match &options.params_file {
+        Some(file) => load_params_file(Path::new(file))?,
+        None => slot_params(&client, &options).await?,
+    };
+    if params_set.is_empty() {
+        return Err("The request set is empty".to_string());
+    }
+
+    println!("Cold pass: {} x {} with unique params...", params_set.len(), options.method);
+    let cold = run_pass(&client, &options, &params_set, 1).await;
+    println!("Warm pass: repeating the same {} requests...", params_set.len());
+    let warm = run_pass(&client, &options, &params_set, 1 + params_set.len() as u64).await;
+    Ok(compare(&options.method, &cold, &warm))
+}
+
+pub fn print_result(result: &ColdWarmResult) {
+    let ms = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |ms| format!("{:.2}", ms));
+    println!("\n=== Cold vs Warm Cache: {} ===", result.method);
+    println!(
+        "{:<6}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}",
+        "Pass", "Requests", "OK", "Avg ms", "p50 ms", "p95 ms", "p99 ms"
+    );
+    for (name, pass) in [("cold", &result.cold), ("warm", &result.warm)] {
+        println!(
+            "{:<6}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}",
+            name,
+            pass.requests,
+            pass.successful,
+            ms(pass.average_ms),
+            ms(pass.p50_ms),
+            ms(pass.p95_ms),
+            ms(pass.p99_ms)
+        );
+    }
+
+    println!("\nPaired requests (successful in both passes): {}", result.pairs);
+    let (Some(saved), Some(faster), Some(speedup)) =
+        (result.median_saved_ms, result.faster_percent, result.median_speedup)
+    else {
+        println!("Cache effect: cannot judge, no request succeeded in both passes");
+        return;
+    };
+    println!("  Median time saved on repeat: {:.2} ms", saved);
+    println!("  Faster on repeat: {:.1}% of requests", faster);
+    println!("  Median speedup (cold / warm): {:.2}x", speedup);
+    let verdict = if speedup >= STRONG_SPEEDUP {
+        "repeated requests are served from a cache"
+    } else if speedup >= WEAK_SPEEDUP {
+        "weak, repeats are only slightly faster"
+    } else {
+        "none, repeats are as slow as first requests"
+    };
+    println!("Cache effect: {}", verdict);
+}
@@ -0,0 +1,294 @@
+// Сжатие ответов (--accept-encoding). Тело читается как есть и распаковывается здесь,
+// а не в reqwest: так видно и размер на проводе, и размер после распаковки.
+// Распаковка через системные zlib и libbrotlidec; там же сжатие тел запросов (--gzip-requests).
+// Библиотеки подключаются cargo features gzip и brotli: без них такие ответы считаются ошибкой распаковки
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AcceptEncoding {
+    Gzip,
+    Br,
+    Identity,
+    // Заголовок Accept-Encoding не отправляется
+    None,
+}
+
+impl AcceptEncoding {
+    pub fn header_value(self) -> Option<&'static str> {
+        match self {
+            AcceptEncoding::Gzip => Some("gzip"),
+            AcceptEncoding::Br => Some("br"),
+            AcceptEncoding::Identity => Some("identity"),
+            AcceptEncoding::None => None,
+        }
+    }
+}
+
+// Сжатие из настроек, для которого сборка без нужной feature: ошибка до старта, а не ошибка каждого запроса
+pub fn check_available(accept_encoding: Option<AcceptEncoding>, gzip_requests: bool) -> Result<(), String> {
+    if (accept_encoding == Some(AcceptEncoding::Gzip) || gzip_requests) && !cfg!(feature = "gzip") {
+        return Err(missing_feature("gzip"));
+    }
+    if accept_encoding == Some(AcceptEncoding::Br) && !cfg!(feature = "brotli") {
+        return Err(missing_feature("brotli"));
+    }
+    Ok(())
+}
+
+fn missing_feature(feature: &str) -> String {
+    format!("{} support is not built in: rebuild with --features {}", feature, feature)
+}
+
+// Распаковывает тело по Content-Encoding ответа
+pub fn decode<'a>(content_encoding: Option<&str>, body: &'a [u8]) -> Result<Cow<'a, [u8]>, String> {
+    match content_encoding.map(|e| e.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("") | Some("identity") => Ok(Cow::Borrowed(body)),
+        Some("gzip") | Some("x-gzip") => gunzip(body).map(Cow::Owned),
+        Some("br") => unbrotli(body).map(Cow::Owned),
+        Some(other) => Err(format!("unsupported Content-Encoding: {}", other)),
+    }
+}
+
+#[cfg(any(feature = "gzip", feature = "brotli"))]
+const CHUNK: usize = 64 * 1024;
+
+#[cfg(feature = "gzip")]
+pub use zlib::gzip;
+#[cfg(feature = "gzip")]
+use zlib::gunzip;
+
+#[cfg(not(feature = "gzip"))]
+pub fn gzip(_body: &[u8]) -> Result<Vec<u8>, String> {
+    Err(missing_feature("gzip"))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_body: &[u8]) -> Result<Vec<u8>, String> {
+    Err(missing_feature("gzip"))
+}
+
+#[cfg(feature = "gzip")]
+mod zlib {
+    use super::CHUNK;
+    use std::ffi::{c_char, c_int, c_uint, c_ulong, c_void};
+
+    #[repr(C)]
+    struct ZStream {
+        next_in: *const u8,
+        avail_in: c_uint,
+        total_in: c_ulong,
+        next_out: *mut u8,
+        avail_out: c_uint,
+        total_out: c_ulong,
+        msg: *const c_char,
+        state: *mut c_void,
+        zalloc: *const c_void,
+        zfree: *const c_void,
+        opaque: *mut c_void,
+        data_type: c_int,
+        adler: c_ulong,
+        reserved: c_ulong,
+    }
+
+    const Z_NO_FLUSH: c_int = 0;
+    const Z_OK: c_int = 0;
+    const Z_STREAM_END: c_int = 1;
+    const GZIP_WINDOW_BITS: c_int = 15 + 16; // 16 - формат gzip, а не zlib
+
+    const Z_FINISH: c_int = 4;
+    const Z_DEFAULT_COMPRESSION: c_int = -1;
+    const Z_DEFLATED: c_int = 8;
+    const Z_DEFAULT_STRATEGY: c_int = 0;
+    const MEM_LEVEL: c_int = 8;
+    const ZLIB_VERSION: &[u8] = b"1.2.11\0";
+
+    #[link(name = "z")]
+    extern "C" {
+        fn inflateInit2_(strm: *mut ZStream, window_bits: c_int, version: *const c_char, stream_size: c_int) -> c_int;
+        fn inflate(strm: *mut ZStream, flush: c_int) -> c_int;
+        fn inflateEnd(strm: *mut ZStream) -> c_int;
+        fn deflateInit2_(
+            strm: *mut ZStream,
+            level: c_int,
+            method: c_int,
+            window_bits: c_int,
+            mem_level: c_int,
+            strategy: c_int,
+            version: *const c_char,
+            stream_size: c_int,
+        ) -> c_int;
+        fn deflateBound(strm: *mut ZStream, source_len: c_ulong) -> c_ulong;
+        fn deflate(strm: *mut ZStream, flush: c_int) -> c_int;
+        fn deflateEnd(strm: *mut ZStream) -> c_int;
+    }
+
+    // Сжимает тело запроса в gzip за один вызов deflate (размер выхода заранее известен из deflateBound)
+    pub fn gzip(body: &[u8]) -> Result<Vec<u8>, String> {
+        // SAFETY: как и в gunzip, нулевой z_stream - начальное состояние; выходной буфер
+        // не меньше deflateBound и живет дольше вызова deflate
+        unsafe {
+            let mut stream: ZStream = std::mem::zeroed();
+            let rc = deflateInit2_(
+                &mut stream,
+                Z_DEFAULT_COMPRESSION,
+                Z_DEFLATED,
+                GZIP_WINDOW_BITS,
+                MEM_LEVEL,
+                Z_DEFAULT_STRATEGY,
+                ZLIB_VERSION.as_ptr() as *const c_char,
+                std::mem::size_of::<ZStream>() as c_int,
+            );
+            if rc != Z_OK {
+                return Err(format!("deflateInit2 failed: {}", rc));
+            }
+            let mut out = vec![0u8; deflateBound(&mut stream, body.len() as c_ulong) as usize];
+            stream.next_in = body.as_ptr();
+            stream.avail_in = body.len() as c_uint;
+            stream.next_out = out.as_mut_ptr();
+            stream.avail_out = out.len() as c_uint;
+            let rc = deflate(&mut stream, Z_FINISH);
+            let written = stream.total_out as usize;
+            deflateEnd(&mut stream);
+            if rc != Z_STREAM_END {
+                return Err(format!("gzip encode error: {}", rc));
+            }
+            out.truncate(written);
+            Ok(out)
+        }
+    }
+
+    pub fn gunzip(body: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::with_capacity(body.len() * 4);
+        let mut chunk = vec![0u8; CHUNK];
+        // SAFETY: нулевой z_stream - документированное начальное состояние (zalloc/zfree = NULL),
+        // указатели входа и выхода живут дольше вызовов inflate
+        unsafe {
+            let mut stream: ZStream = std::mem::zeroed();
+            let rc = inflateInit2_(
+                &mut stream,
+                GZIP_WINDOW_BITS,
+                ZLIB_VERSION.as_ptr() as *const c_char,
+                std::mem::size_of::<ZStream>() as c_int,
+            );
+            if rc != Z_OK {
+                return Err(format!("inflateInit2 failed: {}", rc));
+            }
+            stream.next_in = body.as_ptr();
+            stream.avail_in = body.len() as c_uint;
+            let result = loop {
+                stream.next_out = chunk.as_mut_ptr();
+                stream.avail_out = CHUNK as c_uint;
+                let rc = inflate(&mut stream, Z_NO_FLUSH);
+                out.extend_from_slice(&chunk[..CHUNK - stream.avail_out as usize]);
+                match rc {
+                    Z_STREAM_END => break Ok(()),
+                    // Выход заполнен целиком - читаем дальше; иначе вход закончился раньше конца потока
+                    Z_OK if stream.avail_out == 0 || stream.avail_in > 0 => continue,
+                    Z_OK => break Err("truncated gzip body".to_string()),
+                    rc => break Err(format!("gzip decode error: {}", rc)),
+                }
+            };
+            inflateEnd(&mut stream);
+            result.map(|_| out)
+        }
+    }
+}
+
+#[cfg(feature = "brotli")]
+use brotli::unbrotli;
+
+#[cfg(not(feature = "brotli"))]
+fn unbrotli(_body: &[u8]) -> Result<Vec<u8>, String> {
+    Err(missing_feature("brotli"))
+}
+
+#[cfg(feature = "brotli")]
+mod brotli {
+    use super::CHUNK;
+    use std::ffi::{c_int, c_void};
+
+    const BROTLI_RESULT_SUCCESS: c_int = 1;
+    const BROTLI_RESULT_NEEDS_MORE_INPUT: c_int = 2;
+    const BROTLI_RESULT_NEEDS_MORE_OUTPUT: c_int = 3;
+
+    #[link(name = "brotlidec")]
+    extern "C" {
+        fn BrotliDecoderCreateInstance(alloc: *const c_void, free: *const c_void, opaque: *mut c_void) -> *mut c_void;
+        fn BrotliDecoderDecompressStream(
+            state: *mut c_void,
+            available_in: *mut usize,
+            next_in: *mut *const u8,
+            available_out: *mut usize,
+            next_out: *mut *mut u8,
+            total_out: *mut usize,
+        ) -> c_int;
+        fn BrotliDecoderDestroyInstance(state: *mut c_void);
+    }
+
+    pub fn unbrotli(body: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::with_capacity(body.len() * 4);
+        let mut chunk = vec![0u8; CHUNK];
+        // SAFETY: состояние декодера создается и уничтожается здесь же,
+        // указатели входа и выхода живут дольше вызовов декодера
+        unsafe {
+            let state = BrotliDecoderCreateInstance(std::ptr::null(), std::ptr::null(), std::ptr::null_mut());
+            if state.is_null() {
+                return Err("failed to create brotli decoder".to_string());
+            }
+            let mut available_in = body.len();
+            let mut next_in = body.as_ptr();
+            let result = loop {
+                let mut available_out = CHUNK;
+                let mut next_out = chunk.as_mut_ptr();
+                let rc = BrotliDecoderDecompressStream(
+                    state,
+                    &mut available_in,
+                    &mut next_in,
+                    &mut available_out,
+                    &mut next_out,
+                    std::ptr::null_mut(),
+                );
+                out.extend_from_slice(&chunk[..CHUNK - available_out]);
+                match rc {
+                    BROTLI_RESULT_SUCCESS => break Ok(()),
+                    BROTLI_RESULT_NEEDS_MORE_OUTPUT => continue,
+                    // Входа больше нет, а поток не завершен
+                    BROTLI_RESULT_NEEDS_MORE_INPUT => break Err("truncated brotli body".to_string()),
+                    _ => break Err("brotli decode error".to_string()),
+                }
+            };
+            BrotliDecoderDestroyInstance(state);
+            result.map(|_| out)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "gzip"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gzip_round_trips_through_decode() {
+        let body = br#"{"jsonrpc":"2.0","id":1,"method":"getSlot"}"#;
+        let compressed = gzip(body).unwrap();
+        assert_ne!(compressed.as_slice(), body.as_slice());
+        assert_eq!(decode(Some("gzip"), &compressed).unwrap().as_ref(), body);
+        assert_eq!(decode(Some(" X-Gzip "), &compressed).unwrap().as_ref(), body);
+    }
+
+    #[test]
+    fn gzip_round_trips_bodies_larger_than_a_chunk() {
+        // Несколько выходных буферов подряд при распаковке
+        let body: Vec<u8> = (0..CHUNK * 3).map(|i| (i * 7 % 251) as u8).collect();
+        assert_eq!(decode(Some("gzip"), &gzip(&body).unwrap()).unwrap().as_ref(), body.as_slice());
+    }
+
+    #[test]
+    fn truncated_gzip_is_an_error() {
+        let compressed = gzip(b"getAccountInfo response body").unwrap();
+        assert!(decode(Some("gzip"), &compressed[..compressed.len() / 2]).is_err());
+    }
+}
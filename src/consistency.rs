@@ -0,0 +1,133 @@
+// Согласованность ответов (--hash-responses): result каждого успешного ответа хэшируется, и для одинаковых
+// запросов (метод + params) считается, сколько разных тел вернул endpoint. Несколько реплик за одним URL,
+// отстающих друг от друга, видны как несколько тел у неизменяемых данных и частые смены тела.
+// Ответ с context (getBalance, getLatestBlockhash, ...) сравнивается только с ответами того же
+// context.slot: на разных слотах value законно разный. Методы, следующие за вершиной цепи без context
+// (getSlot, getBlockHeight, ...), не учитываются
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use crate::catalog;
+
+// Сколько запросов выводится в текстовом отчете (в JSON - все)
+pub const REPORT_LIMIT: usize = 10;
+
+#[derive(Default)]
+struct RequestBodies {
+    responses: u64,
+    // context.slot ответа (None - result без context) -> хэш тела -> сколько раз пришло
+    slots: HashMap<Option<u64>, HashMap<u64, u64>>,
+    last: Option<(Option<u64>, u64)>,
+    changes: u64, // ответ отличался от предыдущего ответа на тот же запрос того же слота
+}
+
+#[derive(Default)]
+pub struct ResponseHashes {
+    requests: Mutex<BTreeMap<String, RequestBodies>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RequestConsistency {
+    pub request: String, // метод и params
+    pub responses: u64,
+    pub distinct_bodies: usize, // наибольшее число разных тел на одном слоте
+    pub changes: u64,
+    pub most_common_percent: f64, // доля ответов с самым частым телом своего слота
+}
+
+#[derive(Serialize, Debug)]
+pub struct ConsistencySummary {
+    pub requests: Vec<RequestConsistency>, // сначала запросы с наибольшим числом разных тел
+    pub inconsistent: usize,               // запросов больше чем с одним телом
+}
+
+// Ключ запроса: params сериализуются так же, как уходят в теле. None - ответы метода не сравниваются
+pub fn request_key(method: &str, params: &[Value]) -> Option<String> {
+    (!catalog::follows_tip(method)).then(|| format!("{} {}", method, serde_json::to_string(params).unwrap_or_default()))
+}
+
+// Ответ вида { context: { slot }, value }: слот и value отдельно
+fn split_context(result: &Value) -> (Option<u64>, &Value) {
+    let slot = result.get("context").and_then(|context| context.get("slot")).and_then(Value::as_u64);
+    match (slot, result.get("value")) {
+        (Some(slot), Some(value)) => (Some(slot), value),
+        _ => (None, result),
+    }
+}
+
+impl ResponseHashes {
+    // Объекты serde_json хранит с отсортированными ключами: одинаковые result дают одинаковую строку
+    pub fn record(&self, key: &str, result: &Value) {
+        let (slot, value) = split_context(result);
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(value).unwrap_or_default().hash(&mut hasher);
+        let hash = hasher.finish();
+        let mut requests = self.requests.lock().unwrap();
+        let request = requests.entry(key.to_string()).or_default();
+        request.responses += 1;
+        *request.slots.entry(slot).or_default().entry(hash).or_default() += 1;
+        if request.last.is_some_and(|(last_slot, last)| last_slot == slot && last != hash) {
+            request.changes += 1;
+        }
+        request.last = Some((slot, hash));
+    }
+
+    pub fn summarize(&self) -> ConsistencySummary {
+        let requests = self.requests.lock().unwrap();
+        let mut summaries: Vec<RequestConsistency> = requests
+            .iter()
+            .map(|(key, request)| RequestConsistency {
+                request: key.clone(),
+                responses: request.responses,
+                distinct_bodies: request.slots.values().map(HashMap::len).max().unwrap_or(0),
+                changes: request.changes,
+                most_common_percent: request.slots.values().filter_map(|bodies| bodies.values().max()).sum::<u64>() as f64
+                    / request.responses.max(1) as f64
+                    * 100.0,
+            })
+            .collect();
+        summaries.sort_by(|a, b| b.distinct_bodies.cmp(&a.distinct_bodies).then(b.responses.cmp(&a.responses)));
+        ConsistencySummary {
+            inconsistent: summaries.iter().filter(|request| request.distinct_bodies > 1).count(),
+            requests: summaries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tip_methods_are_not_compared() {
+        assert!(request_key("getSlot", &[]).is_none());
+        assert!(request_key("getBlockHeight", &[json!({ "commitment": "finalized" })]).is_none());
+        assert_eq!(request_key("getBalance", &[json!("a")]).as_deref(), Some(r#"getBalance ["a"]"#));
+    }
+
+    #[test]
+    fn context_responses_are_compared_within_one_slot() {
+        let hashes = ResponseHashes::default();
+        let response = |slot: u64, value: &str| json!({ "context": { "slot": slot }, "value": { "blockhash": value } });
+        // Новый слот - новый blockhash: расхождения нет
+        hashes.record("getLatestBlockhash []", &response(10, "a"));
+        hashes.record("getLatestBlockhash []", &response(11, "b"));
+        hashes.record("getLatestBlockhash []", &response(11, "b"));
+        // Тот же слот, другое тело: реплики расходятся
+        hashes.record("getLatestBlockhash []", &response(12, "c"));
+        hashes.record("getLatestBlockhash []", &response(12, "d"));
+        let summary = hashes.summarize();
+        let request = &summary.requests[0];
+        assert_eq!(request.responses, 5);
+        assert_eq!(request.distinct_bodies, 2);
+        assert_eq!(request.changes, 1);
+        assert_eq!(request.most_common_percent, 80.0);
+        assert_eq!(summary.inconsistent, 1);
+    }
+}
@@ -0,0 +1,130 @@
+// Управление идущим тестом: продление длительности сигналом SIGUSR2 (на --extend-minutes) и командами
+// control-сокета (--control-socket). Soak-тест часто нужно продлить, когда что-то интересное только началось
+//
+// Команды сокета, по одной в строке, ответ - одна строка:
+//   extend <minutes>    продлить тест
+//   duration <minutes>  новая длительность от старта (задает конец и бесконечному тесту)
+//   status              сколько прошло и сколько осталось
+//
+// Только Unix: сокет - Unix domain socket, продление - сигналом
+
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::Stats;
+
+fn describe(stats: &Stats) -> String {
+    let elapsed = stats.started_at.elapsed().as_secs();
+    match stats.duration() {
+        Some(duration) => format!(
+            "elapsed {} sec, duration {} sec, {} sec left",
+            elapsed,
+            duration.as_secs(),
+            duration.as_secs().saturating_sub(elapsed)
+        ),
+        None => format!("elapsed {} sec, infinite test", elapsed),
+    }
+}
+
+fn extend(stats: &Stats, by: Duration) -> Result<String, String> {
+    let duration = stats.duration().ok_or("infinite test: nothing to extend (use `duration <minutes>` to set an end)")?;
+    stats.set_duration(duration + by)?;
+    Ok(format!("extended by {} min: {}", by.as_secs() / 60, describe(stats)))
+}
+
+fn execute(stats: &Stats, command: &str) -> Result<String, String> {
+    let mut parts = command.split_whitespace();
+    let minutes = |value: Option<&str>| -> Result<u64, String> {
+        let value = value.ok_or("expected a number of minutes")?;
+        value.parse().map_err(|_| format!("invalid number of minutes '{}'", value))
+    };
+    match parts.next() {
+        Some("extend") => extend(stats, Duration::from_secs(minutes(parts.next())? * 60)),
+        Some("duration") => {
+            stats.set_duration(Duration::from_secs(minutes(parts.next())? * 60))?;
+            Ok(format!("duration set: {}", describe(stats)))
+        }
+        Some("status") => Ok(describe(stats)),
+        Some(other) => Err(format!("unknown command '{}' (extend <minutes>, duration <minutes>, status)", other)),
+        None => Err("empty command".to_string()),
+    }
+}
+
+async fn serve(stats: Stats, stream: UnixStream) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply = match execute(&stats, line.trim()) {
+            Ok(message) => {
+                // В консоль - только изменения длительности
+                if !line.trim().starts_with("status") {
+                    println!("[Control] {}", message);
+                }
+                format!("ok: {}\n", message)
+            }
+            Err(e) => format!("error: {}\n", e),
+        };
+        if writer.write_all(reply.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+// SIGUSR2 продлевает тест на extend_step
+pub fn spawn_signal_handler(stats: Stats, extend_step: Duration) -> Result<(), String> {
+    let mut signals = signal(SignalKind::user_defined2()).map_err(|e| format!("Failed to handle SIGUSR2: {}", e))?;
+    tokio::spawn(async move {
+        while signals.recv().await.is_some() {
+            match extend(&stats, extend_step) {
+                Ok(message) => println!("\n[SIGUSR2] {}", message),
+                Err(e) => println!("\n[SIGUSR2] ignored: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl ControlSocket {
+    pub fn bind(path: &Path, stats: Stats) -> Result<Self, String> {
+        remove_stale(path)?;
+        let listener =
+            UnixListener::bind(path).map_err(|e| format!("Failed to bind control socket {}: {}", path.display(), e))?;
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stats.clone(), stream));
+            }
+        });
+        Ok(Self { path: path.to_path_buf() })
+    }
+}
+
+// Оставшийся от прошлого прогона сокет удаляется. Обычный файл по этому пути и сокет, который еще
+// принимает подключения (идущий прогон), не трогаем
+fn remove_stale(path: &Path) -> Result<(), String> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to check control socket path {}: {}", path.display(), e)),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(format!("{} exists and is not a socket: choose another --control-socket path", path.display()));
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(format!("Control socket {} is in use by another running test", path.display()));
+    }
+    std::fs::remove_file(path).map_err(|e| format!("Failed to remove stale {}: {}", path.display(), e))
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
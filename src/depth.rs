@@ -0,0 +1,279 @@
+// Глубина архива (подкоманда depth): блоки и транзакции запрашиваются на растущей глубине от вершины
+// (текущий слот, -1k, -100k, -1M, -10M слотов). Латентность и доля ненайденных по уровням глубины
+// показывают, где у endpoint кончается горячее хранилище и начинается теплое и холодное (long-term storage)
+
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::{secrets, send_rpc_request, HeaderCapture, Rng, Stats};
+
+pub const DEFAULT_DEPTHS: [u64; 5] = [0, 1_000, 100_000, 1_000_000, 10_000_000];
+
+// Коды ошибок Solana RPC: слот пропущен лидером - не промах хранилища, блока там просто нет
+const SLOT_SKIPPED: i32 = -32007;
+const NOT_FOUND_CODES: [i32; 4] = [
+    -32004, // Block not available for slot
+    -32009, // Slot was skipped, or missing in long-term storage
+    -32011, // Transaction history is not available from this node
+    -32001, // Block cleaned up, does not exist on node
+];
+
+pub struct DepthOptions {
+    pub url: String,
+    pub depths: Vec<u64>,
+    pub samples: usize,
+    pub window: u64, // слоты уровня берутся из window слотов ниже вершины минус глубина
+    pub transactions: bool,
+    pub concurrency: usize,
+    pub http_timeout: Duration,
+    pub seed: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Outcome {
+    Found(u64), // латентность в микросекундах
+    NotFound,
+    Skipped,
+    Error,
+}
+
+fn outcome(response: &Result<crate::JsonRpcResponse, crate::RequestError>, latency_micros: u64) -> Outcome {
+    match response {
+        Ok(response) => match &response.error {
+            Some(error) if error.code == SLOT_SKIPPED => Outcome::Skipped,
+            Some(error) if NOT_FOUND_CODES.contains(&error.code) => Outcome::NotFound,
+            Some(_) => Outcome::Error,
+            None if response.result.as_ref().is_none_or(Value::is_null) => Outcome::NotFound,
+            None => Outcome::Found(latency_micros),
+        },
+        Err(_) => Outcome::Error,
+    }
+}
+
+// Блок уровня и, если в нем есть транзакции, одна из них
+struct Sample {
+    block: Outcome,
+    transaction: Option<Outcome>,
+}
+
+async fn sample(client: &reqwest::Client, url: &str, slot: u64, transactions: bool, seed: u64) -> Sample {
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+    // Только подписи: размер ответа не зависит от глубины, сравнивается доступ к хранилищу
+    let options = json!({
+        "commitment": "finalized",
+        "encoding": "json",
+        "transactionDetails": "signatures",
+        "maxSupportedTransactionVersion": 0,
+        "rewards": false
+    });
+    let request_start = Instant::now();
+    let response = send_rpc_request(client, url, "getBlock", vec![json!(slot), options], slot, &mut capture).await;
+    let block = outcome(&response, capture.network_micros(request_start));
+    if !transactions || !matches!(block, Outcome::Found(_)) {
+        return Sample { block, transaction: None };
+    }
+    let signatures: Vec<String> = response
+        .ok()
+        .and_then(|response| response.result)
+        .and_then(|result| serde_json::from_value(result.get("signatures")?.clone()).ok())
+        .unwrap_or_default();
+    if signatures.is_empty() {
+        return Sample { block, transaction: None };
+    }
+    let signature = &signatures[Rng::new(seed ^ slot).up_to(signatures.len() as u64 - 1) as usize];
+    let options = json!({ "commitment": "finalized", "encoding": "json", "maxSupportedTransactionVersion": 0 });
+    let request_start = Instant::now();
+    let response = send_rpc_request(client, url, "getTransaction", vec![json!(signature), options], slot, &mut capture).await;
+    Sample {
+        block,
+        transaction: Some(outcome(&response, capture.network_micros(request_start))),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct OutcomeSummary {
+    pub requests: usize,
+    pub found: usize,
+    pub not_found: usize,
+    pub skipped: usize,
+    pub errors: usize,
+    pub not_found_percent: Option<f64>, // среди найденных и ненайденных
+    pub average_ms: Option<f64>,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+}
+
+fn percentile_ms(sorted: &[u64], p: f64) -> Option<f64> {
+    let index = ((sorted.len() as f64 * p).ceil() as usize).saturating_sub(1);
+    (!sorted.is_empty()).then(|| sorted[index.min(sorted.len() - 1)] as f64 / 1000.0)
+}
+
+fn summarize(outcomes: &[Outcome]) -> OutcomeSummary {
+    let mut sorted: Vec<u64> = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            Outcome::Found(latency) => Some(*latency),
+            _ => None,
+        })
+        .collect();
+    sorted.sort_unstable();
+    let count = |expected: Outcome| outcomes.iter().filter(|&&outcome| outcome == expected).count();
+    let not_found = count(Outcome::NotFound);
+    let answered = sorted.len() + not_found;
+    OutcomeSummary {
+        requests: outcomes.len(),
+        found: sorted.len(),
+        not_found,
+        skipped: count(Outcome::Skipped),
+        errors: count(Outcome::Error),
+        not_found_percent: (answered > 0).then(|| not_found as f64 / answered as f64 * 100.0),
+        average_ms: (!sorted.is_empty()).then(|| sorted.iter().sum::<u64>() as f64 / sorted.len() as f64 / 1000.0),
+        p50_ms: percentile_ms(&sorted, 0.50),
+        p95_ms: percentile_ms(&sorted, 0.95),
+    }
+}
+
+#[derive(Debug)]
+pub struct TierResult {
+    pub depth: u64,
+    pub slots: Option<(u64, u64)>, // None - глубина больше высоты цепочки
+    pub blocks: OutcomeSummary,
+    pub transactions: OutcomeSummary,
+}
+
+#[derive(Debug)]
+pub struct DepthResult {
+    pub tip: u64,
+    pub first_available: Option<u64>,
+    pub tiers: Vec<TierResult>,
+}
+
+async fn get_u64(client: &reqwest::Client, url: &str, method: &str, params: Vec<Value>) -> Result<u64, String> {
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+    send_rpc_request(client, url, method, params, 1, &mut capture)
+        .await
+        .map_err(|e| format!("{} failed: {}", method, e))?
+        .result
+        .and_then(|value| value.as_u64())
+        .ok_or_else(|| format!("{} returned no number", method))
+}
+
+pub async fn run(options: DepthOptions) -> Result<DepthResult, String> {
+    let client = reqwest::Client::builder()
+        .timeout(options.http_timeout)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let tip = get_u64(&client, &options.url, "getSlot", vec![json!({ "commitment": "finalized" })]).await?;
+    // Не все endpoint отвечают на getFirstAvailableBlock: без него отчет просто не показывает границу
+    let first_available = get_u64(&client, &options.url, "getFirstAvailableBlock", Vec::new()).await.ok();
+
+    let mut rng = Rng::new(options.seed);
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let mut tiers = Vec::new();
+    for &depth in &options.depths {
+        let Some(top) = tip.checked_sub(depth) else {
+            tiers.push(TierResult {
+                depth,
+                slots: None,
+                blocks: OutcomeSummary::default(),
+                transactions: OutcomeSummary::default(),
+            });
+            continue;
+        };
+        let window = options.window.max(1).min(top + 1);
+        let mut slots = BTreeSet::new();
+        while slots.len() < options.samples.min(window as usize) {
+            slots.insert(top - rng.up_to(window - 1));
+        }
+        println!("Depth {}: {} slots in {}..={}...", depth, slots.len(), top + 1 - window, top);
+
+        let handles: Vec<_> = slots
+            .iter()
+            .map(|&slot| {
+                let semaphore = semaphore.clone();
+                let client = client.clone();
+                let url = options.url.clone();
+                let (transactions, seed) = (options.transactions, options.seed);
+                tokio::spawn(async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    sample(&client, &url, slot, transactions, seed).await
+                })
+            })
+            .collect();
+        let mut blocks = Vec::new();
+        let mut transactions = Vec::new();
+        for handle in handles {
+            let Ok(sample) = handle.await else {
+                blocks.push(Outcome::Error);
+                continue;
+            };
+            blocks.push(sample.block);
+            transactions.extend(sample.transaction);
+        }
+        tiers.push(TierResult {
+            depth,
+            slots: Some((top + 1 - window, top)),
+            blocks: summarize(&blocks),
+            transactions: summarize(&transactions),
+        });
+    }
+    Ok(DepthResult { tip, first_available, tiers })
+}
+
+fn print_table(title: &str, result: &DepthResult, summary: fn(&TierResult) -> &OutcomeSummary) {
+    let ms = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |ms| format!("{:.2}", ms));
+    println!("\n{}:", title);
+    println!(
+        "{:>10}  {:>8}  {:>6}  {:>9}  {:>7}  {:>6}  {:>10}  {:>9}  {:>9}  {:>9}",
+        "Depth", "Requests", "Found", "Not found", "Skipped", "Errors", "Not found%", "Avg ms", "p50 ms", "p95 ms"
+    );
+    for tier in &result.tiers {
+        if tier.slots.is_none() {
+            println!("{:>10}  beyond the first slot of the chain", tier.depth);
+            continue;
+        }
+        let summary = summary(tier);
+        println!(
+            "{:>10}  {:>8}  {:>6}  {:>9}  {:>7}  {:>6}  {:>10}  {:>9}  {:>9}  {:>9}",
+            tier.depth,
+            summary.requests,
+            summary.found,
+            summary.not_found,
+            summary.skipped,
+            summary.errors,
+            summary.not_found_percent.map_or_else(|| "-".to_string(), |percent| format!("{:.1}", percent)),
+            ms(summary.average_ms),
+            ms(summary.p50_ms),
+            ms(summary.p95_ms)
+        );
+    }
+}
+
+pub fn print_result(url: &str, result: &DepthResult, transactions: bool) {
+    println!("\n=== Archival Depth: {} ===", secrets::redact(url));
+    match result.first_available {
+        Some(first) => println!(
+            "Tip (finalized): {}, first available block: {} ({} slots deep)",
+            result.tip,
+            first,
+            result.tip.saturating_sub(first)
+        ),
+        None => println!("Tip (finalized): {}, first available block: unknown", result.tip),
+    }
+    print_table("Blocks (getBlock)", result, |tier| &tier.blocks);
+    if transactions {
+        print_table("Transactions (getTransaction, one per found block)", result, |tier| &tier.transactions);
+    }
+    // Граница хранилища: первый уровень, где ненайденных больше половины
+    let boundary = result
+        .tiers
+        .iter()
+        .find(|tier| tier.blocks.not_found_percent.is_some_and(|percent| percent > 50.0));
+    match boundary {
+        Some(tier) => println!("\nHistory boundary: most blocks are not found from depth {} slots", tier.depth),
+        None => println!("\nHistory boundary: blocks are found at every tested depth"),
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{send_rpc_request, HeaderCapture, Stats};
+
+// Нода из ответа getClusterNodes (нужные нам поля)
+#[derive(Deserialize, Debug)]
+struct ClusterNode {
+    pubkey: String,
+    rpc: Option<String>,
+    version: Option<String>,
+}
+
+// Нода с открытым RPC
+#[derive(Debug, Clone)]
+pub struct RpcNode {
+    pub url: String,
+    pub pubkey: String,
+    pub version: Option<String>,
+}
+
+// Запрашивает getClusterNodes у seed endpoint и возвращает ноды с открытым RPC (не больше max_nodes)
+pub async fn discover_rpc_nodes(
+    client: &reqwest::Client,
+    seed_url: &str,
+    max_nodes: Option<usize>,
+) -> Result<Vec<RpcNode>, String> {
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+    let response = send_rpc_request(client, seed_url, "getClusterNodes", Vec::new(), 1, &mut capture)
+        .await
+        .map_err(|e| format!("getClusterNodes failed: {}", e))?;
+    if let Some(error) = response.error {
+        return Err(format!("getClusterNodes failed: {} {}", error.code, error.message));
+    }
+    let nodes: Vec<ClusterNode> = serde_json::from_value(response.result.unwrap_or_default())
+        .map_err(|e| format!("Unexpected getClusterNodes result: {}", e))?;
+
+    let mut rpc_nodes: Vec<RpcNode> = nodes
+        .into_iter()
+        .filter_map(|node| {
+            node.rpc.map(|rpc| RpcNode {
+                url: format!("http://{}", rpc),
+                pubkey: node.pubkey,
+                version: node.version,
+            })
+        })
+        .collect();
+    rpc_nodes.sort_by(|a, b| a.url.cmp(&b.url));
+    rpc_nodes.dedup_by(|a, b| a.url == b.url);
+    if let Some(max_nodes) = max_nodes {
+        rpc_nodes.truncate(max_nodes);
+    }
+    if rpc_nodes.is_empty() {
+        return Err("getClusterNodes returned no nodes with RPC enabled".to_string());
+    }
+    Ok(rpc_nodes)
+}
+
+// Счетчики одного endpoint: попытки, успешные ответы и их латентность
+#[derive(Default)]
+pub struct NodeStats {
+    attempts: AtomicU64,
+    successful: AtomicU64,
+    latency_sum_micros: AtomicU64,
+    latency_max_micros: AtomicU64,
+}
+
+impl NodeStats {
+    pub fn record_attempts(&self, count: u64) {
+        self.attempts.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn record_success(&self, response_time_micros: u64, count: u64) {
+        self.successful.fetch_add(count, Ordering::Relaxed);
+        self.latency_sum_micros
+            .fetch_add(response_time_micros * count, Ordering::Relaxed);
+        self.latency_max_micros
+            .fetch_max(response_time_micros, Ordering::Relaxed);
+    }
+
+    // Счетчики завершившегося воркера (aggregator.rs)
+    pub fn merge(&mut self, other: Self) {
+        *self.attempts.get_mut() += other.attempts.into_inner();
+        *self.successful.get_mut() += other.successful.into_inner();
+        *self.latency_sum_micros.get_mut() += other.latency_sum_micros.into_inner();
+        let max = self.latency_max_micros.get_mut();
+        *max = (*max).max(other.latency_max_micros.into_inner());
+    }
+
+    pub fn summarize(&self, url: &str) -> NodeSummary {
+        let requests = self.attempts.load(Ordering::Relaxed);
+        let successful = self.successful.load(Ordering::Relaxed);
+        let average_ms = if successful > 0 {
+            self.latency_sum_micros.load(Ordering::Relaxed) as f64 / successful as f64 / 1000.0
+        } else {
+            0.0
+        };
+        NodeSummary {
+            url: url.to_string(),
+            requests,
+            successful,
+            success_rate: if requests > 0 {
+                successful as f64 / requests as f64 * 100.0
+            } else {
+                0.0
+            },
+            average_ms,
+            max_ms: self.latency_max_micros.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct NodeSummary {
+    pub url: String,
+    pub requests: u64,
+    pub successful: u64,
+    pub success_rate: f64,
+    pub average_ms: f64,
+    pub max_ms: f64,
+}
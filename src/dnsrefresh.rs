@@ -0,0 +1,208 @@
+// Повторное разрешение имени endpoint (--dns-refresh): у anycast и GeoDNS провайдеров ответ DNS меняется
+// во время долгого прогона, а пул keep-alive держит соединения со старыми адресами и это скрывает.
+// Имя разрешается заново каждые N секунд; новые соединения воркеров идут на адреса из последнего ответа
+// (каждый воркер начинает со своего адреса, при переподключении берет следующий), а после смены ответа
+// воркеры пересоздают клиентов - и с ними пул соединений. В отчете - все смены ответа по времени
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use serde::Serialize;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::aggregator::BucketClock;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DnsChange {
+    pub at_secs: f64, // от старта теста
+    pub at_utc: String,
+    pub addresses: Vec<IpAddr>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DnsRefreshSummary {
+    pub host: String,
+    pub interval_secs: u64,
+    pub lookups: u64,
+    pub failures: u64, // неудачные разрешения: остаются адреса прошлого ответа
+    pub initial: Vec<IpAddr>,
+    pub changes: Vec<DnsChange>,
+}
+
+#[derive(Default)]
+struct State {
+    addresses: Vec<IpAddr>,
+    initial: Vec<IpAddr>,
+    changes: Vec<DnsChange>,
+    lookups: u64,
+    failures: u64,
+}
+
+pub struct DnsRefresher {
+    host: String,
+    port: u16,
+    interval: Duration,
+    state: Mutex<State>,
+    generation: AtomicU64, // растет с каждой сменой ответа
+}
+
+impl DnsRefresher {
+    // Только для URL с именем хоста: у IP адреса разрешать нечего
+    pub fn new(url: &str, interval: Duration) -> Result<Self, String> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("--dns-refresh: invalid endpoint URL: {}", e))?;
+        let host = parsed.host_str().ok_or("--dns-refresh: endpoint URL has no host")?;
+        if host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().is_ok() {
+            return Err("--dns-refresh needs an endpoint with a hostname, not an IP address".to_string());
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port: parsed.port_or_known_default().unwrap_or(443),
+            interval,
+            state: Mutex::new(State::default()),
+            generation: AtomicU64::new(0),
+        })
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    fn addresses(&self) -> Vec<IpAddr> {
+        self.state.lock().unwrap().addresses.clone()
+    }
+
+    async fn lookup(&self) -> Result<Vec<IpAddr>, String> {
+        let mut addresses: Vec<IpAddr> = tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await
+            .map_err(|e| format!("failed to resolve {}: {}", self.host, e))?
+            .map(|address| address.ip())
+            .collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+        Ok(addresses)
+    }
+
+    // Первое разрешение до старта нагрузки: без адресов воркерам не к чему подключаться
+    pub async fn resolve_initial(&self) -> Result<Vec<IpAddr>, String> {
+        let addresses = self.lookup().await?;
+        if addresses.is_empty() {
+            return Err(format!("{} resolved to no addresses", self.host));
+        }
+        let mut state = self.state.lock().unwrap();
+        state.lookups += 1;
+        state.addresses = addresses.clone();
+        state.initial = addresses.clone();
+        Ok(addresses)
+    }
+
+    async fn refresh(&self, clock: &BucketClock) {
+        let result = self.lookup().await;
+        let mut state = self.state.lock().unwrap();
+        state.lookups += 1;
+        match result {
+            Ok(addresses) if !addresses.is_empty() => {
+                if addresses != state.addresses {
+                    let at_secs = clock.elapsed_secs();
+                    state.changes.push(DnsChange {
+                        at_secs,
+                        at_utc: clock.utc(at_secs),
+                        addresses: addresses.clone(),
+                    });
+                    state.addresses = addresses;
+                    self.generation.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            _ => state.failures += 1,
+        }
+    }
+
+    pub fn summarize(&self) -> DnsRefreshSummary {
+        let state = self.state.lock().unwrap();
+        DnsRefreshSummary {
+            host: self.host.clone(),
+            interval_secs: self.interval.as_secs(),
+            lookups: state.lookups,
+            failures: state.failures,
+            initial: state.initial.clone(),
+            changes: state.changes.clone(),
+        }
+    }
+}
+
+// Фоновое разрешение до конца процесса
+pub fn spawn(refresher: Arc<DnsRefresher>, clock: BucketClock) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(refresher.interval).await;
+            refresher.refresh(&clock).await;
+        }
+    });
+}
+
+// Резолвер клиента воркера: имя endpoint - по последнему ответу, начиная со своего адреса, остальные имена
+// (например, URL ключей пула на другом хосте) - обычным разрешением
+pub struct WorkerResolver {
+    refresher: Arc<DnsRefresher>,
+    next: AtomicUsize, // сдвиг по списку адресов: номер воркера, затем +1 на каждое новое соединение
+}
+
+impl WorkerResolver {
+    pub fn new(refresher: Arc<DnsRefresher>, worker_id: usize) -> Self {
+        Self {
+            refresher,
+            next: AtomicUsize::new(worker_id),
+        }
+    }
+}
+
+impl Resolve for WorkerResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let mut addresses = if name.as_str() == self.refresher.host { self.refresher.addresses() } else { Vec::new() };
+        if addresses.is_empty() {
+            let host = name.as_str().to_string();
+            return Box::pin(async move {
+                let resolved = tokio::net::lookup_host((host.as_str(), 0)).await?;
+                Ok(Box::new(resolved.collect::<Vec<_>>().into_iter()) as Addrs)
+            });
+        }
+        let shift = self.next.fetch_add(1, Ordering::Relaxed) % addresses.len();
+        addresses.rotate_left(shift);
+        // Порт reqwest берет из URL
+        let addrs: Vec<SocketAddr> = addresses.into_iter().map(|ip| SocketAddr::new(ip, 0)).collect();
+        Box::pin(async move { Ok(Box::new(addrs.into_iter()) as Addrs) })
+    }
+}
+
+// Клиент воркера, который пересоздается после смены ответа DNS: новый клиент - новый пул соединений
+pub struct ClientRefresh {
+    refresher: Arc<DnsRefresher>,
+    build: Box<dyn Fn() -> reqwest::Client + Send + Sync>,
+    current: Mutex<(u64, reqwest::Client)>, // поколение ответа DNS и клиент, созданный при нем
+}
+
+impl ClientRefresh {
+    // client - уже созданный при текущем ответе DNS
+    pub fn new(refresher: Arc<DnsRefresher>, client: reqwest::Client, build: Box<dyn Fn() -> reqwest::Client + Send + Sync>) -> Self {
+        let current = (refresher.generation(), client);
+        Self {
+            refresher,
+            build,
+            current: Mutex::new(current),
+        }
+    }
+
+    pub fn client(&self) -> reqwest::Client {
+        let generation = self.refresher.generation();
+        let mut current = self.current.lock().unwrap();
+        if current.0 != generation {
+            *current = (generation, (self.build)());
+        }
+        current.1.clone()
+    }
+}
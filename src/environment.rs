@@ -0,0 +1,70 @@
+// Окружение клиента в каждом отчете: версия инструмента и компилятора, ОС, CPU, потоки tokio и лимит
+// открытых файлов. От них зависит, какую нагрузку клиент вообще может дать, и при сравнении результатов
+// с разных машин о них спрашивают первым делом
+
+use serde::Serialize;
+
+use crate::i18n::Texts;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ClientEnvironment {
+    pub tool_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub target: &'static str,
+    pub rustc: &'static str,
+    pub cpus: Option<usize>,
+    pub tokio_workers: usize,
+    pub open_files_soft: Option<String>, // ulimit -n; None - лимит неизвестен (не Linux)
+    pub open_files_hard: Option<String>,
+}
+
+// Лимиты текущего процесса из /proc/self/limits: "Max open files  1024  4096  files"
+fn open_files_limits() -> (Option<String>, Option<String>) {
+    let Ok(limits) = std::fs::read_to_string("/proc/self/limits") else {
+        return (None, None);
+    };
+    let Some(line) = limits.lines().find(|line| line.starts_with("Max open files")) else {
+        return (None, None);
+    };
+    let mut values = line["Max open files".len()..].split_whitespace().map(str::to_string);
+    (values.next(), values.next())
+}
+
+// Вызывается внутри рантайма tokio
+pub fn capture() -> ClientEnvironment {
+    let (open_files_soft, open_files_hard) = open_files_limits();
+    ClientEnvironment {
+        tool_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        target: env!("BUILD_TARGET"),
+        rustc: env!("BUILD_RUSTC_VERSION"),
+        cpus: std::thread::available_parallelism().ok().map(|cpus| cpus.get()),
+        tokio_workers: tokio::runtime::Handle::current().metrics().num_workers(),
+        open_files_soft,
+        open_files_hard,
+    }
+}
+
+impl ClientEnvironment {
+    // Одна строка для текстового отчета и тикета
+    pub fn describe(&self, t: &Texts) -> String {
+        let unknown = || "?".to_string();
+        format!(
+            "{} {}, {} {} ({}, {}), {} {}, {}, {} {}/{}",
+            env!("CARGO_PKG_NAME"),
+            self.tool_version,
+            self.os,
+            self.arch,
+            self.target,
+            self.rustc,
+            self.cpus.map_or_else(unknown, |cpus| cpus.to_string()),
+            t.cpus,
+            t.count(self.tokio_workers as u64, &t.tokio_workers),
+            t.open_files_limit,
+            self.open_files_soft.clone().unwrap_or_else(unknown),
+            self.open_files_hard.clone().unwrap_or_else(unknown)
+        )
+    }
+}
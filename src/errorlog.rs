@@ -0,0 +1,59 @@
+// Последние ошибки прогона (--last-errors): кольцевой буфер из K последних ошибок воркеров - время,
+// воркер, категория и текст. Счетчики в отчете говорят, сколько было ошибок, а буфер - какие именно
+// были в конце, без --debug и без потока вывода каждого запроса
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+pub const DEFAULT_CAPACITY: usize = 10;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ErrorEntry {
+    pub at_secs: f64, // от старта теста
+    pub at_utc: String,
+    pub worker: usize,
+    pub category: &'static str, // http, timeout, network, parse, rpc, ...
+    pub message: String,
+}
+
+pub struct ErrorLog {
+    capacity: usize,
+    entries: Mutex<VecDeque<ErrorEntry>>,
+    total: AtomicU64, // всего ошибок, включая вытесненные из буфера
+}
+
+#[derive(Serialize, Debug)]
+pub struct ErrorLogSummary {
+    pub total: u64,
+    pub entries: Vec<ErrorEntry>, // от старых к новым
+}
+
+impl ErrorLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            total: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, entry: ErrorEntry) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    // None - ошибок не было
+    pub fn summarize(&self) -> Option<ErrorLogSummary> {
+        let entries: Vec<ErrorEntry> = self.entries.lock().unwrap().iter().cloned().collect();
+        (!entries.is_empty()).then(|| ErrorLogSummary {
+            total: self.total.load(Ordering::Relaxed),
+            entries,
+        })
+    }
+}
@@ -0,0 +1,120 @@
+// CSV фидеры сценариев: каждая строка файла дает значения переменных на одну итерацию
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::Rng;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeederConfig {
+    pub file: String, // относительный путь - от каталога конфига
+    #[serde(default)]
+    pub strategy: FeederStrategy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeederStrategy {
+    // Строки по кругу, общий курсор на все воркеры сценария
+    #[default]
+    Circular,
+    // Случайная строка на каждую итерацию
+    Random,
+    // Каждая строка ровно один раз; когда строки закончились, воркеры сценария останавливаются
+    Once,
+}
+
+pub struct Feeder {
+    pub columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    strategy: FeederStrategy,
+    cursor: AtomicUsize,
+}
+
+impl Feeder {
+    pub fn load(config: &FeederConfig, base_dir: &Path) -> Result<Self, String> {
+        let path = base_dir.join(&config.file);
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read feeder file {}: {}", path.display(), e))?;
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        let columns: Vec<String> = match lines.next() {
+            Some(header) => parse_line(header).into_iter().map(|c| c.trim().to_string()).collect(),
+            None => return Err(format!("Feeder file {} is empty", path.display())),
+        };
+        let mut rows = Vec::new();
+        for (index, line) in lines.enumerate() {
+            let fields = parse_line(line);
+            if fields.len() != columns.len() {
+                return Err(format!(
+                    "Feeder file {}: row {} has {} fields, header has {}",
+                    path.display(),
+                    index + 2,
+                    fields.len(),
+                    columns.len()
+                ));
+            }
+            rows.push(fields.into_iter().map(field_value).collect());
+        }
+        if rows.is_empty() {
+            return Err(format!("Feeder file {} has no data rows", path.display()));
+        }
+        Ok(Self {
+            columns,
+            rows,
+            strategy: config.strategy,
+            cursor: AtomicUsize::new(0),
+        })
+    }
+
+    // Переменные следующей строки; None - строки для стратегии once закончились
+    pub fn next(&self, rng: &mut Rng) -> Option<BTreeMap<String, Value>> {
+        let index = match self.strategy {
+            FeederStrategy::Circular => self.cursor.fetch_add(1, Ordering::Relaxed) % self.rows.len(),
+            FeederStrategy::Random => rng.up_to(self.rows.len() as u64 - 1) as usize,
+            FeederStrategy::Once => {
+                let index = self.cursor.fetch_add(1, Ordering::Relaxed);
+                if index >= self.rows.len() {
+                    return None;
+                }
+                index
+            }
+        };
+        Some(self.columns.iter().cloned().zip(self.rows[index].iter().cloned()).collect())
+    }
+}
+
+// Числа становятся числами JSON (слоты, лимиты), остальное - строками
+fn field_value(field: String) -> Value {
+    if let Ok(number) = field.parse::<u64>() {
+        return Value::from(number);
+    }
+    if let Ok(number) = field.parse::<i64>() {
+        return Value::from(number);
+    }
+    Value::String(field)
+}
+
+// Разбор строки CSV: разделитель запятая, поля в кавычках могут содержать запятые и "" как кавычку
+fn parse_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
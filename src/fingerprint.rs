@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{send_rpc_request, HeaderCapture, Stats};
+
+// С чем именно мерили: версия ПО, кластер (по genesis hash) и конкретная нода.
+// Ответы сохраняются как есть, отсутствующее поле - запрос не удался
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct EndpointFingerprint {
+    pub version: Option<Value>, // результат getVersion: solana-core и feature-set
+    pub genesis_hash: Option<String>,
+    pub identity: Option<String>,
+}
+
+impl EndpointFingerprint {
+    pub fn solana_core(&self) -> Option<&str> {
+        self.version.as_ref()?.get("solana-core")?.as_str()
+    }
+
+    pub fn feature_set(&self) -> Option<u64> {
+        self.version.as_ref()?.get("feature-set")?.as_u64()
+    }
+}
+
+async fn call(client: &reqwest::Client, url: &str, method: &str, request_id: u64) -> Option<Value> {
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+    let response = send_rpc_request(client, url, method, Vec::new(), request_id, &mut capture)
+        .await
+        .ok()?;
+    if response.error.is_some() {
+        return None;
+    }
+    response.result
+}
+
+// Запрашивает getVersion, getGenesisHash и getIdentity до старта теста.
+// Ошибки не прерывают прогон: в отчете просто не будет соответствующего поля
+pub async fn collect(client: &reqwest::Client, url: &str) -> EndpointFingerprint {
+    let version = call(client, url, "getVersion", 1).await;
+    let genesis_hash = call(client, url, "getGenesisHash", 2)
+        .await
+        .and_then(|r| r.as_str().map(str::to_string));
+    let identity = call(client, url, "getIdentity", 3)
+        .await
+        .and_then(|r| r.get("identity")?.as_str().map(str::to_string));
+    EndpointFingerprint {
+        version,
+        genesis_hash,
+        identity,
+    }
+}
+
+// Genesis hash публичных кластеров
+const MAINNET_GENESIS: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+const DEVNET_GENESIS: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+const TESTNET_GENESIS: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+// Кластер, против которого разрешено запускать тест (--expect-cluster / expect_cluster)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum ExpectedCluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Custom(String), // custom:<genesis hash>
+}
+
+impl ExpectedCluster {
+    fn genesis_hash(&self) -> &str {
+        match self {
+            ExpectedCluster::Mainnet => MAINNET_GENESIS,
+            ExpectedCluster::Devnet => DEVNET_GENESIS,
+            ExpectedCluster::Testnet => TESTNET_GENESIS,
+            ExpectedCluster::Custom(hash) => hash,
+        }
+    }
+
+    // Сверяет genesis hash endpoint с ожидаемым; не удалось получить hash - тоже отказ
+    pub fn check(&self, url: &str, endpoint: &EndpointFingerprint) -> Result<(), String> {
+        let actual = endpoint.genesis_hash.as_deref().ok_or_else(|| {
+            format!(
+                "Could not get genesis hash from {} to verify --expect-cluster {}; refusing to run",
+                url, self
+            )
+        })?;
+        if actual != self.genesis_hash() {
+            let known = cluster_name(actual).map_or_else(String::new, |name| format!(" ({})", name));
+            return Err(format!(
+                "Cluster mismatch: expected {} (genesis {}), but {} reports genesis {}{}; refusing to run",
+                self,
+                self.genesis_hash(),
+                url,
+                actual,
+                known
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn cluster_name(genesis_hash: &str) -> Option<&'static str> {
+    match genesis_hash {
+        MAINNET_GENESIS => Some("mainnet"),
+        DEVNET_GENESIS => Some("devnet"),
+        TESTNET_GENESIS => Some("testnet"),
+        _ => None,
+    }
+}
+
+impl FromStr for ExpectedCluster {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "mainnet" | "mainnet-beta" => Ok(ExpectedCluster::Mainnet),
+            "devnet" => Ok(ExpectedCluster::Devnet),
+            "testnet" => Ok(ExpectedCluster::Testnet),
+            _ => match value.strip_prefix("custom:") {
+                Some(hash) if !hash.is_empty() => Ok(ExpectedCluster::Custom(hash.to_string())),
+                _ => Err(format!(
+                    "Invalid cluster '{}': expected mainnet, devnet, testnet or custom:<genesis hash>",
+                    value
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for ExpectedCluster {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ExpectedCluster> for String {
+    fn from(cluster: ExpectedCluster) -> Self {
+        cluster.to_string()
+    }
+}
+
+impl fmt::Display for ExpectedCluster {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpectedCluster::Mainnet => write!(f, "mainnet"),
+            ExpectedCluster::Devnet => write!(f, "devnet"),
+            ExpectedCluster::Testnet => write!(f, "testnet"),
+            ExpectedCluster::Custom(hash) => write!(f, "custom:{}", hash),
+        }
+    }
+}
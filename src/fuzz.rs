@@ -0,0 +1,356 @@
+// Фаззинг запросов (подкоманда fuzz): endpoint получает заведомо неправильные JSON-RPC тела - не JSON,
+// неверные типы, пропущенные поля, огромные ID, невалидный UTF-8, слишком большие params - с заданной
+// частотой, а ответы классифицируются. Проверяется устойчивость шлюза, а не производительность:
+// корректный endpoint отвечает JSON-RPC ошибкой или HTTP 4xx, а не 5xx, таймаутом или обрывом соединения
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::{secrets, Rng};
+
+// Размер строки в params для oversized-params
+const OVERSIZED_PARAM_BYTES: usize = 2 * 1024 * 1024;
+// Глубина вложенности массивов для deep-nesting
+const NESTING_DEPTH: usize = 10_000;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FuzzCase {
+    /// Body is not JSON at all
+    NotJson,
+    /// Truncated JSON object
+    TruncatedJson,
+    /// Invalid UTF-8 inside the method name
+    InvalidUtf8,
+    /// Fields of the wrong types (numeric method, string params)
+    WrongTypes,
+    /// Object without jsonrpc and method
+    MissingFields,
+    /// jsonrpc version other than 2.0
+    WrongVersion,
+    /// Numeric id far beyond 64 bits and a very long string id
+    HugeId,
+    /// Method that does not exist
+    UnknownMethod,
+    /// Valid method with a multi-megabyte parameter
+    OversizedParams,
+    /// Params nested thousands of levels deep
+    DeepNesting,
+    /// Empty batch array
+    EmptyBatch,
+    /// Random bytes
+    RandomBytes,
+}
+
+impl FuzzCase {
+    pub const ALL: [FuzzCase; 12] = [
+        FuzzCase::NotJson,
+        FuzzCase::TruncatedJson,
+        FuzzCase::InvalidUtf8,
+        FuzzCase::WrongTypes,
+        FuzzCase::MissingFields,
+        FuzzCase::WrongVersion,
+        FuzzCase::HugeId,
+        FuzzCase::UnknownMethod,
+        FuzzCase::OversizedParams,
+        FuzzCase::DeepNesting,
+        FuzzCase::EmptyBatch,
+        FuzzCase::RandomBytes,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            FuzzCase::NotJson => "not-json",
+            FuzzCase::TruncatedJson => "truncated-json",
+            FuzzCase::InvalidUtf8 => "invalid-utf8",
+            FuzzCase::WrongTypes => "wrong-types",
+            FuzzCase::MissingFields => "missing-fields",
+            FuzzCase::WrongVersion => "wrong-version",
+            FuzzCase::HugeId => "huge-id",
+            FuzzCase::UnknownMethod => "unknown-method",
+            FuzzCase::OversizedParams => "oversized-params",
+            FuzzCase::DeepNesting => "deep-nesting",
+            FuzzCase::EmptyBatch => "empty-batch",
+            FuzzCase::RandomBytes => "random-bytes",
+        }
+    }
+
+    // Код ошибки, которого требует спецификация JSON-RPC 2.0 (None - спецификация допускает разное)
+    fn expected_code(self) -> Option<i64> {
+        match self {
+            FuzzCase::NotJson | FuzzCase::TruncatedJson | FuzzCase::InvalidUtf8 | FuzzCase::RandomBytes => Some(-32700),
+            FuzzCase::WrongTypes | FuzzCase::MissingFields | FuzzCase::WrongVersion | FuzzCase::EmptyBatch => Some(-32600),
+            FuzzCase::UnknownMethod => Some(-32601),
+            FuzzCase::HugeId | FuzzCase::OversizedParams | FuzzCase::DeepNesting => None,
+        }
+    }
+
+    // Тело запроса; id варьируется, чтобы повторы не отвечались из кэша
+    fn body(self, rng: &mut Rng) -> Vec<u8> {
+        let id = rng.up_to(1_000_000);
+        match self {
+            FuzzCase::NotJson => format!("jsonrpc=2.0&id={}&method=getSlot", id).into_bytes(),
+            FuzzCase::TruncatedJson => {
+                let full = format!(r#"{{"jsonrpc":"2.0","id":{},"method":"getSlot","params":[]}}"#, id);
+                let cut = 1 + rng.up_to(full.len() as u64 - 2) as usize;
+                full.as_bytes()[..cut].to_vec()
+            }
+            FuzzCase::InvalidUtf8 => {
+                let mut body = format!(r#"{{"jsonrpc":"2.0","id":{},"method":"get"#, id).into_bytes();
+                body.extend_from_slice(&[0xff, 0xfe, 0xc3, 0x28]);
+                body.extend_from_slice(br#"Slot","params":[]}"#);
+                body
+            }
+            FuzzCase::WrongTypes => format!(r#"{{"jsonrpc":2.0,"id":{{"n":{}}},"method":12345,"params":"getSlot"}}"#, id).into_bytes(),
+            FuzzCase::MissingFields => format!(r#"{{"id":{}}}"#, id).into_bytes(),
+            FuzzCase::WrongVersion => format!(r#"{{"jsonrpc":"1.0","id":{},"method":"getSlot","params":[]}}"#, id).into_bytes(),
+            FuzzCase::HugeId => {
+                if rng.up_to(1) == 0 {
+                    format!(r#"{{"jsonrpc":"2.0","id":{}{},"method":"getSlot","params":[]}}"#, id, "9".repeat(400)).into_bytes()
+                } else {
+                    format!(r#"{{"jsonrpc":"2.0","id":"{}{}","method":"getSlot","params":[]}}"#, id, "x".repeat(64 * 1024)).into_bytes()
+                }
+            }
+            FuzzCase::UnknownMethod => {
+                format!(r#"{{"jsonrpc":"2.0","id":{},"method":"fuzzUnknownMethod{}","params":[]}}"#, id, rng.up_to(9999)).into_bytes()
+            }
+            FuzzCase::OversizedParams => {
+                let huge = Value::String("1".repeat(OVERSIZED_PARAM_BYTES));
+                let request = serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "getBalance", "params": [huge] });
+                serde_json::to_vec(&request).unwrap_or_default()
+            }
+            FuzzCase::DeepNesting => {
+                let mut body = format!(r#"{{"jsonrpc":"2.0","id":{},"method":"getSlot","params":"#, id);
+                body.push_str(&"[".repeat(NESTING_DEPTH));
+                body.push_str(&"]".repeat(NESTING_DEPTH));
+                body.push('}');
+                body.into_bytes()
+            }
+            FuzzCase::EmptyBatch => b"[]".to_vec(),
+            FuzzCase::RandomBytes => (0..1 + rng.up_to(1023)).map(|_| rng.next_u64() as u8).collect(),
+        }
+    }
+}
+
+// Как endpoint обошелся с неправильным запросом
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    RpcError(i64),     // JSON-RPC ошибка с кодом: правильный ответ
+    HttpRejected(u16), // HTTP 4xx: отклонен до JSON-RPC, тоже нормально
+    Accepted,          // результат, как у правильного запроса
+    ServerError(u16),  // HTTP 5xx: шлюз или нода не справились
+    InvalidResponse,   // тело не JSON-RPC ответ
+    Timeout,
+    ConnectionError,   // обрыв или сброс соединения
+}
+
+impl Outcome {
+    // Сбой endpoint: на неправильный запрос нельзя падать, зависать и отвечать мусором
+    fn failure(self) -> bool {
+        matches!(self, Outcome::ServerError(_) | Outcome::InvalidResponse | Outcome::Timeout | Outcome::ConnectionError)
+    }
+}
+
+// Разбор ответа: одиночный объект или массив (ответ на батч - по первому элементу)
+fn classify_body(body: &[u8]) -> Outcome {
+    let value: Value = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(_) => return Outcome::InvalidResponse,
+    };
+    let response = match &value {
+        Value::Array(items) => match items.first() {
+            Some(first) => first,
+            None => return Outcome::InvalidResponse,
+        },
+        other => other,
+    };
+    if let Some(error) = response.get("error") {
+        return Outcome::RpcError(error.get("code").and_then(Value::as_i64).unwrap_or(0));
+    }
+    if response.get("result").is_some() {
+        return Outcome::Accepted;
+    }
+    Outcome::InvalidResponse
+}
+
+async fn send(client: &reqwest::Client, url: &str, body: Vec<u8>) -> Outcome {
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await;
+    let response = match response {
+        Ok(response) => response,
+        Err(e) if e.is_timeout() => return Outcome::Timeout,
+        Err(_) => return Outcome::ConnectionError,
+    };
+    let status = response.status();
+    let body = match response.bytes().await {
+        Ok(body) => body,
+        Err(e) if e.is_timeout() => return Outcome::Timeout,
+        Err(_) => return Outcome::ConnectionError,
+    };
+    if status.is_server_error() {
+        return Outcome::ServerError(status.as_u16());
+    }
+    // Тело 4xx может быть и JSON-RPC ошибкой: она точнее статуса
+    match classify_body(&body) {
+        Outcome::InvalidResponse if status.is_client_error() => Outcome::HttpRejected(status.as_u16()),
+        outcome => outcome,
+    }
+}
+
+pub struct FuzzOptions {
+    pub url: String,
+    pub cases: Vec<FuzzCase>,
+    pub count: usize,
+    pub rate: f64,
+    pub concurrency: usize,
+    pub http_timeout: Duration,
+    pub seed: u64,
+}
+
+#[derive(Default, Debug)]
+pub struct CaseResult {
+    pub sent: usize,
+    pub outcomes: BTreeMap<Outcome, usize>,
+    pub total_micros: u64,
+}
+
+impl CaseResult {
+    pub fn failures(&self) -> usize {
+        self.outcomes.iter().filter(|(outcome, _)| outcome.failure()).map(|(_, count)| count).sum()
+    }
+}
+
+#[derive(Debug)]
+pub struct FuzzReport {
+    pub url: String,
+    pub cases: BTreeMap<FuzzCase, CaseResult>,
+}
+
+impl FuzzReport {
+    pub fn failed(&self) -> bool {
+        self.cases.values().any(|result| result.failures() > 0)
+    }
+}
+
+pub async fn run(options: FuzzOptions) -> Result<FuzzReport, String> {
+    if options.cases.is_empty() || options.count == 0 {
+        return Err("Nothing to send: no cases or --count 0".to_string());
+    }
+    if !(options.rate > 0.0 && options.rate.is_finite()) {
+        return Err("--rate must be a positive number".to_string());
+    }
+    let client = reqwest::Client::builder()
+        .timeout(options.http_timeout)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    println!(
+        "Fuzzing {} with {} malformed requests ({} cases, {} req/s)...",
+        secrets::redact(&options.url),
+        options.count,
+        options.cases.len(),
+        options.rate
+    );
+
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / options.rate));
+    let mut rng = Rng::new(options.seed);
+    let mut handles = Vec::with_capacity(options.count);
+    // Случаи идут по кругу: каждый получает поровну запросов
+    for index in 0..options.count {
+        ticker.tick().await;
+        let case = options.cases[index % options.cases.len()];
+        let body = case.body(&mut rng);
+        let permit = semaphore.clone().acquire_owned().await.map_err(|e| e.to_string())?;
+        let client = client.clone();
+        let url = options.url.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let start = Instant::now();
+            let outcome = send(&client, &url, body).await;
+            (case, outcome, start.elapsed().as_micros() as u64)
+        }));
+    }
+
+    let mut cases: BTreeMap<FuzzCase, CaseResult> = BTreeMap::new();
+    for handle in handles {
+        let (case, outcome, micros) = handle.await.map_err(|e| e.to_string())?;
+        let result = cases.entry(case).or_default();
+        result.sent += 1;
+        *result.outcomes.entry(outcome).or_default() += 1;
+        result.total_micros += micros;
+    }
+    Ok(FuzzReport {
+        url: options.url,
+        cases,
+    })
+}
+
+fn describe(outcome: Outcome) -> String {
+    match outcome {
+        Outcome::RpcError(code) => format!("rpc {}", code),
+        Outcome::HttpRejected(status) => format!("http {}", status),
+        Outcome::Accepted => "accepted".to_string(),
+        Outcome::ServerError(status) => format!("HTTP {} (server error)", status),
+        Outcome::InvalidResponse => "INVALID RESPONSE".to_string(),
+        Outcome::Timeout => "TIMEOUT".to_string(),
+        Outcome::ConnectionError => "CONNECTION ERROR".to_string(),
+    }
+}
+
+pub fn print_report(report: &FuzzReport) {
+    println!("\n=== Fuzz: {} ===", secrets::redact(&report.url));
+    println!("{:<16}  {:>5}  {:>8}  {:>8}  {:<8}  Responses", "Case", "Sent", "Failures", "Avg ms", "Expected");
+    for (case, result) in &report.cases {
+        let outcomes: Vec<String> =
+            result.outcomes.iter().map(|(outcome, count)| format!("{} × {}", describe(*outcome), count)).collect();
+        println!(
+            "{:<16}  {:>5}  {:>8}  {:>8.2}  {:<8}  {}",
+            case.name(),
+            result.sent,
+            result.failures(),
+            result.total_micros as f64 / result.sent.max(1) as f64 / 1000.0,
+            case.expected_code().map_or_else(|| "-".to_string(), |code| code.to_string()),
+            outcomes.join(", ")
+        );
+    }
+
+    // Ответ с другим кодом, чем требует спецификация, - не сбой, но клиент не сможет отличить ошибку
+    let nonstandard: Vec<&str> = report
+        .cases
+        .iter()
+        .filter(|(case, result)| {
+            case.expected_code().is_some_and(|expected| {
+                result.outcomes.keys().any(|outcome| matches!(outcome, Outcome::RpcError(code) if *code != expected))
+            })
+        })
+        .map(|(case, _)| case.name())
+        .collect();
+    let accepted: Vec<&str> = report
+        .cases
+        .iter()
+        .filter(|(_, result)| result.outcomes.contains_key(&Outcome::Accepted))
+        .map(|(case, _)| case.name())
+        .collect();
+    if !nonstandard.is_empty() {
+        println!("\nNon-standard error codes: {}", nonstandard.join(", "));
+    }
+    if !accepted.is_empty() {
+        println!("Accepted as valid requests: {}", accepted.join(", "));
+    }
+    let failed: Vec<&str> =
+        report.cases.iter().filter(|(_, result)| result.failures() > 0).map(|(case, _)| case.name()).collect();
+    if failed.is_empty() {
+        println!("\nRobustness: OK, every malformed request got a JSON-RPC error or an HTTP 4xx");
+    } else {
+        println!(
+            "\nRobustness: FAILED on {}: server errors, timeouts, dropped connections or non-JSON-RPC responses",
+            failed.join(", ")
+        );
+    }
+}
@@ -0,0 +1,584 @@
+// История прогонов (--history): итоговые метрики каждого прогона дописываются в локальную SQLite базу,
+// подкоманда history выводит прошлые прогоны по endpoint и сравнивает два из них.
+// SQLite - системная libsqlite3 за feature history, как zlib и brotli в compression.rs
+
+use std::collections::BTreeMap;
+
+use crate::report::{self, Summary, Verdict};
+
+// --history без feature history завершается ошибкой до прогона, а не после него
+pub fn check_available(history: bool) -> Result<(), String> {
+    if history && !cfg!(feature = "history") {
+        return Err(missing_feature());
+    }
+    Ok(())
+}
+
+fn missing_feature() -> String {
+    "Run history is not built in: rebuild with --features history".to_string()
+}
+
+#[cfg(feature = "history")]
+pub use sqlite::{append, get, list};
+
+#[cfg(not(feature = "history"))]
+pub fn append(_path: &str, _run: &RunRecord) -> Result<i64, String> {
+    Err(missing_feature())
+}
+
+#[cfg(not(feature = "history"))]
+pub fn list(_path: &str, _filter: Option<&str>, _limit: usize) -> Result<Vec<RunRecord>, String> {
+    Err(missing_feature())
+}
+
+#[cfg(not(feature = "history"))]
+pub fn get(_path: &str, _id: i64) -> Result<RunRecord, String> {
+    Err(missing_feature())
+}
+
+// Строка истории: один прогон
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "history"), allow(dead_code))] // часть полей читает только запись в базу
+pub struct RunRecord {
+    pub id: i64,
+    pub started_at: u64, // unix время старта
+    pub endpoint: String,
+    pub workload: String, // методы и сценарии с числом воркеров
+    pub tags: String,
+    pub duration_secs: f64,
+    pub total_requests: u64,
+    pub successful_requests: u64,
+    pub success_rate: f64,
+    pub rps: f64,
+    pub average_ms: f64,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub max_ms: f64,
+    pub passed: bool,
+    pub violations: String,
+}
+
+fn percentile_ms(sorted: &[u64], p: f64) -> Option<f64> {
+    let index = ((sorted.len() as f64 * p).ceil() as usize).saturating_sub(1);
+    (!sorted.is_empty()).then(|| sorted[index.min(sorted.len() - 1)] as f64 / 1000.0)
+}
+
+impl RunRecord {
+    pub fn from_summary(endpoint: &str, workload: String, summary: &Summary, verdict: &Verdict) -> Self {
+        let mut sorted = summary.latencies_micros.clone();
+        sorted.sort_unstable();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            id: 0,
+            started_at: now.saturating_sub(summary.elapsed_secs as u64),
+            endpoint: endpoint.to_string(),
+            workload,
+            tags: crate::format_tags(&summary.tags),
+            duration_secs: summary.elapsed_secs,
+            total_requests: summary.total_requests,
+            successful_requests: summary.successful_requests,
+            success_rate: summary.success_rate,
+            rps: summary.total_requests as f64 / summary.elapsed_secs.max(0.001),
+            average_ms: summary.latency.average_ms,
+            p50_ms: percentile_ms(&sorted, 0.50),
+            p95_ms: percentile_ms(&sorted, 0.95),
+            p99_ms: percentile_ms(&sorted, 0.99),
+            max_ms: summary.latency.max_ms,
+            passed: verdict.passed,
+            violations: verdict.violations.join("; "),
+        }
+    }
+}
+
+fn format_ms(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |ms| format!("{:.2}", ms))
+}
+
+// Изменение относительно прошлого значения в процентах
+fn change(before: Option<f64>, after: Option<f64>) -> String {
+    match (before, after) {
+        (Some(before), Some(after)) if before > 0.0 => format!("{:+.1}%", (after - before) / before * 100.0),
+        _ => "-".to_string(),
+    }
+}
+
+// Прогоны по endpoint; изменение p95 и RPS - относительно предыдущего прогона того же endpoint
+pub fn print_runs(runs: &[RunRecord]) {
+    if runs.is_empty() {
+        println!("No runs in history");
+        return;
+    }
+    let mut by_endpoint: BTreeMap<&str, Vec<&RunRecord>> = BTreeMap::new();
+    for run in runs {
+        by_endpoint.entry(run.endpoint.as_str()).or_default().push(run);
+    }
+    for (endpoint, runs) in by_endpoint {
+        println!("\n=== {} ===", endpoint);
+        println!(
+            "{:>5}  {:<16}  {:>7}  {:>9}  {:>7}  {:>9}  {:>9}  {:>9}  {:>9}  {:>8}  {:>8}  {:<7}  Workload / tags",
+            "#", "Started (UTC)", "Dur s", "Requests", "OK %", "RPS", "Avg ms", "p95 ms", "p99 ms", "Δ p95", "Δ RPS", "Verdict"
+        );
+        let mut previous: Option<&RunRecord> = None;
+        for run in runs {
+            let tags = if run.tags.is_empty() { String::new() } else { format!(" [{}]", run.tags) };
+            println!(
+                "{:>5}  {:<16}  {:>7.0}  {:>9}  {:>7.2}  {:>9.1}  {:>9.2}  {:>9}  {:>9}  {:>8}  {:>8}  {:<7}  {}{}",
+                run.id,
+                report::format_utc(run.started_at),
+                run.duration_secs,
+                run.total_requests,
+                run.success_rate,
+                run.rps,
+                run.average_ms,
+                format_ms(run.p95_ms),
+                format_ms(run.p99_ms),
+                change(previous.and_then(|previous| previous.p95_ms), run.p95_ms),
+                change(previous.map(|previous| previous.rps), Some(run.rps)),
+                if run.passed { "PASS" } else { "FAIL" },
+                run.workload,
+                tags
+            );
+            previous = Some(run);
+        }
+    }
+}
+
+// Два прогона рядом: метрики и изменение второго относительно первого
+pub fn print_comparison(before: &RunRecord, after: &RunRecord) {
+    println!("=== Run #{} vs run #{} ===", before.id, after.id);
+    if before.endpoint != after.endpoint {
+        println!("Warning: runs are against different endpoints: {} / {}", before.endpoint, after.endpoint);
+    } else {
+        println!("Endpoint: {}", before.endpoint);
+    }
+    if before.workload != after.workload {
+        println!("Warning: workloads differ: {} / {}", before.workload, after.workload);
+    }
+    let header = (format!("#{}", before.id), format!("#{}", after.id));
+    println!("{:<14}  {:>16}  {:>16}  {:>8}", "", header.0, header.1, "Change");
+    let text_row = |name: &str, before: String, after: String| {
+        println!("{:<14}  {:>16}  {:>16}", name, before, after);
+    };
+    text_row("Started (UTC)", report::format_utc(before.started_at), report::format_utc(after.started_at));
+    text_row("Tags", before.tags.clone(), after.tags.clone());
+    // Название, значения и число знаков после запятой
+    let rows = [
+        ("Duration s", Some(before.duration_secs), Some(after.duration_secs), 0),
+        ("Requests", Some(before.total_requests as f64), Some(after.total_requests as f64), 0),
+        ("Success %", Some(before.success_rate), Some(after.success_rate), 2),
+        ("RPS", Some(before.rps), Some(after.rps), 1),
+        ("Avg ms", Some(before.average_ms), Some(after.average_ms), 2),
+        ("p50 ms", before.p50_ms, after.p50_ms, 2),
+        ("p95 ms", before.p95_ms, after.p95_ms, 2),
+        ("p99 ms", before.p99_ms, after.p99_ms, 2),
+        ("Max ms", Some(before.max_ms), Some(after.max_ms), 2),
+    ];
+    for (name, first, second, precision) in rows {
+        let format = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |value| format!("{:.*}", precision, value));
+        println!("{:<14}  {:>16}  {:>16}  {:>8}", name, format(first), format(second), change(first, second));
+    }
+    let verdict = |run: &RunRecord| if run.passed { "PASS".to_string() } else { format!("FAIL ({})", run.violations) };
+    text_row("Verdict", verdict(before), verdict(after));
+}
+
+#[cfg(feature = "history")]
+mod sqlite {
+    use super::RunRecord;
+    use std::ffi::{c_char, c_int, c_void, CStr, CString};
+    use std::path::Path;
+
+    const SQLITE_OK: c_int = 0;
+    const SQLITE_ROW: c_int = 100;
+    const SQLITE_DONE: c_int = 101;
+    const SQLITE_NULL: c_int = 5;
+    // sqlite копирует привязанную строку (SQLITE_TRANSIENT)
+    const SQLITE_TRANSIENT: isize = -1;
+    // Несколько прогонов могут писать в одну базу одновременно
+    const BUSY_TIMEOUT_MS: c_int = 5000;
+
+    #[link(name = "sqlite3")]
+    extern "C" {
+        fn sqlite3_open(filename: *const c_char, db: *mut *mut c_void) -> c_int;
+        fn sqlite3_close(db: *mut c_void) -> c_int;
+        fn sqlite3_errmsg(db: *mut c_void) -> *const c_char;
+        fn sqlite3_busy_timeout(db: *mut c_void, ms: c_int) -> c_int;
+        fn sqlite3_prepare_v2(
+            db: *mut c_void,
+            sql: *const c_char,
+            n_byte: c_int,
+            stmt: *mut *mut c_void,
+            tail: *mut *const c_char,
+        ) -> c_int;
+        fn sqlite3_bind_text(stmt: *mut c_void, index: c_int, text: *const c_char, n: c_int, destructor: isize) -> c_int;
+        fn sqlite3_bind_int64(stmt: *mut c_void, index: c_int, value: i64) -> c_int;
+        fn sqlite3_bind_double(stmt: *mut c_void, index: c_int, value: f64) -> c_int;
+        fn sqlite3_bind_null(stmt: *mut c_void, index: c_int) -> c_int;
+        fn sqlite3_step(stmt: *mut c_void) -> c_int;
+        fn sqlite3_column_type(stmt: *mut c_void, column: c_int) -> c_int;
+        fn sqlite3_column_int64(stmt: *mut c_void, column: c_int) -> i64;
+        fn sqlite3_column_double(stmt: *mut c_void, column: c_int) -> f64;
+        fn sqlite3_column_text(stmt: *mut c_void, column: c_int) -> *const c_char;
+        fn sqlite3_finalize(stmt: *mut c_void) -> c_int;
+        fn sqlite3_last_insert_rowid(db: *mut c_void) -> i64;
+    }
+
+    const SCHEMA: &[&str] = &[
+        "CREATE TABLE IF NOT EXISTS runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at INTEGER NOT NULL,
+            endpoint TEXT NOT NULL,
+            workload TEXT NOT NULL,
+            tags TEXT NOT NULL,
+            duration_secs REAL NOT NULL,
+            total_requests INTEGER NOT NULL,
+            successful_requests INTEGER NOT NULL,
+            success_rate REAL NOT NULL,
+            rps REAL NOT NULL,
+            average_ms REAL NOT NULL,
+            p50_ms REAL,
+            p95_ms REAL,
+            p99_ms REAL,
+            max_ms REAL NOT NULL,
+            passed INTEGER NOT NULL,
+            violations TEXT NOT NULL
+        )",
+        "CREATE INDEX IF NOT EXISTS runs_endpoint ON runs (endpoint, started_at)",
+    ];
+
+    const COLUMNS: &str = "id, started_at, endpoint, workload, tags, duration_secs, total_requests, successful_requests, \
+        success_rate, rps, average_ms, p50_ms, p95_ms, p99_ms, max_ms, passed, violations";
+
+    enum Value {
+        Null,
+        Integer(i64),
+        Real(f64),
+        Text(String),
+    }
+
+    impl Value {
+        fn integer(&self) -> i64 {
+            match self {
+                Value::Integer(value) => *value,
+                Value::Real(value) => *value as i64,
+                _ => 0,
+            }
+        }
+
+        fn real(&self) -> Option<f64> {
+            match self {
+                Value::Integer(value) => Some(*value as f64),
+                Value::Real(value) => Some(*value),
+                _ => None,
+            }
+        }
+
+        fn text(&self) -> String {
+            match self {
+                Value::Text(value) => value.clone(),
+                _ => String::new(),
+            }
+        }
+    }
+
+    struct Database {
+        handle: *mut c_void,
+    }
+
+    // Подготовленный запрос; finalize при выходе из области видимости на любом пути
+    struct Statement {
+        handle: *mut c_void,
+    }
+
+    impl Drop for Statement {
+        fn drop(&mut self) {
+            // SAFETY: handle получен из sqlite3_prepare_v2 и финализируется один раз
+            unsafe {
+                sqlite3_finalize(self.handle);
+            }
+        }
+    }
+
+    impl Drop for Database {
+        fn drop(&mut self) {
+            // SAFETY: все Statement живут внутри методов Database и к этому моменту финализированы
+            unsafe {
+                sqlite3_close(self.handle);
+            }
+        }
+    }
+
+    impl Database {
+        fn open(path: &str) -> Result<Self, String> {
+            let filename = CString::new(path).map_err(|_| format!("Invalid history path: {}", path))?;
+            let mut handle = std::ptr::null_mut();
+            // SAFETY: sqlite3_open выделяет соединение даже при ошибке, его закрывает Drop
+            let rc = unsafe { sqlite3_open(filename.as_ptr(), &mut handle) };
+            let db = Self { handle };
+            if rc != SQLITE_OK {
+                return Err(format!("Failed to open history database {}: {}", path, db.error()));
+            }
+            // SAFETY: handle - открытое соединение
+            unsafe {
+                sqlite3_busy_timeout(db.handle, BUSY_TIMEOUT_MS);
+            }
+            for sql in SCHEMA {
+                db.execute(sql, &[])?;
+            }
+            Ok(db)
+        }
+
+        fn error(&self) -> String {
+            // SAFETY: sqlite3_errmsg возвращает строку, которой владеет соединение
+            unsafe {
+                let message = sqlite3_errmsg(self.handle);
+                if message.is_null() {
+                    return "out of memory".to_string();
+                }
+                CStr::from_ptr(message).to_string_lossy().into_owned()
+            }
+        }
+
+        fn prepare(&self, sql: &str, params: &[Value]) -> Result<Statement, String> {
+            let text = CString::new(sql).map_err(|_| "Invalid SQL".to_string())?;
+            let mut handle = std::ptr::null_mut();
+            // SAFETY: текст запроса и параметры живут до конца вызовов; строки sqlite копирует (SQLITE_TRANSIENT)
+            unsafe {
+                if sqlite3_prepare_v2(self.handle, text.as_ptr(), -1, &mut handle, std::ptr::null_mut()) != SQLITE_OK {
+                    return Err(format!("History database error: {}", self.error()));
+                }
+                let statement = Statement { handle };
+                for (index, param) in params.iter().enumerate() {
+                    let index = index as c_int + 1;
+                    let rc = match param {
+                        Value::Null => sqlite3_bind_null(handle, index),
+                        Value::Integer(value) => sqlite3_bind_int64(handle, index, *value),
+                        Value::Real(value) => sqlite3_bind_double(handle, index, *value),
+                        Value::Text(value) => sqlite3_bind_text(
+                            handle,
+                            index,
+                            value.as_ptr() as *const c_char,
+                            value.len() as c_int,
+                            SQLITE_TRANSIENT,
+                        ),
+                    };
+                    if rc != SQLITE_OK {
+                        return Err(format!("History database error: {}", self.error()));
+                    }
+                }
+                Ok(statement)
+            }
+        }
+
+        fn execute(&self, sql: &str, params: &[Value]) -> Result<(), String> {
+            let statement = self.prepare(sql, params)?;
+            // SAFETY: statement подготовлен на этом соединении
+            match unsafe { sqlite3_step(statement.handle) } {
+                SQLITE_DONE | SQLITE_ROW => Ok(()),
+                _ => Err(format!("History database error: {}", self.error())),
+            }
+        }
+
+        fn query(&self, sql: &str, params: &[Value], columns: usize) -> Result<Vec<Vec<Value>>, String> {
+            let statement = self.prepare(sql, params)?;
+            let mut rows = Vec::new();
+            // SAFETY: statement подготовлен на этом соединении; текст колонки копируется до следующего step
+            unsafe {
+                loop {
+                    match sqlite3_step(statement.handle) {
+                        SQLITE_ROW => {}
+                        SQLITE_DONE => break,
+                        _ => return Err(format!("History database error: {}", self.error())),
+                    }
+                    let row = (0..columns as c_int)
+                        .map(|column| match sqlite3_column_type(statement.handle, column) {
+                            SQLITE_NULL => Value::Null,
+                            1 => Value::Integer(sqlite3_column_int64(statement.handle, column)),
+                            2 => Value::Real(sqlite3_column_double(statement.handle, column)),
+                            _ => {
+                                let text = sqlite3_column_text(statement.handle, column);
+                                if text.is_null() {
+                                    Value::Null
+                                } else {
+                                    Value::Text(CStr::from_ptr(text).to_string_lossy().into_owned())
+                                }
+                            }
+                        })
+                        .collect();
+                    rows.push(row);
+                }
+            }
+            Ok(rows)
+        }
+
+        fn last_insert_id(&self) -> i64 {
+            // SAFETY: handle - открытое соединение
+            unsafe { sqlite3_last_insert_rowid(self.handle) }
+        }
+    }
+
+    impl RunRecord {
+        fn from_row(row: &[Value]) -> Self {
+            Self {
+                id: row[0].integer(),
+                started_at: row[1].integer().max(0) as u64,
+                endpoint: row[2].text(),
+                workload: row[3].text(),
+                tags: row[4].text(),
+                duration_secs: row[5].real().unwrap_or(0.0),
+                total_requests: row[6].integer().max(0) as u64,
+                successful_requests: row[7].integer().max(0) as u64,
+                success_rate: row[8].real().unwrap_or(0.0),
+                rps: row[9].real().unwrap_or(0.0),
+                average_ms: row[10].real().unwrap_or(0.0),
+                p50_ms: row[11].real(),
+                p95_ms: row[12].real(),
+                p99_ms: row[13].real(),
+                max_ms: row[14].real().unwrap_or(0.0),
+                passed: row[15].integer() != 0,
+                violations: row[16].text(),
+            }
+        }
+    }
+
+    // Дописывает прогон в базу (создает ее при первом прогоне) и возвращает его номер
+    pub fn append(path: &str, run: &RunRecord) -> Result<i64, String> {
+        let db = Database::open(path)?;
+        let optional = |value: Option<f64>| value.map_or(Value::Null, Value::Real);
+        db.execute(
+            "INSERT INTO runs (started_at, endpoint, workload, tags, duration_secs, total_requests, successful_requests, \
+             success_rate, rps, average_ms, p50_ms, p95_ms, p99_ms, max_ms, passed, violations) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            &[
+                Value::Integer(run.started_at as i64),
+                Value::Text(run.endpoint.clone()),
+                Value::Text(run.workload.clone()),
+                Value::Text(run.tags.clone()),
+                Value::Real(run.duration_secs),
+                Value::Integer(run.total_requests as i64),
+                Value::Integer(run.successful_requests as i64),
+                Value::Real(run.success_rate),
+                Value::Real(run.rps),
+                Value::Real(run.average_ms),
+                optional(run.p50_ms),
+                optional(run.p95_ms),
+                optional(run.p99_ms),
+                Value::Real(run.max_ms),
+                Value::Integer(run.passed as i64),
+                Value::Text(run.violations.clone()),
+            ],
+        )?;
+        Ok(db.last_insert_id())
+    }
+
+    fn open_existing(path: &str) -> Result<Database, String> {
+        if !Path::new(path).exists() {
+            return Err(format!("History database not found: {}", path));
+        }
+        Database::open(path)
+    }
+
+    // Последние limit прогонов (с endpoint, содержащим filter), от старых к новым
+    pub fn list(path: &str, filter: Option<&str>, limit: usize) -> Result<Vec<RunRecord>, String> {
+        let db = open_existing(path)?;
+        let sql = format!(
+            "SELECT {} FROM runs WHERE instr(endpoint, ?) > 0 ORDER BY started_at DESC, id DESC LIMIT ?",
+            COLUMNS
+        );
+        let params = [Value::Text(filter.unwrap_or_default().to_string()), Value::Integer(limit as i64)];
+        let mut runs: Vec<RunRecord> = db.query(&sql, &params, 17)?.iter().map(|row| RunRecord::from_row(row)).collect();
+        runs.reverse();
+        Ok(runs)
+    }
+
+    pub fn get(path: &str, id: i64) -> Result<RunRecord, String> {
+        let db = open_existing(path)?;
+        let sql = format!("SELECT {} FROM runs WHERE id = ?", COLUMNS);
+        db.query(&sql, &[Value::Integer(id)], 17)?
+            .first()
+            .map(|row| RunRecord::from_row(row))
+            .ok_or_else(|| format!("Run #{} not found in {}", id, path))
+    }
+}
+
+#[cfg(all(test, feature = "history"))]
+mod tests {
+    use super::*;
+
+    fn run(endpoint: &str, started_at: u64, p95_ms: Option<f64>) -> RunRecord {
+        RunRecord {
+            id: 0,
+            started_at,
+            endpoint: endpoint.to_string(),
+            workload: "getSlot x4".to_string(),
+            tags: "build=1".to_string(),
+            duration_secs: 10.0,
+            total_requests: 1000,
+            successful_requests: 990,
+            success_rate: 99.0,
+            rps: 100.0,
+            average_ms: 12.5,
+            p50_ms: Some(10.0),
+            p95_ms,
+            p99_ms: None,
+            max_ms: 80.0,
+            passed: p95_ms.is_some(),
+            violations: if p95_ms.is_some() { String::new() } else { "p95 missing".to_string() },
+        }
+    }
+
+    // Своя база на тест в каталоге временных файлов
+    fn database(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("stress-history-{}-{}.sqlite", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn append_then_get_round_trips_the_run() {
+        let db = database("get");
+        let id = append(&db, &run("http://a", 100, Some(20.0))).unwrap();
+        let saved = get(&db, id).unwrap();
+        assert_eq!(saved.id, id);
+        assert_eq!(saved.endpoint, "http://a");
+        assert_eq!(saved.workload, "getSlot x4");
+        assert_eq!(saved.tags, "build=1");
+        assert_eq!(saved.total_requests, 1000);
+        assert_eq!(saved.successful_requests, 990);
+        assert_eq!(saved.p95_ms, Some(20.0));
+        assert_eq!(saved.p99_ms, None);
+        assert!(saved.passed);
+        assert!(get(&db, id + 1).unwrap_err().contains("not found"));
+        std::fs::remove_file(&db).unwrap();
+    }
+
+    #[test]
+    fn list_filters_by_endpoint_and_keeps_the_latest_runs_oldest_first() {
+        let db = database("list");
+        append(&db, &run("http://a", 300, Some(30.0))).unwrap();
+        append(&db, &run("http://a", 100, Some(10.0))).unwrap();
+        append(&db, &run("http://b", 200, None)).unwrap();
+        append(&db, &run("http://a", 200, Some(20.0))).unwrap();
+
+        let runs = list(&db, Some("//a"), 2).unwrap();
+        let started: Vec<u64> = runs.iter().map(|run| run.started_at).collect();
+        assert_eq!(started, [200, 300]);
+
+        let all = list(&db, None, 10).unwrap();
+        assert_eq!(all.len(), 4);
+        let failed = all.iter().find(|run| run.endpoint == "http://b").unwrap();
+        assert!(!failed.passed);
+        assert_eq!(failed.violations, "p95 missing");
+        std::fs::remove_file(&db).unwrap();
+    }
+
+    #[test]
+    fn reading_a_missing_database_does_not_create_it() {
+        let db = database("missing");
+        assert!(list(&db, None, 10).unwrap_err().contains("not found"));
+        assert!(!std::path::Path::new(&db).exists());
+    }
+}
@@ -0,0 +1,149 @@
+// HTTP GET нагрузка: [[methods]] с method = "GET" и path. Некоторые шлюзы рядом с JSON-RPC отдают REST
+// (/health, /slot); эти запросы идут через ту же статистику, что и JSON-RPC, поэтому смешанный шлюз
+// проверяется одним прогоном. Успех - ожидаемый HTTP статус (expected_status, по умолчанию любой 2xx)
+
+use std::time::Instant;
+use tokio::time::sleep;
+
+use crate::autotune::TuneHandle;
+use crate::limits::Pacer;
+use crate::{
+    charge, deadline_micros, keyed_clients, keys, prepare_request, random_seed, record_raw_outcome, HeaderCapture, MethodConfig,
+    RequestContext, RequestError, Rng, SentRequest, Stats, WorkerSettings,
+};
+
+pub const METHOD: &str = "GET";
+
+// Подстановки в path: ${worker} - номер воркера, ${id} - очередной ID запроса прогона (общий с JSON-RPC),
+// ${random} - случайное число из потока воркера
+fn expand(path: &str, worker_id: usize, id: u64, rng: &mut Rng) -> String {
+    let mut expanded = path.replace("${worker}", &worker_id.to_string()).replace("${id}", &id.to_string());
+    while expanded.contains("${random}") {
+        expanded = expanded.replacen("${random}", &rng.next_u64().to_string(), 1);
+    }
+    expanded
+}
+
+// Путь с / в начале отсчитывается от корня хоста endpoint, без / - от пути URL; полный URL используется как есть
+fn resolve(base: &str, path: &str) -> Result<reqwest::Url, String> {
+    reqwest::Url::parse(base)
+        .and_then(|base| base.join(path))
+        .map_err(|e| format!("GET {}: invalid URL: {}", path, e))
+}
+
+// GET записи проверяются всегда, даже с --no-validate: без path запрос отправить некуда
+pub fn validate(methods: &[MethodConfig], url: &str) -> Result<(), String> {
+    let url = url.replace(keys::URL_PLACEHOLDER, "key");
+    for method in methods {
+        if method.method != METHOD {
+            if method.path.is_some() || method.expected_status.is_some() {
+                return Err(format!("{}: path and expected_status are only for method = \"GET\"", method.method));
+            }
+            continue;
+        }
+        let Some(path) = &method.path else {
+            return Err("GET needs path (e.g. path = \"/health\")".to_string());
+        };
+        if method.params.as_ref().is_some_and(|params| !params.is_empty()) || method.batch.unwrap_or(1) > 1 {
+            return Err(format!("GET {}: params and batch are only for JSON-RPC methods", path));
+        }
+        if method.expected_status.is_some_and(|status| !(100..=599).contains(&status)) {
+            return Err(format!("GET {}: expected_status must be an HTTP status (100-599)", path));
+        }
+        resolve(&url, &expand(path, 0, 1, &mut Rng::new(0)))?;
+    }
+    Ok(())
+}
+
+// Воркер GET записи: тот же цикл, что у JSON-RPC воркера (фаза, лимит RPS, предохранитель, пауза),
+// но тело ответа не разбирается
+pub async fn http_get_worker(
+    worker_id: usize,
+    method_config: MethodConfig,
+    settings: WorkerSettings,
+    stats: Stats,
+    tuning: Option<TuneHandle>,
+) {
+    let phase = method_config.phase();
+    let label = method_config.label();
+    let path = method_config.path.clone().unwrap_or_default();
+    let expected_status = method_config.expected_status;
+    let clients = keyed_clients(&settings, &stats, worker_id);
+    let node = stats.node_stats(&settings.url);
+    let method_apdex = settings.apdex.map(|thresholds| (thresholds, stats.method_apdex(&label)));
+    let mut rng = Rng::new(settings.seed.wrapping_add(worker_id as u64));
+    let mut debug_rng = Rng::new(random_seed().wrapping_add(worker_id as u64));
+    let mut pacer = Pacer::new(settings.pace_factor);
+    let mut capture = HeaderCapture::new(settings.capture_headers.clone(), settings.header_interval_secs, stats.clone())
+        .with_correlation(settings.correlation.as_ref(), worker_id)
+        .with_affinity(settings.affinity.as_ref(), worker_id);
+
+    if !phase.wait_start(&stats).await {
+        return;
+    }
+    while phase.active(&stats) {
+        if tuning.as_ref().is_some_and(|t| t.stopped()) {
+            break;
+        }
+        if stats.breaker_wait().await {
+            continue;
+        }
+        if !stats.rate_limit_wait(1, phase.end(&stats)).await {
+            break;
+        }
+        if !charge(settings.credits.as_deref(), settings.budget.as_ref(), &stats, METHOD, 1) {
+            break;
+        }
+
+        let keyed = &clients[rng.up_to(clients.len() as u64 - 1) as usize];
+        let ctx = RequestContext {
+            transport: &keyed.transport,
+            stats: &stats,
+            node: &node,
+            key: keyed.key.as_deref(),
+            worker_id,
+            debug: settings.debug.as_ref().is_some_and(|filter| filter.admits(METHOD, &mut debug_rng)),
+            near_timeout_micros: settings.http_timeout.as_micros() as u64 * 9 / 10,
+            deadline_micros: deadline_micros(settings.deadline.as_ref()),
+            parser: settings.parser,
+        };
+        node.record_attempts(1);
+        keyed.record_attempts(1);
+        capture.record_group_attempts(1);
+        if let Some((_, counters)) = &method_apdex {
+            counters.record_attempts(1);
+        }
+
+        let request_id = settings.ids.next();
+        let request_start = Instant::now();
+        let sent = SentRequest {
+            method: &label,
+            params: &[],
+            batch_size: 1,
+            request_id,
+        };
+        // Путь проверен до старта, подстановки - только цифры: URL собирается всегда
+        let Ok(url) = resolve(&keyed.transport.url, &expand(&path, worker_id, request_id, &mut rng)) else {
+            break;
+        };
+        let fetched = async {
+            let response = prepare_request(keyed.transport.client().get(url), &mut capture).send().await?;
+            capture.observe(&response);
+            let status = response.status();
+            response.bytes().await?;
+            Ok::<_, RequestError>(status)
+        };
+        let result = fetched.await;
+        let success = |status: reqwest::StatusCode| expected_status.map_or(status.is_success(), |expected| status.as_u16() == expected);
+        if let Some(response_time_micros) = record_raw_outcome(&ctx, sent, request_start, &mut capture, result, success) {
+            if let Some((thresholds, counters)) = &method_apdex {
+                counters.record_success(thresholds, response_time_micros, 1);
+            }
+            if let Some(tuning) = &tuning {
+                tuning.record(response_time_micros);
+            }
+        }
+
+        sleep(pacer.pause(request_start.elapsed(), settings.timeout_ms + rng.up_to(settings.jitter_ms))).await;
+    }
+}
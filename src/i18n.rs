@@ -0,0 +1,620 @@
+// Строки итогового отчета на поддерживаемых языках (--lang)
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    Ru,
+}
+
+// Формы слова после числа: одна (1, 21), несколько (2-4, 22-24), много (остальные)
+pub type Plural = [&'static str; 3];
+
+pub struct Texts {
+    pub statistics_title: &'static str,
+    pub tags: &'static str,
+    pub run_time_utc: &'static str,
+    pub endpoint_version: &'static str,
+    pub genesis_hash: &'static str,
+    pub node_identity: &'static str,
+    pub correlation_ids: &'static str,
+    pub total_requests: &'static str,
+    pub successful: &'static str,
+    pub errors: &'static str,
+    pub http_timeouts: &'static str,
+    pub connect_timeouts: &'static str,
+    pub json_parse_errors: &'static str,
+    pub network_errors: &'static str,
+    pub rpc_errors: &'static str,
+    pub id_mismatches: &'static str,
+    pub summary_by_period: &'static str,
+    pub requests_lower: &'static str,
+    pub connections: &'static str,
+    pub connections_opened: &'static str,
+    pub connections_reused: &'static str,
+    pub of_responses: &'static str,
+    pub tls_handshakes: &'static str,
+    pub connections_closed_by_peer: &'static str,
+    pub connection_resets: &'static str,
+    pub errors_on_reused: &'static str,
+    pub errors_on_new: &'static str,
+    pub of_requests_on_them: &'static str,
+    pub stale_keep_alive_warning: &'static str,
+    pub batch_anomalies: &'static str,
+    pub batches: &'static str,
+    pub missing_responses: &'static str,
+    pub duplicate_responses: &'static str,
+    pub unexpected_ids: &'static str,
+    pub out_of_order_batches: &'static str,
+    pub response_headers: &'static str,
+    pub credits: &'static str,
+    pub request_budget: &'static str,
+    pub response_sizes: &'static str,
+    pub responses: &'static str,
+    pub wire_size: &'static str,
+    pub decoded_size: &'static str,
+    pub bytes_per_response: &'static str,
+    pub compression_ratio: &'static str,
+    pub account_reads: &'static str,
+    pub account_data: &'static str,
+    pub account_size: &'static str,
+    pub decode_on_client: &'static str,
+    pub request_compression: &'static str,
+    pub clock_check: &'static str,
+    pub endpoint_clock_relative: &'static str,
+    pub no_date_header: &'static str,
+    pub block_time: &'static str,
+    pub slot: &'static str,
+    pub clock_skew_warning: &'static str,
+    pub declared_deadline: &'static str,
+    pub late_responses: &'static str,
+    pub of_successful: &'static str,
+    pub recovery_check: &'static str,
+    pub recovery_before: &'static str,
+    pub recovery_after: &'static str,
+    pub errors_lower: &'static str,
+    pub recovered: &'static str,
+    pub after: &'static str,
+    pub not_recovered: &'static str,
+    pub baseline_unavailable: &'static str,
+    pub endpoint: &'static str,
+    pub compression_accepted: &'static str,
+    pub compression_rejected: &'static str,
+    pub compression_partially_rejected: &'static str,
+    pub rejected_responses: &'static str,
+    pub budget_credits: &'static str,
+    pub budget_requests: &'static str,
+    pub limit: &'static str,
+    pub spent: &'static str,
+    pub exhausted_after: &'static str,
+    pub projected_full_duration: &'static str,
+    pub total: &'static str,
+    pub per_second: &'static str,
+    pub rolling_window: &'static str,
+    pub circuit_breaker: &'static str,
+    pub error_rate: &'static str,
+    pub cooldown: &'static str,
+    pub trips: &'static str,
+    pub requests_in_window: &'static str,
+    pub peak: &'static str,
+    pub lowest: &'static str,
+    pub worst_error_rate: &'static str,
+    pub live_status: &'static str,
+    pub live_success: &'static str,
+    pub live_since_start: &'static str,
+    pub live_last: &'static str,
+    pub live_no_window: &'static str,
+    pub projected_30_days: &'static str,
+    pub latency: &'static str,
+    pub client_decode: &'static str,
+    pub control_requests: &'static str,
+    pub response_consistency: &'static str,
+    pub distinct_requests: &'static str,
+    pub with_several_bodies: &'static str,
+    pub distinct_bodies: &'static str,
+    pub body_changes: &'static str,
+    pub most_common_body: &'static str,
+    pub more_in_json: &'static str,
+    pub of_request_time: &'static str,
+    pub outliers: &'static str,
+    pub written_to: &'static str,
+    pub average: &'static str,
+    pub minimum: &'static str,
+    pub maximum: &'static str,
+    pub requests: &'static str,
+    pub ok: &'static str,
+    pub failed: &'static str,
+    pub verdict: &'static str,
+    pub auto_tune: &'static str,
+    pub workers: Plural,
+    pub target: &'static str,
+    pub target_not_reached: &'static str,
+    pub satisfied: &'static str,
+    pub tolerating: &'static str,
+    pub frustrated: &'static str,
+    pub timeout_waits: &'static str,
+    pub warning: &'static str,
+    pub near_timeout_warning: &'static str,
+    pub worker_crashes_warning: &'static str,
+    pub parse_disabled_warning: &'static str,
+    pub endpoint_addresses: &'static str,
+    pub resolved_before_test: &'static str,
+    pub not_resolved_before_test: &'static str,
+    pub responses_lower: &'static str,
+    pub time_to_headers: &'static str,
+    pub nodes: &'static str,
+    pub api_keys: &'static str,
+    pub session_affinity: &'static str,
+    pub with_affinity: &'static str,
+    pub without_affinity: &'static str,
+    pub sessions_pinned: &'static str,
+    pub reassigned: &'static str,
+    pub scenarios: &'static str,
+    pub iterations: &'static str,
+    pub completed: &'static str,
+    pub failed_at_step: &'static str,
+    pub iteration_latency: &'static str,
+    pub last_errors: &'static str,
+    pub of: &'static str,
+    pub worker: &'static str,
+    pub snapshot_downloads: &'static str,
+    pub unavailable: &'static str,
+    pub no_range_support: &'static str,
+    pub downloaded: &'static str,
+    pub complete_files: &'static str,
+    pub time_to_first_byte: &'static str,
+    pub side_channel: &'static str,
+    pub polls: Plural,
+    pub average_lower: &'static str,
+    pub percentiles: &'static str,
+    pub confidence_interval: &'static str,
+    pub low_confidence: &'static str,
+    pub responses_needs: &'static str,
+    pub at_rate: &'static str,
+    pub or_lower: &'static str,
+    pub over_time: &'static str,
+    pub standard_deviation: &'static str,
+    pub interquartile_range: &'static str,
+    pub jitter: &'static str,
+    pub error_incidents: &'static str,
+    pub baseline: &'static str,
+    pub mostly: &'static str,
+    pub latency_shifts: &'static str,
+    pub at_time: &'static str,
+    pub client_environment: &'static str,
+    pub cpus: &'static str,
+    pub tokio_workers: Plural,
+    pub open_files_limit: &'static str,
+    pub socket_exhaustion_errors: &'static str,
+    pub connection_refused: &'static str,
+    pub connection_reset: &'static str,
+    pub other_lower: &'static str,
+    pub dns_refresh: &'static str,
+    pub every_secs: &'static str,
+    pub lookups: Plural,
+    pub initial_answer: &'static str,
+    pub answer_unchanged: &'static str,
+    pub lang: Lang,
+}
+
+const EN: Texts = Texts {
+    statistics_title: "=== Stress Test Statistics ===",
+    tags: "Tags",
+    run_time_utc: "Run time (UTC)",
+    endpoint_version: "Endpoint version",
+    genesis_hash: "Genesis hash",
+    node_identity: "Node identity",
+    correlation_ids: "Correlation IDs",
+    total_requests: "Total requests",
+    successful: "Successful",
+    errors: "Errors",
+    http_timeouts: "HTTP timeouts",
+    connect_timeouts: "Connect timeouts",
+    json_parse_errors: "JSON parse errors",
+    network_errors: "Network errors",
+    rpc_errors: "RPC errors",
+    id_mismatches: "ID mismatches",
+    summary_by_period: "Summary by period",
+    requests_lower: "requests",
+    connections: "Connections",
+    connections_opened: "Opened",
+    connections_reused: "Responses on reused connections",
+    of_responses: "of responses",
+    tls_handshakes: "TLS handshakes",
+    connections_closed_by_peer: "Closed by peer before the response",
+    connection_resets: "Reset",
+    errors_on_reused: "Network errors on reused connections",
+    errors_on_new: "Network errors on new connections",
+    of_requests_on_them: "of requests on them",
+    stale_keep_alive_warning: "network errors happen far more often on reused keep-alive connections than on new ones: the endpoint or its load balancer closes idle connections before the client does, the endpoint itself is reachable",
+    batch_anomalies: "Batch anomalies",
+    batches: "batches",
+    missing_responses: "Missing responses",
+    duplicate_responses: "Duplicate responses",
+    unexpected_ids: "Unexpected IDs",
+    out_of_order_batches: "Out-of-order batches",
+    response_headers: "Response headers",
+    credits: "Credits",
+    request_budget: "Request budget",
+    response_sizes: "Response sizes",
+    responses: "Responses",
+    wire_size: "On the wire",
+    decoded_size: "Decompressed",
+    bytes_per_response: "bytes per response",
+    compression_ratio: "Compression ratio",
+    account_reads: "Account reads by encoding",
+    account_data: "Encoded account data",
+    account_size: "Account size",
+    decode_on_client: "Client decode",
+    request_compression: "Request compression",
+    clock_check: "Clock check",
+    endpoint_clock_relative: "endpoint clock relative to local",
+    no_date_header: "no Date header in responses",
+    block_time: "Block time",
+    slot: "slot",
+    clock_skew_warning: "the local clock differs from the endpoint clock; client and server timestamps will not line up",
+    declared_deadline: "Declared deadline",
+    late_responses: "Successful responses after the deadline",
+    of_successful: "of successful",
+    recovery_check: "Recovery check",
+    recovery_before: "Before the load",
+    recovery_after: "After the load",
+    errors_lower: "errors",
+    recovered: "Recovered to pre-test levels",
+    after: "after",
+    not_recovered: "Did not recover to pre-test levels by the end of the probes",
+    baseline_unavailable: "Cannot judge: all probes before the load failed",
+    endpoint: "Endpoint",
+    compression_accepted: "accepts compressed requests",
+    compression_rejected: "rejects compressed requests",
+    compression_partially_rejected: "rejects some compressed requests",
+    rejected_responses: "rejected",
+    budget_credits: "credits",
+    budget_requests: "requests",
+    limit: "Limit",
+    spent: "Spent",
+    exhausted_after: "Exhausted after",
+    projected_full_duration: "Projected for full duration",
+    total: "Total",
+    per_second: "Per second",
+    rolling_window: "Rolling window",
+    circuit_breaker: "Circuit breaker",
+    error_rate: "error rate",
+    cooldown: "cooldown",
+    trips: "trips",
+    requests_in_window: "requests in window",
+    peak: "peak",
+    lowest: "lowest",
+    worst_error_rate: "worst error rate",
+    live_status: "Live",
+    live_success: "success",
+    live_since_start: "total",
+    live_last: "last",
+    live_no_window: "no completed seconds yet",
+    projected_30_days: "Projected per 30 days",
+    latency: "Latency",
+    client_decode: "Client decode (decompression + JSON, not included above)",
+    control_requests: "Control requests (getSlot for getLatestBlock, not included above)",
+    response_consistency: "Response consistency (results of identical requests)",
+    distinct_requests: "distinct requests",
+    with_several_bodies: "with more than one body",
+    distinct_bodies: "distinct bodies",
+    body_changes: "changes",
+    most_common_body: "most common body",
+    more_in_json: "more in the JSON report",
+    of_request_time: "of request time",
+    outliers: "Outliers",
+    written_to: "written to",
+    average: "Average",
+    minimum: "Minimum",
+    maximum: "Maximum",
+    requests: "Requests",
+    ok: "ok",
+    failed: "failed",
+    verdict: "Verdict",
+    auto_tune: "Auto-tune (optimal concurrency)",
+    workers: ["worker", "workers", "workers"],
+    target: "target",
+    target_not_reached: "p95 target not reached, target",
+    satisfied: "satisfied",
+    tolerating: "tolerating",
+    frustrated: "frustrated",
+    timeout_waits: "Time waited before timeout",
+    warning: "Warning",
+    near_timeout_warning: "successful responses arrived within 10% of the HTTP timeout; the timeout may be hiding a latency tail",
+    worker_crashes_warning: "worker crashes (panics); load was lower than configured unless --respawn-workers was set",
+    parse_disabled_warning: "--no-parse: response bodies were not parsed, success means HTTP 2xx; RPC errors and ID mismatches were not detected",
+    endpoint_addresses: "Endpoint IPs",
+    resolved_before_test: "Resolved before the test",
+    not_resolved_before_test: "not resolved before the test",
+    responses_lower: "responses",
+    time_to_headers: "time to headers",
+    nodes: "Nodes",
+    api_keys: "API keys",
+    session_affinity: "Session affinity",
+    with_affinity: "with affinity",
+    without_affinity: "without affinity",
+    sessions_pinned: "sessions pinned",
+    reassigned: "reassigned",
+    scenarios: "Scenarios",
+    iterations: "iterations",
+    completed: "completed",
+    failed_at_step: "failed at step",
+    iteration_latency: "Iteration latency",
+    last_errors: "Last errors",
+    of: "of",
+    worker: "worker",
+    snapshot_downloads: "Snapshot downloads",
+    unavailable: "unavailable",
+    no_range_support: "no Range support, downloaded whole",
+    downloaded: "Downloaded",
+    complete_files: "Files downloaded to the end",
+    time_to_first_byte: "time to first byte",
+    side_channel: "Side-channel URLs (client: req/s and errors over the seconds before each poll)",
+    polls: ["poll", "polls", "polls"],
+    average_lower: "average",
+    percentiles: "Percentiles",
+    confidence_interval: "confidence interval",
+    low_confidence: "low confidence",
+    responses_needs: "responses, needs",
+    at_rate: "at",
+    or_lower: "or",
+    over_time: "for",
+    standard_deviation: "Standard deviation",
+    interquartile_range: "Interquartile range (p75 - p25)",
+    jitter: "Jitter (consecutive requests)",
+    error_incidents: "Error incidents (bursts over the baseline error rate)",
+    baseline: "baseline",
+    mostly: "mostly",
+    latency_shifts: "Latency shifts (per-second median)",
+    at_time: "at",
+    client_environment: "Client",
+    cpus: "CPUs",
+    tokio_workers: ["tokio worker", "tokio workers", "tokio workers"],
+    open_files_limit: "open files limit (soft/hard)",
+    socket_exhaustion_errors: "Client socket limit errors (open files / ephemeral ports)",
+    connection_refused: "refused",
+    connection_reset: "reset",
+    other_lower: "other",
+    dns_refresh: "DNS re-resolution",
+    every_secs: "every",
+    lookups: ["lookup", "lookups", "lookups"],
+    initial_answer: "Initial answer",
+    answer_unchanged: "the answer did not change",
+    lang: Lang::En,
+};
+
+const RU: Texts = Texts {
+    statistics_title: "=== Статистика стресс-теста ===",
+    tags: "Теги",
+    run_time_utc: "Время прогона (UTC)",
+    endpoint_version: "Версия endpoint",
+    genesis_hash: "Genesis hash",
+    node_identity: "Identity ноды",
+    correlation_ids: "ID корреляции",
+    total_requests: "Всего запросов",
+    successful: "Успешных",
+    errors: "Ошибки",
+    http_timeouts: "HTTP таймауты",
+    connect_timeouts: "Таймауты соединения",
+    json_parse_errors: "Ошибки разбора JSON",
+    network_errors: "Сетевые ошибки",
+    rpc_errors: "Ошибки RPC",
+    id_mismatches: "Несовпадения ID",
+    summary_by_period: "Итоги по периодам",
+    requests_lower: "запросов",
+    connections: "Соединения",
+    connections_opened: "Открыто",
+    connections_reused: "Ответов по переиспользованным соединениям",
+    of_responses: "от ответов",
+    tls_handshakes: "TLS рукопожатий",
+    connections_closed_by_peer: "Закрыто endpoint до ответа",
+    connection_resets: "Сброшено",
+    errors_on_reused: "Сетевые ошибки на переиспользованных соединениях",
+    errors_on_new: "Сетевые ошибки на новых соединениях",
+    of_requests_on_them: "от запросов по ним",
+    stale_keep_alive_warning: "сетевые ошибки на переиспользованных keep-alive соединениях намного чаще, чем на новых: endpoint или его балансировщик закрывает простаивающие соединения раньше клиента, сам endpoint доступен",
+    batch_anomalies: "Аномалии батчей",
+    batches: "батчей",
+    missing_responses: "Пропавшие ответы",
+    duplicate_responses: "Повторные ответы",
+    unexpected_ids: "Чужие ID",
+    out_of_order_batches: "Батчи с нарушенным порядком",
+    response_headers: "Заголовки ответа",
+    credits: "Кредиты",
+    request_budget: "Бюджет запросов",
+    response_sizes: "Размеры ответов",
+    responses: "Ответов",
+    wire_size: "На проводе",
+    decoded_size: "После распаковки",
+    bytes_per_response: "байт на ответ",
+    compression_ratio: "Степень сжатия",
+    account_reads: "Чтение аккаунтов по кодировкам",
+    account_data: "Данные аккаунтов в ответе",
+    account_size: "Размер аккаунтов",
+    decode_on_client: "Декодирование на клиенте",
+    request_compression: "Сжатие запросов",
+    clock_check: "Проверка часов",
+    endpoint_clock_relative: "часы endpoint относительно локальных",
+    no_date_header: "в ответах нет заголовка Date",
+    block_time: "Время блока",
+    slot: "слот",
+    clock_skew_warning: "локальные часы расходятся с часами endpoint; время клиента и сервера не совпадет при сопоставлении логов",
+    declared_deadline: "Объявленный дедлайн",
+    late_responses: "Успешных ответов после дедлайна",
+    of_successful: "от успешных",
+    recovery_check: "Проверка восстановления",
+    recovery_before: "До нагрузки",
+    recovery_after: "После нагрузки",
+    errors_lower: "ошибки",
+    recovered: "Восстановился до уровня до теста",
+    after: "через",
+    not_recovered: "Не восстановился до уровня до теста к концу проб",
+    baseline_unavailable: "Оценить нельзя: все пробы до нагрузки неудачны",
+    endpoint: "Endpoint",
+    compression_accepted: "принимает сжатые запросы",
+    compression_rejected: "не принимает сжатые запросы",
+    compression_partially_rejected: "принимает сжатые запросы не всегда",
+    rejected_responses: "отклонено",
+    budget_credits: "кредиты",
+    budget_requests: "запросы",
+    limit: "Лимит",
+    spent: "Израсходовано",
+    exhausted_after: "Исчерпан через",
+    projected_full_duration: "Прогноз на полную длительность",
+    total: "Всего",
+    per_second: "В секунду",
+    rolling_window: "Скользящее окно",
+    circuit_breaker: "Предохранитель",
+    error_rate: "доля ошибок",
+    cooldown: "пауза",
+    trips: "срабатываний",
+    requests_in_window: "запросов в окне",
+    peak: "пик",
+    lowest: "минимум",
+    worst_error_rate: "худшая доля ошибок",
+    live_status: "Ход теста",
+    live_success: "успешных",
+    live_since_start: "с начала",
+    live_last: "за последние",
+    live_no_window: "еще нет завершенных секунд",
+    projected_30_days: "Прогноз на 30 дней",
+    latency: "Латентность",
+    client_decode: "Декодирование на клиенте (распаковка + JSON, в латентность выше не входит)",
+    control_requests: "Служебные запросы (getSlot для getLatestBlock, в статистику выше не входят)",
+    response_consistency: "Согласованность ответов (result одинаковых запросов)",
+    distinct_requests: "разных запросов",
+    with_several_bodies: "с несколькими телами",
+    distinct_bodies: "разных тел",
+    body_changes: "смен",
+    most_common_body: "самое частое тело",
+    more_in_json: "еще в JSON отчете",
+    of_request_time: "от времени запроса",
+    outliers: "Выбросы",
+    written_to: "записаны в",
+    average: "Средняя",
+    minimum: "Минимальная",
+    maximum: "Максимальная",
+    requests: "Запросы",
+    ok: "успешных",
+    failed: "неудачных",
+    verdict: "Вердикт",
+    auto_tune: "Автотюнинг (оптимальная конкурентность)",
+    workers: ["воркер", "воркера", "воркеров"],
+    target: "цель",
+    target_not_reached: "целевой p95 не достигнут, цель",
+    satisfied: "довольны",
+    tolerating: "терпимо",
+    frustrated: "недовольны",
+    timeout_waits: "Ожидание до таймаута",
+    warning: "Внимание",
+    near_timeout_warning: "успешных ответов пришли в последние 10% HTTP таймаута; таймаут может скрывать хвост латентности",
+    worker_crashes_warning: "падений воркеров (panic); без --respawn-workers нагрузка была ниже заданной",
+    parse_disabled_warning: "--no-parse: тела ответов не разбирались, успех означает HTTP 2xx; RPC ошибки и несовпадения ID не обнаруживались",
+    endpoint_addresses: "IP адреса endpoint",
+    resolved_before_test: "Адреса хоста перед тестом",
+    not_resolved_before_test: "не было среди адресов перед тестом",
+    responses_lower: "ответов",
+    time_to_headers: "до заголовков ответа",
+    nodes: "Ноды",
+    api_keys: "API ключи",
+    session_affinity: "Привязка сессии",
+    with_affinity: "с привязкой",
+    without_affinity: "без привязки",
+    sessions_pinned: "сессий с привязкой",
+    reassigned: "смен привязки",
+    scenarios: "Сценарии",
+    iterations: "итераций",
+    completed: "завершено",
+    failed_at_step: "сбой на шаге",
+    iteration_latency: "Длительность итерации",
+    last_errors: "Последние ошибки",
+    of: "из",
+    worker: "воркер",
+    snapshot_downloads: "Загрузка снапшотов",
+    unavailable: "недоступен",
+    no_range_support: "без поддержки Range, качается целиком",
+    downloaded: "Скачано",
+    complete_files: "Файлов скачано до конца",
+    time_to_first_byte: "до первого байта",
+    side_channel: "Служебные URL (клиент: запросов в секунду и ошибки за секунды перед каждым опросом)",
+    polls: ["опрос", "опроса", "опросов"],
+    average_lower: "в среднем",
+    percentiles: "Перцентили",
+    confidence_interval: "доверительный интервал",
+    low_confidence: "низкая достоверность",
+    responses_needs: "ответов, нужно",
+    at_rate: "при",
+    or_lower: "или",
+    over_time: "за",
+    standard_deviation: "Стандартное отклонение",
+    interquartile_range: "Межквартильный размах (p75 - p25)",
+    jitter: "Джиттер (соседние запросы)",
+    error_incidents: "Инциденты (всплески ошибок над фоном)",
+    baseline: "фон",
+    mostly: "в основном",
+    latency_shifts: "Смена уровня латентности (медиана по секундам)",
+    at_time: "на",
+    client_environment: "Клиент",
+    cpus: "CPU",
+    tokio_workers: ["поток tokio", "потока tokio", "потоков tokio"],
+    open_files_limit: "лимит открытых файлов (soft/hard)",
+    socket_exhaustion_errors: "Исчерпание сокетов клиента (открытые файлы / эфемерные порты)",
+    connection_refused: "отказ в соединении",
+    connection_reset: "сброс",
+    other_lower: "прочие",
+    dns_refresh: "Повторное разрешение DNS",
+    every_secs: "каждые",
+    lookups: ["запрос", "запроса", "запросов"],
+    initial_answer: "Первый ответ",
+    answer_unchanged: "ответ не менялся",
+    lang: Lang::Ru,
+};
+
+impl Texts {
+    // Число со словом в нужной форме: "1 поток tokio", "4 потока tokio", "16 потоков tokio"
+    pub fn count(&self, n: u64, forms: &Plural) -> String {
+        let form = match self.lang {
+            Lang::En if n == 1 => 0,
+            Lang::En => 2,
+            Lang::Ru if n % 10 == 1 && n % 100 != 11 => 0,
+            Lang::Ru if (2..=4).contains(&(n % 10)) && !(12..=14).contains(&(n % 100)) => 1,
+            Lang::Ru => 2,
+        };
+        format!("{} {}", n, forms[form])
+    }
+}
+
+pub fn texts(lang: Lang) -> &'static Texts {
+    match lang {
+        Lang::En => &EN,
+        Lang::Ru => &RU,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_picks_russian_plural_forms() {
+        let t = texts(Lang::Ru);
+        let forms = |n| t.count(n, &t.tokio_workers);
+        assert_eq!(forms(1), "1 поток tokio");
+        assert_eq!(forms(21), "21 поток tokio");
+        assert_eq!(forms(3), "3 потока tokio");
+        assert_eq!(forms(24), "24 потока tokio");
+        assert_eq!(forms(11), "11 потоков tokio");
+        assert_eq!(forms(12), "12 потоков tokio");
+        assert_eq!(forms(5), "5 потоков tokio");
+        assert_eq!(forms(0), "0 потоков tokio");
+    }
+
+    #[test]
+    fn count_picks_english_plural_forms() {
+        let t = texts(Lang::En);
+        assert_eq!(t.count(1, &t.workers), "1 worker");
+        assert_eq!(t.count(21, &t.workers), "21 workers");
+        assert_eq!(t.count(0, &t.workers), "0 workers");
+    }
+}
@@ -0,0 +1,80 @@
+// Как клиент представляется endpoint: User-Agent (с шаблоном на воркер) и заголовки клиентских библиотек.
+// Нужен, чтобы нагрузку можно было найти в логах провайдера и чтобы шлюзы, по-разному
+// обрабатывающие запросы разных библиотек, видели ту библиотеку, которую эмулируем
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use serde::{Deserialize, Serialize};
+
+// Шаблон User-Agent: {worker} заменяется на номер воркера
+pub const WORKER_PLACEHOLDER: &str = "{worker}";
+
+pub const DEFAULT_USER_AGENT: &str = concat!("solana-rpc-stress-test/", env!("CARGO_PKG_VERSION"));
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClientPreset {
+    /// @solana/web3.js in Node.js
+    Web3Js,
+    /// solana-py (httpx)
+    SolanaPy,
+    /// solana-client (Rust RpcClient)
+    Rust,
+}
+
+impl ClientPreset {
+    pub fn name(self) -> &'static str {
+        match self {
+            ClientPreset::Web3Js => "web3-js",
+            ClientPreset::SolanaPy => "solana-py",
+            ClientPreset::Rust => "rust",
+        }
+    }
+
+    fn user_agent(self) -> &'static str {
+        match self {
+            ClientPreset::Web3Js => "node-fetch",
+            ClientPreset::SolanaPy => "python-httpx/0.27.0",
+            ClientPreset::Rust => DEFAULT_USER_AGENT,
+        }
+    }
+
+    // Заголовок solana-client, который web3.js и RpcClient добавляют к каждому запросу
+    fn headers(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            ClientPreset::Web3Js => &[("solana-client", "js/1.0.0-maint")],
+            ClientPreset::SolanaPy => &[],
+            ClientPreset::Rust => &[("solana-client", "rust/1.18.26")],
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ClientIdentity {
+    pub user_agent: String, // шаблон
+    pub preset: Option<ClientPreset>,
+}
+
+impl ClientIdentity {
+    // Явный --user-agent важнее User-Agent библиотеки
+    pub fn new(user_agent: Option<String>, preset: Option<ClientPreset>) -> Result<Self, String> {
+        let user_agent = user_agent
+            .or_else(|| preset.map(|preset| preset.user_agent().to_string()))
+            .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string());
+        HeaderValue::from_str(&user_agent.replace(WORKER_PLACEHOLDER, "0"))
+            .map_err(|_| format!("Invalid User-Agent '{}'", user_agent))?;
+        Ok(Self { user_agent, preset })
+    }
+
+    // Заголовки клиента воркера (для предварительных запросов - воркер 0)
+    pub fn headers(&self, worker_id: usize) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let user_agent = self.user_agent.replace(WORKER_PLACEHOLDER, &worker_id.to_string());
+        if let Ok(value) = HeaderValue::from_str(&user_agent) {
+            headers.insert(USER_AGENT, value);
+        }
+        for (name, value) in self.preset.map(ClientPreset::headers).unwrap_or_default() {
+            headers.insert(HeaderName::from_static(name), HeaderValue::from_static(value));
+        }
+        headers
+    }
+}
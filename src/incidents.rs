@@ -0,0 +1,169 @@
+// Всплески ошибок ("инциденты"): агрегатор сравнивает долю ошибок каждой закрытой секунды с фоном - долей
+// ошибок за последние спокойные секунды. Секунда с резким ростом открывает инцидент, несколько спокойных
+// секунд подряд его закрывают. В отчете - начало и конец, запросы и ошибки за инцидент и самый частый тип ошибки
+
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::aggregator::BucketClock;
+
+// Фон - доля ошибок за столько последних секунд вне инцидентов
+const BASELINE_SECS: usize = 60;
+// Секунда - всплеск, если ошибок не меньше стольких и их доля выше фона хотя бы на столько процентных пунктов
+const MIN_BURST_ERRORS: u64 = 5;
+const SPIKE_POINTS: f64 = 20.0;
+// Инцидент закрывается после стольких секунд подряд без всплеска
+const CALM_SECS: u64 = 3;
+
+#[derive(Serialize, Debug)]
+pub struct Incident {
+    pub start_secs: f64, // от старта теста
+    pub end_secs: f64,
+    pub start_utc: String,
+    pub end_utc: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate_percent: f64,
+    pub baseline_error_rate_percent: f64,
+    pub dominant_error: String,
+    pub dominant_errors: u64,
+}
+
+#[derive(Default)]
+struct Counts {
+    requests: u64,
+    errors: u64,
+    kinds: BTreeMap<String, u64>,
+}
+
+impl Counts {
+    fn merge(&mut self, other: Counts) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        for (kind, count) in other.kinds {
+            *self.kinds.entry(kind).or_insert(0) += count;
+        }
+    }
+}
+
+struct OpenIncident {
+    start_second: u64, // по часам корзин
+    last_spike_second: u64,
+    baseline_rate: f64,
+    counts: Counts,
+    calm: Counts, // спокойные секунды после последнего всплеска: войдут в инцидент, если он продолжится
+}
+
+pub struct IncidentDetector {
+    clock: BucketClock,
+    kinds: BTreeMap<String, u64>, // ошибки текущей секунды по типу
+    baseline: VecDeque<(u64, u64)>, // запросы и ошибки спокойных секунд
+    baseline_requests: u64,
+    baseline_errors: u64,
+    open: Option<OpenIncident>,
+    closed: Vec<Incident>,
+}
+
+impl IncidentDetector {
+    pub fn new(clock: BucketClock) -> Self {
+        Self {
+            clock,
+            kinds: BTreeMap::new(),
+            baseline: VecDeque::new(),
+            baseline_requests: 0,
+            baseline_errors: 0,
+            open: None,
+            closed: Vec::new(),
+        }
+    }
+
+    pub fn add_error(&mut self, kind: String) {
+        *self.kinds.entry(kind).or_insert(0) += 1;
+    }
+
+    fn baseline_rate(&self) -> f64 {
+        if self.baseline_requests > 0 {
+            self.baseline_errors as f64 / self.baseline_requests as f64 * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    // Закрытая секунда second (по часам корзин) с ее запросами и ошибками
+    pub fn close_second(&mut self, second: u64, requests: u64, errors: u64) {
+        let counts = Counts {
+            requests,
+            errors,
+            kinds: std::mem::take(&mut self.kinds),
+        };
+        let rate = if requests > 0 { errors as f64 / requests as f64 * 100.0 } else { 0.0 };
+        let spike = errors >= MIN_BURST_ERRORS && rate >= self.baseline_rate() + SPIKE_POINTS;
+        match &mut self.open {
+            Some(incident) if spike => {
+                incident.counts.merge(std::mem::take(&mut incident.calm));
+                incident.counts.merge(counts);
+                incident.last_spike_second = second;
+            }
+            Some(incident) if second - incident.last_spike_second < CALM_SECS => incident.calm.merge(counts),
+            Some(_) => {
+                self.close_incident();
+                self.add_baseline(requests, errors);
+            }
+            None if spike => {
+                self.open = Some(OpenIncident {
+                    start_second: second,
+                    last_spike_second: second,
+                    baseline_rate: self.baseline_rate(),
+                    counts,
+                    calm: Counts::default(),
+                })
+            }
+            None => self.add_baseline(requests, errors),
+        }
+    }
+
+    fn add_baseline(&mut self, requests: u64, errors: u64) {
+        self.baseline.push_back((requests, errors));
+        self.baseline_requests += requests;
+        self.baseline_errors += errors;
+        if self.baseline.len() > BASELINE_SECS {
+            let (requests, errors) = self.baseline.pop_front().unwrap_or_default();
+            self.baseline_requests -= requests;
+            self.baseline_errors -= errors;
+        }
+    }
+
+    fn close_incident(&mut self) {
+        let Some(incident) = self.open.take() else {
+            return;
+        };
+        let counts = incident.counts;
+        let (dominant_error, dominant_errors) = counts
+            .kinds
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .unwrap_or_default();
+        let start_secs = self.clock.run_secs(incident.start_second);
+        // Последняя секунда может быть незавершенной: конец - не позже конца теста
+        let end_secs = self.clock.run_secs(incident.last_spike_second + 1).min(self.clock.elapsed_secs());
+        self.closed.push(Incident {
+            start_secs,
+            end_secs,
+            start_utc: self.clock.utc(start_secs),
+            end_utc: self.clock.utc(end_secs),
+            requests: counts.requests,
+            errors: counts.errors,
+            error_rate_percent: counts.errors as f64 / counts.requests.max(1) as f64 * 100.0,
+            baseline_error_rate_percent: incident.baseline_rate,
+            dominant_error,
+            dominant_errors,
+        });
+    }
+
+    // Все инциденты; незавершенная текущая секунда тоже проверяется, открытый инцидент закрывается концом теста
+    pub fn finish(&mut self, current_second: u64, requests: u64, errors: u64) -> Vec<Incident> {
+        self.close_second(current_second, requests, errors);
+        self.close_incident();
+        std::mem::take(&mut self.closed)
+    }
+}
@@ -0,0 +1,198 @@
+// Подкоманда init: генерирует закомментированный TOML конфиг с выбранными методами.
+// Без --method / --profile спрашивает параметры в терминале.
+// params берутся из каталога (или профиля) и переводятся из JSON в TOML, чтобы не писать их вручную
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufRead, Write as _};
+use std::path::Path;
+
+use crate::catalog;
+use crate::profile::Profile;
+use crate::MethodConfig;
+
+pub struct InitOptions {
+    pub output: String,
+    pub force: bool,
+    pub methods: Vec<String>,
+    pub profile: Option<Profile>,
+    pub workers: Option<usize>,
+    pub url: String,
+    pub duration: u64,
+}
+
+const DEFAULT_WORKERS: usize = 10;
+
+// Вопрос в терминале; пустой ответ (или конец ввода) - значение по умолчанию
+fn ask(input: &mut impl BufRead, question: &str, default: &str) -> io::Result<String> {
+    eprint!("{} [{}]: ", question, default);
+    io::stderr().flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let answer = line.trim();
+    Ok(if answer.is_empty() { default.to_string() } else { answer.to_string() })
+}
+
+// Спрашивает URL, длительность, методы и воркеров; в ответ на методы можно указать имя профиля
+fn wizard(options: &mut InitOptions) -> Result<(), String> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let io_error = |e: io::Error| e.to_string();
+    options.url = ask(&mut input, "RPC endpoint URL", &options.url).map_err(io_error)?;
+    let duration = ask(&mut input, "Test duration in seconds (0 = infinite)", &options.duration.to_string())
+        .map_err(io_error)?;
+    options.duration = duration.parse().map_err(|_| format!("invalid duration '{}'", duration))?;
+
+    eprintln!("Methods: comma-separated names (see `methods list`) or a profile: read-heavy, indexer, wallet, archival, dashboard, big-accounts, das");
+    let methods = ask(&mut input, "Methods", "getSlot").map_err(io_error)?;
+    match <Profile as clap::ValueEnum>::from_str(&methods, true) {
+        Ok(profile) => options.profile = Some(profile),
+        Err(_) => {
+            options.methods = methods
+                .split(',')
+                .map(|m| m.trim().to_string())
+                .filter(|m| !m.is_empty())
+                .collect()
+        }
+    }
+
+    let (question, default) = match options.profile {
+        Some(_) => ("Workers multiplier for the profile mix", 1),
+        None => ("Workers per method", DEFAULT_WORKERS),
+    };
+    let workers = ask(&mut input, question, &default.to_string()).map_err(io_error)?;
+    options.workers = Some(workers.parse().map_err(|_| format!("invalid number of workers '{}'", workers))?);
+    Ok(())
+}
+
+fn toml_value(value: &serde_json::Value) -> Result<String, String> {
+    toml::Value::try_from(value)
+        .map(|value| value.to_string())
+        .map_err(|e| format!("params {} cannot be written as TOML: {}", value, e))
+}
+
+fn write_header(out: &mut String, options: &InitOptions) -> std::fmt::Result {
+    writeln!(out, "# Generated by `solana-rpc-stress-test init`. Run with:")?;
+    writeln!(out, "#   solana-rpc-stress-test --config {}", options.output)?;
+    writeln!(out, "# Command-line flags are used for keys that are not set here.")?;
+    writeln!(out)?;
+    writeln!(out, "url = {}", toml::Value::String(options.url.clone()))?;
+    writeln!(out, "{:<22}# seconds, 0 = infinite", format!("duration = {}", options.duration))?;
+    writeln!(out, "{:<22}# pause between requests of each worker", "timeout_ms = 1")?;
+    writeln!(out, "{:<22}# seconds", "http_timeout = 30")?;
+    writeln!(out)?;
+    writeln!(out, "# Optional settings, uncomment to use:")?;
+    writeln!(out, "# jitter_ms = 0                         # random addition to timeout_ms")?;
+    writeln!(out, "# seed = 42                             # reproducible randomness")?;
+    writeln!(out, "# request_budget = 100000               # stop after this many requests (credits with [credits])")?;
+    writeln!(out, "# expect_cluster = \"mainnet\"            # refuse to run against another cluster")?;
+    writeln!(out, "# breaker_error_rate = 50               # pause workers when the 10 s error rate reaches 50%")?;
+    writeln!(out, "# breaker_cooldown = 30                 # seconds")?;
+    writeln!(out, "# api_keys = [\"${{env:KEY_1}}\", \"${{env:KEY_2}}\"]  # replace {{api_key}} in url, or set api_key_header")?;
+    writeln!(out, "# api_key_rotation = \"per-worker\"       # or \"per-request\"")?;
+    writeln!(out, "# accept_encoding = \"gzip\"              # report wire vs decompressed sizes")?;
+    writeln!(out, "# capture_headers = [\"x-ratelimit-remaining\"]")?;
+    writeln!(out, "#")?;
+    writeln!(out, "# [apdex]")?;
+    writeln!(out, "# satisfied_ms = 100")?;
+    writeln!(out, "# tolerating_ms = 400")?;
+    writeln!(out, "#")?;
+    writeln!(out, "# [metadata]                            # becomes run tags in all reports")?;
+    writeln!(out, "# provider = \"my-provider\"")
+}
+
+// Секция [[methods]] с сигнатурой метода и пояснением заглушек в комментариях
+fn write_method(out: &mut String, method_config: &MethodConfig, spec: &catalog::MethodSpec, params: &str) -> std::fmt::Result {
+    let uses = |placeholder| {
+        method_config
+            .params
+            .as_deref()
+            .is_some_and(|p| catalog::uses_placeholder(p, placeholder))
+    };
+    writeln!(out)?;
+    writeln!(out, "# {}", catalog::signature(spec))?;
+    if uses(catalog::LATEST_SLOT) {
+        writeln!(out, "# \"{}\" is replaced with the current slot before the test starts", catalog::LATEST_SLOT)?;
+    }
+    if uses(catalog::FIRST_AVAILABLE_BLOCK) {
+        writeln!(
+            out,
+            "# \"{}\" is replaced with the oldest block the node stores before the test starts",
+            catalog::FIRST_AVAILABLE_BLOCK
+        )?;
+    }
+    writeln!(out, "[[methods]]")?;
+    writeln!(out, "method = \"{}\"", method_config.method)?;
+    writeln!(out, "params = {}", params)?;
+    writeln!(out, "workers = {}", method_config.workers)?;
+    if let Some(batch) = method_config.batch.filter(|&batch| batch > 1) {
+        writeln!(out, "batch = {}", batch)?;
+    }
+    Ok(())
+}
+
+fn render(options: &InitOptions, methods: &[MethodConfig]) -> Result<String, String> {
+    let mut out = String::new();
+    // Запись в String не может завершиться ошибкой
+    let _ = write_header(&mut out, options);
+    for method_config in methods {
+        let spec = catalog::lookup(&method_config.method)?;
+        let params = match &method_config.params {
+            Some(params) => toml_value(&serde_json::Value::Array(params.clone()))?,
+            None => "[]".to_string(),
+        };
+        let _ = write_method(&mut out, method_config, spec, &params);
+    }
+    Ok(out)
+}
+
+pub fn run(mut options: InitOptions) -> Result<(), String> {
+    if options.output != "-" && Path::new(&options.output).exists() && !options.force {
+        return Err(format!("{} already exists; use --force to overwrite", options.output));
+    }
+    if options.methods.is_empty() && options.profile.is_none() {
+        wizard(&mut options)?;
+    }
+
+    let methods: Vec<MethodConfig> = match options.profile {
+        Some(profile) => profile.methods(options.workers.unwrap_or(1), 1),
+        None => options
+            .methods
+            .iter()
+            .map(|method| MethodConfig {
+                method: method.clone(),
+                alias: None,
+                params: catalog::default_params(method),
+                workers: options.workers.unwrap_or(DEFAULT_WORKERS),
+                batch: None,
+                start_offset: None,
+                end_offset: None,
+                duration: None,
+                path: None,
+                expected_status: None,
+            })
+            .collect(),
+    };
+    if methods.is_empty() {
+        return Err("No methods selected".to_string());
+    }
+    let problems: Vec<String> = methods
+        .iter()
+        .filter_map(|m| catalog::lookup(&m.method).err())
+        .collect();
+    if !problems.is_empty() {
+        return Err(problems.join("\n"));
+    }
+
+    let config = render(&options, &methods)?;
+    if options.output == "-" {
+        print!("{}", config);
+    } else {
+        fs::write(&options.output, config).map_err(|e| format!("Failed to write {}: {}", options.output, e))?;
+        eprintln!(
+            "Config written to {}; run it with: solana-rpc-stress-test --config {}",
+            options.output, options.output
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,410 @@
+// Нагрузка на Jito block engine (подкоманда bundles): заранее собранные бандлы из файла отправляются через
+// sendBundle с заданной частотой, параллельно идет getTipAccounts. Латентность приема бандла (ответ с ID) и
+// доля отказов по причинам показывают емкость endpoint; с --status-check ID принятых бандлов опрашиваются через
+// getInflightBundleStatuses, и в отчете видно, сколько бандлов попало в блок и через сколько
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::secrets;
+
+pub const DEFAULT_URL: &str = "https://mainnet.block-engine.jito.wtf/api/v1/bundles";
+// Ограничения block engine: транзакций в бандле и ID в одном getInflightBundleStatuses
+const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+const STATUS_BATCH: usize = 5;
+const STATUS_POLL: Duration = Duration::from_secs(1);
+// UUID для повышенного лимита запросов
+const AUTH_HEADER: &str = "x-jito-auth";
+// Текст отказа в отчете обрезается: в нем бывают подписи транзакций
+const REASON_MAX_CHARS: usize = 80;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BundleEncoding {
+    /// Transactions in the file are base58
+    Base58,
+    /// Transactions in the file are base64
+    Base64,
+}
+
+impl BundleEncoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            BundleEncoding::Base58 => "base58",
+            BundleEncoding::Base64 => "base64",
+        }
+    }
+}
+
+pub struct BundleOptions {
+    pub url: String,
+    pub bundles: Vec<Vec<String>>, // пусто - только getTipAccounts
+    pub encoding: BundleEncoding,
+    pub duration: Duration,
+    pub bundle_rate: f64, // sendBundle в секунду
+    pub tip_rate: f64, // getTipAccounts в секунду, 0 - выключен
+    pub concurrency: usize,
+    pub http_timeout: Duration,
+    pub auth: Option<String>,
+    pub status_timeout: Option<Duration>, // --status-check: сколько ждать итогового статуса бандла
+}
+
+// Бандлы: по JSON массиву подписанных транзакций на строку, пустые строки и комментарии (#) пропускаются
+pub fn load_bundles(path: &Path) -> Result<Vec<Vec<String>>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read bundles file {}: {}", path.display(), e))?;
+    let mut bundles = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fail = |e: String| format!("{}:{}: {}", path.display(), line_number + 1, e);
+        let bundle: Vec<String> = serde_json::from_str(line)
+            .map_err(|e| fail(format!("a bundle must be a JSON array of encoded transactions: {}", e)))?;
+        if bundle.is_empty() || bundle.len() > MAX_BUNDLE_TRANSACTIONS {
+            return Err(fail(format!("a bundle has 1 to {} transactions, got {}", MAX_BUNDLE_TRANSACTIONS, bundle.len())));
+        }
+        bundles.push(bundle);
+    }
+    if bundles.is_empty() {
+        return Err(format!("Bundles file {} has no bundles", path.display()));
+    }
+    Ok(bundles)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    Accepted,
+    Rejected(String), // JSON-RPC ошибка: текст причины
+    RateLimited,
+    Http(u16),
+    Timeout,
+    ConnectionError,
+    InvalidResponse,
+}
+
+impl Outcome {
+    fn describe(&self) -> String {
+        match self {
+            Outcome::Accepted => "accepted".to_string(),
+            Outcome::Rejected(reason) => format!("rejected: {}", reason),
+            Outcome::RateLimited => "HTTP 429 (rate limited)".to_string(),
+            Outcome::Http(status) => format!("HTTP {}", status),
+            Outcome::Timeout => "timeout".to_string(),
+            Outcome::ConnectionError => "connection error".to_string(),
+            Outcome::InvalidResponse => "invalid response".to_string(),
+        }
+    }
+}
+
+async fn call(client: &reqwest::Client, options: &BundleOptions, method: &str, params: Value) -> (Outcome, Option<Value>) {
+    let mut request = client.post(&options.url).json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }));
+    if let Some(auth) = &options.auth {
+        request = request.header(AUTH_HEADER, auth);
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) if e.is_timeout() => return (Outcome::Timeout, None),
+        Err(_) => return (Outcome::ConnectionError, None),
+    };
+    let status = response.status();
+    let body: Option<Value> = match response.bytes().await {
+        Ok(body) => serde_json::from_slice(&body).ok(),
+        Err(e) if e.is_timeout() => return (Outcome::Timeout, None),
+        Err(_) => return (Outcome::ConnectionError, None),
+    };
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return (Outcome::RateLimited, None);
+    }
+    // Отказ в приеме бандла приходит JSON-RPC ошибкой, иногда вместе с HTTP 400
+    if let Some(error) = body.as_ref().and_then(|body| body.get("error")) {
+        let reason = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+        return (Outcome::Rejected(reason.chars().take(REASON_MAX_CHARS).collect()), None);
+    }
+    if !status.is_success() {
+        return (Outcome::Http(status.as_u16()), None);
+    }
+    match body.and_then(|mut body| body.get_mut("result").map(Value::take)) {
+        Some(result) => (Outcome::Accepted, Some(result)),
+        None => (Outcome::InvalidResponse, None),
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct MethodResult {
+    pub sent: usize,
+    pub outcomes: BTreeMap<Outcome, usize>,
+    pub accepted_micros: Vec<u64>, // латентность принятых запросов
+}
+
+impl MethodResult {
+    fn record(&mut self, outcome: Outcome, micros: u64) {
+        self.sent += 1;
+        if outcome == Outcome::Accepted {
+            self.accepted_micros.push(micros);
+        }
+        *self.outcomes.entry(outcome).or_default() += 1;
+    }
+
+    fn count(&self, matches: impl Fn(&Outcome) -> bool) -> usize {
+        self.outcomes.iter().filter(|(outcome, _)| matches(outcome)).map(|(_, count)| count).sum()
+    }
+}
+
+// Принятый бандл, статус которого еще опрашивается
+struct Submitted {
+    id: String,
+    at: Instant,
+    status: String, // последний статус getInflightBundleStatuses
+}
+
+#[derive(Default, Debug)]
+pub struct StatusResult {
+    pub statuses: BTreeMap<String, usize>, // итоговый или последний статус -> бандлов
+    pub landed_micros: Vec<u64>, // от отправки до статуса Landed
+    pub requests: usize,
+    pub errors: usize,
+}
+
+#[derive(Debug)]
+pub struct BundleReport {
+    pub url: String,
+    pub elapsed_secs: f64,
+    pub send_bundle: MethodResult,
+    pub tip_accounts: MethodResult,
+    pub status: Option<StatusResult>,
+}
+
+// Запросы метода с частотой rate до конца теста; params - по номеру запроса
+async fn run_method(
+    client: reqwest::Client,
+    options: Arc<BundleOptions>,
+    semaphore: Arc<Semaphore>,
+    method: &'static str,
+    rate: f64,
+    params: impl Fn(usize) -> Value,
+    submitted: Option<Arc<Mutex<Vec<Submitted>>>>,
+) -> MethodResult {
+    let end = Instant::now() + options.duration;
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+    let mut handles = Vec::new();
+    let mut index = 0;
+    while Instant::now() < end {
+        ticker.tick().await;
+        let Ok(permit) = semaphore.clone().acquire_owned().await else {
+            break;
+        };
+        let (client, options, submitted, params) = (client.clone(), options.clone(), submitted.clone(), params(index));
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let start = Instant::now();
+            let (outcome, result) = call(&client, &options, method, params).await;
+            let micros = start.elapsed().as_micros() as u64;
+            if let (Some(submitted), Some(Value::String(id))) = (submitted, result) {
+                submitted.lock().unwrap().push(Submitted { id, at: start, status: "Unknown".to_string() });
+            }
+            (outcome, micros)
+        }));
+        index += 1;
+    }
+    let mut result = MethodResult::default();
+    for handle in handles {
+        if let Ok((outcome, micros)) = handle.await {
+            result.record(outcome, micros);
+        }
+    }
+    result
+}
+
+// Опрос статусов принятых бандлов раз в секунду; после конца нагрузки - пока все не получат итоговый статус
+// или не выйдет timeout. Landed и Failed - итоговые, Pending и Invalid (еще не виден) опрашиваются дальше
+async fn poll_statuses(
+    client: reqwest::Client,
+    options: Arc<BundleOptions>,
+    submitted: Arc<Mutex<Vec<Submitted>>>,
+    timeout: Duration,
+) -> StatusResult {
+    let load_end = Instant::now() + options.duration;
+    let mut result = StatusResult::default();
+    loop {
+        tokio::time::sleep(STATUS_POLL).await;
+        let now = Instant::now();
+        let (done, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut *submitted.lock().unwrap())
+            .into_iter()
+            .partition(|bundle| now.duration_since(bundle.at) > timeout);
+        for bundle in done {
+            *result.statuses.entry(bundle.status).or_default() += 1;
+        }
+        let mut still_pending = Vec::new();
+        for chunk in pending.chunks(STATUS_BATCH) {
+            let ids: Vec<&str> = chunk.iter().map(|bundle| bundle.id.as_str()).collect();
+            result.requests += 1;
+            let (outcome, response) = call(&client, &options, "getInflightBundleStatuses", json!([ids])).await;
+            if outcome != Outcome::Accepted {
+                result.errors += 1;
+            }
+            let values = response.as_ref().and_then(|response| response.get("value")).and_then(Value::as_array);
+            for bundle in chunk {
+                let status = values
+                    .into_iter()
+                    .flatten()
+                    .find(|value| value.get("bundle_id").and_then(Value::as_str) == Some(&bundle.id))
+                    .and_then(|value| value.get("status").and_then(Value::as_str))
+                    .unwrap_or(&bundle.status)
+                    .to_string();
+                match status.as_str() {
+                    "Landed" => {
+                        result.landed_micros.push(bundle.at.elapsed().as_micros() as u64);
+                        *result.statuses.entry(status).or_default() += 1;
+                    }
+                    "Failed" => *result.statuses.entry(status).or_default() += 1,
+                    _ => still_pending.push(Submitted { id: bundle.id.clone(), at: bundle.at, status }),
+                }
+            }
+        }
+        let mut guard = submitted.lock().unwrap();
+        guard.extend(still_pending);
+        if guard.is_empty() && Instant::now() >= load_end {
+            break;
+        }
+    }
+    result
+}
+
+pub async fn run(options: BundleOptions) -> Result<BundleReport, String> {
+    let send_bundles = !options.bundles.is_empty();
+    if !send_bundles && options.tip_rate <= 0.0 {
+        return Err("Nothing to send: no --bundles-file and --tip-rate 0".to_string());
+    }
+    if send_bundles && !(options.bundle_rate > 0.0 && options.bundle_rate.is_finite()) {
+        return Err("--rate must be a positive number".to_string());
+    }
+    if !options.tip_rate.is_finite() || options.tip_rate < 0.0 {
+        return Err("--tip-rate must be a non-negative number".to_string());
+    }
+    let client = reqwest::Client::builder()
+        .timeout(options.http_timeout)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    println!(
+        "Stressing {} for {}s: sendBundle {} per sec ({} bundles), getTipAccounts {} per sec...",
+        secrets::redact(&options.url),
+        options.duration.as_secs(),
+        if send_bundles { options.bundle_rate } else { 0.0 },
+        options.bundles.len(),
+        options.tip_rate
+    );
+
+    let options = Arc::new(options);
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let submitted = options.status_timeout.map(|_| Arc::new(Mutex::new(Vec::new())));
+    let started = Instant::now();
+    let status = options.status_timeout.zip(submitted.clone()).map(|(timeout, submitted)| {
+        tokio::spawn(poll_statuses(client.clone(), options.clone(), submitted, timeout))
+    });
+
+    let bundles = options.clone();
+    let encoding = json!({ "encoding": options.encoding.name() });
+    let send_bundle = async {
+        if !send_bundles {
+            return MethodResult::default();
+        }
+        // Бандлы идут по кругу: повтор уже принятого бандла block engine отклоняет, это видно в причинах отказов
+        let params = |index: usize| json!([bundles.bundles[index % bundles.bundles.len()], encoding]);
+        run_method(client.clone(), options.clone(), semaphore.clone(), "sendBundle", options.bundle_rate, params, submitted).await
+    };
+    let tip_accounts = async {
+        if options.tip_rate <= 0.0 {
+            return MethodResult::default();
+        }
+        run_method(client.clone(), options.clone(), semaphore.clone(), "getTipAccounts", options.tip_rate, |_| json!([]), None)
+            .await
+    };
+    let (send_bundle, tip_accounts) = tokio::join!(send_bundle, tip_accounts);
+    let elapsed_secs = started.elapsed().as_secs_f64();
+    let status = match status {
+        Some(handle) => Some(handle.await.map_err(|e| e.to_string())?),
+        None => None,
+    };
+    Ok(BundleReport {
+        url: options.url.clone(),
+        elapsed_secs,
+        send_bundle,
+        tip_accounts,
+        status,
+    })
+}
+
+fn percentile_ms(sorted: &[u64], p: f64) -> Option<f64> {
+    let index = ((sorted.len() as f64 * p).ceil() as usize).saturating_sub(1);
+    (!sorted.is_empty()).then(|| sorted[index.min(sorted.len() - 1)] as f64 / 1000.0)
+}
+
+pub fn print_report(report: &BundleReport) {
+    let ms = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |ms| format!("{:.2}", ms));
+    println!("\n=== Jito Block Engine: {} ===", secrets::redact(&report.url));
+    println!(
+        "{:<14}  {:>6}  {:>8}  {:>8}  {:>6}  {:>6}  {:>10}  {:>9}  {:>9}  {:>9}  {:>9}",
+        "Method", "Sent", "Accepted", "Rejected", "429", "Errors", "Accepted/s", "Avg ms", "p50 ms", "p95 ms", "p99 ms"
+    );
+    for (method, result) in [("sendBundle", &report.send_bundle), ("getTipAccounts", &report.tip_accounts)] {
+        if result.sent == 0 {
+            continue;
+        }
+        let mut sorted = result.accepted_micros.clone();
+        sorted.sort_unstable();
+        let accepted = sorted.len();
+        let rejected = result.count(|outcome| matches!(outcome, Outcome::Rejected(_)));
+        let limited = result.count(|outcome| *outcome == Outcome::RateLimited);
+        println!(
+            "{:<14}  {:>6}  {:>8}  {:>8}  {:>6}  {:>6}  {:>10.2}  {:>9}  {:>9}  {:>9}  {:>9}",
+            method,
+            result.sent,
+            accepted,
+            rejected,
+            limited,
+            result.sent - accepted - rejected - limited,
+            accepted as f64 / report.elapsed_secs.max(0.001),
+            ms((!sorted.is_empty()).then(|| sorted.iter().sum::<u64>() as f64 / sorted.len() as f64 / 1000.0)),
+            ms(percentile_ms(&sorted, 0.50)),
+            ms(percentile_ms(&sorted, 0.95)),
+            ms(percentile_ms(&sorted, 0.99))
+        );
+    }
+
+    for (method, result) in [("sendBundle", &report.send_bundle), ("getTipAccounts", &report.tip_accounts)] {
+        let other: Vec<String> = result
+            .outcomes
+            .iter()
+            .filter(|(outcome, _)| **outcome != Outcome::Accepted)
+            .map(|(outcome, count)| format!("  {} × {}", outcome.describe(), count))
+            .collect();
+        if !other.is_empty() {
+            println!("\n{} not accepted:\n{}", method, other.join("\n"));
+        }
+    }
+
+    if let Some(status) = &report.status {
+        let mut landed = status.landed_micros.clone();
+        landed.sort_unstable();
+        let statuses: Vec<String> = status.statuses.iter().map(|(name, count)| format!("{} {}", name, count)).collect();
+        println!(
+            "\nBundle status (getInflightBundleStatuses, {} requests, {} errors): {}",
+            status.requests,
+            status.errors,
+            if statuses.is_empty() { "no accepted bundles".to_string() } else { statuses.join(", ") }
+        );
+        if !landed.is_empty() {
+            println!(
+                "Time to land: p50 {} ms, p95 {} ms (from submission, 1 s polling)",
+                ms(percentile_ms(&landed, 0.50)),
+                ms(percentile_ms(&landed, 0.95))
+            );
+        }
+    }
+}
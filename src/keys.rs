@@ -0,0 +1,180 @@
+// Пул API ключей: нагрузка идет как от множества разных клиентов, чтобы проверить лимиты провайдера на ключ.
+// Ключ подставляется в URL вместо {api_key} или отправляется в заголовке (--api-key-header).
+// Распределение зависит от seed: тот же seed дает те же ключи тем же воркерам
+
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::discovery::NodeStats;
+use crate::Rng;
+
+pub const URL_PLACEHOLDER: &str = "{api_key}";
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyRotation {
+    /// Each worker keeps one key for the whole run
+    #[default]
+    PerWorker,
+    /// Every request (scenario iteration) picks a random key
+    PerRequest,
+}
+
+impl KeyRotation {
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyRotation::PerWorker => "per-worker",
+            KeyRotation::PerRequest => "per-request",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyPool {
+    keys: Arc<Vec<String>>,
+    pub header: Option<String>,
+    pub rotation: KeyRotation,
+}
+
+impl KeyPool {
+    // Ключ должен куда-то попасть: либо в URL, либо в заголовок
+    pub fn new(keys: Vec<String>, header: Option<String>, rotation: KeyRotation, url: &str) -> Result<Self, String> {
+        if keys.is_empty() {
+            return Err("API key pool is empty".to_string());
+        }
+        match &header {
+            Some(name) => {
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("Invalid API key header name '{}'", name))?;
+            }
+            None if !url.contains(URL_PLACEHOLDER) => {
+                return Err(format!(
+                    "API keys need {} in the URL or --api-key-header to be sent",
+                    URL_PLACEHOLDER
+                ))
+            }
+            None => {}
+        }
+        if let Some(index) = keys.iter().position(|key| HeaderValue::from_str(key).is_err() || key.is_empty()) {
+            return Err(format!("API key {} is empty or contains invalid characters", label(&keys, index)));
+        }
+        Ok(Self {
+            keys: Arc::new(keys),
+            header,
+            rotation,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub fn label(&self, index: usize) -> String {
+        label(&self.keys, index)
+    }
+
+    // URL для ключа (без {api_key} в URL не меняется)
+    pub fn url(&self, url: &str, index: usize) -> String {
+        url.replace(URL_PLACEHOLDER, &self.keys[index])
+    }
+
+    // Заголовок с ключом: (имя, значение)
+    pub fn header(&self, index: usize) -> Option<(&str, &str)> {
+        self.header.as_deref().map(|name| (name, self.keys[index].as_str()))
+    }
+
+    // Индексы ключей, которые использует воркер: один на весь прогон или все (выбор на каждый запрос).
+    // Воркеры получают ключи по кругу со сдвигом от seed, поэтому ключи нагружены равномерно
+    pub fn worker_keys(&self, seed: u64, worker_id: usize) -> Vec<usize> {
+        match self.rotation {
+            KeyRotation::PerWorker => {
+                let offset = Rng::new(seed).next_u64() as usize;
+                vec![offset.wrapping_add(worker_id) % self.keys.len()]
+            }
+            KeyRotation::PerRequest => (0..self.keys.len()).collect(),
+        }
+    }
+}
+
+// Номер ключа и его последние символы: по ним ключ можно найти у провайдера, не раскрывая его в отчете
+fn label(keys: &[String], index: usize) -> String {
+    let key = &keys[index];
+    let chars = key.chars().count();
+    if chars > 8 {
+        let tail: String = key.chars().skip(chars - 4).collect();
+        format!("#{} (...{})", index + 1, tail)
+    } else {
+        format!("#{}", index + 1)
+    }
+}
+
+// Файл ключей: по одному на строку, пустые строки и комментарии (#) пропускаются
+pub fn load_file(path: &Path) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read API keys file {}: {}", path.display(), e))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+// Итог по ключу пула: по доле успешных ответов видно, какие ключи упирались в лимиты
+#[derive(Serialize, Debug)]
+pub struct KeySummary {
+    pub key: String,
+    pub requests: u64,
+    pub successful: u64,
+    pub success_rate: f64,
+    pub average_ms: f64,
+    pub max_ms: f64,
+}
+
+// Счетчики ключа пула: те же, что у endpoint, плюс подпись ключа для отчета
+pub struct KeyStats {
+    label: String,
+    counters: NodeStats,
+}
+
+impl KeyStats {
+    pub fn new(label: String) -> Self {
+        Self {
+            label,
+            counters: NodeStats::default(),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn record_attempts(&self, count: u64) {
+        self.counters.record_attempts(count);
+    }
+
+    pub fn record_success(&self, response_time_micros: u64, count: u64) {
+        self.counters.record_success(response_time_micros, count);
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.counters.merge(other.counters);
+    }
+
+    pub fn summarize(&self) -> KeySummary {
+        let summary = self.counters.summarize(&self.label);
+        KeySummary {
+            key: summary.url,
+            requests: summary.requests,
+            successful: summary.successful,
+            success_rate: summary.success_rate,
+            average_ms: summary.average_ms,
+            max_ms: summary.max_ms,
+        }
+    }
+}
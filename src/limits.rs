@@ -0,0 +1,172 @@
+// Ограничения нагрузки: общий лимит RPS (--max-rps), пресеты тарифов провайдеров (--provider-limits),
+// которые задают лимит RPS, число одновременных воркеров и размер батча, и адаптивная пауза воркеров
+// (--pace-factor). С ними тест выбирает всю разрешенную квоту, но не выходит за нее и не получает бан
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderPlan {
+    /// api.mainnet-beta.solana.com and other Solana Foundation public endpoints
+    SolanaPublic,
+    /// Helius Free
+    HeliusFree,
+    /// Helius Developer
+    HeliusDeveloper,
+    /// Helius Business
+    HeliusBusiness,
+    /// Helius Professional
+    HeliusProfessional,
+    /// QuickNode Free (Discover)
+    QuicknodeFree,
+}
+
+// Лимиты тарифа. None - тариф это не ограничивает
+#[derive(Clone, Copy, Debug)]
+pub struct PlanLimits {
+    pub max_rps: u64,
+    pub max_concurrency: Option<usize>,
+    pub max_batch: Option<usize>,
+}
+
+impl ProviderPlan {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProviderPlan::SolanaPublic => "solana-public",
+            ProviderPlan::HeliusFree => "helius-free",
+            ProviderPlan::HeliusDeveloper => "helius-developer",
+            ProviderPlan::HeliusBusiness => "helius-business",
+            ProviderPlan::HeliusProfessional => "helius-professional",
+            ProviderPlan::QuicknodeFree => "quicknode-free",
+        }
+    }
+
+    // Опубликованные лимиты тарифов; провайдеры их меняют, поэтому явный --max-rps важнее пресета
+    pub fn limits(self) -> PlanLimits {
+        let (max_rps, max_concurrency, max_batch) = match self {
+            // 100 запросов за 10 с и 40 одновременных соединений с одного IP
+            ProviderPlan::SolanaPublic => (10, Some(40), None),
+            ProviderPlan::HeliusFree => (10, None, None),
+            ProviderPlan::HeliusDeveloper => (50, None, None),
+            ProviderPlan::HeliusBusiness => (200, None, None),
+            ProviderPlan::HeliusProfessional => (500, None, None),
+            ProviderPlan::QuicknodeFree => (15, None, Some(10)),
+        };
+        PlanLimits {
+            max_rps,
+            max_concurrency,
+            max_batch,
+        }
+    }
+}
+
+// Общий для всех воркеров лимит JSON-RPC запросов в секунду: запросы идут не чаще одного
+// на интервал. Без всплесков - провайдеры считают запросы в коротких окнах, и всплеск
+// на границе окна тоже приводит к 429
+pub struct RateLimiter {
+    interval_nanos: u64,
+    next_nanos: AtomicU64, // от started_at
+    started_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(max_rps: u64) -> Self {
+        Self {
+            interval_nanos: 1_000_000_000 / max_rps.max(1),
+            next_nanos: AtomicU64::new(0),
+            started_at: Instant::now(),
+        }
+    }
+
+    // Занимает место для count запросов (батч - сразу все), если его очередь уже наступила,
+    // иначе возвращает, сколько ждать. Место берется в момент отправки, а не заранее:
+    // иначе воркеры, проснувшиеся с опозданием (например, при занятом рантайме), ушли бы разом
+    pub fn try_acquire(&self, count: u64) -> Result<(), Duration> {
+        let now = self.started_at.elapsed().as_nanos() as u64;
+        let cost = self.interval_nanos * count.max(1);
+        self.next_nanos
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |next| (next <= now).then_some(now + cost))
+            .map(|_| ())
+            .map_err(|next| Duration::from_nanos(next - now))
+    }
+}
+
+// Сглаживание среднего времени ответа для --pace-factor: вес последнего запроса
+const PACE_SMOOTHING: f64 = 0.2;
+
+// Пауза воркера после запроса: таймаут плюс factor × недавнее среднее время ответа. Воркер ждет ответа
+// постоянную долю времени (1 / (1 + factor)), поэтому замедлившийся endpoint сам получает меньше запросов
+pub struct Pacer {
+    factor: Option<f64>,
+    average_ms: Option<f64>,
+}
+
+impl Pacer {
+    pub fn new(factor: Option<f64>) -> Self {
+        Self {
+            factor,
+            average_ms: None,
+        }
+    }
+
+    // response_time - сколько занял последний запрос (с ошибками и таймаутами: они тоже нагрузка)
+    pub fn pause(&mut self, response_time: Duration, base_ms: u64) -> Duration {
+        let Some(factor) = self.factor else {
+            return Duration::from_millis(base_ms);
+        };
+        let latest = response_time.as_secs_f64() * 1000.0;
+        let average = match self.average_ms {
+            Some(average) => average + (latest - average) * PACE_SMOOTHING,
+            None => latest,
+        };
+        self.average_ms = Some(average);
+        Duration::from_millis(base_ms) + Duration::from_secs_f64(average * factor / 1000.0)
+    }
+}
+
+// Урезает число воркеров методов и сценариев до лимита одновременных запросов тарифа:
+// пропорционально, но не меньше одного воркера на метод или сценарий
+pub fn fit_workers(workers: &[usize], max_concurrency: usize) -> Result<Vec<usize>, String> {
+    let total: usize = workers.iter().sum();
+    if total <= max_concurrency {
+        return Ok(workers.to_vec());
+    }
+    if workers.len() > max_concurrency {
+        return Err(format!(
+            "{} methods and scenarios need at least {} workers, the plan allows {} concurrent requests",
+            workers.len(),
+            workers.len(),
+            max_concurrency
+        ));
+    }
+    let mut fitted: Vec<usize> = workers.iter().map(|w| (w * max_concurrency / total).max(1)).collect();
+    // Минимум в один воркер мог вывести за лимит: снимаем лишнее с самых больших
+    while fitted.iter().sum::<usize>() > max_concurrency {
+        if let Some(largest) = fitted.iter_mut().max() {
+            *largest -= 1;
+        }
+    }
+    Ok(fitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_workers_keeps_workers_within_the_limit() {
+        assert_eq!(fit_workers(&[4, 2], 10).unwrap(), [4, 2]);
+        assert_eq!(fit_workers(&[30, 10], 20).unwrap(), [15, 5]);
+    }
+
+    #[test]
+    fn fit_workers_gives_every_method_at_least_one_worker() {
+        assert_eq!(fit_workers(&[90, 1], 9).unwrap(), [8, 1]);
+        // Пропорционально 2, 0 и 0: лишний после минимума воркер снимается с самого большого
+        assert_eq!(fit_workers(&[10, 1, 1], 3).unwrap(), [1, 1, 1]);
+        assert_eq!(fit_workers(&[5, 5, 5], 3).unwrap(), [1, 1, 1]);
+        assert!(fit_workers(&[5, 5, 5], 2).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+// Строка состояния во время теста (--live-interval): раз в N секунд - сколько прошло, сколько запросов,
+// доля успешных с начала теста и за последние LIVE_WINDOW_SECS закрытых секунд. Накопленная доля
+// успешных прячет отказ, начавшийся пару минут назад: за окно он виден сразу
+
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::aggregator::StatEvent;
+use crate::i18n::{texts, Lang, Texts};
+use crate::sidechannel::ClientWindow;
+use crate::Stats;
+
+pub const LIVE_WINDOW_SECS: usize = 30;
+
+// Ответ агрегатора на StatEvent::Live
+pub struct LiveStatus {
+    pub total_requests: u64,
+    pub successful_requests: u64,
+    pub window: Option<ClientWindow>, // None - еще нет закрытых секунд
+}
+
+fn format_elapsed(secs: u64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn format_line(t: &Texts, elapsed_secs: u64, status: &LiveStatus) -> String {
+    let total = if status.total_requests > 0 {
+        format!("{:.2}%", status.successful_requests as f64 / status.total_requests as f64 * 100.0)
+    } else {
+        "-".to_string()
+    };
+    let window = match &status.window {
+        Some(window) => format!(
+            "{:.2}% {} {} s, {:.1} req/s {} {} s",
+            100.0 - window.error_rate_percent,
+            t.live_last,
+            window.window_secs,
+            window.rps,
+            t.live_last,
+            window.window_secs
+        ),
+        None => t.live_no_window.to_string(),
+    };
+    format!(
+        "[{} {}] {} {}, {} {} {}, {}",
+        t.live_status,
+        format_elapsed(elapsed_secs),
+        status.total_requests,
+        t.requests_lower,
+        t.live_success,
+        total,
+        t.live_since_start,
+        window
+    )
+}
+
+// Первая строка - через интервал, на языке отчета (--lang); задача заканчивается вместе с тестом
+pub fn spawn(interval: Duration, stats: Stats, lang: Lang) -> tokio::task::JoinHandle<()> {
+    let t = texts(lang);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !stats.running() {
+                break;
+            }
+            let (reply, status) = oneshot::channel();
+            stats.send(StatEvent::Live(reply));
+            let Ok(status) = status.await else {
+                break;
+            };
+            println!("{}", format_line(t, stats.started_at.elapsed().as_secs(), &status));
+        }
+    })
+}
@@ -1,17 +1,84 @@
 use clap::Parser;
+use i18n::Lang;
+use report::SummaryFormat;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fs;
-use std::path::Path;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use crossbeam::queue::SegQueue;
+use std::sync::atomic::AtomicU64;
+use tokio::sync::oneshot;
+use aggregator::{HeaderSample, StatEvent, StatsState, WorkerTotals};
+use transport::{HttpTransport, Transport};
+
+mod accounts;
+mod affinity;
+mod aggregator;
+mod apdex;
+mod autotune;
+mod breaker;
+mod catalog;
+mod changepoints;
+mod chunks;
+mod clock;
+mod coldwarm;
+mod compression;
+mod consistency;
+#[cfg(unix)]
+mod control;
+mod depth;
+mod discovery;
+mod dnsrefresh;
+mod environment;
+mod errorlog;
+mod neterror;
+mod feeder;
+mod fingerprint;
+mod fuzz;
+mod history;
+mod httpget;
+mod i18n;
+mod identity;
+mod incidents;
+mod init;
+mod jito;
+mod keys;
+mod limits;
+mod live;
+mod mockserver;
+mod outliers;
+mod paramsweep;
+mod percentiles;
+mod plan;
+mod report;
+mod scenario;
+mod secrets;
+mod sidechannel;
+mod snapshot;
+mod sockets;
+mod preview;
+mod probe;
+mod profile;
+mod recovery;
+mod redact;
+mod region;
+mod survey;
+mod sweep;
+mod ticket;
+mod tls;
+mod transport;
+mod trend;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+
     /// Number of workers (parallel threads)
     #[arg(short, long, default_value_t = 1)]
     workers: usize,
@@ -20,9 +87,9 @@ struct Args {
     #[arg(short, long, default_value = "getHealth")]
     method: String,
 
-    /// Timeout between requests for each worker in milliseconds
-    #[arg(short, long, default_value_t = 1)]
-    timeout_ms: u64,
+    /// Timeout between requests for each worker in milliseconds: a number or a range "50..200" (random per request)
+    #[arg(short, long, default_value = "1", value_parser = parse_pause)]
+    timeout_ms: Pause,
 
     /// URL Solana RPC endpoint
     #[arg(short, long, default_value = "https://api.mainnet-beta.solana.com")]
@@ -36,10 +103,22 @@ struct Args {
     #[arg(long, default_value_t = 30)]
     http_timeout: u64,
 
+    /// TCP connect timeout in seconds (by default only --http-timeout applies)
+    #[arg(long)]
+    connect_timeout: Option<u64>,
+
     /// Debug mode: output RPC responses to console
     #[arg(short = 'v', long)]
     debug: bool,
 
+    /// Debug output for only this fraction of requests, e.g. 0.001 (implies --debug)
+    #[arg(long)]
+    debug_sample: Option<f64>,
+
+    /// Debug output for only this method; repeatable (implies --debug)
+    #[arg(long)]
+    debug_method: Vec<String>,
+
     /// Perform preliminary ping test (10 packets)
     #[arg(short = 'p', long)]
     ping: bool,
@@ -47,25 +126,848 @@ struct Args {
     /// Path to configuration file (if specified, parameters are taken from it)
     #[arg(short = 'c', long)]
     config: Option<String>,
+
+    /// Run a test plan written by --export-plan exactly as exported (instead of --config)
+    #[arg(long, conflicts_with_all = ["config", "profile"])]
+    run_plan: Option<String>,
+
+    /// Write the fully resolved test plan (settings, seed, method mix with explicit params, sweep stages) to this path before the load starts
+    #[arg(long)]
+    export_plan: Option<String>,
+
+    /// Built-in workload: a curated method mix with params; --workers multiplies each method's share
+    #[arg(long, value_enum, conflicts_with_all = ["config", "method"])]
+    profile: Option<profile::Profile>,
+
+    /// CSV feeder of asset IDs (header: asset) for --profile das
+    #[arg(long, requires = "profile")]
+    das_assets: Option<String>,
+
+    /// CSV feeder of owner addresses (header: owner) for --profile das
+    #[arg(long, requires = "profile")]
+    das_owners: Option<String>,
+
+    /// Seed for all randomness in the run (random if not specified)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Random jitter added to the timeout between requests in milliseconds
+    #[arg(long, default_value_t = 0)]
+    jitter_ms: u64,
+
+    /// Delay between worker starts in milliseconds: worker N starts N * this after the test start
+    #[arg(long, default_value_t = 0)]
+    stagger_start_ms: u64,
+
+    /// Number of JSON-RPC requests per HTTP batch (1 = no batching)
+    #[arg(long, default_value_t = 1)]
+    batch_size: usize,
+
+    /// Additional response header to track over time (repeatable)
+    #[arg(long = "capture-header")]
+    capture_headers: Vec<String>,
+
+    /// Interval for response header tracking in seconds
+    #[arg(long, default_value_t = 10)]
+    header_interval: u64,
+
+    /// Run tag in key=value form, added to all reports (repeatable)
+    #[arg(long = "tag", value_parser = parse_tag)]
+    tags: Vec<(String, String)>,
+
+    /// Directory for run artifacts (a timestamped subdirectory is created per run)
+    #[arg(long)]
+    out_dir: Option<String>,
+
+    /// Print nothing but the final one-line verdict (exit code 1 on failure)
+    #[arg(short = 'q', long)]
+    quiet: bool,
+
+    /// Final summary format
+    #[arg(long, value_enum, default_value_t = SummaryFormat::Full)]
+    summary_format: SummaryFormat,
+
+    /// Language of the final report
+    #[arg(long, value_enum, default_value_t = Lang::En)]
+    lang: Lang,
+
+    /// Minimum success rate in percent for the run to pass
+    #[arg(long, default_value_t = 0.0)]
+    min_success_rate: f64,
+
+    /// Confidence level in percent for latency percentile intervals; percentiles without enough responses for it are flagged
+    #[arg(long, default_value_t = percentiles::DEFAULT_CONFIDENCE)]
+    percentile_confidence: f64,
+
+    /// Write a small verdict JSON (pass/fail, violations, key metrics) to this path
+    #[arg(long)]
+    verdict_file: Option<String>,
+
+    /// Write a support ticket bundle (environment, settings, error timeline with correlation IDs, sample failing requests) to this path
+    #[arg(long)]
+    output_ticket: Option<String>,
+
+    /// Write a JUnit XML report (one test case per threshold) to this path for CI test report UIs
+    #[arg(long)]
+    junit_file: Option<String>,
+
+    /// Append the run's summary metrics to this SQLite database (see the history subcommand)
+    #[arg(long)]
+    history: Option<String>,
+
+    /// Adjust worker counts during the run to keep p95 latency at --target-p95-ms
+    #[arg(long)]
+    auto_tune: bool,
+
+    /// Target p95 latency in milliseconds for --auto-tune
+    #[arg(long, default_value_t = 500.0)]
+    target_p95_ms: f64,
+
+    /// Interval between --auto-tune adjustments in seconds
+    #[arg(long, default_value_t = 5)]
+    tune_interval: u64,
+
+    /// Upper limit of workers per method for --auto-tune
+    #[arg(long, default_value_t = 512)]
+    max_workers: usize,
+
+    /// Apdex "satisfied" latency threshold in milliseconds (enables Apdex reporting)
+    #[arg(long)]
+    apdex_satisfied_ms: Option<u64>,
+
+    /// Apdex "tolerating" latency threshold in milliseconds (default: 4x satisfied)
+    #[arg(long)]
+    apdex_tolerating_ms: Option<u64>,
+
+    /// Minutes added to the running test's duration by each SIGUSR2 (Unix only)
+    #[arg(long, default_value_t = 5)]
+    extend_minutes: u64,
+
+    /// Print a status line every N seconds during the test: requests and success rate since the start and over the last 30 seconds (0 = off)
+    #[arg(long, default_value_t = 0)]
+    live_interval: u64,
+
+    /// Unix socket for commands to the running test: "extend <minutes>", "duration <minutes>", "status" (Unix only)
+    #[arg(long)]
+    control_socket: Option<String>,
+
+    /// Start without asking for confirmation after the test plan estimate
+    #[arg(short = 'y', long)]
+    yes: bool,
+
+    /// Print the generated request plan without sending anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Number of requests to print in dry-run mode
+    #[arg(long, default_value_t = 20)]
+    dry_run_requests: usize,
+
+    /// Send the load to a built-in mock JSON-RPC server on 127.0.0.1 instead of the endpoint (check configs and the tool's own overhead)
+    #[arg(long)]
+    mock_server: bool,
+
+    /// Delay of every mock server response in milliseconds
+    #[arg(long, default_value_t = 0, requires = "mock_server")]
+    mock_latency_ms: u64,
+
+    /// Percent of mock server responses that fail, spread evenly over requests
+    #[arg(long, default_value_t = 0.0, requires = "mock_server")]
+    mock_error_rate: f64,
+
+    /// How failing mock server responses fail
+    #[arg(long, value_enum, default_value_t = mockserver::MockError::Rpc, requires = "mock_server")]
+    mock_error: mockserver::MockError,
+
+    /// TOML script of mock server behavior: latency distribution, errors, rate limit (429), slow loris responses and timed phases
+    #[arg(long, requires = "mock_server")]
+    mock_script: Option<String>,
+
+    /// Discover RPC-enabled nodes via getClusterNodes on --url, print them and exit
+    #[arg(long)]
+    discover: bool,
+
+    /// Discover RPC-enabled nodes via getClusterNodes and spread workers across them
+    #[arg(long, conflicts_with = "auto_tune")]
+    fan_out: bool,
+
+    /// Maximum number of discovered nodes to use (default: all)
+    #[arg(long)]
+    max_nodes: Option<usize>,
+
+    /// First JSON-RPC request ID; IDs are assigned sequentially across all workers
+    #[arg(long, default_value_t = 1)]
+    id_start: u64,
+
+    /// Restart workers that panic for the rest of the test (crashes are always counted)
+    #[arg(long)]
+    respawn_workers: bool,
+
+    /// Skip checking method names and params against the built-in Solana RPC catalog
+    #[arg(long)]
+    no_validate: bool,
+
+    /// Custom method name sent as is with arbitrary params, e.g. getAsset on DAS API endpoints; repeatable
+    #[arg(long = "custom-method")]
+    custom_methods: Vec<String>,
+
+    /// Read and discard response bodies without JSON parsing (success = HTTP 2xx; RPC errors are not detected)
+    #[arg(long)]
+    no_parse: bool,
+
+    /// Response parser for method workers: serde (full) or fast (only id and error, result is skipped)
+    #[arg(long, value_enum, default_value_t = ResponseParser::Serde)]
+    parser: ResponseParser,
+
+    /// Accept-Encoding to request; reports compressed (wire) and decompressed response sizes
+    #[arg(long, value_enum)]
+    accept_encoding: Option<compression::AcceptEncoding>,
+
+    /// Overall spend cap: credits if [credits] is configured, otherwise requests; the test stops when it is exhausted
+    #[arg(long)]
+    request_budget: Option<u64>,
+
+    /// Refuse to run unless the endpoint's genesis hash matches: mainnet, devnet, testnet or custom:<genesis hash>
+    #[arg(long)]
+    expect_cluster: Option<fingerprint::ExpectedCluster>,
+
+    /// Pause all workers when the rolling 10-second error rate reaches this percentage
+    #[arg(long)]
+    breaker_error_rate: Option<f64>,
+
+    /// How long the circuit breaker pauses workers, in seconds
+    #[arg(long, default_value_t = 30)]
+    breaker_cooldown: u64,
+
+    /// API key for the key pool (repeatable); replaces {api_key} in --url or is sent in --api-key-header
+    #[arg(long = "api-key")]
+    api_keys: Vec<String>,
+
+    /// File with API keys for the key pool, one per line
+    #[arg(long)]
+    api_keys_file: Option<String>,
+
+    /// Send the API key in this request header (e.g. x-api-key) instead of the URL
+    #[arg(long)]
+    api_key_header: Option<String>,
+
+    /// How workers use the key pool
+    #[arg(long, value_enum, default_value_t = keys::KeyRotation::PerWorker)]
+    api_key_rotation: keys::KeyRotation,
+
+    /// User-Agent of requests; {worker} is replaced with the worker number (default: solana-rpc-stress-test/<version>)
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// Send the User-Agent and headers of a Solana client library
+    #[arg(long, value_enum)]
+    emulate_client: Option<identity::ClientPreset>,
+
+    /// Gzip request bodies (Content-Encoding: gzip) and report whether the endpoint accepts them
+    #[arg(long)]
+    gzip_requests: bool,
+
+    /// Send the client deadline in milliseconds in this request header and count responses that arrive after it
+    #[arg(long, num_args = 0..=1, default_missing_value = "x-deadline-ms")]
+    deadline_header: Option<String>,
+
+    /// Deadline declared in --deadline-header, in milliseconds (default: --http-timeout)
+    #[arg(long, requires = "deadline_header")]
+    deadline_ms: Option<u64>,
+
+    /// Probe the endpoint at a low rate before and after the load and report whether it recovered
+    #[arg(long)]
+    recovery_check: bool,
+
+    /// Length of the post-test probe phase of --recovery-check, in seconds
+    #[arg(long, default_value_t = 30)]
+    recovery_secs: u64,
+
+    /// Write every request slower than this many milliseconds (method, params, timings, response headers) to --outliers-file
+    #[arg(long)]
+    outlier_ms: Option<u64>,
+
+    /// JSONL file for --outlier-ms (default: outliers.jsonl)
+    #[arg(long, requires = "outlier_ms")]
+    outliers_file: Option<String>,
+
+    /// Send a unique correlation ID with every request in this header (also written to --outliers-file)
+    #[arg(long, num_args = 0..=1, default_missing_value = "x-request-id")]
+    correlation_header: Option<String>,
+
+    /// Before the test compare the local clock with the endpoint's HTTP Date header and warn about skew
+    #[arg(long)]
+    clock_check: bool,
+
+    /// Also compare the local clock with getBlockTime of the latest confirmed slot (implies --clock-check)
+    #[arg(long)]
+    clock_check_block_time: bool,
+
+    /// Limit JSON-RPC requests per second across all workers (a batch counts as its size)
+    #[arg(long)]
+    max_rps: Option<u64>,
+
+    /// Latency-adaptive pacing: after each request a worker also waits factor × its recent average response time
+    #[arg(long)]
+    pace_factor: Option<f64>,
+
+    /// Stay within a provider plan: caps requests per second, concurrent workers and batch size
+    #[arg(long, value_enum)]
+    provider_limits: Option<limits::ProviderPlan>,
+
+    /// Add per-period sub-summaries (RPS, error rate, p95) every N minutes to the report
+    /// (default: 5 for tests of 20 minutes or longer and for unlimited ones, 0 disables)
+    #[arg(long)]
+    summary_window: Option<u64>,
+
+    /// Align per-second buckets, --summary-window periods and --header-interval buckets to UTC wall-clock boundaries
+    #[arg(long)]
+    align_buckets: bool,
+
+    /// Resolve the endpoint's IPs before the test and report which IP the responses actually came from
+    #[arg(long)]
+    endpoint_ips: bool,
+
+    /// Annotate endpoint IPs with ASN and location from an IP info service (URL template with {ip}; implies --endpoint-ips)
+    #[arg(long, num_args = 0..=1, default_missing_value = region::DEFAULT_GEO_URL)]
+    geo_lookup: Option<String>,
+
+    /// Re-resolve the endpoint hostname every N seconds, spread new connections over the returned IPs and drop pooled connections when the answer changes
+    #[arg(long)]
+    dns_refresh: Option<u64>,
+
+    /// Hash response results and report how many distinct bodies identical requests returned
+    #[arg(long)]
+    hash_responses: bool,
+
+    /// Sticky session: replay a cookie or header from the first response that carries it (cookie:<name> or header:<name>)
+    #[arg(long)]
+    affinity: Option<String>,
+
+    /// Show the last N worker errors (time, worker, category, message) after the summary; 0 disables
+    #[arg(long, default_value_t = errorlog::DEFAULT_CAPACITY)]
+    last_errors: usize,
+
+    /// Redact the outliers file, debug output and last errors: transactions, keys; repeatable
+    #[arg(long, value_enum)]
+    redact: Vec<redact::RedactRule>,
+
+    /// Download the node's snapshots with this many parallel range requests alongside the RPC load
+    #[arg(long)]
+    snapshot_downloads: Option<usize>,
+
+    /// Snapshot path on the endpoint host; repeatable (default: /snapshot.tar.bz2 and /incremental-snapshot.tar.bz2)
+    #[arg(long = "snapshot-path")]
+    snapshot_paths: Vec<String>,
+
+    /// Size of each snapshot range request in MB
+    #[arg(long, default_value_t = snapshot::DEFAULT_RANGE_MB)]
+    snapshot_range_mb: u64,
+
+    /// Plain-HTTP URL polled during the run (node /health, /metrics); a path starting with / is on the endpoint host; repeatable
+    #[arg(long = "poll-url")]
+    poll_urls: Vec<String>,
+
+    /// Seconds between polls of --poll-url
+    #[arg(long, default_value_t = sidechannel::DEFAULT_INTERVAL_SECS)]
+    poll_interval: u64,
+
+    /// Keep only Prometheus metrics starting with this name from --poll-url responses; repeatable
+    #[arg(long = "poll-metric")]
+    poll_metrics: Vec<String>,
+
+    /// Give --affinity to even workers only and compare them with the odd workers that run without it
+    #[arg(long, requires = "affinity")]
+    affinity_compare: bool,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum CliCommand {
+    /// Inspect the built-in Solana RPC method catalog
+    Methods {
+        #[command(subcommand)]
+        action: MethodsAction,
+    },
+    /// Quickly probe RPC endpoints (getVersion, getHealth, getSlot latency) and rank them
+    Survey {
+        /// RPC endpoint URLs to probe
+        #[arg(required = true)]
+        urls: Vec<String>,
+
+        /// Number of getSlot probes per endpoint (used for latency)
+        #[arg(long, default_value_t = 5)]
+        probes: usize,
+
+        /// Number of endpoints probed at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+
+        /// HTTP timeout for each probe in seconds
+        #[arg(long, default_value_t = 5)]
+        http_timeout: u64,
+    },
+    /// Send the same unique-params request set twice (cold, then warm) and compare latency to measure caching
+    ColdWarm {
+        /// URL Solana RPC endpoint
+        #[arg(short, long, default_value = "https://api.mainnet-beta.solana.com")]
+        url: String,
+
+        /// Method to send; without --params-file its default params must take a slot
+        #[arg(short, long, default_value = "getBlock")]
+        method: String,
+
+        /// Number of distinct past slots to request (ignored with --params-file)
+        #[arg(short = 'n', long, default_value_t = 50)]
+        count: usize,
+
+        /// Pick slots among this many slots below the current one
+        #[arg(long, default_value_t = 100_000)]
+        slot_range: u64,
+
+        /// File with one JSON params array per line (duplicates are dropped)
+        #[arg(long)]
+        params_file: Option<String>,
+
+        /// Number of requests in flight at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+
+        /// HTTP timeout for each request in seconds
+        #[arg(long, default_value_t = 30)]
+        http_timeout: u64,
+
+        /// Seed for picking slots (random if not set)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Request blocks and transactions at increasing depth below the tip and report latency and not-found rate per depth
+    Depth {
+        /// URL Solana RPC endpoint
+        #[arg(short, long, default_value = "https://api.mainnet-beta.solana.com")]
+        url: String,
+
+        /// Depth in slots below the finalized tip (repeatable; default: 0, 1000, 100000, 1000000, 10000000)
+        #[arg(long = "depth")]
+        depths: Vec<u64>,
+
+        /// Distinct slots to request at each depth
+        #[arg(short = 'n', long, default_value_t = 20)]
+        samples: usize,
+
+        /// Pick each depth's slots among this many slots below it
+        #[arg(long, default_value_t = 100)]
+        window: u64,
+
+        /// Only getBlock, without getTransaction of a transaction from each found block
+        #[arg(long)]
+        blocks_only: bool,
+
+        /// Number of slots requested at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+
+        /// HTTP timeout for each request in seconds
+        #[arg(long, default_value_t = 30)]
+        http_timeout: u64,
+
+        /// Seed for picking slots (random if not set)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Sweep the number of accounts per getMultipleAccounts call and report latency and throughput per chunk size
+    Chunks {
+        /// URL Solana RPC endpoint
+        #[arg(short, long, default_value = "https://api.mainnet-beta.solana.com")]
+        url: String,
+
+        /// File with one account address per line
+        #[arg(long)]
+        accounts_file: String,
+
+        /// Accounts per getMultipleAccounts call (repeatable; default: 1, 10, 50, 100)
+        #[arg(long = "chunk")]
+        chunks: Vec<usize>,
+
+        /// Seconds of load for each chunk size
+        #[arg(short, long, default_value_t = 20)]
+        duration: u64,
+
+        /// Number of requests in flight at the same time
+        #[arg(long, default_value_t = 8)]
+        concurrency: usize,
+
+        /// Account data encoding
+        #[arg(long, default_value = "base64")]
+        encoding: String,
+
+        /// HTTP timeout for each request in seconds
+        #[arg(long, default_value_t = 30)]
+        http_timeout: u64,
+    },
+    /// Run the workload from the main flags or config at a sequence of worker counts and report throughput and latency per count
+    Sweep {
+        /// Worker counts to run, in order (comma-separated; default: 1,2,4,8,16,32,64,128,256)
+        #[arg(long, value_delimiter = ',')]
+        steps: Vec<usize>,
+
+        /// Seconds of load for each worker count
+        #[arg(long, default_value_t = 20)]
+        step_duration: u64,
+    },
+    /// Run the config's workload once per value of the [param_sweep] field and compare the variants in one table
+    ParamSweep {
+        /// Seconds of load for each value
+        #[arg(long, default_value_t = 20)]
+        step_duration: u64,
+    },
+    /// Stress Jito block-engine endpoints: sendBundle with pre-built bundles and getTipAccounts, report acceptance latency
+    Bundles {
+        /// Block-engine bundles endpoint
+        #[arg(short, long, default_value = jito::DEFAULT_URL)]
+        url: String,
+
+        /// File with one bundle per line: a JSON array of signed transactions (without it only getTipAccounts is sent)
+        #[arg(long)]
+        bundles_file: Option<String>,
+
+        /// Encoding of the transactions in the bundles file
+        #[arg(long, value_enum, default_value_t = jito::BundleEncoding::Base64)]
+        encoding: jito::BundleEncoding,
+
+        /// Seconds of load
+        #[arg(short, long, default_value_t = 30)]
+        duration: u64,
+
+        /// sendBundle requests per second
+        #[arg(long, default_value_t = 1.0)]
+        rate: f64,
+
+        /// getTipAccounts requests per second (0 disables)
+        #[arg(long, default_value_t = 1.0)]
+        tip_rate: f64,
+
+        /// Number of requests in flight at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+
+        /// HTTP timeout for each request in seconds
+        #[arg(long, default_value_t = 10)]
+        http_timeout: u64,
+
+        /// UUID sent in the x-jito-auth header (for a raised rate limit)
+        #[arg(long)]
+        auth_uuid: Option<String>,
+
+        /// Poll getInflightBundleStatuses for accepted bundles and report how many landed
+        #[arg(long)]
+        status_check: bool,
+
+        /// Seconds to wait for a final status of each accepted bundle with --status-check
+        #[arg(long, default_value_t = 60)]
+        status_timeout: u64,
+    },
+    /// List previous runs recorded with --history per endpoint, or compare two of them
+    History {
+        /// SQLite database written by --history
+        db: String,
+
+        /// Only runs whose endpoint URL contains this text
+        #[arg(long)]
+        endpoint: Option<String>,
+
+        /// Number of latest runs to list
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        /// Compare two runs by their numbers instead of listing
+        #[arg(long, num_args = 2, value_names = ["RUN_A", "RUN_B"])]
+        compare: Option<Vec<i64>>,
+    },
+    /// Print a metric across past --history runs of one endpoint and flag regressions (exit code 1 if the latest run regressed)
+    Trend {
+        /// SQLite database written by --history
+        db: String,
+
+        /// Endpoint URL (or a unique part of it)
+        #[arg(short, long)]
+        url: String,
+
+        /// Metric to analyze
+        #[arg(long, value_enum, default_value_t = trend::Metric::P99)]
+        metric: trend::Metric,
+
+        /// Number of latest runs to analyze
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+
+        /// Number of previous runs each run is compared against
+        #[arg(long, default_value_t = 10)]
+        baseline: usize,
+    },
+    /// Send malformed JSON-RPC payloads and classify the responses to test gateway robustness (exit code 1 on failures)
+    Fuzz {
+        /// URL Solana RPC endpoint
+        #[arg(short, long, default_value = "https://api.mainnet-beta.solana.com")]
+        url: String,
+
+        /// Malformed request kinds to send (repeatable; all by default)
+        #[arg(long = "case", value_enum)]
+        cases: Vec<fuzz::FuzzCase>,
+
+        /// Total number of requests, spread evenly over the cases
+        #[arg(short = 'n', long, default_value_t = 120)]
+        count: usize,
+
+        /// Requests per second
+        #[arg(long, default_value_t = 10.0)]
+        rate: f64,
+
+        /// Number of requests in flight at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+
+        /// HTTP timeout for each request in seconds
+        #[arg(long, default_value_t = 10)]
+        http_timeout: u64,
+
+        /// Seed for generated payloads (random if not set)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Binary-search the endpoint's limits: request body size, batch length and getMultipleAccounts accounts
+    Limits {
+        /// URL Solana RPC endpoint
+        #[arg(short, long, default_value = "https://api.mainnet-beta.solana.com")]
+        url: String,
+
+        /// Limits to probe (repeatable; all by default)
+        #[arg(long = "probe", value_enum)]
+        probes: Vec<probe::Probe>,
+
+        /// Upper bound of the body size search in KB
+        #[arg(long, default_value_t = 65_536)]
+        max_body_kb: u64,
+
+        /// Upper bound of the batch length search
+        #[arg(long, default_value_t = 1000)]
+        max_batch: u64,
+
+        /// Upper bound of the getMultipleAccounts search
+        #[arg(long, default_value_t = 1000)]
+        max_accounts: u64,
+
+        /// HTTP timeout for each request in seconds
+        #[arg(long, default_value_t = 30)]
+        http_timeout: u64,
+
+        /// Pause before each probe request in milliseconds
+        #[arg(long, default_value_t = 200)]
+        pause_ms: u64,
+    },
+    /// Generate a commented TOML config; asks interactively unless --method or --profile is given
+    Init {
+        /// Where to write the config ("-" for stdout)
+        #[arg(short, long, default_value = "config.toml")]
+        output: String,
+
+        /// Overwrite the output file if it exists
+        #[arg(long)]
+        force: bool,
+
+        /// Method to include with its default params (repeatable)
+        #[arg(short, long = "method")]
+        methods: Vec<String>,
+
+        /// Start from a built-in profile's method mix
+        #[arg(long, value_enum, conflicts_with = "methods")]
+        profile: Option<profile::Profile>,
+
+        /// Workers per method (for --profile: multiplier of each method's share)
+        #[arg(short, long)]
+        workers: Option<usize>,
+
+        /// URL Solana RPC endpoint
+        #[arg(short, long, default_value = "https://api.mainnet-beta.solana.com")]
+        url: String,
+
+        /// Test duration in seconds (0 = infinite)
+        #[arg(short, long, default_value_t = 60)]
+        duration: u64,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum MethodsAction {
+    /// List known methods with their params and default params
+    List,
 }
 
 #[derive(Deserialize, Debug)]
 struct Config {
     url: Option<String>,
-    timeout_ms: Option<u64>,
+    timeout_ms: Option<Pause>,
     duration: Option<u64>,
     http_timeout: Option<u64>,
+    connect_timeout: Option<u64>,
+    seed: Option<u64>,
+    jitter_ms: Option<u64>,
+    stagger_start_ms: Option<u64>,
+    capture_headers: Option<Vec<String>>,
+    header_interval: Option<u64>,
+    id_start: Option<u64>,
+    request_budget: Option<u64>,
+    accept_encoding: Option<compression::AcceptEncoding>,
+    parser: Option<ResponseParser>,
+    expect_cluster: Option<fingerprint::ExpectedCluster>,
+    breaker_error_rate: Option<f64>,
+    breaker_cooldown: Option<u64>,
+    api_keys: Option<Vec<String>>,
+    api_keys_file: Option<String>,
+    api_key_header: Option<String>,
+    api_key_rotation: Option<keys::KeyRotation>,
+    user_agent: Option<String>,
+    emulate_client: Option<identity::ClientPreset>,
+    gzip_requests: Option<bool>,
+    deadline_header: Option<String>,
+    deadline_ms: Option<u64>,
+    recovery_check: Option<bool>,
+    recovery_secs: Option<u64>,
+    outlier_ms: Option<u64>,
+    outliers_file: Option<String>,
+    correlation_header: Option<String>,
+    clock_check: Option<bool>,
+    clock_check_block_time: Option<bool>,
+    max_rps: Option<u64>,
+    pace_factor: Option<f64>,
+    provider_limits: Option<limits::ProviderPlan>,
+    endpoint_ips: Option<bool>,
+    hash_responses: Option<bool>,
+    affinity: Option<String>,
+    affinity_compare: Option<bool>,
+    geo_lookup: Option<String>,
+    dns_refresh: Option<u64>,
+    summary_window: Option<u64>,
+    align_buckets: Option<bool>,
+    last_errors: Option<usize>,
+    redact: Option<Vec<redact::RedactRule>>,
+    snapshot_downloads: Option<usize>,
+    snapshot_paths: Option<Vec<String>>,
+    snapshot_range_mb: Option<u64>,
+    poll_urls: Option<Vec<String>>,
+    poll_interval: Option<u64>,
+    poll_metrics: Option<Vec<String>>,
+    custom_methods: Option<Vec<String>>,
+    credits: Option<CreditConfig>,
+    apdex: Option<apdex::ApdexThresholds>,
+    metadata: Option<BTreeMap<String, toml::Value>>,
+    #[serde(default)]
     methods: Vec<MethodConfig>,
+    #[serde(default)]
+    scenarios: Vec<scenario::ScenarioConfig>,
+    param_sweep: Option<paramsweep::ParamSweepConfig>, // только для подкоманды param-sweep
 }
 
-#[derive(Deserialize, Debug, Clone)]
+// Стоимость методов в кредитах провайдера: [credits] default = 1, getBlock = 10, ...
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CreditConfig {
+    #[serde(default)]
+    default: u64,
+    #[serde(flatten)]
+    methods: HashMap<String, u64>,
+}
+
+impl CreditConfig {
+    fn cost(&self, method: &str) -> u64 {
+        self.methods.get(method).copied().unwrap_or(self.default)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct MethodConfig {
     method: String,
+    // Имя записи в настройках и отчете: один метод с разными params или короткое имя нестандартного метода
+    alias: Option<String>,
     params: Option<Vec<serde_json::Value>>,
     workers: usize,
+    batch: Option<usize>,
+    // Фаза метода в секундах от старта теста: start_offset..end_offset или start_offset + duration.
+    // Без них метод работает весь тест
+    start_offset: Option<u64>,
+    end_offset: Option<u64>,
+    duration: Option<u64>,
+    // Только для method = "GET" (httpget.rs): путь запроса и ожидаемый HTTP статус
+    path: Option<String>,
+    expected_status: Option<u16>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl MethodConfig {
+    // Имя в настройках и отчете: alias, у GET - вместе с путем
+    fn label(&self) -> String {
+        match &self.path {
+            _ if self.alias.is_some() => self.alias.clone().unwrap_or_default(),
+            Some(path) if self.method == httpget::METHOD => format!("{} {}", self.method, path),
+            _ => self.method.clone(),
+        }
+    }
+
+    fn phase(&self) -> Phase {
+        let start = Duration::from_secs(self.start_offset.unwrap_or(0));
+        let end = match (self.end_offset, self.duration) {
+            (Some(end), _) => Some(Duration::from_secs(end)),
+            (None, Some(duration)) => Some(start + Duration::from_secs(duration)),
+            (None, None) => None,
+        };
+        Phase { start, end }
+    }
+
+    fn describe_phase(&self) -> Option<String> {
+        let phase = self.phase();
+        match phase.end {
+            Some(end) => Some(format!("from {} to {} sec", phase.start.as_secs(), end.as_secs())),
+            None if !phase.start.is_zero() => Some(format!("from {} sec", phase.start.as_secs())),
+            None => None,
+        }
+    }
+}
+
+// Окно работы воркеров метода относительно старта теста (end None - до конца теста)
+#[derive(Clone, Copy, Debug)]
+struct Phase {
+    start: Duration,
+    end: Option<Duration>,
+}
+
+impl Phase {
+    // Ждет начала фазы; false - тест закончился раньше
+    async fn wait_start(&self, stats: &Stats) -> bool {
+        loop {
+            if !stats.running() {
+                return false;
+            }
+            let elapsed = stats.started_at.elapsed();
+            if elapsed >= self.start {
+                return true;
+            }
+            // Ждем частями: тест могут укоротить через control-сокет
+            sleep((self.start - elapsed).min(Duration::from_secs(1))).await;
+        }
+    }
+
+    fn active(&self, stats: &Stats) -> bool {
+        stats.running() && self.end.is_none_or(|end| stats.started_at.elapsed() < end)
+    }
+
+    // Ближайший из концов фазы и теста
+    fn end(&self, stats: &Stats) -> Option<Instant> {
+        let phase_end = self.end.map(|end| stats.started_at + end);
+        match (stats.end(), phase_end) {
+            (Some(test_end), Some(phase_end)) => Some(test_end.min(phase_end)),
+            (test_end, phase_end) => test_end.or(phase_end),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
 struct JsonRpcRequest {
     jsonrpc: String,
     id: u64,
@@ -73,520 +975,3649 @@ struct JsonRpcRequest {
     params: Vec<serde_json::Value>,
 }
 
+// params уходят массивом, а у методов с именованными params (DAS API) - единственным объектом массива
+impl Serialize for JsonRpcRequest {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut request = serializer.serialize_struct("JsonRpcRequest", 4)?;
+        request.serialize_field("jsonrpc", &self.jsonrpc)?;
+        request.serialize_field("id", &self.id)?;
+        request.serialize_field("method", &self.method)?;
+        match self.params.as_slice() {
+            [named @ serde_json::Value::Object(_)] if catalog::named_params(&self.method) => {
+                request.serialize_field("params", named)?
+            }
+            params => request.serialize_field("params", params)?,
+        }
+        request.end()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct JsonRpcResponse {
     jsonrpc: String,
-    id: u64,
+    id: serde_json::Value, // Может быть числом, строкой или null (у некоторых прокси)
     result: Option<serde_json::Value>,
     error: Option<JsonRpcError>,
 }
 
+// --parser fast: из ответа читаются только id и error, result пропускается без построения дерева JSON.
+// jsonrpc обязателен, как и в полном разборе, чтобы ошибки парсинга считались одинаково
+#[derive(Deserialize)]
+struct JsonRpcResponseHead {
+    #[allow(dead_code)]
+    jsonrpc: serde::de::IgnoredAny,
+    id: serde_json::Value,
+    error: Option<JsonRpcError>,
+}
+
+impl From<JsonRpcResponseHead> for JsonRpcResponse {
+    fn from(head: JsonRpcResponseHead) -> Self {
+        JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: head.id,
+            result: None,
+            error: head.error,
+        }
+    }
+}
+
+// Разбор ответов воркерами методов: полный (serde_json::Value) или только id и error
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum ResponseParser {
+    #[default]
+    Serde,
+    Fast,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct JsonRpcError {
     code: i32,
     message: String,
 }
 
+// Общие настройки воркера, одинаковые для всех методов
 #[derive(Clone)]
-struct Stats {
-    total_requests: Arc<std::sync::atomic::AtomicU64>,
-    successful_requests: Arc<std::sync::atomic::AtomicU64>,
-    http_errors: Arc<Mutex<HashMap<String, Arc<std::sync::atomic::AtomicU64>>>>,
-    http_timeouts: Arc<std::sync::atomic::AtomicU64>,
-    json_parse_errors: Arc<std::sync::atomic::AtomicU64>,
-    network_errors: Arc<std::sync::atomic::AtomicU64>,
-    rpc_errors: Arc<std::sync::atomic::AtomicU64>,
-    response_times: Arc<SegQueue<u64>>, // микросекунды
+struct WorkerSettings {
+    url: String,
+    timeout_ms: u64,
+    jitter_ms: u64,
+    stagger_start: Duration, // задержка старта между соседними воркерами
+    http_timeout: Duration,
+    connect_timeout: Option<Duration>,
+    duration: Duration,
+    debug: Option<DebugFilter>, // None - без --debug
+    seed: u64,
+    capture_headers: Vec<String>,
+    header_interval_secs: u64,
+    credits: Option<Arc<CreditConfig>>,
+    apdex: Option<apdex::ApdexThresholds>,
+    ids: RequestIds,
+    budget: Option<RequestBudget>,
+    accept_encoding: Option<compression::AcceptEncoding>,
+    no_parse: bool, // --no-parse: тела ответов не разбираются
+    parser: ResponseParser,
+    respawn: bool, // перезапускать упавших воркеров
+    expect_cluster: Option<fingerprint::ExpectedCluster>,
+    breaker: Option<breaker::BreakerSettings>,
+    api_keys: Option<keys::KeyPool>,
+    identity: identity::ClientIdentity,
+    gzip_requests: bool, // сжимать тела запросов
+    deadline: Option<Deadline>,
+    recovery: Option<Duration>, // длительность пробной фазы после нагрузки
+    outliers: Option<outliers::OutlierSettings>,
+    correlation: Option<Correlation>,
+    clock_check: Option<clock::ClockCheck>,
+    max_rps: Option<u64>, // явный или из тарифа провайдера
+    pace_factor: Option<f64>, // --pace-factor: пауза растет с временем ответа
+    provider_limits: Option<limits::ProviderPlan>,
+    addresses: Option<region::AddressCheck>,
+    dns_refresh: Option<Duration>, // --dns-refresh: интервал повторного разрешения имени endpoint
+    hash_responses: bool, // --hash-responses
+    affinity: Option<affinity::AffinitySettings>,
+    summary_window: Option<Duration>, // период итогов --summary-window
+    align_buckets: bool, // корзины статистики по границам часов UTC, а не от старта
+    last_errors: usize, // размер буфера последних ошибок, 0 - выключен
+    redact: redact::Redaction,
+    snapshot: Option<snapshot::SnapshotSettings>, // загрузка снапшотов вместе с RPC нагрузкой
+    side_channel: Option<sidechannel::PollSettings>, // опрос служебных URL (--poll-url)
+    custom_methods: Vec<String>, // не проверяются по каталогу и отправляются как есть
 }
 
-impl Stats {
-    fn new() -> Self {
-        Self {
-            total_requests: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            successful_requests: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            http_errors: Arc::new(Mutex::new(HashMap::new())),
-            http_timeouts: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            json_parse_errors: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            network_errors: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            rpc_errors: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            response_times: Arc::new(SegQueue::new()),
-        }
-    }
+// Какие запросы выводятся с --debug: с сотнями воркеров вывод каждого ответа забивает терминал.
+// --debug-sample оставляет долю запросов, --debug-method - только выбранные методы
+#[derive(Clone, Debug)]
+struct DebugFilter {
+    sample: f64, // 1.0 - все запросы
+    methods: Vec<String>, // пусто - все методы
+}
 
-    fn record_success(&self, response_time_micros: u64) {
-        self.total_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        self.successful_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        self.response_times.push(response_time_micros);
+impl DebugFilter {
+    // Выводить ли запрос метода method; rng - отдельный поток воркера, чтобы выборка не меняла прогон с --seed
+    fn admits(&self, method: &str, rng: &mut Rng) -> bool {
+        // getLatestBlock отправляет getBlock: --debug-method getBlock выбирает и его
+        let method_selected = self.methods.is_empty()
+            || self.methods.iter().any(|name| name == method || (method == "getLatestBlock" && name == "getBlock"));
+        method_selected && (self.sample >= 1.0 || (rng.next_u64() as f64 / u64::MAX as f64) < self.sample)
     }
+}
 
-    fn record_http_error(&self, status_code: u16, reason: &str) {
-        self.total_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        let error_key = format!("{} {}", status_code, reason);
-        
-        let mut errors = self.http_errors.lock().unwrap();
-        let counter = errors
-            .entry(error_key)
-            .or_insert_with(|| Arc::new(std::sync::atomic::AtomicU64::new(0)))
-            .clone();
-        drop(errors); // Освобождаем мьютекс как можно быстрее
-        counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+fn debug_filter(debug: bool, sample: Option<f64>, methods: &[String]) -> Result<Option<DebugFilter>, String> {
+    if !debug && sample.is_none() && methods.is_empty() {
+        return Ok(None);
     }
-
-    fn record_http_timeout(&self) {
-        self.total_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        self.http_timeouts.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let sample = sample.unwrap_or(1.0);
+    if !(sample > 0.0 && sample <= 1.0) {
+        return Err(format!("--debug-sample must be in (0, 1], got {}", sample));
     }
+    Ok(Some(DebugFilter {
+        sample,
+        methods: methods.to_vec(),
+    }))
+}
 
-    fn record_json_parse_error(&self) {
-        self.total_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        self.json_parse_errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+// Дедлайн, который клиент объявляет в заголовке запроса: некоторые шлюзы прекращают обработку
+// после него. Ответы, пришедшие позже, значат, что endpoint дедлайн не соблюдает
+#[derive(Clone, Debug)]
+struct Deadline {
+    header: String,
+    ms: u64,
+}
+
+// ID корреляции в заголовке запроса: <прогон>-<воркер>-<номер запроса воркера>. По нему операторы RPC
+// находят в своих логах именно те запросы, которые инструмент видел медленными
+#[derive(Clone, Debug)]
+struct Correlation {
+    header: reqwest::header::HeaderName,
+    run: String, // случайный префикс прогона, чтобы ID разных прогонов не совпадали
+}
+
+fn correlation(header: Option<String>) -> Result<Option<Correlation>, String> {
+    let Some(header) = header else {
+        return Ok(None);
+    };
+    let header = reqwest::header::HeaderName::from_bytes(header.as_bytes())
+        .map_err(|_| format!("Invalid correlation header name '{}'", header))?;
+    Ok(Some(Correlation {
+        header,
+        run: format!("{:08x}", Rng::new(random_seed()).next_u64() as u32),
+    }))
+}
+
+// Имя заголовка проверяется до старта, чтобы не падать при создании клиентов воркеров
+fn deadline(header: Option<String>, ms: Option<u64>, http_timeout: Duration) -> Result<Option<Deadline>, String> {
+    let Some(header) = header else {
+        return Ok(None);
+    };
+    reqwest::header::HeaderName::from_bytes(header.as_bytes())
+        .map_err(|_| format!("Invalid deadline header name '{}'", header))?;
+    Ok(Some(Deadline {
+        header,
+        ms: ms.unwrap_or(http_timeout.as_millis() as u64),
+    }))
+}
+
+// Детерминированный генератор (SplitMix64): один и тот же seed дает тот же прогон
+#[derive(Clone)]
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
     }
 
-    fn record_network_error(&self) {
-        self.total_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        self.network_errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
     }
 
-    fn record_rpc_error(&self) {
-        self.total_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        self.rpc_errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    // Случайное число в диапазоне [0, max]
+    fn up_to(&mut self, max: u64) -> u64 {
+        if max == 0 {
+            return 0;
+        }
+        self.next_u64() % (max + 1)
     }
+}
 
-    fn print_summary(&self) {
-        let total = self.total_requests.load(std::sync::atomic::Ordering::Relaxed);
-        let successful = self.successful_requests.load(std::sync::atomic::Ordering::Relaxed);
-        let http_timeouts = self.http_timeouts.load(std::sync::atomic::Ordering::Relaxed);
-        let json_parse_errors = self.json_parse_errors.load(std::sync::atomic::Ordering::Relaxed);
-        let network_errors = self.network_errors.load(std::sync::atomic::Ordering::Relaxed);
-        let rpc_errors = self.rpc_errors.load(std::sync::atomic::Ordering::Relaxed);
+// Общий генератор ID запросов: последовательные ID на весь прогон, без пересечений между воркерами.
+// Блок для батча всегда непрерывный; при переполнении u64 нумерация начинается заново с 1
+#[derive(Clone)]
+struct RequestIds {
+    next: Arc<std::sync::atomic::AtomicU64>,
+    start: u64,
+}
 
-        // Собираем все времена ответов
-        let mut times: Vec<u64> = Vec::new();
-        while let Some(time) = self.response_times.pop() {
-            times.push(time);
+impl RequestIds {
+    fn new(start: u64) -> Self {
+        let start = start.max(1);
+        Self {
+            next: Arc::new(std::sync::atomic::AtomicU64::new(start)),
+            start,
         }
+    }
 
-        let avg_latency = if !times.is_empty() {
-            let sum: u64 = times.iter().sum();
-            (sum as f64 / times.len() as f64) / 1000.0 // конвертируем в миллисекунды
-        } else {
-            0.0
-        };
-
-        let min_latency = times.iter().min().map(|&t| t as f64 / 1000.0).unwrap_or(0.0);
-        let max_latency = times.iter().max().map(|&t| t as f64 / 1000.0).unwrap_or(0.0);
+    fn next(&self) -> u64 {
+        self.next_block(1)
+    }
 
-        let success_rate = if total > 0 {
-            (successful as f64 / total as f64) * 100.0
-        } else {
-            0.0
-        };
+    // Первый ID из count подряд идущих
+    fn next_block(&self, count: u64) -> u64 {
+        let mut first = 0;
+        let _ = self.next.fetch_update(
+            std::sync::atomic::Ordering::Relaxed,
+            std::sync::atomic::Ordering::Relaxed,
+            |current| {
+                first = if current.checked_add(count).is_some() { current } else { 1 };
+                Some(first + count)
+            },
+        );
+        first
+    }
 
-        println!("\n=== Stress Test Statistics ===");
-        println!("Total requests: {}", total);
-        println!("Successful: {} ({:.2}%)", successful, success_rate);
-        println!("\nErrors:");
-        
-        // Выводим HTTP ошибки по каждому статусу
-        let http_errors = self.http_errors.lock().unwrap();
-        if !http_errors.is_empty() {
-            let mut error_vec: Vec<_> = http_errors.iter().collect();
-            error_vec.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-            for (error_name, counter) in error_vec {
-                let count = counter.load(std::sync::atomic::Ordering::Relaxed);
-                println!("  {}: {}", error_name, count);
-            }
-        }
-        drop(http_errors);
-        
-        println!("  HTTP timeouts: {}", http_timeouts);
-        println!("  JSON parse errors: {}", json_parse_errors);
-        println!("  Network errors: {}", network_errors);
-        println!("  RPC errors: {}", rpc_errors);
-        println!("\nLatency:");
-        println!("  Average: {:.2} ms", avg_latency);
-        if !times.is_empty() {
-            println!("  Minimum: {:.2} ms", min_latency);
-            println!("  Maximum: {:.2} ms", max_latency);
-        }
+    // Последний выданный ID (None, если запросов не было)
+    fn last(&self) -> Option<u64> {
+        let next = self.next.load(std::sync::atomic::Ordering::Relaxed);
+        (next != self.start).then(|| next.wrapping_sub(1))
     }
 }
 
-async fn send_rpc_request(
-    client: &reqwest::Client,
-    url: &str,
-    method: &str,
+// Общий лимит расхода на прогон (--request-budget): в кредитах, если задан [credits], иначе в запросах.
+// Стоимость списывается до отправки, поэтому лимит никогда не превышается
+#[derive(Clone)]
+struct RequestBudget {
+    limit: u64,
+    remaining: Arc<std::sync::atomic::AtomicU64>,
+}
+
+impl RequestBudget {
+    fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            remaining: Arc::new(std::sync::atomic::AtomicU64::new(limit)),
+        }
+    }
+
+    // false - на запрос не хватает остатка бюджета
+    fn try_spend(&self, cost: u64) -> bool {
+        self.remaining
+            .fetch_update(
+                std::sync::atomic::Ordering::Relaxed,
+                std::sync::atomic::Ordering::Relaxed,
+                |remaining| remaining.checked_sub(cost),
+            )
+            .is_ok()
+    }
+
+    fn spent(&self) -> u64 {
+        self.limit - self.remaining.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+// Списывает стоимость count запросов метода: кредиты в статистику и из бюджета.
+// false - бюджет исчерпан, запрос отправлять нельзя
+fn charge(
+    credits: Option<&CreditConfig>,
+    budget: Option<&RequestBudget>,
+    stats: &Stats,
+    method: &str,
+    count: u64,
+) -> bool {
+    if let Some(budget) = budget {
+        let cost = credits.map_or(1, |credits| credits.cost(method)) * count;
+        if !budget.try_spend(cost) {
+            stats.record_budget_exhausted();
+            return false;
+        }
+    }
+    if let Some(credits) = credits {
+        stats.record_credits(credits.cost(method) * count);
+    }
+    true
+}
+
+fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// Статистика прогона. Счетчики принадлежат задаче-агрегатору (aggregator.rs),
+// сюда воркеры только отправляют события
+#[derive(Clone)]
+struct Stats {
+    events: aggregator::EventSender,
+    budget_exhausted_micros: Arc<AtomicU64>, // 0 - бюджет не исчерпан
+    breaker: Option<breaker::Breaker>,
+    outliers: Option<Arc<outliers::OutlierLog>>, // только с --outlier-ms
+    rate_limiter: Option<Arc<limits::RateLimiter>>, // только с --max-rps или --provider-limits
+    dns: Option<Arc<dnsrefresh::DnsRefresher>>, // только с --dns-refresh: адреса endpoint для клиентов воркеров
+    remote_addresses: bool, // учитывать IP ответов (--endpoint-ips)
+    response_hashes: Option<Arc<consistency::ResponseHashes>>, // только с --hash-responses
+    error_log: Option<Arc<errorlog::ErrorLog>>, // None - --last-errors 0
+    failure_samples: Option<Arc<ticket::FailureSamples>>, // только с --output-ticket
+    redaction: redact::Redaction, // --redact для отладочного вывода и последних ошибок
+    // Конец теста в миллисекундах от started_at, 0 - бесконечный; продлевается во время теста (control.rs)
+    end_millis: Arc<AtomicU64>,
+    started_at: Instant,
+    clock: aggregator::BucketClock, // границы корзин статистики
+}
+
+// Захват заголовков ответа (rate-limit и т.п.): каждый воркер сохраняет
+// не больше одного значения каждого заголовка за интервал
+struct HeaderCapture {
+    names: Vec<String>,
+    interval_secs: u64,
+    last_interval: HashMap<String, u64>,
+    stats: Stats,
+    last_decode_micros: u64, // распаковка и разбор JSON последнего ответа
+    last_body_bytes: (u64, u64), // тело последнего ответа на проводе и после распаковки
+    gzip_requests: bool, // тела запросов воркера сжимаются
+    last_response: Option<(u16, reqwest::header::HeaderMap)>, // статус и заголовки для файла выбросов
+    correlation: Option<(reqwest::header::HeaderName, String)>, // заголовок и префикс ID воркера
+    sent_requests: u64,
+    last_correlation_id: Option<String>,
+    request_start: Option<Instant>, // начало последнего HTTP запроса: до заголовков ответа для --endpoint-ips
+    connections: VecDeque<(SocketAddr, SocketAddr)>, // последние соединения воркера (локальный и удаленный адрес)
+    idle_since: Option<Instant>, // с какого момента в пуле ждет keep-alive соединение прошлого ответа
+    reused_connection: bool, // текущий запрос ушел по соединению из пула
+    control: bool, // идет служебный запрос: соединение, размеры и декодирование в статистику нагрузки не пишутся
+    affinity: Option<affinity::Session>, // --affinity: привязка сессии воркера
+    affinity_group: Option<Arc<aggregator::WorkerCounters<affinity::GroupStats>>>, // счетчики группы воркера (с привязкой или без)
+}
+
+// Воркер шлет запросы последовательно, поэтому одновременно открытых соединений у него немного;
+// соединение, выпавшее из списка, давно закрыто
+const RECENT_CONNECTIONS: usize = 64;
+
+// Сколько reqwest держит простаивающее соединение в пуле (значение по умолчанию)
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+// Останется ли соединение открытым после ответа: HTTP/1.1 - если endpoint не ответил Connection: close,
+// HTTP/1.0 - только с Connection: keep-alive
+fn keeps_alive(response: &reqwest::Response) -> bool {
+    let connection = response
+        .headers()
+        .get(reqwest::header::CONNECTION)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match response.version() {
+        reqwest::Version::HTTP_09 | reqwest::Version::HTTP_10 => connection.contains("keep-alive"),
+        reqwest::Version::HTTP_11 => !connection.contains("close"),
+        _ => true,
+    }
+}
+
+impl HeaderCapture {
+    fn new(names: Vec<String>, interval_secs: u64, stats: Stats) -> Self {
+        Self {
+            names,
+            interval_secs: interval_secs.max(1),
+            last_interval: HashMap::new(),
+            last_decode_micros: 0,
+            last_body_bytes: (0, 0),
+            gzip_requests: false,
+            last_response: None,
+            correlation: None,
+            sent_requests: 0,
+            last_correlation_id: None,
+            request_start: None,
+            connections: VecDeque::new(),
+            idle_since: None,
+            reused_connection: false,
+            control: false,
+            affinity: None,
+            affinity_group: None,
+            stats,
+        }
+    }
+
+    fn with_gzip_requests(mut self, gzip_requests: bool) -> Self {
+        self.gzip_requests = gzip_requests;
+        self
+    }
+
+    fn with_correlation(mut self, correlation: Option<&Correlation>, worker_id: usize) -> Self {
+        self.correlation = correlation
+            .map(|correlation| (correlation.header.clone(), format!("{}-{}", correlation.run, worker_id)));
+        self
+    }
+
+    fn with_affinity(mut self, affinity: Option<&affinity::AffinitySettings>, worker_id: usize) -> Self {
+        if let Some(settings) = affinity {
+            let pinned = settings.pinned(worker_id);
+            let group = Arc::new(self.stats.affinity_group(pinned));
+            self.affinity = pinned.then(|| affinity::Session::new(settings.source.clone(), group.clone()));
+            self.affinity_group = Some(group);
+        }
+        self
+    }
+
+    // Счетчики группы --affinity: запросы нагрузки (служебные сюда не попадают)
+    fn record_group_attempts(&self, count: u64) {
+        if let Some(group) = &self.affinity_group {
+            group.record_attempts(count);
+        }
+    }
+
+    fn record_group_success(&self, response_time_micros: u64, count: u64) {
+        if let Some(group) = &self.affinity_group {
+            group.record_success(response_time_micros, count);
+        }
+    }
+
+    // Заголовок с ID корреляции очередного HTTP запроса (ID запоминается для файла выбросов)
+    fn next_correlation(&mut self) -> Option<(reqwest::header::HeaderName, String)> {
+        let (header, prefix) = self.correlation.as_ref()?;
+        self.sent_requests += 1;
+        let id = format!("{}-{}", prefix, self.sent_requests);
+        self.last_correlation_id = Some(id.clone());
+        Some((header.clone(), id))
+    }
+
+    fn observe(&mut self, response: &reqwest::Response) {
+        let headers = response.headers();
+        if let Some(session) = &mut self.affinity {
+            session.observe(headers);
+        }
+        // Заголовки копируются, только если их может понадобиться записать в файл выбросов или тикет
+        if self.stats.outliers.is_some() || self.stats.failure_samples.is_some() {
+            self.last_response = Some((response.status().as_u16(), headers.clone()));
+        }
+        // Соединение узнаем по паре адресов: первый ответ на нем - новое соединение, следующие - переиспользование.
+        // Ответы служебных запросов не учитываются: соединение считается открытым с первого запроса нагрузки
+        if let Some(info) = response.extensions().get::<hyper::client::connect::HttpInfo>().filter(|_| !self.control) {
+            let connection = (info.local_addr(), info.remote_addr());
+            let new = !self.connections.contains(&connection);
+            if new {
+                if self.connections.len() == RECENT_CONNECTIONS {
+                    self.connections.pop_front();
+                }
+                self.connections.push_back(connection);
+            }
+            let tls = response.url().scheme() == "https";
+            self.stats.send(StatEvent::Connection { new, tls });
+        }
+        self.idle_since = keeps_alive(response).then(Instant::now);
+        if self.stats.remote_addresses && !self.control {
+            if let (Some(addr), Some(start)) = (response.remote_addr(), self.request_start) {
+                let micros = start.elapsed().as_micros() as u64;
+                self.stats.send(StatEvent::RemoteAddress { ip: addr.ip(), micros });
+            }
+        }
+        if self.names.is_empty() {
+            return;
+        }
+        let interval = self.stats.clock.now_secs() / self.interval_secs;
+        for name in &self.names {
+            if self.last_interval.get(name) == Some(&interval) {
+                continue;
+            }
+            if let Some(value) = headers.get(name.as_str()).and_then(|v| v.to_str().ok()) {
+                self.last_interval.insert(name.clone(), interval);
+                self.stats.send(StatEvent::Header(HeaderSample {
+                    interval_start_secs: self.stats.clock.run_secs(interval * self.interval_secs) as u64,
+                    interval_start_unix: self.stats.clock.unix(interval * self.interval_secs),
+                    name: name.clone(),
+                    value: value.to_string(),
+                }));
+            }
+        }
+    }
+
+    fn record_body_size(&mut self, wire_bytes: u64, decoded_bytes: u64) {
+        self.last_body_bytes = (wire_bytes, decoded_bytes);
+        if !self.control {
+            self.stats.record_body_size(wire_bytes, decoded_bytes);
+        }
+    }
+
+    fn record_request_body(&self, raw_bytes: u64, sent_bytes: u64) {
+        if !self.control {
+            self.stats.send(StatEvent::RequestBody { raw_bytes, sent_bytes });
+        }
+    }
+
+    // Endpoint не принял сжатое тело: 415 или JSON-RPC ошибка разбора (-32700)
+    fn record_compression_rejected(&self) {
+        if !self.control {
+            self.stats.send(StatEvent::CompressionRejected);
+        }
+    }
+
+    fn record_decode(&mut self, decode_micros: u64) {
+        self.last_decode_micros = decode_micros;
+        if !self.control {
+            self.stats.record_decode_time(decode_micros);
+        }
+    }
+
+    // Перед каждым запросом: данные прошлого ответа не должны попасть в выброс, на который ответа не было
+    fn reset_last_response(&mut self) {
+        self.last_response = None;
+        self.last_decode_micros = 0;
+        self.last_body_bytes = (0, 0);
+        self.last_correlation_id = None;
+        self.request_start = Some(Instant::now());
+        // Воркер шлет запросы по одному: если после прошлого ответа соединение осталось в пуле,
+        // запрос уйдет по нему. После ошибки соединение не возвращается в пул
+        self.reused_connection = self.idle_since.take().is_some_and(|since| since.elapsed() < POOL_IDLE_TIMEOUT);
+    }
+
+    // Время с начала запроса без декодирования последнего ответа: латентность самого endpoint
+    fn network_micros(&self, request_start: Instant) -> u64 {
+        (request_start.elapsed().as_micros() as u64).saturating_sub(self.last_decode_micros)
+    }
+}
+
+impl Stats {
+    fn new() -> Self {
+        Self::with_breaker(None, None, false)
+    }
+
+    // summary_window - длина периода итогов --summary-window, align_buckets - корзины по часам (--align-buckets)
+    fn with_breaker(breaker: Option<breaker::Breaker>, summary_window: Option<Duration>, align_buckets: bool) -> Self {
+        let started_at = Instant::now();
+        let clock = aggregator::BucketClock::new(started_at, align_buckets);
+        Self {
+            events: aggregator::spawn(clock, breaker.clone(), summary_window.map(|window| window.as_secs())),
+            budget_exhausted_micros: Arc::new(AtomicU64::new(0)),
+            breaker,
+            outliers: None,
+            rate_limiter: None,
+            dns: None,
+            remote_addresses: false,
+            response_hashes: None,
+            error_log: None,
+            failure_samples: None,
+            redaction: redact::Redaction::default(),
+            end_millis: Arc::new(AtomicU64::new(0)),
+            started_at,
+            clock,
+        }
+    }
+
+    fn with_duration(self, duration: Duration) -> Self {
+        self.end_millis.store(duration.as_millis() as u64, std::sync::atomic::Ordering::Relaxed);
+        self
+    }
+
+    // Длительность теста с учетом продлений (None - бесконечный)
+    fn duration(&self) -> Option<Duration> {
+        let millis = self.end_millis.load(std::sync::atomic::Ordering::Relaxed);
+        (millis > 0).then(|| Duration::from_millis(millis))
+    }
+
+    fn end(&self) -> Option<Instant> {
+        self.duration().map(|duration| self.started_at + duration)
+    }
+
+    // Воркеры работают, пока не наступил конец теста
+    fn running(&self) -> bool {
+        self.end().is_none_or(|end| Instant::now() < end)
+    }
+
+    // Новая длительность теста от старта; конец теста в прошлом не задать - воркеры уже могли завершиться
+    #[cfg(unix)]
+    fn set_duration(&self, duration: Duration) -> Result<(), String> {
+        if duration <= self.started_at.elapsed() {
+            return Err(format!("the test has already run for {} sec", self.started_at.elapsed().as_secs()));
+        }
+        self.end_millis.store(duration.as_millis() as u64, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn with_outliers(mut self, outliers: Option<outliers::OutlierLog>) -> Self {
+        self.outliers = outliers.map(Arc::new);
+        self
+    }
+
+    fn with_rate_limit(mut self, max_rps: Option<u64>) -> Self {
+        self.rate_limiter = max_rps.map(|max_rps| Arc::new(limits::RateLimiter::new(max_rps)));
+        self
+    }
+
+    fn with_remote_addresses(mut self, enabled: bool) -> Self {
+        self.remote_addresses = enabled;
+        self
+    }
+
+    fn with_response_hashes(mut self, enabled: bool) -> Self {
+        self.response_hashes = enabled.then(Default::default);
+        self
+    }
+
+    fn with_error_log(mut self, capacity: usize) -> Self {
+        self.error_log = (capacity > 0).then(|| Arc::new(errorlog::ErrorLog::new(capacity)));
+        self
+    }
+
+    fn with_dns_refresh(mut self, refresher: Option<dnsrefresh::DnsRefresher>) -> Self {
+        self.dns = refresher.map(Arc::new);
+        self
+    }
+
+    fn with_failure_samples(mut self, enabled: bool) -> Self {
+        self.failure_samples = enabled.then(|| Arc::new(ticket::FailureSamples::new(self.redaction)));
+        self
+    }
+
+    fn with_redaction(mut self, redaction: redact::Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    // Ошибка воркера в буфер последних ошибок (--last-errors)
+    fn log_error(&self, worker_id: usize, category: &'static str, message: impl Into<String>) {
+        if let Some(log) = &self.error_log {
+            log.record(errorlog::ErrorEntry {
+                at_secs: self.started_at.elapsed().as_secs_f64(),
+                at_utc: self.clock.utc_now(),
+                worker: worker_id,
+                category,
+                // В тексте ошибки reqwest бывает URL с ключом
+                message: self.redaction.text(&secrets::redact(&message.into())).into_owned(),
+            });
+        }
+    }
+
+    // --hash-responses: result успешного ответа на запрос key
+    fn record_response_body(&self, key: Option<&str>, result: Option<&serde_json::Value>) {
+        if let (Some(hashes), Some(key), Some(result)) = (&self.response_hashes, key, result) {
+            hashes.record(key, result);
+        }
+    }
+
+    // Успешное чтение аккаунтов: размеры и разбор ответа по кодировке запроса
+    fn record_account_read(&self, encoding: String, result: Option<&serde_json::Value>, micros: u64, capture: &HeaderCapture) {
+        let (data_bytes, space_bytes) = result.map_or((0, 0), accounts::data_sizes);
+        let (wire_bytes, decoded_bytes) = capture.last_body_bytes;
+        self.send(StatEvent::AccountRead(accounts::AccountRead {
+            encoding,
+            micros,
+            wire_bytes,
+            decoded_bytes,
+            data_bytes,
+            space_bytes,
+            decode_micros: capture.last_decode_micros,
+        }));
+    }
+
+    // Ждет очереди на отправку count JSON-RPC запросов под общим лимитом RPS и места в очереди событий агрегатора.
+    // false - наступил end (конец теста), а очередь так и не дошла
+    async fn rate_limit_wait(&self, count: u64, end: Option<Instant>) -> bool {
+        self.events.ready().await;
+        let Some(limiter) = &self.rate_limiter else {
+            return true;
+        };
+        loop {
+            let Err(wait) = limiter.try_acquire(count) else {
+                return true;
+            };
+            if end.is_some_and(|end| Instant::now() + wait >= end) {
+                return false;
+            }
+            sleep(wait).await;
+        }
+    }
+
+    fn send(&self, event: StatEvent) {
+        self.events.send(event);
+    }
+
+    // Забирает накопленное агрегатором состояние: все события, отправленные до вызова, в нем учтены
+    async fn collect(&self) -> StatsState {
+        let (reply, state) = oneshot::channel();
+        self.send(StatEvent::Collect(reply));
+        state.await.unwrap_or_default()
+    }
+
+    // Начало запроса - момент записи минус время ответа (разбор ответа после него сюда не входит)
+    fn record_success(&self, response_time_micros: u64) {
+        let started_micros = (self.started_at.elapsed().as_micros() as u64).saturating_sub(response_time_micros);
+        self.send(StatEvent::Success { micros: response_time_micros, started_micros });
+    }
+
+    fn record_http_error(&self, status_code: u16, reason: &'static str) {
+        self.send(StatEvent::HttpError { status: status_code, reason });
+    }
+
+    fn record_http_timeout(&self, waited_micros: u64) {
+        self.send(StatEvent::HttpTimeout { waited_micros });
+    }
+
+    fn record_connect_timeout(&self) {
+        self.send(StatEvent::ConnectTimeout);
+    }
+
+    // Успешный ответ, пришедший в последние 10% HTTP таймаута
+    fn record_near_timeout(&self) {
+        self.send(StatEvent::NearTimeout);
+    }
+
+    // Успешные ответы, пришедшие после объявленного в заголовке дедлайна
+    fn record_late_responses(&self, count: u64) {
+        self.send(StatEvent::LateResponses(count));
+    }
+
+    fn record_worker_crash(&self) {
+        self.send(StatEvent::WorkerCrash);
+    }
+
+    fn record_control_request(&self, ok: bool, elapsed: Duration) {
+        self.send(StatEvent::ControlRequest { ok, micros: elapsed.as_micros() as u64 });
+    }
+
+    fn record_json_parse_error(&self) {
+        self.send(StatEvent::JsonParseError);
+    }
+
+    fn record_network_error(&self, kind: neterror::NetworkErrorKind) {
+        self.send(StatEvent::NetworkError { kind });
+    }
+
+    fn record_socket_exhaustion(&self) {
+        self.send(StatEvent::SocketExhaustion);
+    }
+
+    fn record_rpc_error(&self) {
+        self.send(StatEvent::RpcError);
+    }
+
+    fn record_id_mismatch(&self) {
+        self.send(StatEvent::IdMismatch);
+    }
+
+    fn record_batch(&self) {
+        self.send(StatEvent::Batch);
+    }
+
+    fn record_batch_missing(&self) {
+        self.send(StatEvent::BatchMissing);
+    }
+
+    fn record_batch_duplicate(&self) {
+        self.send(StatEvent::BatchDuplicate);
+    }
+
+    fn record_batch_unexpected(&self) {
+        self.send(StatEvent::BatchUnexpected);
+    }
+
+    fn record_batch_out_of_order(&self) {
+        self.send(StatEvent::BatchOutOfOrder);
+    }
+
+    fn record_credits(&self, credits: u64) {
+        self.send(StatEvent::Credits(credits));
+    }
+
+    // Запоминает момент первого отказа по бюджету: от него считается прогноз на полную длительность
+    fn record_budget_exhausted(&self) {
+        let elapsed = (self.started_at.elapsed().as_micros() as u64).max(1);
+        let _ = self.budget_exhausted_micros.compare_exchange(
+            0,
+            elapsed,
+            std::sync::atomic::Ordering::Relaxed,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+
+    fn record_body_size(&self, wire_bytes: u64, decoded_bytes: u64) {
+        self.send(StatEvent::BodySize { wire_bytes, decoded_bytes });
+    }
+
+    fn record_decode_time(&self, decode_micros: u64) {
+        self.send(StatEvent::DecodeTime(decode_micros));
+    }
+
+    // Предохранитель разомкнут: ждем не дольше секунды, чтобы воркер успел заметить конец теста.
+    // true - запрос отправлять еще рано
+    async fn breaker_wait(&self) -> bool {
+        match self.breaker.as_ref().and_then(|breaker| breaker.remaining(self.started_at)) {
+            Some(remaining) => {
+                sleep(remaining.min(Duration::from_secs(1))).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn budget_exhausted(&self) -> bool {
+        self.budget_exhausted_micros.load(std::sync::atomic::Ordering::Relaxed) > 0
+    }
+
+    // Счетчики Apdex метода (воркер берет их один раз при старте; свои у каждого воркера)
+    fn method_apdex(&self, method: &str) -> aggregator::WorkerCounters<apdex::MethodApdex> {
+        let method = method.to_string();
+        aggregator::WorkerCounters::new(&self.events, Default::default(), |counters| WorkerTotals::Apdex(method, counters))
+    }
+
+    // Счетчики endpoint (воркер берет их один раз при старте)
+    fn node_stats(&self, url: &str) -> aggregator::WorkerCounters<discovery::NodeStats> {
+        let url = url.to_string();
+        aggregator::WorkerCounters::new(&self.events, Default::default(), |counters| WorkerTotals::Node(url, counters))
+    }
+
+    // Счетчики ключа из пула; агрегатор сводит их по ключу со всех воркеров
+    fn key_stats(&self, pool: &keys::KeyPool, index: usize) -> aggregator::WorkerCounters<keys::KeyStats> {
+        let counters = keys::KeyStats::new(pool.label(index));
+        aggregator::WorkerCounters::new(&self.events, counters, move |counters| WorkerTotals::Key(index, counters))
+    }
+
+    // Счетчики группы --affinity; агрегатор сводит их по группе со всех воркеров
+    fn affinity_group(&self, pinned: bool) -> aggregator::WorkerCounters<affinity::GroupStats> {
+        aggregator::WorkerCounters::new(&self.events, Default::default(), move |counters| WorkerTotals::Affinity(pinned, counters))
+    }
+
+    // Счетчики итераций сценария; агрегатор сводит их по сценарию со всех воркеров
+    fn scenario_stats(&self, scenario: &scenario::ScenarioConfig) -> aggregator::WorkerCounters<scenario::ScenarioStats> {
+        let name = scenario.name.clone();
+        let counters = scenario::ScenarioStats::new(&scenario.steps);
+        aggregator::WorkerCounters::new(&self.events, counters, |counters| WorkerTotals::Scenario(name, Box::new(counters)))
+    }
+
+    fn budget_summary(&self, budget: &RequestBudget, settings: &WorkerSettings) -> report::BudgetSummary {
+        let exhausted_micros = self.budget_exhausted_micros.load(std::sync::atomic::Ordering::Relaxed);
+        let exhausted_after_secs = (exhausted_micros > 0).then(|| exhausted_micros as f64 / 1_000_000.0);
+        let active_secs = exhausted_after_secs.unwrap_or_else(|| self.started_at.elapsed().as_secs_f64());
+        let spent = budget.spent();
+        report::BudgetSummary {
+            unit: if settings.credits.is_some() { "credits" } else { "requests" },
+            limit: budget.limit,
+            spent,
+            exhausted_after_secs,
+            projected_full_duration: self
+                .duration()
+                .map(|duration| spent as f64 / active_secs.max(0.001) * duration.as_secs_f64()),
+        }
+    }
+}
+
+// Ошибка одного HTTP запроса: транспорт, распаковка тела или разбор JSON
+#[derive(Debug)]
+enum RequestError {
+    Http(reqwest::Error),
+    Decompress(String),
+    Parse(serde_json::Error),
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RequestError::Http(e) => write!(f, "{}", e),
+            RequestError::Decompress(e) => write!(f, "response decompression failed: {}", e),
+            RequestError::Parse(e) => write!(f, "error decoding response body: {}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for RequestError {
+    fn from(e: reqwest::Error) -> Self {
+        RequestError::Http(e)
+    }
+}
+
+// Читает тело ответа и разбирает JSON, учитывая размер на проводе и после распаковки
+async fn read_json<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    capture: &mut HeaderCapture,
+) -> Result<T, RequestError> {
+    capture.observe(&response);
+    let content_encoding = response
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let wire = response.bytes().await?;
+    // Дальше работа только на клиенте: ее время не входит в латентность endpoint
+    let decode_start = Instant::now();
+    let body = compression::decode(content_encoding.as_deref(), &wire).map_err(RequestError::Decompress)?;
+    capture.record_body_size(wire.len() as u64, body.len() as u64);
+    if capture.gzip_requests && is_parse_error(&body) {
+        capture.record_compression_rejected();
+    }
+    let parsed = serde_json::from_slice(&body).map_err(RequestError::Parse);
+    capture.record_decode(decode_start.elapsed().as_micros() as u64);
+    parsed
+}
+
+fn deadline_micros(deadline: Option<&Deadline>) -> u64 {
+    deadline.map_or(u64::MAX, |deadline| deadline.ms * 1000)
+}
+
+// Ответ - одиночная JSON-RPC ошибка разбора запроса: так отвечает endpoint, который не распаковал тело.
+// Ответ на батч при этом тоже одиночный объект, а не массив
+fn is_parse_error(body: &[u8]) -> bool {
+    const PARSE_ERROR: i32 = -32700;
+    serde_json::from_slice::<JsonRpcResponseHead>(body)
+        .ok()
+        .and_then(|response| response.error)
+        .is_some_and(|error| error.code == PARSE_ERROR)
+}
+
+// Заголовки воркера для очередного HTTP запроса: ID корреляции и привязка сессии
+fn prepare_request(mut request: reqwest::RequestBuilder, capture: &mut HeaderCapture) -> reqwest::RequestBuilder {
+    capture.reset_last_response();
+    if let Some((header, id)) = capture.next_correlation() {
+        request = request.header(header, id);
+    }
+    if let Some((header, value)) = capture.affinity.as_ref().and_then(affinity::Session::request_header) {
+        request = request.header(header, value);
+    }
+    request
+}
+
+// POST тела JSON-RPC. С --gzip-requests тело сжимается и отправляется с Content-Encoding: gzip;
+// ответ 415 на сжатое тело значит, что endpoint сжатые запросы не принимает
+async fn post_json<T: Serialize + ?Sized>(
+    client: &reqwest::Client,
+    url: &str,
+    body: &T,
+    capture: &mut HeaderCapture,
+) -> Result<reqwest::Response, RequestError> {
+    let request = prepare_request(client.post(url), capture);
+    if !capture.gzip_requests {
+        return Ok(request.json(body).send().await?);
+    }
+    let json = serde_json::to_vec(body).map_err(RequestError::Parse)?;
+    // Сжатие в памяти не падает на корректном входе; если все же не удалось - отправляем как есть
+    let Ok(compressed) = compression::gzip(&json) else {
+        return Ok(request.json(body).send().await?);
+    };
+    capture.record_request_body(json.len() as u64, compressed.len() as u64);
+    let response = request
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(reqwest::header::CONTENT_ENCODING, "gzip")
+        .body(compressed)
+        .send()
+        .await?;
+    if response.status() == reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE {
+        capture.record_compression_rejected();
+    }
+    Ok(response)
+}
+
+// Одиночный запрос вне воркеров (проверки до и после теста, подкоманды)
+async fn send_rpc_request(
+    client: &reqwest::Client,
+    url: &str,
+    method: &str,
     params: Vec<serde_json::Value>,
     request_id: u64,
-) -> Result<JsonRpcResponse, reqwest::Error> {
-    let request = JsonRpcRequest {
+    capture: &mut HeaderCapture,
+) -> Result<JsonRpcResponse, RequestError> {
+    let request = json_rpc_request(method, params, request_id);
+    let response = post_json(client, url, &request, capture).await?;
+    read_json(response, capture).await
+}
+
+fn json_rpc_request(method: &str, params: Vec<serde_json::Value>, request_id: u64) -> JsonRpcRequest {
+    JsonRpcRequest {
         jsonrpc: "2.0".to_string(),
         id: request_id,
         method: method.to_string(),
         params,
-    };
+    }
+}
 
-    let response = client
-        .post(url)
-        .json(&request)
-        .send()
-        .await?;
+fn batch_requests(method: &str, params: &[serde_json::Value], first_id: u64, batch_size: usize) -> Vec<JsonRpcRequest> {
+    (0..batch_size as u64)
+        .map(|i| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: first_id + i,
+            method: method.to_string(),
+            params: params.to_vec(),
+        })
+        .collect()
+}
 
-    response.json::<JsonRpcResponse>().await
+// getSlot перед getBlock - служебный запрос: он учитывается отдельно и не попадает в запросы,
+// ошибки и латентность нагрузки (и в окно автоматического выключателя)
+async fn get_latest_slot<T: Transport>(transport: &T, request_id: u64, capture: &mut HeaderCapture) -> Option<u64> {
+    let start = Instant::now();
+    capture.control = true;
+    let slot = transport
+        .call(&json_rpc_request("getSlot", vec![], request_id), capture, ResponseParser::Serde)
+        .await
+        .ok()
+        .and_then(|response| response.result)
+        .and_then(|result| serde_json::from_value::<u64>(result).ok());
+    capture.control = false;
+    capture.stats.record_control_request(slot.is_some(), start.elapsed());
+    slot
 }
 
-async fn get_latest_slot(
-    client: &reqwest::Client,
-    url: &str,
-    request_id: u64,
-) -> Option<u64> {
-    match send_rpc_request(client, url, "getSlot", vec![], request_id).await {
-        Ok(response) => {
-            if let Some(result) = response.result {
-                if let Ok(slot) = serde_json::from_value::<u64>(result) {
-                    return Some(slot);
-                }
+// Параметры getBlock для getLatestBlock: актуальный слот + опции из params (или дефолтные)
+fn latest_block_params(slot: u64, params: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let options = if params.len() > 1 {
+        // params[0] должен быть старый слот (игнорируем), params[1] - опции
+        params[1].clone()
+    } else if let Some(options) = params.first() {
+        // Только опции без слота
+        options.clone()
+    } else {
+        // Дефолтные опции
+        catalog::block_options()
+    };
+    vec![serde_json::Value::Number(slot.into()), options]
+}
+
+// Классифицирует ошибку reqwest и записывает ее в статистику
+// reused - запрос ушел по переиспользованному соединению (HeaderCapture::reused_connection)
+fn record_request_error(stats: &Stats, worker_id: usize, e: &RequestError, elapsed: Duration, reused: bool, debug: bool) {
+    let e = match e {
+        // Тело, которое не удалось распаковать или разобрать, учитываем как ошибку парсинга
+        RequestError::Decompress(_) | RequestError::Parse(_) => {
+            if debug {
+                println!("[Worker {}] JSON Parse Error: {}", worker_id, e);
+            }
+            stats.log_error(worker_id, "parse", e.to_string());
+            stats.record_json_parse_error();
+            return;
+        }
+        RequestError::Http(e) => e,
+    };
+    // Проверяем, является ли это ошибкой парсинга JSON
+    if e.is_decode() {
+        if debug {
+            println!("[Worker {}] JSON Parse Error: {}", worker_id, e);
+        }
+        stats.log_error(worker_id, "parse", e.to_string());
+        stats.record_json_parse_error();
+    } else if e.is_status() {
+        // HTTP ошибка
+        if let Some(status) = e.status() {
+            let status_code = status.as_u16();
+            let reason = status.canonical_reason().unwrap_or("Unknown");
+            if debug {
+                println!("[Worker {}] HTTP Error Status: {} {}", worker_id, status_code, reason);
+            }
+            stats.log_error(worker_id, "http", format!("{} {}", status_code, reason));
+            stats.record_http_error(status_code, reason);
+        } else {
+            if debug {
+                println!("[Worker {}] Request Error: {}", worker_id, e);
+            }
+            stats.log_error(worker_id, "network", e.to_string());
+            stats.record_network_error(neterror::NetworkErrorKind::Other);
+        }
+    } else if e.is_connect() && e.is_timeout() {
+        // Таймаут установки соединения - отдельная проблема (сеть, балансировщик), чем медленный ответ
+        if debug {
+            println!("[Worker {}] Connect Timeout: {}", worker_id, e);
+        }
+        stats.log_error(worker_id, "connect timeout", e.to_string());
+        stats.record_connect_timeout();
+    } else if e.is_timeout() {
+        if debug {
+            println!("[Worker {}] Request Timeout after {:?}: {}", worker_id, elapsed, e);
+        }
+        stats.log_error(worker_id, "timeout", format!("after {:?}: {}", elapsed, e));
+        stats.record_http_timeout(elapsed.as_micros() as u64);
+    } else if let Some(reason) = sockets::exhaustion(e) {
+        // Клиенту не хватило сокетов: запрос не ушел, endpoint тут ни при чем
+        if debug {
+            println!("[Worker {}] Socket Limit: {}: {}", worker_id, reason, e);
+        }
+        stats.log_error(worker_id, "socket limit", format!("{}: {}", reason, e));
+        stats.record_socket_exhaustion();
+    } else {
+        if debug {
+            println!("[Worker {}] Request Error: {}", worker_id, e);
+        }
+        let kind = neterror::classify(e);
+        stats.log_error(worker_id, kind.label(), e.to_string());
+        stats.record_network_error(kind);
+        record_connection_failure(stats, e, reused);
+    }
+}
+
+// Сетевая ошибка по новому или переиспользованному соединению (ошибка установки соединения - всегда новое)
+// и ее причина: endpoint закрыл соединение, не ответив (часто - балансировщик закрыл простаивавшее
+// keep-alive соединение), или соединение сброшено
+fn record_connection_failure(stats: &Stats, e: &reqwest::Error, reused: bool) {
+    stats.send(StatEvent::ConnectionFailure { reused: reused && !e.is_connect() });
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(e);
+    while let Some(error) = source {
+        if error.downcast_ref::<hyper::Error>().is_some_and(hyper::Error::is_incomplete_message) {
+            stats.send(StatEvent::ConnectionClosedByPeer);
+            return;
+        }
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind;
+            if matches!(io.kind(), ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe) {
+                stats.send(StatEvent::ConnectionReset);
+                return;
+            }
+        }
+        source = error.source();
+    }
+}
+
+// Проверяет, что ответы батча покрывают ровно отправленные ID, и записывает результат
+fn record_batch_responses(
+    stats: &Stats,
+    worker_id: usize,
+    responses: &[JsonRpcResponse],
+    first_id: u64,
+    batch_size: usize,
+    response_time_micros: u64,
+    debug: bool,
+) -> u64 {
+    let sent = first_id..first_id + batch_size as u64;
+    let mut successes = 0;
+    let mut seen = std::collections::HashSet::new();
+    let mut last_id = None;
+    let mut out_of_order = false;
+
+    for response in responses {
+        let id = match response.id.as_u64() {
+            Some(id) if sent.contains(&id) => id,
+            _ => {
+                if debug {
+                    println!("[Worker {}] Batch: unexpected ID {}", worker_id, response.id);
+                }
+                stats.log_error(worker_id, "batch", format!("unexpected ID {}", response.id));
+                stats.record_batch_unexpected();
+                continue;
+            }
+        };
+        if !seen.insert(id) {
+            if debug {
+                println!("[Worker {}] Batch: duplicate response for ID {}", worker_id, id);
+            }
+            stats.log_error(worker_id, "batch", format!("duplicate response for ID {}", id));
+            stats.record_batch_duplicate();
+            continue;
+        }
+        if last_id.is_some_and(|last| id < last) {
+            out_of_order = true;
+        }
+        last_id = Some(id);
+
+        if response.error.is_none() {
+            stats.record_success(response_time_micros);
+            successes += 1;
+        } else {
+            if debug {
+                println!("[Worker {}] RPC Error: {:?}", worker_id, response.error);
+            }
+            if let Some(error) = &response.error {
+                stats.log_error(worker_id, "rpc", format!("{}: {}", error.code, error.message));
+            }
+            stats.record_rpc_error();
+        }
+    }
+
+    // Пропавшие ответы считаем отдельными неудачными запросами
+    for id in sent {
+        if !seen.contains(&id) {
+            if debug {
+                println!("[Worker {}] Batch: missing response for ID {}", worker_id, id);
+            }
+            stats.log_error(worker_id, "batch", format!("missing response for ID {}", id));
+            stats.record_batch_missing();
+        }
+    }
+    if out_of_order {
+        stats.record_batch_out_of_order();
+    }
+    stats.record_batch();
+    successes
+}
+
+// headers - заголовки клиента (User-Agent и т.п., см. identity.rs);
+// api_key - заголовок с ключом из пула (имя и значение проверены при создании пула);
+// resolver - адреса endpoint по --dns-refresh вместо системного разрешения
+fn build_client(
+    http_timeout: Duration,
+    connect_timeout: Option<Duration>,
+    accept_encoding: Option<compression::AcceptEncoding>,
+    mut headers: reqwest::header::HeaderMap,
+    api_key: Option<(&str, &str)>,
+    resolver: Option<Arc<dnsrefresh::WorkerResolver>>,
+) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder().timeout(http_timeout);
+    if let Some(resolver) = resolver {
+        builder = builder.dns_resolver(resolver);
+    }
+    if let Some(connect_timeout) = connect_timeout {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+    if let Some(value) = accept_encoding.and_then(|encoding| encoding.header_value()) {
+        headers.insert(reqwest::header::ACCEPT_ENCODING, reqwest::header::HeaderValue::from_static(value));
+    }
+    if let Some((name, key)) = api_key {
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes()).expect("Invalid API key header name");
+        let mut value = reqwest::header::HeaderValue::from_str(key).expect("Invalid API key");
+        value.set_sensitive(true);
+        headers.insert(name, value);
+    }
+    if !headers.is_empty() {
+        builder = builder.default_headers(headers);
+    }
+    builder.build().expect("Failed to create HTTP client")
+}
+
+// Транспорт воркера для одного ключа пула (без пула ключей - единственный, на URL воркера)
+struct KeyedClient {
+    transport: HttpTransport,
+    key: Option<aggregator::WorkerCounters<keys::KeyStats>>, // счетчики ключа для отчета
+}
+
+impl KeyedClient {
+    fn record_attempts(&self, count: u64) {
+        if let Some(key) = &self.key {
+            key.record_attempts(count);
+        }
+    }
+
+    fn record_success(&self, response_time_micros: u64, count: u64) {
+        if let Some(key) = &self.key {
+            key.record_success(response_time_micros, count);
+        }
+    }
+}
+
+// Клиенты для всех ключей, которые использует воркер; при ротации на каждый запрос ключ выбирается из них случайно
+fn keyed_clients(settings: &WorkerSettings, stats: &Stats, worker_id: usize) -> Vec<KeyedClient> {
+    let mut headers = settings.identity.headers(worker_id);
+    if let Some(deadline) = &settings.deadline {
+        // Имя проверено при разборе настроек
+        if let Ok(name) = reqwest::header::HeaderName::from_bytes(deadline.header.as_bytes()) {
+            headers.insert(name, reqwest::header::HeaderValue::from(deadline.ms));
+        }
+    }
+    let resolver = stats.dns.as_ref().map(|dns| Arc::new(dnsrefresh::WorkerResolver::new(dns.clone(), worker_id)));
+    let (http_timeout, connect_timeout, accept_encoding) = (settings.http_timeout, settings.connect_timeout, settings.accept_encoding);
+    // С --dns-refresh транспорт пересоздает клиента сам, поэтому сборка клиента - владеющее замыкание
+    let transport = |url: String, api_key: Option<(&str, &str)>| {
+        let api_key = api_key.map(|(name, key)| (name.to_string(), key.to_string()));
+        let headers = headers.clone();
+        let resolver = resolver.clone();
+        let build = move || {
+            let api_key = api_key.as_ref().map(|(name, key)| (name.as_str(), key.as_str()));
+            build_client(http_timeout, connect_timeout, accept_encoding, headers.clone(), api_key, resolver.clone())
+        };
+        let client = build();
+        let refresh = stats.dns.as_ref().map(|dns| dnsrefresh::ClientRefresh::new(dns.clone(), client.clone(), Box::new(build)));
+        HttpTransport { client, url, refresh }
+    };
+    match &settings.api_keys {
+        Some(pool) => pool
+            .worker_keys(settings.seed, worker_id)
+            .into_iter()
+            .map(|index| KeyedClient {
+                transport: transport(pool.url(&settings.url, index), pool.header(index)),
+                key: Some(stats.key_stats(pool, index)),
+            })
+            .collect(),
+        None => vec![KeyedClient {
+            transport: transport(settings.url.clone(), None),
+            key: None,
+        }],
+    }
+}
+
+// Все, что нужно воркеру для отправки одиночного запроса и учета его результата
+struct RequestContext<'a, T: Transport = HttpTransport> {
+    transport: &'a T,
+    stats: &'a Stats,
+    node: &'a discovery::NodeStats,
+    key: Option<&'a keys::KeyStats>, // счетчики ключа из пула
+    worker_id: usize,
+    debug: bool,
+    near_timeout_micros: u64,
+    deadline_micros: u64, // объявленный дедлайн (u64::MAX - не объявлялся)
+    parser: ResponseParser,
+}
+
+// Отправленный запрос для файла выбросов: у батча - метод и params его запросов и ID первого
+struct SentRequest<'a> {
+    method: &'a str,
+    params: &'a [serde_json::Value],
+    batch_size: usize,
+    request_id: u64,
+}
+
+impl<T: Transport> RequestContext<'_, T> {
+    // Запрос дольше порога --outlier-ms записывается в файл выбросов вместе со своим ответом
+    fn record_outlier(&self, capture: &HeaderCapture, sent: &SentRequest, latency_micros: u64, outcome: impl Into<String>) {
+        let Some(outliers) = self.stats.outliers.as_deref() else {
+            return;
+        };
+        if latency_micros <= outliers.threshold_micros() {
+            return;
+        }
+        outliers.record(self.outlier(capture, sent, &secrets::redact(self.transport.endpoint()), latency_micros, outcome.into()));
+    }
+
+    // Неудачный запрос - образец для тикета провайдера (--output-ticket)
+    fn record_failure(&self, capture: &HeaderCapture, sent: &SentRequest, latency_micros: u64, outcome: &str) {
+        let Some(samples) = self.stats.failure_samples.as_deref() else {
+            return;
+        };
+        samples.record(self.outlier(capture, sent, &secrets::redact(self.transport.endpoint()), latency_micros, outcome.to_string()));
+    }
+
+    fn outlier<'a>(
+        &'a self,
+        capture: &'a HeaderCapture,
+        sent: &'a SentRequest,
+        url: &'a str,
+        latency_micros: u64,
+        outcome: String,
+    ) -> outliers::Outlier<'a> {
+        let at_secs = self.stats.started_at.elapsed().as_secs_f64();
+        outliers::Outlier {
+            at_secs,
+            started_utc: self.stats.clock.utc(at_secs - latency_micros as f64 / 1_000_000.0),
+            ended_utc: self.stats.clock.utc(at_secs),
+            worker_id: self.worker_id,
+            correlation_id: capture.last_correlation_id.as_deref(),
+            url,
+            api_key: self.key.map(keys::KeyStats::label),
+            method: sent.method,
+            params: sent.params,
+            batch_size: sent.batch_size,
+            request_id: sent.request_id,
+            latency_micros,
+            decode_micros: capture.last_decode_micros,
+            outcome,
+            response: capture.last_response.as_ref(),
+        }
+    }
+}
+
+// Отправляет один JSON-RPC запрос и учитывает результат в статистике.
+// Для успешного ответа возвращает его вместе с временем ответа в микросекундах
+async fn send_and_record<T: Transport>(
+    ctx: &RequestContext<'_, T>,
+    method: &str,
+    params: Vec<serde_json::Value>,
+    request_id: u64,
+    request_start: Instant,
+    capture: &mut HeaderCapture,
+) -> Option<(JsonRpcResponse, u64)> {
+    let RequestContext { stats, worker_id, debug, .. } = *ctx;
+    // params уходят в запрос; копия нужна, только если запрос может оказаться выбросом или образцом для тикета
+    let outlier_params = if stats.outliers.is_some() || stats.failure_samples.is_some() { params.clone() } else { Vec::new() };
+    let hash_key = stats.response_hashes.as_ref().and_then(|_| consistency::request_key(method, &params));
+    let account_encoding = accounts::encoding(method, &params);
+    let sent = SentRequest {
+        method,
+        params: &outlier_params,
+        batch_size: 1,
+        request_id,
+    };
+    let request = json_rpc_request(method, params, request_id);
+    match ctx.transport.call(&request, capture, ctx.parser).await {
+        Ok(json_response) => {
+            let response_time_micros = capture.network_micros(request_start);
+
+            // ID в ответе должен совпадать с ID запроса (прокси и батчеры иногда их путают)
+            if json_response.id.as_u64() != Some(request_id) {
+                if debug {
+                    println!("[Worker {}] ID Mismatch: sent {}, got {}", worker_id, request_id, json_response.id);
+                }
+                stats.log_error(worker_id, "id mismatch", format!("{}: sent {}, got {}", method, request_id, json_response.id));
+                stats.record_id_mismatch();
+                ctx.record_failure(capture, &sent, response_time_micros, "id mismatch");
+                ctx.record_outlier(capture, &sent, response_time_micros, "id mismatch");
+                None
+            } else if json_response.error.is_none() {
+                if debug {
+                    let mut printed = serde_json::to_value(&json_response).unwrap_or_default();
+                    stats.redaction.body(&mut printed);
+                    println!("[Worker {}] Success - Response: {}", worker_id,
+                        serde_json::to_string_pretty(&printed).unwrap_or_else(|_| format!("{:?}", json_response)));
+                }
+                stats.record_success(response_time_micros);
+                ctx.node.record_success(response_time_micros, 1);
+                if let Some(key) = ctx.key {
+                    key.record_success(response_time_micros, 1);
+                }
+                capture.record_group_success(response_time_micros, 1);
+                if response_time_micros >= ctx.near_timeout_micros {
+                    stats.record_near_timeout();
+                }
+                if response_time_micros > ctx.deadline_micros {
+                    stats.record_late_responses(1);
+                }
+                ctx.record_outlier(capture, &sent, response_time_micros, "ok");
+                stats.record_response_body(hash_key.as_deref(), json_response.result.as_ref());
+                if let Some(encoding) = account_encoding {
+                    stats.record_account_read(encoding, json_response.result.as_ref(), response_time_micros, capture);
+                }
+                Some((json_response, response_time_micros))
+            } else {
+                if debug {
+                    println!("[Worker {}] RPC Error: {:?}", worker_id, json_response.error);
+                }
+                stats.record_rpc_error();
+                if let Some(error) = &json_response.error {
+                    stats.log_error(worker_id, "rpc", format!("{}: {} {}", method, error.code, error.message));
+                    let outcome = format!("rpc error {}: {}", error.code, error.message);
+                    ctx.record_failure(capture, &sent, response_time_micros, &outcome);
+                    ctx.record_outlier(capture, &sent, response_time_micros, outcome);
+                }
+                None
+            }
+        }
+        Err(e) => {
+            record_request_error(stats, worker_id, &e, request_start.elapsed(), capture.reused_connection, debug);
+            let latency_micros = request_start.elapsed().as_micros() as u64;
+            ctx.record_failure(capture, &sent, latency_micros, &e.to_string());
+            ctx.record_outlier(capture, &sent, latency_micros, e.to_string());
+            None
+        }
+    }
+}
+
+// --no-parse: тело ответа читается и отбрасывается, успех определяется только HTTP статусом.
+// sent - что в теле (для батча - сколько JSON-RPC запросов). Возвращает время ответа для успешного запроса
+async fn send_raw_and_record<T: Transport, B: Serialize + Sync>(
+    ctx: &RequestContext<'_, T>,
+    body: &B,
+    sent: SentRequest<'_>,
+    request_start: Instant,
+    capture: &mut HeaderCapture,
+) -> Option<u64> {
+    let result = ctx.transport.send_raw(body, capture).await;
+    record_raw_outcome(ctx, sent, request_start, capture, result, |status| status.is_success())
+}
+
+// Учитывает ответ, тело которого не разбиралось: успех определяет только HTTP статус (success)
+fn record_raw_outcome<T: Transport>(
+    ctx: &RequestContext<'_, T>,
+    sent: SentRequest<'_>,
+    request_start: Instant,
+    capture: &mut HeaderCapture,
+    result: Result<reqwest::StatusCode, RequestError>,
+    success: impl Fn(reqwest::StatusCode) -> bool,
+) -> Option<u64> {
+    let RequestContext { stats, worker_id, debug, .. } = *ctx;
+    let count = sent.batch_size as u64;
+    match result {
+        Ok(status) if success(status) => {
+            let response_time_micros = request_start.elapsed().as_micros() as u64;
+            for _ in 0..count {
+                stats.record_success(response_time_micros);
+            }
+            ctx.node.record_success(response_time_micros, count);
+            if let Some(key) = ctx.key {
+                key.record_success(response_time_micros, count);
+            }
+            capture.record_group_success(response_time_micros, count);
+            if response_time_micros >= ctx.near_timeout_micros {
+                stats.record_near_timeout();
+            }
+            if response_time_micros > ctx.deadline_micros {
+                stats.record_late_responses(count);
+            }
+            ctx.record_outlier(capture, &sent, response_time_micros, "ok");
+            Some(response_time_micros)
+        }
+        Ok(status) => {
+            let reason = status.canonical_reason().unwrap_or("Unknown");
+            if debug {
+                println!("[Worker {}] HTTP Error Status: {} {}", worker_id, status.as_u16(), reason);
+            }
+            stats.log_error(worker_id, "http", format!("{}: {} {}", sent.method, status.as_u16(), reason));
+            stats.record_http_error(status.as_u16(), reason);
+            let outcome = format!("HTTP {} {}", status.as_u16(), reason);
+            let latency_micros = request_start.elapsed().as_micros() as u64;
+            ctx.record_failure(capture, &sent, latency_micros, &outcome);
+            ctx.record_outlier(capture, &sent, latency_micros, outcome);
+            None
+        }
+        Err(e) => {
+            record_request_error(stats, worker_id, &e, request_start.elapsed(), capture.reused_connection, debug);
+            let latency_micros = request_start.elapsed().as_micros() as u64;
+            ctx.record_failure(capture, &sent, latency_micros, &e.to_string());
+            ctx.record_outlier(capture, &sent, latency_micros, e.to_string());
+            None
+        }
+    }
+}
+
+// Запускает воркер под присмотром: паника не теряется молча, а учитывается в статистике,
+// и с --respawn-workers воркер перезапускается на оставшееся время теста
+fn spawn_supervised<F, Fut>(worker_id: usize, settings: WorkerSettings, stats: Stats, make: F) -> tokio::task::JoinHandle<()>
+where
+    F: Fn(WorkerSettings) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        // --stagger-start-ms: воркеры стартуют по очереди, а не все в первую миллисекунду
+        sleep(settings.stagger_start * worker_id as u32).await;
+        loop {
+            // Перезапущенный воркер работает до общего конца теста
+            if !stats.running() {
+                break;
+            }
+            match tokio::spawn(make(settings.clone())).await {
+                Err(e) if e.is_panic() => {
+                    let panic = e.into_panic();
+                    let message = panic
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    eprintln!("[Worker {}] Crashed: {}", worker_id, message);
+                    stats.log_error(worker_id, "crash", message.clone());
+                    stats.record_worker_crash();
+                    if !settings.respawn {
+                        break;
+                    }
+                }
+                _ => break,
+            }
+        }
+    })
+}
+
+async fn worker(
+    worker_id: usize,
+    method_config: MethodConfig,
+    settings: WorkerSettings,
+    stats: Stats,
+    tuning: Option<autotune::TuneHandle>,
+) {
+    if method_config.method == httpget::METHOD {
+        return httpget::http_get_worker(worker_id, method_config, settings, stats, tuning).await;
+    }
+    let phase = method_config.phase();
+    let label = method_config.label();
+    let method = method_config.method;
+    let params = method_config.params.unwrap_or_default();
+    let batch_size = method_config.batch.unwrap_or(1);
+    let clients = keyed_clients(&settings, &stats, worker_id);
+    let WorkerSettings {
+        url,
+        timeout_ms,
+        jitter_ms,
+        stagger_start: _,
+        http_timeout,
+        connect_timeout: _,
+        duration: _,
+        debug,
+        seed,
+        capture_headers,
+        header_interval_secs,
+        credits,
+        apdex,
+        ids,
+        budget,
+        accept_encoding: _,
+        no_parse,
+        parser,
+        respawn: _,
+        expect_cluster: _,
+        breaker: _,
+        api_keys: _,
+        identity: _,
+        gzip_requests,
+        deadline,
+        recovery: _,
+        outliers: _,
+        correlation,
+        clock_check: _,
+        max_rps: _,
+        pace_factor,
+        provider_limits: _,
+        addresses: _,
+        dns_refresh: _,
+        hash_responses: _,
+        affinity,
+        summary_window: _,
+        align_buckets: _,
+        last_errors: _,
+        redact: _,
+        snapshot: _,
+        side_channel: _,
+        custom_methods: _,
+    } = settings;
+
+    let method_apdex = apdex.map(|thresholds| (thresholds, stats.method_apdex(&label)));
+    let node = stats.node_stats(&url);
+    let near_timeout_micros = http_timeout.as_micros() as u64 * 9 / 10;
+    let deadline_micros = deadline_micros(deadline.as_ref());
+
+    // Кредиты и бюджет списываются за каждый отправленный JSON-RPC запрос
+    let charge = |method: &str, count: u64| charge(credits.as_deref(), budget.as_ref(), &stats, method, count);
+
+    // У каждого воркера свой поток случайных чисел, производный от общего seed
+    let mut rng = Rng::new(seed.wrapping_add(worker_id as u64));
+    let mut debug_rng = Rng::new(random_seed().wrapping_add(worker_id as u64));
+    let mut pacer = limits::Pacer::new(pace_factor);
+    let mut capture = HeaderCapture::new(capture_headers, header_interval_secs, stats.clone())
+        .with_gzip_requests(gzip_requests)
+        .with_correlation(correlation.as_ref(), worker_id)
+        .with_affinity(affinity.as_ref(), worker_id);
+
+    // Фаза метода: воркер ждет ее начала и завершается с ее концом, даже если тест продлили
+    if !phase.wait_start(&stats).await {
+        return;
+    }
+    // Общий конец теста: его можно продлить во время теста
+    while phase.active(&stats) {
+        // Автотюнер может остановить лишние воркеры
+        if tuning.as_ref().is_some_and(|t| t.stopped()) {
+            break;
+        }
+        if stats.breaker_wait().await {
+            continue;
+        }
+        // Лимит RPS: getLatestBlock - два запроса (getSlot и getBlock), батч - batch_size запросов.
+        // Если очередь дойдет только после конца теста, воркер завершается
+        let requests = if method == "getLatestBlock" { 2 } else { batch_size as u64 };
+        let end = phase.end(&stats);
+        if !stats.rate_limit_wait(requests, end).await {
+            break;
+        }
+
+        // Ключ из пула: у воркера один клиент или (при ротации на каждый запрос) клиент на каждый ключ
+        let keyed = &clients[rng.up_to(clients.len() as u64 - 1) as usize];
+        // --debug-sample и --debug-method: решение о выводе - на весь запрос, включая служебный getSlot
+        let debug = debug.as_ref().is_some_and(|filter| filter.admits(&method, &mut debug_rng));
+        let ctx = RequestContext {
+            transport: &keyed.transport,
+            stats: &stats,
+            node: &node,
+            key: keyed.key.as_deref(),
+            worker_id,
+            debug,
+            near_timeout_micros,
+            deadline_micros,
+            parser,
+        };
+
+        let mut request_start = Instant::now();
+
+        // Батч: один HTTP запрос с batch_size JSON-RPC запросами подряд идущих ID
+        if batch_size > 1 && method != "getLatestBlock" {
+            if !charge(&method, batch_size as u64) {
+                break;
+            }
+            let first_id = ids.next_block(batch_size as u64);
+            node.record_attempts(batch_size as u64);
+            keyed.record_attempts(batch_size as u64);
+            capture.record_group_attempts(batch_size as u64);
+            if let Some((_, counters)) = &method_apdex {
+                counters.record_attempts(batch_size as u64);
+            }
+            let requests = batch_requests(&method, &params, first_id, batch_size);
+            let sent = SentRequest {
+                method: &method,
+                params: &params,
+                batch_size,
+                request_id: first_id,
+            };
+            if no_parse {
+                let sent = send_raw_and_record(&ctx, &requests, sent, request_start, &mut capture).await;
+                if let Some(response_time_micros) = sent {
+                    if let Some((thresholds, counters)) = &method_apdex {
+                        counters.record_success(thresholds, response_time_micros, batch_size as u64);
+                    }
+                    if let Some(tuning) = &tuning {
+                        tuning.record(response_time_micros);
+                    }
+                }
+                sleep(pacer.pause(request_start.elapsed(), timeout_ms + rng.up_to(jitter_ms))).await;
+                continue;
+            }
+            match ctx.transport.call_batch(&requests, &mut capture, parser).await {
+                Ok(responses) => {
+                    let response_time_micros = capture.network_micros(request_start);
+                    let successes = record_batch_responses(&stats, worker_id, &responses, first_id, batch_size, response_time_micros, debug);
+                    // Все запросы батча одинаковые: у каждого ответа без ошибки свое тело
+                    if let Some(key) = stats.response_hashes.as_ref().and_then(|_| consistency::request_key(&method, &params)) {
+                        for response in responses.iter().filter(|response| response.error.is_none()) {
+                            stats.record_response_body(Some(&key), response.result.as_ref());
+                        }
+                    }
+                    node.record_success(response_time_micros, successes);
+                    keyed.record_success(response_time_micros, successes);
+                    capture.record_group_success(response_time_micros, successes);
+                    if let Some((thresholds, counters)) = &method_apdex {
+                        counters.record_success(thresholds, response_time_micros, successes);
+                    }
+                    if successes > 0 && response_time_micros >= near_timeout_micros {
+                        stats.record_near_timeout();
+                    }
+                    if successes > 0 && response_time_micros > deadline_micros {
+                        stats.record_late_responses(successes);
+                    }
+                    if let Some(tuning) = &tuning {
+                        tuning.record(response_time_micros);
+                    }
+                    let outcome = format!("{}/{} ok", successes, batch_size);
+                    if successes < batch_size as u64 {
+                        ctx.record_failure(&capture, &sent, response_time_micros, &outcome);
+                    }
+                    ctx.record_outlier(&capture, &sent, response_time_micros, outcome);
+                }
+                Err(e) => {
+                    record_request_error(&stats, worker_id, &e, request_start.elapsed(), capture.reused_connection, debug);
+                    let latency_micros = request_start.elapsed().as_micros() as u64;
+                    ctx.record_failure(&capture, &sent, latency_micros, &e.to_string());
+                    ctx.record_outlier(&capture, &sent, latency_micros, e.to_string());
+                }
+            }
+            sleep(pacer.pause(request_start.elapsed(), timeout_ms + rng.up_to(jitter_ms))).await;
+            continue;
+        }
+
+        // getLatestBlock начинается с getSlot: его и списываем первым
+        let latest_block = method == "getLatestBlock";
+        if !charge(if latest_block { "getSlot" } else { &method }, 1) {
+            break;
+        }
+        let (actual_method, actual_params) = if latest_block {
+            // Кастомный метод: сначала получаем актуальный слот, затем getBlock
+            let slot_request_id = ids.next();
+
+            match get_latest_slot(ctx.transport, slot_request_id, &mut capture).await {
+                Some(slot) => {
+                    if debug {
+                        println!("[Worker {}] Got latest slot: {}", worker_id, slot);
+                    }
+                    // Латентность getBlock считается без служебного getSlot
+                    request_start = Instant::now();
+                    ("getBlock".to_string(), latest_block_params(slot, &params))
+                }
+                None => {
+                    // Ошибка служебного запроса учтена отдельно: getBlock не отправлялся
+                    if debug {
+                        println!("[Worker {}] Failed to get latest slot", worker_id);
+                    }
+                    sleep(pacer.pause(request_start.elapsed(), timeout_ms + rng.up_to(jitter_ms))).await;
+                    continue;
+                }
+            }
+        } else {
+            (method.clone(), params.clone())
+        };
+
+        if latest_block && !charge(&actual_method, 1) {
+            break;
+        }
+        node.record_attempts(1);
+        keyed.record_attempts(1);
+        capture.record_group_attempts(1);
+        if let Some((_, counters)) = &method_apdex {
+            counters.record_attempts(1);
+        }
+        let request_id = ids.next();
+        let sent = if no_parse {
+            let request = JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: request_id,
+                method: actual_method,
+                params: actual_params,
+            };
+            let sent = SentRequest {
+                method: &request.method,
+                params: &request.params,
+                batch_size: 1,
+                request_id,
+            };
+            send_raw_and_record(&ctx, &request, sent, request_start, &mut capture).await
+        } else {
+            send_and_record(&ctx, &actual_method, actual_params, request_id, request_start, &mut capture)
+                .await
+                .map(|(_, response_time_micros)| response_time_micros)
+        };
+        if let Some(response_time_micros) = sent {
+            if let Some((thresholds, counters)) = &method_apdex {
+                counters.record_success(thresholds, response_time_micros, 1);
+            }
+            if let Some(tuning) = &tuning {
+                tuning.record(response_time_micros);
+            }
+        }
+
+        // Таймаут между запросами (с джиттером, если задан)
+        sleep(pacer.pause(request_start.elapsed(), timeout_ms + rng.up_to(jitter_ms))).await;
+    }
+}
+
+// Заголовки rate-limit, которые отслеживаются всегда
+const DEFAULT_CAPTURE_HEADERS: &[&str] = &["x-ratelimit-remaining", "x-ratelimit-limit", "retry-after"];
+
+fn header_names(from_args: &[String], from_config: Option<&[String]>) -> Vec<String> {
+    let mut names: Vec<String> = DEFAULT_CAPTURE_HEADERS.iter().map(|h| h.to_string()).collect();
+    for name in from_args.iter().chain(from_config.unwrap_or_default()) {
+        let name = name.to_ascii_lowercase();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn apdex_thresholds(args: &Args) -> Option<apdex::ApdexThresholds> {
+    args.apdex_satisfied_ms.map(|satisfied_ms| apdex::ApdexThresholds {
+        satisfied_ms,
+        tolerating_ms: args.apdex_tolerating_ms.unwrap_or(satisfied_ms * 4),
+    })
+}
+
+fn breaker_settings(error_rate_percent: Option<f64>, cooldown_secs: u64) -> Option<breaker::BreakerSettings> {
+    error_rate_percent.map(|error_rate_percent| breaker::BreakerSettings {
+        error_rate_percent,
+        cooldown: Duration::from_secs(cooldown_secs.max(1)),
+    })
+}
+
+// Явный лимит RPS важнее лимита тарифа
+fn max_rps(explicit: Option<u64>, plan: Option<limits::ProviderPlan>) -> Option<u64> {
+    explicit.or(plan.map(|plan| plan.limits().max_rps)).filter(|&rps| rps > 0)
+}
+
+// Лимиты тарифа на воркеры и батчи применяются до старта; возвращает описание изменений
+fn apply_provider_limits(
+    plan: limits::ProviderPlan,
+    methods: &mut [MethodConfig],
+    scenarios: &mut [scenario::ScenarioConfig],
+) -> Result<Vec<String>, String> {
+    let limits = plan.limits();
+    let mut changes = Vec::new();
+    if let Some(max_batch) = limits.max_batch {
+        for method in methods.iter_mut().filter(|method| method.batch.unwrap_or(1) > max_batch) {
+            changes.push(format!("{} batch size {} -> {}", method.method, method.batch.unwrap_or(1), max_batch));
+            method.batch = Some(max_batch);
+        }
+    }
+    if let Some(max_concurrency) = limits.max_concurrency {
+        let workers: Vec<usize> = methods
+            .iter()
+            .map(|method| method.workers)
+            .chain(scenarios.iter().map(|scenario| scenario.workers))
+            .collect();
+        let fitted = limits::fit_workers(&workers, max_concurrency)?;
+        let (before, after): (usize, usize) = (workers.iter().sum(), fitted.iter().sum());
+        if before != after {
+            changes.push(format!("workers {} -> {}", before, after));
+        }
+        let slots = methods
+            .iter_mut()
+            .map(|method| &mut method.workers)
+            .chain(scenarios.iter_mut().map(|scenario| &mut scenario.workers));
+        for (slot, workers) in slots.zip(fitted) {
+            *slot = workers;
+        }
+    }
+    Ok(changes)
+}
+
+fn outlier_settings(threshold_ms: Option<u64>, file: Option<String>) -> Option<outliers::OutlierSettings> {
+    threshold_ms.map(|threshold_ms| outliers::OutlierSettings {
+        threshold_ms,
+        file: file.unwrap_or_else(|| outliers::DEFAULT_FILE.to_string()),
+    })
+}
+
+// Пул API ключей из списка и файла; None - ключи не заданы.
+// Ключи запоминаются как секреты, чтобы не попасть в вывод настроек и сохраненный конфиг
+fn api_key_pool(
+    mut api_keys: Vec<String>,
+    file: Option<&Path>,
+    header: Option<String>,
+    rotation: keys::KeyRotation,
+    url: &str,
+) -> Result<Option<keys::KeyPool>, String> {
+    if let Some(file) = file {
+        api_keys.extend(keys::load_file(file)?);
+    }
+    if api_keys.is_empty() {
+        if url.contains(keys::URL_PLACEHOLDER) || header.is_some() {
+            return Err("No API keys given: use --api-key or --api-keys-file".to_string());
+        }
+        return Ok(None);
+    }
+    for key in &api_keys {
+        secrets::remember(key.clone());
+    }
+    keys::KeyPool::new(api_keys, header, rotation, url).map(Some)
+}
+
+// Пауза между запросами воркера: число или диапазон "50..200". Диапазон сводится к timeout_ms = 50
+// и джиттеру 150: пауза равномерно случайна на каждый запрос, и воркеры не ходят в ногу
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(try_from = "PauseValue")]
+struct Pause {
+    min_ms: u64,
+    max_ms: u64,
+}
+
+impl Pause {
+    fn spread(self) -> u64 {
+        self.max_ms - self.min_ms
+    }
+}
+
+// timeout_ms в конфиге: 200 или "50..200"
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PauseValue {
+    Fixed(u64),
+    Range(String),
+}
+
+impl TryFrom<PauseValue> for Pause {
+    type Error = String;
+
+    fn try_from(value: PauseValue) -> Result<Self, String> {
+        match value {
+            PauseValue::Fixed(ms) => Ok(Pause { min_ms: ms, max_ms: ms }),
+            PauseValue::Range(text) => parse_pause(&text),
+        }
+    }
+}
+
+fn parse_pause(s: &str) -> Result<Pause, String> {
+    let number = |part: &str| part.trim().parse::<u64>().map_err(|_| format!("expected milliseconds or min..max, got '{}'", s));
+    let (min_ms, max_ms) = match s.split_once("..") {
+        Some((min, max)) => (number(min)?, number(max)?),
+        None => (number(s)?, number(s)?),
+    };
+    if min_ms > max_ms {
+        return Err(format!("range {} is empty: min is greater than max", s));
+    }
+    Ok(Pause { min_ms, max_ms })
+}
+
+fn parse_tag(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.trim().to_string())),
+        _ => Err(format!("expected key=value, got '{}'", s)),
+    }
+}
+
+// Теги прогона: [metadata] из конфига, поверх них --tag из командной строки
+fn run_tags(
+    from_args: &[(String, String)],
+    from_config: Option<&BTreeMap<String, toml::Value>>,
+) -> BTreeMap<String, String> {
+    let mut tags = BTreeMap::new();
+    for (key, value) in from_config.into_iter().flatten() {
+        let value = match value {
+            toml::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        tags.insert(key.clone(), value);
+    }
+    for (key, value) in from_args {
+        tags.insert(key.clone(), value.clone());
+    }
+    tags
+}
+
+fn format_tags(tags: &BTreeMap<String, String>) -> String {
+    tags.iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Нагрузка прогона для истории: "getSlot x4, scenario swap x2"
+fn workload_label(methods: &[MethodConfig], scenarios: &[scenario::ScenarioConfig]) -> String {
+    methods
+        .iter()
+        .map(|m| format!("{} x{}", m.label(), m.workers))
+        .chain(scenarios.iter().map(|s| format!("scenario {} x{}", s.name, s.workers)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Итоговые параметры прогона в формате конфига: сохраняются в --out-dir для повторного запуска
+#[derive(Serialize)]
+struct ResolvedConfig<'a> {
+    url: &'a str,
+    timeout_ms: u64,
+    jitter_ms: u64,
+    stagger_start_ms: u64,
+    duration: u64,
+    http_timeout: u64,
+    connect_timeout: Option<u64>,
+    seed: u64,
+    capture_headers: &'a [String],
+    header_interval: u64,
+    id_start: u64,
+    request_budget: Option<u64>,
+    accept_encoding: Option<compression::AcceptEncoding>,
+    parser: ResponseParser,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expect_cluster: Option<&'a fingerprint::ExpectedCluster>,
+    breaker_error_rate: Option<f64>,
+    breaker_cooldown: Option<u64>,
+    // Ключи попадают в файл замаскированными, как и все секреты
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_keys: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key_header: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key_rotation: Option<keys::KeyRotation>,
+    user_agent: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    emulate_client: Option<identity::ClientPreset>,
+    gzip_requests: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deadline_header: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deadline_ms: Option<u64>,
+    recovery_check: bool,
+    recovery_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outlier_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outliers_file: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    correlation_header: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_rps: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pace_factor: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provider_limits: Option<limits::ProviderPlan>,
+    clock_check: bool,
+    clock_check_block_time: bool,
+    endpoint_ips: bool,
+    hash_responses: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    affinity: Option<String>,
+    affinity_compare: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    geo_lookup: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dns_refresh: Option<u64>,
+    summary_window: u64, // минуты, 0 - без итогов по периодам
+    align_buckets: bool,
+    last_errors: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    redact: Vec<redact::RedactRule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot_downloads: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot_paths: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot_range_mb: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    poll_urls: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    poll_interval: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    poll_metrics: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    custom_methods: &'a [String],
+    credits: Option<&'a CreditConfig>,
+    apdex: Option<apdex::ApdexThresholds>,
+    metadata: &'a BTreeMap<String, String>,
+    methods: &'a [MethodConfig],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    scenarios: &'a [scenario::ScenarioConfig],
+}
+
+fn resolved_config_toml(
+    settings: &WorkerSettings,
+    methods: &[MethodConfig],
+    scenarios: &[scenario::ScenarioConfig],
+    tags: &BTreeMap<String, String>,
+) -> Result<String, toml::ser::Error> {
+    toml::to_string(&ResolvedConfig {
+        url: &settings.url,
+        timeout_ms: settings.timeout_ms,
+        jitter_ms: settings.jitter_ms,
+        stagger_start_ms: settings.stagger_start.as_millis() as u64,
+        duration: settings.duration.as_secs(),
+        http_timeout: settings.http_timeout.as_secs(),
+        connect_timeout: settings.connect_timeout.map(|t| t.as_secs()),
+        seed: settings.seed,
+        capture_headers: &settings.capture_headers,
+        header_interval: settings.header_interval_secs,
+        id_start: settings.ids.start,
+        request_budget: settings.budget.as_ref().map(|budget| budget.limit),
+        accept_encoding: settings.accept_encoding,
+        parser: settings.parser,
+        expect_cluster: settings.expect_cluster.as_ref(),
+        breaker_error_rate: settings.breaker.map(|b| b.error_rate_percent),
+        breaker_cooldown: settings.breaker.map(|b| b.cooldown.as_secs()),
+        api_keys: settings.api_keys.as_ref().map(|pool| pool.keys()),
+        api_key_header: settings.api_keys.as_ref().and_then(|pool| pool.header.as_deref()),
+        api_key_rotation: settings.api_keys.as_ref().map(|pool| pool.rotation),
+        user_agent: &settings.identity.user_agent,
+        emulate_client: settings.identity.preset,
+        gzip_requests: settings.gzip_requests,
+        deadline_header: settings.deadline.as_ref().map(|deadline| deadline.header.as_str()),
+        deadline_ms: settings.deadline.as_ref().map(|deadline| deadline.ms),
+        recovery_check: settings.recovery.is_some(),
+        recovery_secs: settings.recovery.map(|duration| duration.as_secs()),
+        outlier_ms: settings.outliers.as_ref().map(|outliers| outliers.threshold_ms),
+        outliers_file: settings.outliers.as_ref().map(|outliers| outliers.file.as_str()),
+        correlation_header: settings.correlation.as_ref().map(|correlation| correlation.header.as_str()),
+        clock_check: settings.clock_check.is_some(),
+        clock_check_block_time: settings.clock_check.is_some_and(|check| check.block_time),
+        max_rps: settings.max_rps,
+        pace_factor: settings.pace_factor,
+        provider_limits: settings.provider_limits,
+        endpoint_ips: settings.addresses.is_some(),
+        hash_responses: settings.hash_responses,
+        affinity: settings.affinity.as_ref().map(affinity::AffinitySettings::spec),
+        affinity_compare: settings.affinity.as_ref().is_some_and(|affinity| affinity.compare),
+        geo_lookup: settings.addresses.as_ref().and_then(|addresses| addresses.geo_url.as_deref()),
+        dns_refresh: settings.dns_refresh.map(|interval| interval.as_secs()),
+        summary_window: settings.summary_window.map_or(0, |window| window.as_secs() / 60),
+        align_buckets: settings.align_buckets,
+        last_errors: settings.last_errors,
+        redact: settings.redact.rules(),
+        snapshot_downloads: settings.snapshot.as_ref().map(|snapshot| snapshot.workers),
+        snapshot_paths: settings.snapshot.as_ref().map(|snapshot| snapshot.paths.as_slice()),
+        snapshot_range_mb: settings.snapshot.as_ref().map(|snapshot| snapshot.range_mb),
+        poll_urls: settings.side_channel.as_ref().map(|poll| poll.urls.as_slice()),
+        poll_interval: settings.side_channel.as_ref().map(|poll| poll.interval.as_secs()),
+        poll_metrics: settings.side_channel.as_ref().map(|poll| poll.metrics.as_slice()).filter(|metrics| !metrics.is_empty()),
+        custom_methods: &settings.custom_methods,
+        credits: settings.credits.as_deref(),
+        apdex: settings.apdex,
+        metadata: tags,
+        methods,
+        scenarios,
+    })
+}
+
+fn load_config(config_path: &str) -> Result<Config, Box<dyn std::error::Error>> {
+    let mut merged = toml::Value::Table(load_config_table(Path::new(config_path), &mut Vec::new())?);
+    secrets::resolve(&mut merged)?;
+    let config: Config = merged.try_into()?;
+    Ok(config)
+}
+
+// Читает конфиг вместе с include = ["base.toml", ...]: сначала включенные файлы по порядку,
+// затем сам файл поверх них. Пути считаются от каталога включающего файла
+fn load_config_table(path: &Path, chain: &mut Vec<PathBuf>) -> Result<toml::Table, Box<dyn std::error::Error>> {
+    let canonical = fs::canonicalize(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if chain.contains(&canonical) {
+        return Err(format!("Config include cycle: {} includes itself", path.display()).into());
+    }
+    let content = fs::read_to_string(path)?;
+    let mut table: toml::Table = toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let includes = match table.remove("include") {
+        None => Vec::new(),
+        Some(toml::Value::Array(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                toml::Value::String(include) => Ok(include),
+                other => Err(format!("{}: include entries must be paths, got {}", path.display(), other)),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(other) => return Err(format!("{}: include must be an array of paths, got {}", path.display(), other).into()),
+    };
+
+    chain.push(canonical);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut merged = toml::Table::new();
+    for include in includes {
+        merge_config_table(&mut merged, load_config_table(&dir.join(include), chain)?);
+    }
+    chain.pop();
+    merge_config_table(&mut merged, table);
+    Ok(merged)
+}
+
+// Вложенные таблицы ([credits], [apdex], [metadata]) сливаются по ключам,
+// остальные значения, в том числе [[methods]] и [[scenarios]], заменяются целиком
+fn merge_config_table(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_config_table(base_table, overlay_table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn extract_host_from_url(url: &str) -> Option<String> {
+    // Простой парсинг URL для извлечения хоста
+    if let Some(start) = url.find("://") {
+        let after_protocol = &url[start + 3..];
+        let host_port = if let Some(end) = after_protocol.find('/') {
+            &after_protocol[..end]
+        } else if let Some(end) = after_protocol.find('?') {
+            &after_protocol[..end]
+        } else {
+            after_protocol
+        };
+        // Извлекаем хост (без порта)
+        Some(host_port.split(':').next().unwrap_or(host_port).to_string())
+    } else {
+        None
+    }
+}
+
+fn ping_host(host: &str, count: usize) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+    let output = Command::new("ping")
+        .arg("-c")
+        .arg(count.to_string())
+        .arg(host)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!("Ping failed: {}", String::from_utf8_lossy(&output.stderr)).into());
+    }
+
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    let mut latencies = Vec::new();
+
+    // Парсим вывод ping (формат: "64 bytes from ... time=12.345 ms" или "time=12.345ms")
+    for line in output_str.lines() {
+        // Ищем паттерн time=XXX ms или time=XXXms
+        if let Some(time_pos) = line.find("time=") {
+            let after_time = &line[time_pos + 5..];
+            // Пробуем найти " ms" или "ms"
+            let latency_str = if let Some(ms_pos) = after_time.find(" ms") {
+                &after_time[..ms_pos]
+            } else if let Some(ms_pos) = after_time.find("ms") {
+                &after_time[..ms_pos]
+            } else {
+                continue;
+            };
+            
+            if let Ok(latency) = latency_str.trim().parse::<f64>() {
+                latencies.push(latency);
+            }
+        }
+    }
+
+    Ok(latencies)
+}
+
+fn perform_ping_test(url: &str) {
+    println!("\n=== Preliminary Ping Test (10 packets) ===");
+    
+    let host = match extract_host_from_url(url) {
+        Some(h) => h,
+        None => {
+            println!("Failed to extract host from URL: {}", url);
+            return;
+        }
+    };
+
+    println!("Pinging host: {}", host);
+    
+    match ping_host(&host, 10) {
+        Ok(latencies) => {
+            if latencies.is_empty() {
+                println!("Failed to get ping results");
+                return;
+            }
+
+            let avg = latencies.iter().sum::<f64>() / latencies.len() as f64;
+            let min = latencies.iter().fold(f64::INFINITY, |a, &b| a.min(b));
+            let max = latencies.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+
+            println!("Ping results:");
+            println!("  Packets sent: 10");
+            println!("  Responses received: {}", latencies.len());
+            println!("  Minimum latency: {:.2} ms", min);
+            println!("  Maximum latency: {:.2} ms", max);
+            println!("  Average latency: {:.2} ms", avg);
+            
+            if latencies.len() < 10 {
+                println!("  Warning: {} packets lost", 10 - latencies.len());
+            }
+        }
+        Err(e) => {
+            println!("Error executing ping: {}", e);
+            println!("Make sure 'ping' command is available in the system");
+        }
+    }
+    println!();
+}
+
+// Параметры TLS отдельного соединения: версия, шифр, ALPN и сертификат
+fn print_tls_info(url: &str, timeout: Duration) {
+    println!("=== TLS ===");
+    match tls::probe(url, timeout) {
+        Ok(None) => println!("Endpoint uses plain HTTP, no TLS"),
+        Ok(Some(info)) => {
+            println!("  Version: {}", info.version);
+            println!("  Cipher: {}", info.cipher);
+            // Сам тест идет через HTTP/1.1 клиент, ALPN показывает, что предлагает сервер
+            println!("  ALPN: {}", info.alpn.as_deref().unwrap_or("not negotiated (http/1.1)"));
+            println!("  Certificate subject: {}", info.subject);
+            println!("  Certificate issuer: {}", info.issuer);
+            match info.days_left {
+                Some(days) if days < 0 => {
+                    println!("  Certificate expires: {} (EXPIRED {} days ago)", info.not_after, -days)
+                }
+                Some(days) => println!("  Certificate expires: {} ({} days left)", info.not_after, days),
+                None => println!("  Certificate expires: {}", info.not_after),
+            }
+            if !info.verified {
+                println!("  Warning: certificate chain is not trusted");
+            }
+        }
+        Err(e) => println!("TLS probe failed: {}", e),
+    }
+    println!();
+}
+
+// Один запланированный запрос для --dry-run
+struct PlannedRequest {
+    offset_ms: u64,
+    worker_id: usize,
+    request_id: u64,
+    scenario: Option<String>,
+    method: String,
+    params: Vec<serde_json::Value>,
+    batch_size: usize,
+}
+
+// Разворачивает методы и сценарии в последовательность запросов так, как их отправят воркеры
+// (без учета времени ответа: только таймауты, джиттер и think time с тем же seed)
+fn build_request_plan(
+    methods: &[MethodConfig],
+    scenarios: &[scenario::ScenarioConfig],
+    feeders: &[Option<Arc<feeder::Feeder>>],
+    settings: &WorkerSettings,
+    count: usize,
+) -> Vec<PlannedRequest> {
+    let mut plan = Vec::new();
+    let mut worker_id = 0;
+    for method_config in methods {
+        let params = method_config.params.clone().unwrap_or_default();
+        let batch_size = method_config.batch.unwrap_or(1).max(1);
+        let phase = method_config.phase();
+        let end_ms = match (settings.duration.is_zero(), phase.end) {
+            (true, end) => end.map(|end| end.as_millis() as u64),
+            (false, end) => Some(end.unwrap_or(settings.duration).min(settings.duration).as_millis() as u64),
+        };
+        for _ in 0..method_config.workers {
+            let mut rng = Rng::new(settings.seed.wrapping_add(worker_id as u64));
+            // Порядковый номер запроса воркера; настоящие ID раздаются после сортировки
+            let mut request_id = 0;
+            let mut offset_ms =
+                (settings.stagger_start.as_millis() as u64 * worker_id as u64).max(phase.start.as_millis() as u64);
+            for _ in 0..count {
+                if end_ms.is_some_and(|end_ms| offset_ms >= end_ms) {
+                    break;
+                }
+                request_id += 1;
+                if method_config.method == "getLatestBlock" {
+                    plan.push(PlannedRequest {
+                        offset_ms,
+                        worker_id,
+                        request_id,
+                        scenario: None,
+                        method: "getSlot".to_string(),
+                        params: Vec::new(),
+                        batch_size: 1,
+                    });
+                    request_id += 1;
+                    let mut block_params = latest_block_params(0, &params);
+                    block_params[0] = serde_json::Value::String("<latest slot>".to_string());
+                    plan.push(PlannedRequest {
+                        offset_ms,
+                        worker_id,
+                        request_id,
+                        scenario: None,
+                        method: "getBlock".to_string(),
+                        params: block_params,
+                        batch_size: 1,
+                    });
+                } else {
+                    plan.push(PlannedRequest {
+                        offset_ms,
+                        worker_id,
+                        request_id,
+                        scenario: None,
+                        method: method_config.label(),
+                        params: params.clone(),
+                        batch_size,
+                    });
+                }
+                offset_ms += settings.timeout_ms + rng.up_to(settings.jitter_ms);
+            }
+            worker_id += 1;
+        }
+    }
+    // Воркеры сценариев нумеруются после воркеров методов, как при запуске
+    for (scenario_config, feeder) in scenarios.iter().zip(feeders) {
+        for _ in 0..scenario_config.workers {
+            let mut rng = Rng::new(settings.seed.wrapping_add(worker_id as u64));
+            let keys = settings.api_keys.as_ref().map_or(1, |pool| pool.worker_keys(settings.seed, worker_id).len());
+            let mut request_id = 0;
+            let mut offset_ms = settings.stagger_start.as_millis() as u64 * worker_id as u64;
+            let end_ms = (!settings.duration.is_zero()).then_some(settings.duration.as_millis() as u64);
+            // Итерация, начатая до конца теста, доходит до конца, как у воркера
+            while request_id < count && end_ms.is_none_or(|end_ms| offset_ms < end_ms) {
+                let Some(steps) = scenario::plan_iteration(scenario_config, feeder.as_deref(), keys, &mut rng) else {
+                    break;
+                };
+                if steps.is_empty() {
+                    break;
+                }
+                for step in steps {
+                    request_id += 1;
+                    plan.push(PlannedRequest {
+                        offset_ms,
+                        worker_id,
+                        request_id: request_id as u64,
+                        scenario: Some(scenario_config.name.clone()),
+                        method: step.method,
+                        params: step.params,
+                        batch_size: 1,
+                    });
+                    offset_ms += step.think_time.as_millis() as u64;
+                }
+                offset_ms += settings.timeout_ms + rng.up_to(settings.jitter_ms);
+            }
+            worker_id += 1;
+        }
+    }
+    plan.sort_by_key(|r| (r.offset_ms, r.worker_id, r.request_id));
+    plan.truncate(count);
+
+    // ID из общего генератора в порядке отправки, как у настоящих воркеров
+    let ids = RequestIds::new(settings.ids.start);
+    for request in &mut plan {
+        request.request_id = ids.next_block(request.batch_size as u64);
+    }
+    plan
+}
+
+fn print_request_plan(
+    methods: &[MethodConfig],
+    scenarios: &[scenario::ScenarioConfig],
+    feeders: &[Option<Arc<feeder::Feeder>>],
+    settings: &WorkerSettings,
+    count: usize,
+) {
+    let plan = build_request_plan(methods, scenarios, feeders, settings, count);
+    println!("\n=== Dry Run: first {} requests ===", plan.len());
+    println!("Endpoint: {}", secrets::redact(&settings.url));
+    for request in &plan {
+        let ids = if request.batch_size > 1 {
+            format!("{}..{}", request.request_id, request.request_id + request.batch_size as u64 - 1)
+        } else {
+            request.request_id.to_string()
+        };
+        let scenario = request.scenario.as_ref().map(|name| format!("scenario '{}' ", name)).unwrap_or_default();
+        println!(
+            "  [+{} ms] worker {} id={} {}{} {}",
+            request.offset_ms,
+            request.worker_id,
+            ids,
+            scenario,
+            request.method,
+            serde_json::to_string(&request.params).unwrap_or_default()
+        );
+    }
+    println!("\nDry run: no requests were sent");
+}
+
+fn print_settings(
+    args: &Args,
+    settings: &WorkerSettings,
+    methods: &[MethodConfig],
+    scenarios: &[scenario::ScenarioConfig],
+    tags: &BTreeMap<String, String>,
+) {
+    match &args.config {
+        Some(config_path) => println!("=== Stress Test Settings (from config: {}) ===", config_path),
+        None => println!("=== Stress Test Settings ==="),
+    }
+    println!("URL: {}", secrets::redact(&settings.url));
+    if let Some(profile) = args.profile {
+        println!("Profile: {}", profile.name());
+    } else if args.config.is_none() {
+        println!("Method: {}", args.method);
+        // Воркеры и батч - после урезания до лимитов тарифа
+        let first = methods.first();
+        println!("Workers: {}", first.map_or(args.workers, |m| m.workers));
+        if let Some(params) = first.and_then(|m| m.params.as_ref()) {
+            println!("Params: {}", serde_json::to_string(params).unwrap_or_default());
+        }
+        let batch_size = first.and_then(|m| m.batch).unwrap_or(args.batch_size);
+        if batch_size > 1 {
+            println!("Batch size: {}", batch_size);
+        }
+    }
+    if settings.jitter_ms > 0 {
+        println!("Request timeout: {}..{} ms", settings.timeout_ms, settings.timeout_ms + settings.jitter_ms);
+    } else {
+        println!("Request timeout: {} ms", settings.timeout_ms);
+    }
+    println!("Jitter: {} ms", settings.jitter_ms);
+    if !settings.stagger_start.is_zero() {
+        println!("Stagger start: {} ms between workers", settings.stagger_start.as_millis());
+    }
+    println!("HTTP timeout: {} sec", settings.http_timeout.as_secs());
+    if let Some(connect_timeout) = settings.connect_timeout {
+        println!("Connect timeout: {} sec", connect_timeout.as_secs());
+    }
+    println!("Duration: {} sec", settings.duration.as_secs());
+    if !settings.duration.is_zero() && cfg!(unix) {
+        println!("Extend: kill -USR2 {} adds {} min", std::process::id(), args.extend_minutes);
+    }
+    if let Some(path) = &args.control_socket {
+        println!("Control socket: {}", path);
+    }
+    println!("Seed: {}", settings.seed);
+    println!("Request IDs: sequential from {} across all workers", settings.ids.start);
+    if let Some(budget) = &settings.budget {
+        let unit = if settings.credits.is_some() { "credits" } else { "requests" };
+        println!("Request budget: {} {}", budget.limit, unit);
+    }
+    if settings.no_parse {
+        println!("Response parsing: disabled (--no-parse), success = HTTP 2xx");
+    } else if settings.parser == ResponseParser::Fast {
+        println!("Response parsing: fast (id and error only)");
+    }
+    if let Some(encoding) = settings.accept_encoding {
+        println!("Accept-Encoding: {}", encoding.header_value().unwrap_or("(not sent)"));
+    }
+    if settings.gzip_requests {
+        println!("Request bodies: gzip (Content-Encoding: gzip)");
+    }
+    if let Some(deadline) = &settings.deadline {
+        println!("Deadline header: {}: {}", deadline.header, deadline.ms);
+    }
+    if let Some(recovery) = settings.recovery {
+        println!(
+            "Recovery check: {} sec baseline before, {} sec probes after the load",
+            recovery::BASELINE_SECS,
+            recovery.as_secs()
+        );
+    }
+    if let Some(outliers) = &settings.outliers {
+        println!("Latency outliers: requests over {} ms are written to {}", outliers.threshold_ms, outliers.file);
+    }
+    if let Some(correlation) = &settings.correlation {
+        println!("Correlation IDs: {}: {}-<worker>-<n>", correlation.header, correlation.run);
+    }
+    if let Some(max_rps) = settings.max_rps {
+        match settings.provider_limits {
+            Some(plan) => println!("Rate limit: {} req/s (provider limits: {})", max_rps, plan.name()),
+            None => println!("Rate limit: {} req/s", max_rps),
+        }
+    }
+    if let Some(factor) = settings.pace_factor {
+        println!("Adaptive pacing: pause grows by {} × recent response time", factor);
+    }
+    if let Some(addresses) = &settings.addresses {
+        match &addresses.geo_url {
+            Some(geo_url) => println!("Endpoint IPs: resolved before the test, per-IP responses in the report (geo: {})", geo_url),
+            None => println!("Endpoint IPs: resolved before the test, per-IP responses in the report"),
+        }
+    }
+    if let Some(interval) = settings.dns_refresh {
+        println!("DNS refresh: endpoint hostname re-resolved every {} s, connections dropped when the answer changes", interval.as_secs());
+    }
+    if settings.hash_responses {
+        println!("Response hashing: distinct bodies per identical request in the report");
+    }
+    if let Some(affinity) = &settings.affinity {
+        match affinity.compare {
+            true => println!("Session affinity: {} on even workers, odd workers without it", affinity.spec()),
+            false => println!("Session affinity: {} replayed by every worker", affinity.spec()),
+        }
+    }
+    if let Some(window) = settings.summary_window {
+        println!("Summary by period: every {} min", window.as_secs() / 60);
+    }
+    if settings.align_buckets {
+        println!("Buckets: aligned to UTC wall-clock boundaries");
+    }
+    if settings.last_errors > 0 {
+        println!("Last errors: up to {} shown after the summary", settings.last_errors);
+    }
+    let redact_rules: Vec<&str> = settings.redact.rules().into_iter().map(redact::RedactRule::name).collect();
+    if !redact_rules.is_empty() {
+        println!("Redaction: {} (outliers file, debug output, last errors)", redact_rules.join(", "));
+    }
+    if !settings.custom_methods.is_empty() {
+        println!("Custom methods: {} (not checked against the catalog)", settings.custom_methods.join(", "));
+    }
+    if let Some(snapshot) = &settings.snapshot {
+        println!(
+            "Snapshot downloads: {} parallel {} MB range requests of {}",
+            snapshot.workers,
+            snapshot.range_mb,
+            snapshot.paths.join(", ")
+        );
+    }
+    if let Some(poll) = &settings.side_channel {
+        let metrics = if poll.metrics.is_empty() { String::new() } else { format!(", metrics {}", poll.metrics.join(", ")) };
+        println!("Side-channel polling: {} every {}s{}", poll.urls.join(", "), poll.interval.as_secs(), metrics);
+    }
+    if let Some(check) = settings.clock_check {
+        let block_time = if check.block_time { " and getBlockTime of the latest confirmed slot" } else { "" };
+        println!("Clock check: HTTP Date{} before the test", block_time);
+    }
+    if let Some(cluster) = &settings.expect_cluster {
+        println!("Expected cluster: {}", cluster);
+    }
+    if let Some(breaker) = &settings.breaker {
+        println!(
+            "Circuit breaker: pause {} sec when {} sec error rate >= {:.1}%",
+            breaker.cooldown.as_secs(),
+            aggregator::ROLLING_WINDOW_SECS,
+            breaker.error_rate_percent
+        );
+    }
+    if let Some(pool) = &settings.api_keys {
+        let sent_in = match &pool.header {
+            Some(header) => format!("header {}", header),
+            None => "URL".to_string(),
+        };
+        println!("API keys: {} ({}, in {})", pool.len(), pool.rotation.name(), sent_in);
+    }
+    println!("User-Agent: {}", settings.identity.user_agent);
+    if let Some(preset) = settings.identity.preset {
+        println!("Emulated client: {}", preset.name());
+    }
+    if !tags.is_empty() {
+        println!("Tags: {}", format_tags(tags));
+    }
+    match &settings.debug {
+        None => println!("Debug mode: disabled"),
+        Some(filter) if filter.sample >= 1.0 && filter.methods.is_empty() => println!("Debug mode: enabled"),
+        Some(filter) => {
+            let methods = if filter.methods.is_empty() { "all methods".to_string() } else { filter.methods.join(", ") };
+            println!("Debug mode: {:.4}% of requests, {}", filter.sample * 100.0, methods);
+        }
+    }
+    if args.auto_tune {
+        println!(
+            "Auto-tune: target p95 {} ms, every {} sec, up to {} workers per method",
+            args.target_p95_ms, args.tune_interval, args.max_workers
+        );
+    }
+    if args.config.is_some() || args.profile.is_some() {
+        match args.profile {
+            Some(profile) => println!("\nMethods from profile {}:", profile.name()),
+            None => println!("\nMethods from config:"),
+        }
+        for method_config in methods {
+            let mut details = vec![format!("workers: {}", method_config.workers)];
+            if method_config.alias.is_some() {
+                details.insert(0, format!("method: {}", method_config.method));
+            }
+            if let Some(batch) = method_config.batch.filter(|batch| *batch > 1) {
+                details.push(format!("batch: {}", batch));
+            }
+            details.extend(method_config.describe_phase());
+            println!("  - {} ({})", method_config.label(), details.join(", "));
+        }
+        for scenario in scenarios {
+            let steps: Vec<&str> = scenario.steps.iter().map(|step| step.method.as_str()).collect();
+            println!(
+                "  - scenario {} (workers: {}): {}",
+                scenario.name,
+                scenario.workers,
+                steps.join(" -> ")
+            );
+        }
+    }
+}
+
+fn print_clock_check(clock: &clock::ClockSummary) {
+    match (clock.date_offset_ms, clock.date_uncertainty_ms) {
+        (Some(offset), Some(uncertainty)) => println!(
+            "HTTP Date: endpoint clock {:+.0} ms (± {:.0} ms) relative to local",
+            offset, uncertainty
+        ),
+        _ => println!("HTTP Date: no Date header in responses"),
+    }
+    if let Some(slot) = clock.block_slot {
+        match clock.block_time_offset_secs {
+            Some(offset) => println!("Block time of slot {}: {:+} s relative to local", slot, offset),
+            None => println!("Block time of slot {}: unavailable", slot),
+        }
+    }
+    if clock.date_skewed() || clock.block_time_skewed() {
+        println!("Warning: the local clock differs from the endpoint; client and server timestamps will not line up");
+    }
+}
+
+fn dns_refresh(secs: Option<u64>) -> Result<Option<Duration>, String> {
+    match secs {
+        Some(0) => Err("--dns-refresh must be at least 1 second".to_string()),
+        secs => Ok(secs.map(Duration::from_secs)),
+    }
+}
+
+// Период итогов: явный (0 - отключить) или 5 минут для тестов от 20 минут и бесконечных,
+// в агрегате за которые теряются все изменения по ходу прогона
+fn summary_window(minutes: Option<u64>, duration: Duration) -> Option<Duration> {
+    const DEFAULT_MINUTES: u64 = 5;
+    const LONG_TEST: Duration = Duration::from_secs(20 * 60);
+    let minutes = minutes.unwrap_or(if duration.is_zero() || duration >= LONG_TEST { DEFAULT_MINUTES } else { 0 });
+    (minutes > 0).then(|| Duration::from_secs(minutes * 60))
+}
+
+fn print_endpoint_addresses(endpoint: &region::ResolvedEndpoint) {
+    if let Some(error) = &endpoint.error {
+        println!("\nEndpoint IPs: failed to resolve {}: {}", endpoint.host, error);
+        return;
+    }
+    println!("\nEndpoint IPs of {}:", endpoint.host);
+    for ip in &endpoint.ips {
+        match endpoint.geo.get(ip) {
+            Some(geo) => println!("  {}  {}", ip, geo.describe()),
+            None => println!("  {}", ip),
+        }
+    }
+}
+
+fn print_discovered_nodes(seed_url: &str, nodes: &[discovery::RpcNode]) {
+    println!("=== RPC nodes discovered via {} ===", secrets::redact(seed_url));
+    for node in nodes {
+        println!(
+            "  {}  {}  {}",
+            node.url,
+            node.version.as_deref().unwrap_or("-"),
+            node.pubkey
+        );
+    }
+    println!("\nTotal: {} nodes", nodes.len());
+}
+
+fn print_method_catalog() {
+    println!("=== Solana RPC methods ===");
+    for spec in catalog::METHODS {
+        match catalog::default_params(spec.name) {
+            Some(params) => println!(
+                "  {}\n      default params: {}",
+                catalog::signature(spec),
+                serde_json::to_string(&params).unwrap_or_default()
+            ),
+            None => println!("  {}", catalog::signature(spec)),
+        }
+    }
+    println!("\n{} is replaced with the current slot (getSlot) right before the test starts", catalog::LATEST_SLOT);
+    println!(
+        "{} is replaced with the oldest block the node stores (getFirstAvailableBlock)",
+        catalog::FIRST_AVAILABLE_BLOCK
+    );
+}
+
+// Методам без params в конфиге/CLI подставляем params по умолчанию из каталога
+fn apply_default_params(methods: &mut [MethodConfig]) {
+    for method_config in methods.iter_mut().filter(|m| m.params.is_none()) {
+        method_config.params = catalog::default_params(&method_config.method);
+    }
+}
+
+// Заменяет заглушки слотов в params на актуальные значения; запрос делается только если заглушка есть
+async fn resolve_slot_placeholders(
+    methods: &mut [MethodConfig],
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<(), String> {
+    let placeholders = [
+        (catalog::LATEST_SLOT, "getSlot", "the current slot"),
+        (catalog::FIRST_AVAILABLE_BLOCK, "getFirstAvailableBlock", "the first available block"),
+    ];
+    for (placeholder, method, what) in placeholders {
+        let used = methods
+            .iter()
+            .any(|m| m.params.as_deref().is_some_and(|params| catalog::uses_placeholder(params, placeholder)));
+        if !used {
+            continue;
+        }
+
+        let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+        let slot = send_rpc_request(client, url, method, vec![], 0, &mut capture)
+            .await
+            .ok()
+            .and_then(|response| response.result)
+            .and_then(|result| result.as_u64())
+            .ok_or_else(|| format!("Failed to get {} for default params ({})", what, method))?;
+        for params in methods.iter_mut().filter_map(|m| m.params.as_mut()) {
+            catalog::fill_placeholder(params, placeholder, slot);
+        }
+    }
+    Ok(())
+}
+
+// Фазы методов: end_offset и duration взаимоисключающие, фаза не пустая и начинается до конца теста
+fn validate_phases(methods: &[MethodConfig], test_duration: Duration) -> Result<(), String> {
+    for method in methods {
+        if method.end_offset.is_some() && method.duration.is_some() {
+            return Err(format!("{}: set either end_offset or duration, not both", method.method));
+        }
+        let phase = method.phase();
+        if phase.end.is_some_and(|end| end <= phase.start) {
+            return Err(format!("{}: the phase ends before it starts", method.method));
+        }
+        if !test_duration.is_zero() && phase.start >= test_duration {
+            return Err(format!(
+                "{}: start_offset {} sec is past the end of the test ({} sec)",
+                method.method,
+                phase.start.as_secs(),
+                test_duration.as_secs()
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Проверяет все методы по каталогу и возвращает сводную ошибку со всеми проблемами.
+// В шагах сценариев проверяются только имена: params могут содержать переменные.
+// Объявленные нестандартные методы (custom_methods) не проверяются: их params произвольны
+fn validate_methods(
+    methods: &[MethodConfig],
+    scenarios: &[scenario::ScenarioConfig],
+    custom_methods: &[String],
+) -> Result<(), String> {
+    let is_custom = |method: &str| custom_methods.iter().any(|custom| custom == method);
+    let step_methods = scenarios.iter().flat_map(|scenario| &scenario.steps).filter(|step| !is_custom(&step.method));
+    let problems: Vec<String> = methods
+        .iter()
+        .filter(|m| m.method != httpget::METHOD && !is_custom(&m.method))
+        .filter_map(|m| catalog::validate(&m.method, m.params.as_deref().unwrap_or_default()).err())
+        .chain(step_methods.filter_map(|step| catalog::lookup(&step.method).err()))
+        .collect();
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "Invalid method configuration: {} (declare non-standard methods in custom_methods or with --custom-method)",
+        problems.join("; ")
+    ))
+}
+
+// Настройки воркеров из флагов командной строки (без --config)
+fn cli_settings(args: &Args) -> Result<WorkerSettings, String> {
+    Ok(WorkerSettings {
+        url: args.url.clone(),
+        timeout_ms: args.timeout_ms.min_ms,
+        jitter_ms: args.jitter_ms + args.timeout_ms.spread(),
+        stagger_start: Duration::from_millis(args.stagger_start_ms),
+        http_timeout: Duration::from_secs(args.http_timeout),
+        connect_timeout: args.connect_timeout.map(Duration::from_secs),
+        duration: Duration::from_secs(args.duration),
+        debug: debug_filter(args.debug, args.debug_sample, &args.debug_method)?,
+        seed: args.seed.unwrap_or_else(random_seed),
+        capture_headers: header_names(&args.capture_headers, None),
+        header_interval_secs: args.header_interval,
+        credits: None,
+        apdex: apdex_thresholds(args),
+        ids: RequestIds::new(args.id_start),
+        budget: args.request_budget.map(RequestBudget::new),
+        accept_encoding: args.accept_encoding,
+        no_parse: args.no_parse,
+        parser: args.parser,
+        respawn: args.respawn_workers,
+        expect_cluster: args.expect_cluster.clone(),
+        breaker: breaker_settings(args.breaker_error_rate, args.breaker_cooldown),
+        api_keys: api_key_pool(
+            args.api_keys.clone(),
+            args.api_keys_file.as_deref().map(Path::new),
+            args.api_key_header.clone(),
+            args.api_key_rotation,
+            &args.url,
+        )?,
+        identity: identity::ClientIdentity::new(args.user_agent.clone(), args.emulate_client)?,
+        gzip_requests: args.gzip_requests,
+        deadline: deadline(args.deadline_header.clone(), args.deadline_ms, Duration::from_secs(args.http_timeout))?,
+        recovery: args.recovery_check.then(|| Duration::from_secs(args.recovery_secs)),
+        outliers: outlier_settings(args.outlier_ms, args.outliers_file.clone()),
+        correlation: correlation(args.correlation_header.clone())?,
+        clock_check: clock::ClockCheck::new(args.clock_check, args.clock_check_block_time),
+        max_rps: max_rps(args.max_rps, args.provider_limits),
+        pace_factor: args.pace_factor,
+        provider_limits: args.provider_limits,
+        addresses: region::AddressCheck::new(args.endpoint_ips, args.geo_lookup.clone()),
+        dns_refresh: dns_refresh(args.dns_refresh)?,
+        hash_responses: args.hash_responses,
+        affinity: affinity::AffinitySettings::new(args.affinity.clone(), args.affinity_compare)?,
+        summary_window: summary_window(args.summary_window, Duration::from_secs(args.duration)),
+        align_buckets: args.align_buckets,
+        last_errors: args.last_errors,
+        redact: redact::Redaction::new(&args.redact),
+        snapshot: snapshot::SnapshotSettings::new(args.snapshot_downloads, args.snapshot_paths.clone(), args.snapshot_range_mb)?,
+        side_channel: sidechannel::PollSettings::new(args.poll_urls.clone(), args.poll_interval, args.poll_metrics.clone())?,
+        custom_methods: args.custom_methods.clone(),
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = Args::parse();
+
+    // План - конфиг с секцией [plan]: из нее же берутся этапы sweep
+    if let Some(path) = args.run_plan.clone() {
+        let header = plan::load(Path::new(&path))?;
+        if header.tool_version != env!("CARGO_PKG_VERSION") {
+            eprintln!(
+                "Warning: the plan was exported by version {}, this is {}; results may not be comparable",
+                header.tool_version,
+                env!("CARGO_PKG_VERSION")
+            );
+        }
+        let step_duration = header.step_duration.unwrap_or(20);
+        match header.stages {
+            Some(plan::Stages::Sweep) => args.command = Some(CliCommand::Sweep { steps: header.steps, step_duration }),
+            Some(plan::Stages::ParamSweep) => args.command = Some(CliCommand::ParamSweep { step_duration }),
+            None if matches!(args.command, Some(CliCommand::Sweep { .. } | CliCommand::ParamSweep { .. })) => {
+                return Err("--run-plan: the plan was exported from a single run, not from a sweep".into());
+            }
+            None => {}
+        }
+        args.config = Some(path);
+    }
+
+    match &args.command {
+        Some(CliCommand::Methods { action: MethodsAction::List }) => {
+            print_method_catalog();
+            return Ok(());
+        }
+        Some(CliCommand::Survey { urls, probes, concurrency, http_timeout }) => {
+            let results = survey::run(urls.clone(), *probes, *concurrency, Duration::from_secs(*http_timeout)).await;
+            survey::print_table(&results);
+            return Ok(());
+        }
+        Some(CliCommand::ColdWarm { url, method, count, slot_range, params_file, concurrency, http_timeout, seed }) => {
+            let result = coldwarm::run(coldwarm::ColdWarmOptions {
+                url: url.clone(),
+                method: method.clone(),
+                count: *count,
+                concurrency: *concurrency,
+                http_timeout: Duration::from_secs(*http_timeout),
+                params_file: params_file.clone(),
+                slot_range: *slot_range,
+                seed: seed.unwrap_or_else(random_seed),
+            })
+            .await?;
+            coldwarm::print_result(&result);
+            return Ok(());
+        }
+        Some(CliCommand::Depth { url, depths, samples, window, blocks_only, concurrency, http_timeout, seed }) => {
+            let mut depths = if depths.is_empty() { depth::DEFAULT_DEPTHS.to_vec() } else { depths.clone() };
+            depths.sort_unstable();
+            depths.dedup();
+            let result = depth::run(depth::DepthOptions {
+                url: url.clone(),
+                depths,
+                samples: *samples,
+                window: *window,
+                transactions: !blocks_only,
+                concurrency: *concurrency,
+                http_timeout: Duration::from_secs(*http_timeout),
+                seed: seed.unwrap_or_else(random_seed),
+            })
+            .await?;
+            depth::print_result(url, &result, !blocks_only);
+            return Ok(());
+        }
+        Some(CliCommand::Chunks { url, accounts_file, chunks, duration, concurrency, encoding, http_timeout }) => {
+            let mut chunks = if chunks.is_empty() { chunks::DEFAULT_CHUNKS.to_vec() } else { chunks.clone() };
+            chunks.sort_unstable();
+            chunks.dedup();
+            if chunks.first() == Some(&0) {
+                return Err("--chunk must be at least 1".into());
+            }
+            let accounts = chunks::load_accounts(Path::new(accounts_file))?;
+            let account_count = accounts.len();
+            let results = chunks::run(chunks::ChunkOptions {
+                url: url.clone(),
+                accounts,
+                chunks,
+                duration: Duration::from_secs((*duration).max(1)),
+                concurrency: *concurrency,
+                encoding: encoding.clone(),
+                http_timeout: Duration::from_secs(*http_timeout),
+            })
+            .await?;
+            chunks::print_result(url, encoding, account_count, &results);
+            return Ok(());
+        }
+        Some(CliCommand::Bundles {
+            url,
+            bundles_file,
+            encoding,
+            duration,
+            rate,
+            tip_rate,
+            concurrency,
+            http_timeout,
+            auth_uuid,
+            status_check,
+            status_timeout,
+        }) => {
+            let bundles = match bundles_file {
+                Some(path) => jito::load_bundles(Path::new(path))?,
+                None => Vec::new(),
+            };
+            let report = jito::run(jito::BundleOptions {
+                url: url.clone(),
+                bundles,
+                encoding: *encoding,
+                duration: Duration::from_secs((*duration).max(1)),
+                bundle_rate: *rate,
+                tip_rate: *tip_rate,
+                concurrency: *concurrency,
+                http_timeout: Duration::from_secs(*http_timeout),
+                auth: auth_uuid.clone(),
+                status_timeout: status_check.then(|| Duration::from_secs(*status_timeout)),
+            })
+            .await?;
+            jito::print_report(&report);
+            return Ok(());
+        }
+        Some(CliCommand::History { db, endpoint, limit, compare }) => {
+            match compare.as_deref() {
+                Some(&[before, after]) => history::print_comparison(&history::get(db, before)?, &history::get(db, after)?),
+                _ => history::print_runs(&history::list(db, endpoint.as_deref(), *limit)?),
+            }
+            return Ok(());
+        }
+        Some(CliCommand::Trend { db, url, metric, limit, baseline }) => {
+            let report = trend::analyze(trend::load(db, url, *limit)?, *metric, *baseline)?;
+            trend::print_report(&report);
+            if report.regressed() {
+                std::process::exit(1);
             }
+            return Ok(());
         }
-        Err(_) => {}
+        Some(CliCommand::Fuzz { url, cases, count, rate, concurrency, http_timeout, seed }) => {
+            let mut cases = if cases.is_empty() { fuzz::FuzzCase::ALL.to_vec() } else { cases.clone() };
+            cases.sort_unstable();
+            cases.dedup();
+            let report = fuzz::run(fuzz::FuzzOptions {
+                url: url.clone(),
+                cases,
+                count: *count,
+                rate: *rate,
+                concurrency: *concurrency,
+                http_timeout: Duration::from_secs(*http_timeout),
+                seed: seed.unwrap_or_else(random_seed),
+            })
+            .await?;
+            fuzz::print_report(&report);
+            if report.failed() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(CliCommand::Limits { url, probes, max_body_kb, max_batch, max_accounts, http_timeout, pause_ms }) => {
+            let mut probes = if probes.is_empty() { probe::Probe::ALL.to_vec() } else { probes.clone() };
+            probes.sort_unstable();
+            probes.dedup();
+            let results = probe::run(probe::LimitsOptions {
+                url: url.clone(),
+                probes,
+                max_body_kb: *max_body_kb,
+                max_batch: *max_batch,
+                max_accounts: *max_accounts,
+                http_timeout: Duration::from_secs(*http_timeout),
+                pause: Duration::from_millis(*pause_ms),
+            })
+            .await?;
+            probe::print_results(url, &results);
+            return Ok(());
+        }
+        Some(CliCommand::Init { output, force, methods, profile, workers, url, duration }) => {
+            init::run(init::InitOptions {
+                output: output.clone(),
+                force: *force,
+                methods: methods.clone(),
+                profile: *profile,
+                workers: *workers,
+                url: url.clone(),
+                duration: *duration,
+            })?;
+            return Ok(());
+        }
+        // Нагрузка для sweep собирается из основных флагов или конфига, как для обычного прогона
+        Some(CliCommand::Sweep { .. } | CliCommand::ParamSweep { .. }) | None => {}
     }
-    None
-}
 
-async fn worker(
-    worker_id: usize,
-    url: String,
-    method: String,
-    params: Vec<serde_json::Value>,
-    timeout_ms: u64,
-    http_timeout: Duration,
-    stats: Stats,
-    duration: Duration,
-    debug: bool,
-) {
-    let client = reqwest::Client::builder()
-        .timeout(http_timeout)
-        .build()
-        .expect("Failed to create HTTP client");
+    // Собираем настройки, список методов и теги из конфига или из командной строки
+    let (mut settings, mut methods, mut scenarios, tags, param_sweep) = if let Some(config_path) = &args.config {
+        if !Path::new(config_path).exists() {
+            return Err(format!("Configuration file not found: {}", config_path).into());
+        }
 
-    let start_time = Instant::now();
-    let mut request_id = worker_id as u64 * 1_000_000; // Уникальные ID для каждого воркера
+        let config = load_config(config_path)?;
+        let url = config.url.as_ref().unwrap_or(&args.url).clone();
+        // Файл ключей из конфига ищется от каталога конфига, как и файлы фидеров
+        let api_keys_file = match &config.api_keys_file {
+            Some(file) => Some(Path::new(config_path).parent().unwrap_or(Path::new(".")).join(file)),
+            None => args.api_keys_file.as_ref().map(PathBuf::from),
+        };
+        let api_keys = api_key_pool(
+            config.api_keys.clone().unwrap_or_else(|| args.api_keys.clone()),
+            api_keys_file.as_deref(),
+            config.api_key_header.clone().or_else(|| args.api_key_header.clone()),
+            config.api_key_rotation.unwrap_or(args.api_key_rotation),
+            &url,
+        )?;
 
-    while start_time.elapsed() < duration || duration.as_secs() == 0 {
-        request_id += 1;
+        // Используем параметры из конфига, если они указаны, иначе из аргументов
+        let settings = WorkerSettings {
+            url,
+            timeout_ms: config.timeout_ms.unwrap_or(args.timeout_ms).min_ms,
+            jitter_ms: config.jitter_ms.unwrap_or(args.jitter_ms) + config.timeout_ms.unwrap_or(args.timeout_ms).spread(),
+            stagger_start: Duration::from_millis(config.stagger_start_ms.unwrap_or(args.stagger_start_ms)),
+            http_timeout: Duration::from_secs(config.http_timeout.unwrap_or(args.http_timeout)),
+            connect_timeout: config.connect_timeout.or(args.connect_timeout).map(Duration::from_secs),
+            duration: Duration::from_secs(config.duration.unwrap_or(args.duration)),
+            debug: debug_filter(args.debug, args.debug_sample, &args.debug_method)?,
+            seed: args.seed.or(config.seed).unwrap_or_else(random_seed),
+            capture_headers: header_names(&args.capture_headers, config.capture_headers.as_deref()),
+            header_interval_secs: config.header_interval.unwrap_or(args.header_interval),
+            credits: config.credits.map(Arc::new),
+            apdex: apdex_thresholds(&args).or(config.apdex),
+            ids: RequestIds::new(config.id_start.unwrap_or(args.id_start)),
+            budget: config.request_budget.or(args.request_budget).map(RequestBudget::new),
+            accept_encoding: config.accept_encoding.or(args.accept_encoding),
+            no_parse: args.no_parse,
+            parser: config.parser.unwrap_or(args.parser),
+            respawn: args.respawn_workers,
+            expect_cluster: config.expect_cluster.or_else(|| args.expect_cluster.clone()),
+            breaker: breaker_settings(
+                config.breaker_error_rate.or(args.breaker_error_rate),
+                config.breaker_cooldown.unwrap_or(args.breaker_cooldown),
+            ),
+            api_keys,
+            identity: identity::ClientIdentity::new(
+                config.user_agent.or_else(|| args.user_agent.clone()),
+                config.emulate_client.or(args.emulate_client),
+            )?,
+            gzip_requests: config.gzip_requests.unwrap_or(args.gzip_requests),
+            deadline: deadline(
+                config.deadline_header.or_else(|| args.deadline_header.clone()),
+                config.deadline_ms.or(args.deadline_ms),
+                Duration::from_secs(config.http_timeout.unwrap_or(args.http_timeout)),
+            )?,
+            recovery: config
+                .recovery_check
+                .unwrap_or(args.recovery_check)
+                .then(|| Duration::from_secs(config.recovery_secs.unwrap_or(args.recovery_secs))),
+            outliers: outlier_settings(
+                config.outlier_ms.or(args.outlier_ms),
+                config.outliers_file.or_else(|| args.outliers_file.clone()),
+            ),
+            correlation: correlation(config.correlation_header.or_else(|| args.correlation_header.clone()))?,
+            clock_check: clock::ClockCheck::new(
+                config.clock_check.unwrap_or(args.clock_check),
+                config.clock_check_block_time.unwrap_or(args.clock_check_block_time),
+            ),
+            max_rps: max_rps(config.max_rps.or(args.max_rps), config.provider_limits.or(args.provider_limits)),
+            pace_factor: config.pace_factor.or(args.pace_factor),
+            provider_limits: config.provider_limits.or(args.provider_limits),
+            addresses: region::AddressCheck::new(
+                config.endpoint_ips.unwrap_or(args.endpoint_ips),
+                config.geo_lookup.or_else(|| args.geo_lookup.clone()),
+            ),
+            dns_refresh: dns_refresh(config.dns_refresh.or(args.dns_refresh))?,
+            hash_responses: config.hash_responses.unwrap_or(args.hash_responses),
+            affinity: affinity::AffinitySettings::new(
+                config.affinity.or_else(|| args.affinity.clone()),
+                config.affinity_compare.unwrap_or(args.affinity_compare),
+            )?,
+            summary_window: summary_window(
+                config.summary_window.or(args.summary_window),
+                Duration::from_secs(config.duration.unwrap_or(args.duration)),
+            ),
+            align_buckets: config.align_buckets.unwrap_or(args.align_buckets),
+            last_errors: config.last_errors.unwrap_or(args.last_errors),
+            redact: redact::Redaction::new(config.redact.as_deref().unwrap_or(&args.redact)),
+            snapshot: snapshot::SnapshotSettings::new(
+                config.snapshot_downloads.or(args.snapshot_downloads),
+                config.snapshot_paths.unwrap_or_else(|| args.snapshot_paths.clone()),
+                config.snapshot_range_mb.unwrap_or(args.snapshot_range_mb),
+            )?,
+            side_channel: sidechannel::PollSettings::new(
+                config.poll_urls.unwrap_or_else(|| args.poll_urls.clone()),
+                config.poll_interval.unwrap_or(args.poll_interval),
+                config.poll_metrics.unwrap_or_else(|| args.poll_metrics.clone()),
+            )?,
+            custom_methods: config.custom_methods.unwrap_or_else(|| args.custom_methods.clone()),
+        };
+        let tags = run_tags(&args.tags, config.metadata.as_ref());
+        (settings, config.methods, config.scenarios, tags, config.param_sweep)
+    } else {
+        // Используем параметры из командной строки
+        let settings = cli_settings(&args)?;
+        let feeders = profile::ProfileFeeders {
+            assets: args.das_assets.clone(),
+            owners: args.das_owners.clone(),
+        };
+        let (methods, scenarios) = match args.profile {
+            Some(profile) => profile.plan(args.workers, args.batch_size, &feeders),
+            None => (
+                vec![MethodConfig {
+                    method: args.method.clone(),
+                    alias: None,
+                    params: None, // params по умолчанию подставит каталог методов
+                    workers: args.workers,
+                    batch: Some(args.batch_size),
+                    start_offset: None,
+                    end_offset: None,
+                    duration: None,
+                    path: None,
+                    expected_status: None,
+                }],
+                Vec::new(),
+            ),
+        };
+        (settings, methods, scenarios, run_tags(&args.tags, None), None)
+    };
 
-        let request_start = Instant::now();
-        let (actual_method, actual_params) = if method == "getLatestBlock" {
-            // Кастомный метод: сначала получаем актуальный слот, затем getBlock
-            let slot_request_id = request_id;
-            request_id += 1; // Используем следующий ID для getBlock
-            
-            match get_latest_slot(&client, &url, slot_request_id).await {
-                Some(slot) => {
-                    if debug {
-                        println!("[Worker {}] Got latest slot: {}", worker_id, slot);
-                    }
-                    
-                    // Формируем параметры для getBlock
-                    // Если в params есть опции для getBlock, используем их, иначе дефолтные
-                    let block_params = if !params.is_empty() && params.len() > 1 {
-                        // params[0] должен быть старый слот (игнорируем), params[1] - опции
-                        vec![
-                            serde_json::Value::Number(slot.into()),
-                            params[1].clone(),
-                        ]
-                    } else if !params.is_empty() {
-                        // Только опции без слота
-                        vec![
-                            serde_json::Value::Number(slot.into()),
-                            params[0].clone(),
-                        ]
-                    } else {
-                        // Дефолтные опции
-                        vec![
-                            serde_json::Value::Number(slot.into()),
-                            serde_json::json!({
-                                "commitment": "finalized",
-                                "encoding": "json",
-                                "transactionDetails": "full",
-                                "maxSupportedTransactionVersion": 0,
-                                "rewards": false
-                            }),
-                        ]
-                    };
-                    
-                    ("getBlock".to_string(), block_params)
-                }
-                None => {
-                    if debug {
-                        println!("[Worker {}] Failed to get latest slot", worker_id);
-                    }
-                    stats.record_rpc_error();
-                    sleep(Duration::from_millis(timeout_ms)).await;
-                    continue;
-                }
-            }
-        } else {
-            (method.clone(), params.clone())
+    // Встроенный mock вместо endpoint из конфига или --url: все запросы, включая предварительные, идут на него
+    let mock = if args.mock_server {
+        let script = match &args.mock_script {
+            Some(path) => mockserver::load_script(Path::new(path))?,
+            None => mockserver::MockScript::default(),
         };
+        let mock = mockserver::start(mockserver::MockOptions {
+            defaults: mockserver::Behavior {
+                latency_ms: Some(args.mock_latency_ms),
+                error_rate: Some(args.mock_error_rate),
+                error: Some(args.mock_error),
+                ..Default::default()
+            },
+            script,
+            seed: settings.seed,
+        })
+        .await?;
+        settings.url = mock.url.clone();
+        if !args.quiet {
+            println!("Mock server: {}", mock.describe());
+        }
+        Some(mock)
+    } else {
+        None
+    };
 
-        match send_rpc_request(&client, &url, &actual_method, actual_params, request_id).await {
-            Ok(json_response) => {
-                let response_time = request_start.elapsed();
-                let response_time_micros = response_time.as_micros() as u64;
-                
-                if json_response.error.is_none() {
-                    if debug {
-                        println!("[Worker {}] Success - Response: {}", worker_id, 
-                            serde_json::to_string_pretty(&json_response).unwrap_or_else(|_| format!("{:?}", json_response)));
-                    }
-                    stats.record_success(response_time_micros);
-                } else {
-                    if debug {
-                        println!("[Worker {}] RPC Error: {:?}", worker_id, json_response.error);
-                    }
-                    stats.record_rpc_error();
-                }
-            }
-            Err(e) => {
-                // Проверяем, является ли это ошибкой парсинга JSON
-                if e.is_decode() {
-                    if debug {
-                        println!("[Worker {}] JSON Parse Error: {}", worker_id, e);
-                    }
-                    stats.record_json_parse_error();
-                } else if e.is_status() {
-                    // HTTP ошибка
-                    if let Some(status) = e.status() {
-                        let status_code = status.as_u16();
-                        let reason = status.canonical_reason().unwrap_or("Unknown");
-                        if debug {
-                            println!("[Worker {}] HTTP Error Status: {} {}", worker_id, status_code, reason);
-                        }
-                        stats.record_http_error(status_code, reason);
-                    } else {
-                        if debug {
-                            println!("[Worker {}] Request Error: {}", worker_id, e);
-                        }
-                        stats.record_network_error();
-                    }
-                } else if e.is_timeout() {
-                    if debug {
-                        println!("[Worker {}] Request Timeout: {}", worker_id, e);
-                    }
-                    stats.record_http_timeout();
-                } else {
-                    if debug {
-                        println!("[Worker {}] Request Error: {}", worker_id, e);
-                    }
-                    stats.record_network_error();
-                }
+    apply_default_params(&mut methods);
+    if let Some(plan) = settings.provider_limits {
+        let changes = apply_provider_limits(plan, &mut methods, &mut scenarios)?;
+        if !changes.is_empty() && !args.quiet {
+            println!("Provider limits ({}): {}", plan.name(), changes.join(", "));
+        }
+    }
+    // Пути к файлам фидеров считаются от каталога конфига
+    let config_dir = args
+        .config
+        .as_deref()
+        .and_then(|path| Path::new(path).parent())
+        .unwrap_or(Path::new("."));
+    let feeders = scenario::load_feeders(&scenarios, config_dir)?;
+    scenario::validate(&scenarios, &feeders)?;
+    if methods.is_empty() && scenarios.is_empty() {
+        return Err("Nothing to run: the config has no [[methods]] or [[scenarios]]".into());
+    }
+    validate_phases(&methods, settings.duration)?;
+    httpget::validate(&methods, &settings.url)?;
+    if args.auto_tune && !scenarios.is_empty() {
+        return Err("--auto-tune does not support scenarios".into());
+    }
+    if args.no_parse && !scenarios.is_empty() {
+        return Err("--no-parse does not support scenarios: steps need parsed responses".into());
+    }
+    compression::check_available(settings.accept_encoding, settings.gzip_requests)?;
+    history::check_available(args.history.is_some())?;
+    if args.control_socket.is_some() && !cfg!(unix) {
+        return Err("--control-socket is not supported on this platform: it needs Unix domain sockets".into());
+    }
+    if let Some(breaker) = &settings.breaker {
+        if !(breaker.error_rate_percent > 0.0 && breaker.error_rate_percent <= 100.0) {
+            return Err("--breaker-error-rate must be in (0, 100]".into());
+        }
+    }
+    if settings.hash_responses && (settings.no_parse || settings.parser == ResponseParser::Fast) {
+        return Err("--hash-responses needs response results: not compatible with --no-parse and --parser fast".into());
+    }
+    if settings.pace_factor.is_some_and(|factor| !(factor >= 0.0 && factor.is_finite())) {
+        return Err("--pace-factor must be a non-negative number".into());
+    }
+    percentiles::validate(args.percentile_confidence)?;
+    let sweep_steps = match &args.command {
+        Some(CliCommand::Sweep { steps, step_duration }) => {
+            sweep::check_workload(&methods, !scenarios.is_empty(), args.auto_tune)?;
+            let steps = if steps.is_empty() { sweep::DEFAULT_STEPS.to_vec() } else { steps.clone() };
+            if steps.contains(&0) {
+                return Err("sweep --steps must be at least 1".into());
             }
+            Some((steps, Duration::from_secs((*step_duration).max(1))))
+        }
+        _ => None,
+    };
+    let param_sweep = match &args.command {
+        Some(CliCommand::ParamSweep { step_duration }) => {
+            let Some(config) = param_sweep else {
+                return Err("param-sweep needs a --config with a [param_sweep] section".into());
+            };
+            sweep::check_workload(&methods, !scenarios.is_empty(), args.auto_tune)?;
+            paramsweep::validate(&config, &methods)?;
+            Some((config, Duration::from_secs((*step_duration).max(1))))
         }
+        _ => None,
+    };
 
-        // Таймаут между запросами
-        sleep(Duration::from_millis(timeout_ms)).await;
+    // Проверяем методы до старта, чтобы опечатка не превратилась в тест из одних ошибок
+    if !args.no_validate {
+        validate_methods(&methods, &scenarios, &settings.custom_methods)?;
     }
-}
 
-fn load_config(config_path: &str) -> Result<Config, Box<dyn std::error::Error>> {
-    let content = fs::read_to_string(config_path)?;
-    let config: Config = toml::from_str(&content)?;
-    Ok(config)
-}
+    // Предварительные запросы (discovery, fingerprint, заглушки слотов) идут с первым ключом пула
+    let probe_headers = settings.identity.headers(0);
+    let (probe_url, probe_client) = match &settings.api_keys {
+        Some(pool) => (
+            pool.url(&settings.url, 0),
+            build_client(settings.http_timeout, None, None, probe_headers, pool.header(0), None),
+        ),
+        None => (settings.url.clone(), build_client(settings.http_timeout, None, None, probe_headers, None, None)),
+    };
 
-fn extract_host_from_url(url: &str) -> Option<String> {
-    // Простой парсинг URL для извлечения хоста
-    if let Some(start) = url.find("://") {
-        let after_protocol = &url[start + 3..];
-        let host_port = if let Some(end) = after_protocol.find('/') {
-            &after_protocol[..end]
-        } else if let Some(end) = after_protocol.find('?') {
-            &after_protocol[..end]
-        } else {
-            after_protocol
-        };
-        // Извлекаем хост (без порта)
-        Some(host_port.split(':').next().unwrap_or(host_port).to_string())
+    // Ноды кластера с открытым RPC (в --dry-run запросы не отправляем, план строится для --url)
+    let nodes = if args.discover || (args.fan_out && !args.dry_run) {
+        discovery::discover_rpc_nodes(&probe_client, &probe_url, args.max_nodes).await?
     } else {
-        None
+        Vec::new()
+    };
+    if args.discover {
+        print_discovered_nodes(&settings.url, &nodes);
+        return Ok(());
     }
-}
 
-fn ping_host(host: &str, count: usize) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
-    let output = Command::new("ping")
-        .arg("-c")
-        .arg(count.to_string())
-        .arg(host)
-        .output()?;
+    // Версия ПО и кластер endpoint: для отчета и проверки --expect-cluster (при fan-out - seed endpoint,
+    // ноды берутся из его же getClusterNodes)
+    let endpoint = if args.dry_run {
+        None
+    } else {
+        Some(fingerprint::collect(&probe_client, &probe_url).await)
+    };
+    if let (Some(expected), Some(endpoint)) = (&settings.expect_cluster, &endpoint) {
+        expected.check(&secrets::redact(&settings.url), endpoint)?;
+    }
 
-    if !output.status.success() {
-        return Err(format!("Ping failed: {}", String::from_utf8_lossy(&output.stderr)).into());
+    // Выполняем предварительный ping тест, если указан флаг
+    if args.ping && !args.dry_run && !args.quiet {
+        perform_ping_test(&settings.url);
+        print_tls_info(&settings.url, settings.http_timeout);
     }
 
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let mut latencies = Vec::new();
+    if !args.quiet {
+        print_settings(&args, &settings, &methods, &scenarios, &tags);
+        if !nodes.is_empty() {
+            println!("Fan-out: {} nodes discovered via getClusterNodes", nodes.len());
+        }
+    }
 
-    // Парсим вывод ping (формат: "64 bytes from ... time=12.345 ms" или "time=12.345ms")
-    for line in output_str.lines() {
-        // Ищем паттерн time=XXX ms или time=XXXms
-        if let Some(time_pos) = line.find("time=") {
-            let after_time = &line[time_pos + 5..];
-            // Пробуем найти " ms" или "ms"
-            let latency_str = if let Some(ms_pos) = after_time.find(" ms") {
-                &after_time[..ms_pos]
-            } else if let Some(ms_pos) = after_time.find("ms") {
-                &after_time[..ms_pos]
-            } else {
-                continue;
-            };
-            
-            if let Ok(latency) = latency_str.trim().parse::<f64>() {
-                latencies.push(latency);
-            }
+    // Оценка объема теста; в терминале без --yes стартуем только после подтверждения
+    if !args.quiet {
+        match &sweep_steps {
+            Some((steps, step_duration)) => println!(
+                "\nSweep: {} steps of {} sec at {} workers (~{} sec in total)",
+                steps.len(),
+                step_duration.as_secs(),
+                steps.iter().map(|workers| workers.to_string()).collect::<Vec<_>>().join(", "),
+                step_duration.as_secs() * steps.len() as u64
+            ),
+            None => match &param_sweep {
+                Some((config, step_duration)) => println!(
+                    "\nParameter sweep: {} = {} values of {} sec each (~{} sec in total)",
+                    config.field,
+                    config.values.len(),
+                    step_duration.as_secs(),
+                    step_duration.as_secs() * config.values.len() as u64
+                ),
+                None => preview::print_plan(&preview::estimate(&methods, &scenarios, &settings), &settings),
+            },
         }
     }
 
-    Ok(latencies)
-}
+    if args.dry_run {
+        print_request_plan(&methods, &scenarios, &feeders, &settings, args.dry_run_requests);
+        return Ok(());
+    }
 
-fn perform_ping_test(url: &str) {
-    println!("\n=== Preliminary Ping Test (10 packets) ===");
-    
-    let host = match extract_host_from_url(url) {
-        Some(h) => h,
-        None => {
-            println!("Failed to extract host from URL: {}", url);
-            return;
-        }
-    };
+    if !args.yes && !preview::confirm()? {
+        println!("Cancelled: no load was sent");
+        return Ok(());
+    }
 
-    println!("Pinging host: {}", host);
-    
-    match ping_host(&host, 10) {
-        Ok(latencies) => {
-            if latencies.is_empty() {
-                println!("Failed to get ping results");
-                return;
-            }
+    resolve_slot_placeholders(&mut methods, &probe_client, &probe_url).await?;
 
-            let avg = latencies.iter().sum::<f64>() / latencies.len() as f64;
-            let min = latencies.iter().fold(f64::INFINITY, |a, &b| a.min(b));
-            let max = latencies.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+    // Лимиты сокетов - по наибольшему числу одновременных воркеров (у sweep - по последнему этапу)
+    let peak_workers = match &sweep_steps {
+        Some((steps, _)) => steps.iter().copied().max().unwrap_or(0),
+        None => methods.iter().map(|method| method.workers).sum::<usize>() + scenarios.iter().map(|scenario| scenario.workers).sum::<usize>(),
+    };
+    let preflight = sockets::preflight(peak_workers);
+    if !args.quiet {
+        for note in &preflight.notes {
+            println!("{}", note);
+        }
+    }
+    for warning in &preflight.warnings {
+        eprintln!("Warning: {}", warning);
+    }
 
-            println!("Ping results:");
-            println!("  Packets sent: 10");
-            println!("  Responses received: {}", latencies.len());
-            println!("  Minimum latency: {:.2} ms", min);
-            println!("  Maximum latency: {:.2} ms", max);
-            println!("  Average latency: {:.2} ms", avg);
-            
-            if latencies.len() < 10 {
-                println!("  Warning: {} packets lost", 10 - latencies.len());
-            }
+    // План пишется после подстановки слотов: повторный прогон пойдет по тем же блокам
+    if let Some(path) = &args.export_plan {
+        let (stages, steps, step_duration) = match (&sweep_steps, &param_sweep) {
+            (Some((steps, step_duration)), _) => (Some(plan::Stages::Sweep), steps.clone(), Some(step_duration.as_secs())),
+            (None, Some((_, step_duration))) => (Some(plan::Stages::ParamSweep), Vec::new(), Some(step_duration.as_secs())),
+            (None, None) => (None, Vec::new(), None),
+        };
+        let header = plan::PlanHeader::new(stages, steps, step_duration);
+        let resolved_config = resolved_config_toml(&settings, &methods, &scenarios, &tags)?;
+        let masked = plan::write(Path::new(path), &header, &resolved_config, param_sweep.as_ref().map(|(config, _)| config))?;
+        if masked {
+            eprintln!("Warning: the plan {} has masked API keys (***); fill them in or use ${{env:NAME}} references before --run-plan", path);
         }
-        Err(e) => {
-            println!("Error executing ping: {}", e);
-            println!("Make sure 'ping' command is available in the system");
+        if !args.quiet {
+            println!("Plan saved to: {}", path);
         }
     }
-    println!();
-}
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
+    // Фазы сценария mock-сервера - от старта нагрузки (у обычного прогона отсчет начнется заново ниже)
+    if let Some(mock) = &mock {
+        mock.mark_load_start();
+    }
+    if let Some((steps, step_duration)) = sweep_steps {
+        let mut step_settings = settings.clone();
+        step_settings.duration = step_duration;
+        let results = sweep::run(&methods, &step_settings, &steps, args.quiet).await;
+        sweep::print_result(&settings.url, &results);
+        return Ok(());
+    }
+    if let Some((config, step_duration)) = param_sweep {
+        let mut step_settings = settings.clone();
+        step_settings.duration = step_duration;
+        let results = paramsweep::run(&methods, &step_settings, &config, args.quiet).await?;
+        paramsweep::print_result(&settings.url, &config.field, &results);
+        return Ok(());
+    }
 
-    let stats = Stats::new();
-    let mut handles = Vec::new();
+    // Каталог прогона создаем до старта, чтобы не потерять результаты из-за ошибки в пути
+    let run_dir = match &args.out_dir {
+        Some(out_dir) => Some(report::create_run_dir(out_dir)?),
+        None => None,
+    };
 
-    // Если указан конфиг, загружаем параметры из него
-    if let Some(config_path) = &args.config {
-        if !Path::new(config_path).exists() {
-            return Err(format!("Configuration file not found: {}", config_path).into());
+    // Часы сверяются до нагрузки: под нагрузкой ответы медленнее и интервал оценки шире
+    let clock = match settings.clock_check {
+        Some(check) => {
+            if !args.quiet {
+                println!("\nClock check: comparing the local clock with the endpoint...");
+            }
+            let clock = clock::check(&probe_client, &probe_url, check.block_time).await;
+            if !args.quiet {
+                print_clock_check(&clock);
+            }
+            Some(clock)
         }
+        None => None,
+    };
 
-        let config = load_config(config_path)?;
+    // Адреса endpoint перед тестом: с ними сравниваются IP, с которых пришли ответы
+    let resolved_endpoint = match &settings.addresses {
+        Some(addresses) => {
+            let mut endpoint = region::resolve(&settings.url).await;
+            if let Some(geo_url) = &addresses.geo_url {
+                let ips = endpoint.ips.clone();
+                if let Err(e) = region::lookup_geo(geo_url, &ips, &mut endpoint.geo).await {
+                    eprintln!("Warning: {}", e);
+                }
+            }
+            if !args.quiet {
+                print_endpoint_addresses(&endpoint);
+            }
+            Some(endpoint)
+        }
+        None => None,
+    };
 
-        // Используем параметры из конфига, если они указаны, иначе из аргументов
-        let url = config.url.as_ref().unwrap_or(&args.url).clone();
-        let timeout_ms = config.timeout_ms.unwrap_or(args.timeout_ms);
-        let duration_secs = config.duration.unwrap_or(args.duration);
-        let http_timeout_secs = config.http_timeout.unwrap_or(args.http_timeout);
-        let duration = Duration::from_secs(duration_secs);
-        let http_timeout = Duration::from_secs(http_timeout_secs);
-
-        // Выполняем предварительный ping тест, если указан флаг
-        if args.ping {
-            perform_ping_test(&url);
-        }
-
-        println!("=== Stress Test Settings (from config: {}) ===", config_path);
-        println!("URL: {}", url);
-        println!("Request timeout: {} ms", timeout_ms);
-        println!("HTTP timeout: {} sec", http_timeout_secs);
-        println!("Duration: {} sec", duration_secs);
-        println!("Debug mode: {}", if args.debug { "enabled" } else { "disabled" });
-        println!("\nMethods from config:");
-        for method_config in &config.methods {
-            println!("  - {} (workers: {})", method_config.method, method_config.workers);
+    // Базовый уровень для проверки восстановления: до нагрузки, тем же клиентом, что и пробы после нее
+    let baseline = match settings.recovery {
+        Some(_) => {
+            if !args.quiet {
+                println!("\nRecovery check: measuring baseline for {} sec...", recovery::BASELINE_SECS);
+            }
+            Some(recovery::baseline(&probe_client, &probe_url).await)
         }
+        None => None,
+    };
+
+    if !args.quiet {
         println!("\nStarting test...");
+    }
+    if let Some(mock) = &mock {
+        mock.mark_load_start();
+    }
+
+    let outlier_log = settings.outliers.as_ref().map(|outliers| outliers::OutlierLog::create(outliers, settings.redact)).transpose()?;
+
+    // Первое разрешение имени - до старта: с него воркеры начинают подключаться
+    let dns_refresher = match settings.dns_refresh {
+        Some(interval) => {
+            let refresher = dnsrefresh::DnsRefresher::new(&settings.url, interval)?;
+            let addresses = refresher.resolve_initial().await?;
+            if !args.quiet {
+                let addresses: Vec<String> = addresses.iter().map(|ip| ip.to_string()).collect();
+                println!("DNS refresh: {} resolved to {}", refresher.host(), addresses.join(", "));
+            }
+            Some(refresher)
+        }
+        None => None,
+    };
+
+    // Статистику создаем непосредственно перед стартом: от нее отсчитываются интервалы и длительность
+    let stats = Stats::with_breaker(settings.breaker.map(breaker::Breaker::new), settings.summary_window, settings.align_buckets)
+        .with_outliers(outlier_log)
+        .with_rate_limit(settings.max_rps)
+        .with_remote_addresses(settings.addresses.is_some())
+        .with_response_hashes(settings.hash_responses)
+        .with_error_log(settings.last_errors)
+        .with_redaction(settings.redact)
+        .with_failure_samples(args.output_ticket.is_some())
+        .with_dns_refresh(dns_refresher)
+        .with_duration(settings.duration);
+    if let Some(refresher) = &stats.dns {
+        dnsrefresh::spawn(refresher.clone(), stats.clock);
+    }
+    // Длительность можно изменить во время теста
+    #[cfg(unix)]
+    control::spawn_signal_handler(stats.clone(), Duration::from_secs(args.extend_minutes * 60))?;
+    #[cfg(unix)]
+    let control_socket = match &args.control_socket {
+        Some(path) => Some(control::ControlSocket::bind(Path::new(path), stats.clone())?),
+        None => None,
+    };
+    let mut handles = Vec::new();
+    let mut tune_results = Vec::new();
+    // Снапшоты качаются с хоста основного endpoint все время теста
+    let snapshot_stats = settings
+        .snapshot
+        .as_ref()
+        .map(|snapshot| snapshot::spawn(snapshot.clone(), settings.url.clone(), settings.http_timeout, stats.clone()))
+        .transpose()?;
+    let side_channel = settings
+        .side_channel
+        .as_ref()
+        .map(|poll| sidechannel::spawn(poll.clone(), &settings.url, settings.http_timeout, stats.clone()))
+        .transpose()?;
+    let live = (args.live_interval > 0 && !args.quiet)
+        .then(|| live::spawn(Duration::from_secs(args.live_interval), stats.clone(), args.lang));
 
-        // Запускаем воркеры для каждого метода из конфига
+    if args.auto_tune {
+        let tune = autotune::AutoTuneSettings {
+            target_p95_ms: args.target_p95_ms,
+            interval: Duration::from_secs(args.tune_interval.max(1)),
+            // Лимит одновременных запросов тарифа делится между методами
+            max_workers: match settings.provider_limits.and_then(|plan| plan.limits().max_concurrency) {
+                Some(max_concurrency) => args.max_workers.min((max_concurrency / methods.len()).max(1)),
+                None => args.max_workers,
+            },
+        };
+        let (tuned_handles, results) = autotune::run(&methods, &settings, &stats, tune).await;
+        handles = tuned_handles;
+        tune_results = results;
+    } else {
+        // Запускаем воркеры для каждого метода
         let mut worker_id_counter = 0;
-        for method_config in &config.methods {
-            let params = method_config.params.clone().unwrap_or_default();
+        for method_config in &methods {
             for _ in 0..method_config.workers {
-                let handle = tokio::spawn(worker(
-                    worker_id_counter,
-                    url.clone(),
-                    method_config.method.clone(),
-                    params.clone(),
-                    timeout_ms,
-                    http_timeout,
-                    stats.clone(),
-                    duration,
-                    args.debug,
-                ));
-                handles.push(handle);
+                // При fan-out воркеры распределяются по нодам по кругу
+                let mut worker_settings = settings.clone();
+                if !nodes.is_empty() {
+                    worker_settings.url = nodes[worker_id_counter % nodes.len()].url.clone();
+                }
+                let worker_id = worker_id_counter;
+                let method_config = method_config.clone();
+                let worker_stats = stats.clone();
+                handles.push(spawn_supervised(worker_id, worker_settings, stats.clone(), move |settings| {
+                    worker(worker_id, method_config.clone(), settings, worker_stats.clone(), None)
+                }));
                 worker_id_counter += 1;
             }
         }
-    } else {
-        // Используем параметры из командной строки
-        println!("=== Stress Test Settings ===");
-        println!("URL: {}", args.url);
-        println!("Method: {}", args.method);
-        println!("Workers: {}", args.workers);
-        println!("Request timeout: {} ms", args.timeout_ms);
-        println!("HTTP timeout: {} sec", args.http_timeout);
-        println!("Duration: {} sec", args.duration);
-        println!("Debug mode: {}", if args.debug { "enabled" } else { "disabled" });
-        println!("\nStarting test...");
 
-        // Выполняем предварительный ping тест, если указан флаг
-        if args.ping {
-            perform_ping_test(&args.url);
-        }
-
-        let duration = Duration::from_secs(args.duration);
-        let http_timeout = Duration::from_secs(args.http_timeout);
-
-        // Запускаем воркеры
-        for i in 0..args.workers {
-            let handle = tokio::spawn(worker(
-                i,
-                args.url.clone(),
-                args.method.clone(),
-                Vec::new(), // Без параметров по умолчанию
-                args.timeout_ms,
-                http_timeout,
-                stats.clone(),
-                duration,
-                args.debug,
-            ));
-            handles.push(handle);
+        for (scenario_config, feeder) in scenarios.iter().zip(&feeders) {
+            for _ in 0..scenario_config.workers {
+                let mut worker_settings = settings.clone();
+                if !nodes.is_empty() {
+                    worker_settings.url = nodes[worker_id_counter % nodes.len()].url.clone();
+                }
+                let worker_id = worker_id_counter;
+                let scenario_config = scenario_config.clone();
+                let feeder = feeder.clone();
+                let worker_stats = stats.clone();
+                handles.push(spawn_supervised(worker_id, worker_settings, stats.clone(), move |settings| {
+                    scenario::scenario_worker(
+                        worker_id,
+                        scenario_config.clone(),
+                        feeder.clone(),
+                        settings,
+                        worker_stats.clone(),
+                    )
+                }));
+                worker_id_counter += 1;
+            }
         }
     }
 
@@ -594,10 +4625,173 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for handle in handles {
         let _ = handle.await;
     }
+    let snapshot_stats = match snapshot_stats {
+        Some((handle, counters)) => {
+            let _ = handle.await;
+            Some(counters)
+        }
+        None => None,
+    };
+    if let Some(handle) = side_channel {
+        let _ = handle.await;
+    }
+    // Строка состояния после конца теста не нужна: отчет идет следом
+    if let Some(handle) = live {
+        handle.abort();
+    }
+    #[cfg(unix)]
+    drop(control_socket);
 
     // Выводим статистику
-    stats.print_summary();
+    let mut state = stats.collect().await;
+    let remote_addresses = std::mem::take(&mut state.remote_addresses);
+    let periods = std::mem::take(&mut state.periods);
+    let response_sizes = settings.accept_encoding.map(|encoding| state.response_size_summary(encoding));
+    let request_compression = settings.gzip_requests.then(|| state.request_compression_summary());
+    let (late_responses, successful) = (state.late_responses, state.successful_requests);
+    let breaker = settings.breaker.map(|breaker| report::BreakerSummary {
+        error_rate_percent: breaker.error_rate_percent,
+        window_secs: aggregator::ROLLING_WINDOW_SECS as u64,
+        cooldown_secs: breaker.cooldown.as_secs(),
+        trips: state.breaker_trips.clone(),
+    });
+    let apdex = settings.apdex.map(|thresholds| report::ApdexReport {
+        thresholds,
+        methods: state.apdex.iter().map(|(method, counters)| counters.summarize(method)).collect(),
+    });
+    let mut summary = stats.summarize(state, &tags);
+    summary.endpoint = endpoint;
+    summary.environment = Some(environment::capture());
+    summary.clock = clock;
+    summary.correlation = settings.correlation.as_ref().map(|correlation| report::CorrelationSummary {
+        header: correlation.header.to_string(),
+        run_id: correlation.run.clone(),
+    });
+    summary.periods = settings.summary_window.map(|window| report::PeriodReport {
+        period_secs: window.as_secs(),
+        periods,
+    });
+    summary.parse_disabled = settings.no_parse;
+    summary.response_sizes = response_sizes;
+    summary.request_compression = request_compression;
+    summary.deadline = settings.deadline.as_ref().map(|deadline| report::DeadlineSummary {
+        header: deadline.header.clone(),
+        deadline_ms: deadline.ms,
+        late_responses,
+        late_percent: if successful > 0 { late_responses as f64 / successful as f64 * 100.0 } else { 0.0 },
+    });
+    summary.breaker = breaker;
+    summary.outliers = stats.outliers.as_ref().map(|outliers| outliers.summarize());
+    summary.consistency = stats.response_hashes.as_ref().map(|hashes| hashes.summarize());
+    summary.dns_refresh = stats.dns.as_ref().map(|refresher| refresher.summarize());
+    summary.last_errors = stats.error_log.as_ref().and_then(|log| log.summarize());
+    if let (Some(counters), Some(snapshot)) = (&snapshot_stats, &settings.snapshot) {
+        summary.snapshot = Some(counters.summarize(snapshot, summary.elapsed_secs));
+    }
+    summary.budget = settings.budget.as_ref().map(|budget| stats.budget_summary(budget, &settings));
+    summary.auto_tune = tune_results;
+    summary.apdex = apdex;
+    summary.request_ids = settings.ids.last().map(|last| report::RequestIdRange {
+        first: settings.ids.start,
+        last,
+    });
+    if let (Some(mut endpoint), Some(addresses)) = (resolved_endpoint, &settings.addresses) {
+        // Геоданные и для адресов, которых не было среди адресов хоста перед тестом
+        if let Some(geo_url) = &addresses.geo_url {
+            let ips: Vec<_> = remote_addresses.keys().copied().collect();
+            if let Err(e) = region::lookup_geo(geo_url, &ips, &mut endpoint.geo).await {
+                eprintln!("Warning: {}", e);
+            }
+        }
+        summary.addresses = Some(region::summarize(endpoint, remote_addresses));
+    }
+    // Пробы после нагрузки идут уже после подсчета итогов, чтобы не попасть в длительность и RPS теста
+    if let (Some(baseline), Some(duration)) = (baseline, settings.recovery) {
+        if !args.quiet {
+            println!("\nRecovery check: probing for {} sec after the load...", duration.as_secs());
+        }
+        summary.recovery = Some(baseline.check(&probe_client, &probe_url, duration).await);
+    }
+    summary.latency.percentiles = percentiles::summarize(&summary.latencies_micros, args.percentile_confidence);
+    let verdict = summary.verdict(args.min_success_rate);
+    let format = if args.quiet { SummaryFormat::Oneline } else { args.summary_format };
+    summary.print(format, &verdict, args.lang);
+    if let (Some(mock), false) = (&mock, args.quiet) {
+        println!("\n{}", mock.summary_line());
+    }
+
+    if let Some(dir) = run_dir {
+        let resolved_config = resolved_config_toml(&settings, &methods, &scenarios, &tags)?;
+        let resolved_config = secrets::redact(&resolved_config);
+        report::write_run_artifacts(&dir, &resolved_config, &summary, &verdict, args.lang)?;
+        if !args.quiet {
+            println!("\nResults saved to: {}", dir.display());
+        }
+    }
+
+    if let Some(path) = &args.verdict_file {
+        report::write_verdict_file(Path::new(path), &summary, &verdict)?;
+    }
+    if let Some(path) = &args.output_ticket {
+        let resolved_config = resolved_config_toml(&settings, &methods, &scenarios, &tags)?;
+        let ticket = ticket::Ticket {
+            url: &settings.url,
+            user_agent: &settings.identity.user_agent,
+            resolved_config: &secrets::redact(&resolved_config),
+            summary: &summary,
+            verdict_line: &summary.render_oneline(&verdict),
+            samples: stats.failure_samples.as_deref(),
+            redaction: settings.redact,
+        };
+        ticket::write(Path::new(path), &ticket)?;
+        if !args.quiet {
+            println!("Support ticket saved to: {}", path);
+        }
+    }
+    if let Some(path) = &args.junit_file {
+        report::write_junit_file(Path::new(path), &summary, &verdict)?;
+    }
+    if let Some(path) = &args.history {
+        let workload = workload_label(&methods, &scenarios);
+        let run = history::RunRecord::from_summary(&secrets::redact(&settings.url), workload, &summary, &verdict);
+        let id = history::append(path, &run)?;
+        if !args.quiet {
+            println!("Run #{} saved to history: {}", id, path);
+        }
+    }
 
+    // Код возврата отражает вердикт, чтобы скрипты могли не разбирать вывод
+    if !verdict.passed {
+        std::process::exit(1);
+    }
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_ids_are_sequential_and_blocks_are_contiguous() {
+        let ids = RequestIds::new(0);
+        assert_eq!(ids.last(), None);
+        assert_eq!(ids.next(), 1);
+        assert_eq!(ids.next_block(3), 2);
+        assert_eq!(ids.next(), 5);
+        assert_eq!(ids.last(), Some(5));
+    }
+
+    #[test]
+    fn request_ids_wrap_around_to_one() {
+        let ids = RequestIds::new(u64::MAX - 2);
+        assert_eq!(ids.next(), u64::MAX - 2);
+        assert_eq!(ids.next(), u64::MAX - 1);
+        assert_eq!(ids.next(), 1);
+        assert_eq!(ids.last(), Some(1));
+
+        // Батч не разрывается на переполнении: весь блок - с начала
+        let ids = RequestIds::new(u64::MAX - 3);
+        assert_eq!(ids.next_block(5), 1);
+        assert_eq!(ids.next(), 6);
+    }
+}
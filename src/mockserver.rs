@@ -0,0 +1,591 @@
+// Встроенный mock JSON-RPC (--mock-server): сервер в том же процессе на 127.0.0.1, нагрузка идет на него
+// вместо endpoint. Проверить конфиг, сценарии и накладные расходы самого инструмента можно, не трогая
+// реальный endpoint и не тратя кредиты. Ответы - правдоподобные значения для распространенных методов
+// (слот растет раз в 400 мс), для остальных - null.
+//
+// Поведение задается флагами или сценарием (--mock-script): распределение задержки, доля ошибок, лимит
+// запросов с ответами 429, медленная отдача тела (slow loris) и фазы по времени от старта нагрузки, в которых
+// все это меняется. Так ретраи, breaker и учет coordinated omission проверяются одинаково от прогона к прогону:
+// ошибки распределены по запросам равномерно, а случайные задержки зависят только от seed и номера запроса
+//
+// latency_ms = 20
+// jitter_ms = 30            # к задержке добавляется равномерно 0..30 мс
+// slow_rate = 1             # 1% ответов дольше на slow_ms
+// slow_ms = 2000
+// rate_limit_rps = 500      # сверх 500 запросов в секунду - HTTP 429
+//
+// [[phases]]                # с 30 по 60 секунду половина ответов - HTTP 503
+// from = 30
+// to = 60
+// error_rate = 50
+// error = "unavailable"
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{compression, Rng};
+
+const START_SLOT: u64 = 300_000_000;
+const SLOT_MILLIS: u128 = 400;
+const BLOCKHASH: &str = "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N";
+const GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+// Тело ответа slow loris отдается этим числом частей, равномерно за slow_loris_ms
+const TRICKLE_PARTS: u64 = 20;
+
+#[derive(clap::ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MockError {
+    /// JSON-RPC error -32005 "Node is behind" with HTTP 200
+    #[default]
+    Rpc,
+    /// HTTP 429 with Retry-After: 1
+    RateLimited,
+    /// HTTP 503
+    Unavailable,
+    /// Close the connection without a response
+    Reset,
+}
+
+impl MockError {
+    fn name(self) -> &'static str {
+        match self {
+            MockError::Rpc => "JSON-RPC error",
+            MockError::RateLimited => "HTTP 429",
+            MockError::Unavailable => "HTTP 503",
+            MockError::Reset => "connection reset",
+        }
+    }
+}
+
+// Параметры поведения; в фазе заданные поля меняют базовые, остальные остаются
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+pub struct Behavior {
+    pub latency_ms: Option<u64>,
+    pub jitter_ms: Option<u64>,
+    pub slow_rate: Option<f64>, // проценты
+    pub slow_ms: Option<u64>,
+    pub error_rate: Option<f64>, // проценты
+    pub error: Option<MockError>,
+    pub rate_limit_rps: Option<u64>,
+    pub slow_loris_rate: Option<f64>, // проценты
+    pub slow_loris_ms: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Phase {
+    from: u64, // секунды от старта нагрузки
+    to: Option<u64>,
+    #[serde(flatten)]
+    behavior: Behavior,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct MockScript {
+    #[serde(flatten)]
+    base: Behavior,
+    #[serde(default)]
+    phases: Vec<Phase>,
+}
+
+pub fn load_script(path: &Path) -> Result<MockScript, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read mock script {}: {}", path.display(), e))?;
+    toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Итоговое поведение в момент запроса
+#[derive(Clone, Copy, Debug)]
+struct Effective {
+    latency_ms: u64,
+    jitter_ms: u64,
+    slow_rate: f64,
+    slow_ms: u64,
+    error_rate: f64,
+    error: MockError,
+    rate_limit_rps: Option<u64>,
+    slow_loris_rate: f64,
+    slow_loris_ms: u64,
+}
+
+impl Effective {
+    fn apply(mut self, behavior: &Behavior) -> Self {
+        self.latency_ms = behavior.latency_ms.unwrap_or(self.latency_ms);
+        self.jitter_ms = behavior.jitter_ms.unwrap_or(self.jitter_ms);
+        self.slow_rate = behavior.slow_rate.unwrap_or(self.slow_rate);
+        self.slow_ms = behavior.slow_ms.unwrap_or(self.slow_ms);
+        self.error_rate = behavior.error_rate.unwrap_or(self.error_rate);
+        self.error = behavior.error.unwrap_or(self.error);
+        // 0 в фазе снимает лимит базового поведения
+        self.rate_limit_rps = behavior.rate_limit_rps.map_or(self.rate_limit_rps, |rps| (rps > 0).then_some(rps));
+        self.slow_loris_rate = behavior.slow_loris_rate.unwrap_or(self.slow_loris_rate);
+        self.slow_loris_ms = behavior.slow_loris_ms.unwrap_or(self.slow_loris_ms);
+        self
+    }
+
+    fn validate(&self, what: &str) -> Result<(), String> {
+        for (name, rate) in [("error_rate", self.error_rate), ("slow_rate", self.slow_rate), ("slow_loris_rate", self.slow_loris_rate)] {
+            if !(0.0..=100.0).contains(&rate) {
+                return Err(format!("mock server {}: {} must be in [0, 100]", what, name));
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = vec![format!("latency {} ms", self.latency_ms)];
+        if self.jitter_ms > 0 {
+            parts[0].push_str(&format!(" + 0..{} ms", self.jitter_ms));
+        }
+        if self.slow_rate > 0.0 {
+            parts.push(format!("{}% slow by {} ms", self.slow_rate, self.slow_ms));
+        }
+        if self.error_rate > 0.0 {
+            parts.push(format!("{}% errors: {}", self.error_rate, self.error.name()));
+        }
+        if let Some(rps) = self.rate_limit_rps {
+            parts.push(format!("HTTP 429 above {} req/s", rps));
+        }
+        if self.slow_loris_rate > 0.0 {
+            parts.push(format!("{}% slow loris over {} ms", self.slow_loris_rate, self.slow_loris_ms));
+        }
+        parts.join(", ")
+    }
+}
+
+pub struct MockOptions {
+    pub defaults: Behavior, // из флагов --mock-*; сценарий их перекрывает
+    pub script: MockScript,
+    pub seed: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    http_requests: AtomicU64,
+    calls: AtomicU64, // JSON-RPC вызовы, в батче - каждый
+    errors: AtomicU64,
+    rate_limited: AtomicU64,
+    slow: AtomicU64,
+    trickled: AtomicU64,
+}
+
+struct Server {
+    base: Effective,
+    phases: Vec<Phase>,
+    seed: u64,
+    started: Instant,
+    load_started: Mutex<Instant>, // отсчет фаз
+    window: Mutex<(u64, u64)>,    // секунда от старта и запросов в ней - для rate_limit_rps
+    counters: Counters,
+}
+
+// Ответ: целиком, по частям (slow loris) или закрытие соединения
+enum Reply {
+    Full(Vec<u8>),
+    Trickle(Vec<u8>, Duration),
+    Close,
+}
+
+pub struct MockServer {
+    pub url: String,
+    server: Arc<Server>,
+}
+
+impl MockServer {
+    pub fn describe(&self) -> String {
+        let mut text = format!("{} ({})", self.url, self.server.base.describe());
+        if !self.server.phases.is_empty() {
+            text.push_str(&format!(", {} scripted phases", self.server.phases.len()));
+        }
+        text
+    }
+
+    // Фазы сценария отсчитываются от этого момента: вызывается перед стартом нагрузки
+    pub fn mark_load_start(&self) {
+        *self.server.load_started.lock().unwrap() = Instant::now();
+    }
+
+    // Что сервер видел сам: сверка со счетчиками отчета
+    pub fn summary_line(&self) -> String {
+        let counters = &self.server.counters;
+        let mut line = format!(
+            "Mock server: served {} HTTP requests ({} JSON-RPC calls), injected {} errors",
+            counters.http_requests.load(Ordering::Relaxed),
+            counters.calls.load(Ordering::Relaxed),
+            counters.errors.load(Ordering::Relaxed)
+        );
+        for (count, what) in [
+            (&counters.rate_limited, "rate limited (429)"),
+            (&counters.slow, "slow"),
+            (&counters.trickled, "slow loris"),
+        ] {
+            let count = count.load(Ordering::Relaxed);
+            if count > 0 {
+                line.push_str(&format!(", {} {}", count, what));
+            }
+        }
+        line
+    }
+}
+
+pub async fn start(options: MockOptions) -> Result<MockServer, String> {
+    let base = Effective {
+        latency_ms: 0,
+        jitter_ms: 0,
+        slow_rate: 0.0,
+        slow_ms: 0,
+        error_rate: 0.0,
+        error: MockError::Rpc,
+        rate_limit_rps: None,
+        slow_loris_rate: 0.0,
+        slow_loris_ms: 10_000,
+    }
+    .apply(&options.defaults)
+    .apply(&options.script.base);
+    base.validate("settings")?;
+    for phase in &options.script.phases {
+        if phase.to.is_some_and(|to| to <= phase.from) {
+            return Err(format!("mock server phase from {} sec: the phase ends before it starts", phase.from));
+        }
+        base.apply(&phase.behavior).validate(&format!("phase from {} sec", phase.from))?;
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("Failed to start the mock server: {}", e))?;
+    let address = listener.local_addr().map_err(|e| format!("Failed to start the mock server: {}", e))?;
+    let server = Arc::new(Server {
+        base,
+        phases: options.script.phases,
+        seed: options.seed,
+        started: Instant::now(),
+        load_started: Mutex::new(Instant::now()),
+        window: Mutex::new((0, 0)),
+        counters: Counters::default(),
+    });
+    let accepting = server.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve(stream, accepting.clone()));
+        }
+    });
+    Ok(MockServer {
+        url: format!("http://{}", address),
+        server,
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+// Keep-alive соединение: запросы подряд, ответ на каждый после заданной задержки
+async fn serve(mut stream: TcpStream, server: Arc<Server>) {
+    let _ = stream.set_nodelay(true);
+    let mut buf = Vec::with_capacity(4096);
+    let mut chunk = [0u8; 4096];
+    loop {
+        while let Some(header_end) = find(&buf, b"\r\n\r\n") {
+            let headers = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+            let body_start = header_end + 4;
+            let body_end = body_start + header(&headers, "content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+            if buf.len() < body_end {
+                break;
+            }
+            let written = match server.respond(&headers, &buf[body_start..body_end]).await {
+                Reply::Full(response) => stream.write_all(&response).await,
+                Reply::Trickle(response, over) => trickle(&mut stream, &response, over).await,
+                Reply::Close => return,
+            };
+            if written.is_err() {
+                return;
+            }
+            buf.drain(..body_end);
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+// Заголовки сразу, тело - частями с паузами: клиент ждет ответ, который уже начался
+async fn trickle(stream: &mut TcpStream, response: &[u8], over: Duration) -> std::io::Result<()> {
+    let body_start = find(response, b"\r\n\r\n").map_or(0, |end| end + 4);
+    stream.write_all(&response[..body_start]).await?;
+    let body = &response[body_start..];
+    let part = (body.len() as u64).div_ceil(TRICKLE_PARTS).max(1) as usize;
+    let pause = over / TRICKLE_PARTS as u32;
+    for piece in body.chunks(part) {
+        tokio::time::sleep(pause).await;
+        stream.write_all(piece).await?;
+    }
+    Ok(())
+}
+
+fn http_response(status: &str, content_type: &str, extra_headers: &str, body: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\n{}\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        extra_headers,
+        body
+    )
+    .into_bytes()
+}
+
+// Выпадает ли событие с вероятностью rate% (roll - случайное число запроса)
+fn hit(roll: u64, rate: f64) -> bool {
+    rate > 0.0 && ((roll % 10_000) as f64) < rate * 100.0
+}
+
+impl Server {
+    // Поведение в момент запроса: базовое и поверх него фазы, которые идут сейчас (при пересечении - последняя)
+    fn behavior(&self) -> Effective {
+        let elapsed = self.load_started.lock().unwrap().elapsed().as_secs();
+        self.phases
+            .iter()
+            .filter(|phase| elapsed >= phase.from && phase.to.is_none_or(|to| elapsed < to))
+            .fold(self.base, |effective, phase| effective.apply(&phase.behavior))
+    }
+
+    // Запросы сверх лимита в текущей секунде
+    fn over_rate_limit(&self, rps: u64) -> bool {
+        let second = self.load_started.lock().unwrap().elapsed().as_secs();
+        let mut window = self.window.lock().unwrap();
+        if window.0 != second {
+            *window = (second, 0);
+        }
+        window.1 += 1;
+        window.1 > rps
+    }
+
+    async fn respond(&self, headers: &str, body: &[u8]) -> Reply {
+        let n = self.counters.http_requests.fetch_add(1, Ordering::Relaxed);
+        let behavior = self.behavior();
+        if behavior.rate_limit_rps.is_some_and(|rps| self.over_rate_limit(rps)) {
+            self.counters.rate_limited.fetch_add(1, Ordering::Relaxed);
+            return Reply::Full(http_response("429 Too Many Requests", "text/plain", "retry-after: 1\r\n", "Too many requests"));
+        }
+
+        // Случайные величины запроса - от seed и номера запроса: тот же seed дает то же распределение
+        let mut rng = Rng::new(self.seed ^ n.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let mut latency = behavior.latency_ms + rng.up_to(behavior.jitter_ms);
+        if hit(rng.next_u64(), behavior.slow_rate) {
+            self.counters.slow.fetch_add(1, Ordering::Relaxed);
+            latency += behavior.slow_ms;
+        }
+        if latency > 0 {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+        // Ошибка на запросе n, если доля ошибок переходит через целое: ровно rate% запросов, равномерно
+        let rate = behavior.error_rate / 100.0;
+        let inject = ((n + 1) as f64 * rate).floor() > (n as f64 * rate).floor();
+        if inject {
+            self.counters.errors.fetch_add(1, Ordering::Relaxed);
+            match behavior.error {
+                MockError::RateLimited => {
+                    return Reply::Full(http_response("429 Too Many Requests", "text/plain", "retry-after: 1\r\n", "Too many requests"))
+                }
+                MockError::Unavailable => return Reply::Full(http_response("503 Service Unavailable", "text/plain", "", "Service unavailable")),
+                MockError::Reset => return Reply::Close,
+                MockError::Rpc => {}
+            }
+        }
+
+        let response = self.response(headers, body, inject);
+        if hit(rng.next_u64(), behavior.slow_loris_rate) {
+            self.counters.trickled.fetch_add(1, Ordering::Relaxed);
+            return Reply::Trickle(response, Duration::from_millis(behavior.slow_loris_ms));
+        }
+        Reply::Full(response)
+    }
+
+    fn response(&self, headers: &str, body: &[u8], error: bool) -> Vec<u8> {
+        // GET (httpget.rs): любой путь отвечает 200
+        if headers.starts_with("GET ") {
+            return http_response("200 OK", "text/plain", "", "ok");
+        }
+        let body = match compression::decode(header(headers, "content-encoding"), body) {
+            Ok(body) => body,
+            Err(e) => return http_response("415 Unsupported Media Type", "text/plain", "", &e),
+        };
+        let request: Value = match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(_) => {
+                let error = json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32700, "message": "Parse error"}});
+                return http_response("200 OK", "application/json", "", &error.to_string());
+            }
+        };
+        let response = match request {
+            Value::Array(calls) => Value::Array(calls.iter().map(|call| self.call(call, error)).collect()),
+            call => self.call(&call, error),
+        };
+        http_response("200 OK", "application/json", "", &response.to_string())
+    }
+
+    fn call(&self, call: &Value, error: bool) -> Value {
+        self.counters.calls.fetch_add(1, Ordering::Relaxed);
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        if error {
+            return json!({"jsonrpc": "2.0", "id": id, "error": {"code": -32005, "message": "Node is behind (mock)"}});
+        }
+        let method = call.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = call.get("params").cloned().unwrap_or(Value::Null);
+        let slot = START_SLOT + (self.started.elapsed().as_millis() / SLOT_MILLIS) as u64;
+        json!({"jsonrpc": "2.0", "id": id, "result": result(method, &params, slot)})
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn with_context(slot: u64, value: Value) -> Value {
+    json!({"context": {"slot": slot}, "value": value})
+}
+
+fn result(method: &str, params: &Value, slot: u64) -> Value {
+    match method {
+        "getSlot" | "getBlockHeight" | "getTransactionCount" => json!(slot),
+        "getFirstAvailableBlock" | "minimumLedgerSlot" => json!(START_SLOT),
+        "getHealth" => json!("ok"),
+        "getVersion" => json!({"solana-core": "mock", "feature-set": 0}),
+        "getGenesisHash" => json!(GENESIS_HASH),
+        "getClusterNodes" => json!([]),
+        "getBlockTime" => json!(unix_secs()),
+        "getEpochInfo" => json!({
+            "absoluteSlot": slot,
+            "blockHeight": slot,
+            "epoch": slot / 432_000,
+            "slotIndex": slot % 432_000,
+            "slotsInEpoch": 432_000,
+            "transactionCount": slot
+        }),
+        "getLatestBlockhash" => with_context(slot, json!({"blockhash": BLOCKHASH, "lastValidBlockHeight": slot + 150})),
+        "getBalance" => with_context(slot, json!(0)),
+        "getAccountInfo" => with_context(slot, Value::Null),
+        "getMultipleAccounts" => {
+            let count = params.get(0).and_then(Value::as_array).map_or(0, Vec::len);
+            with_context(slot, Value::Array(vec![Value::Null; count]))
+        }
+        "getBlock" => {
+            let block = params.get(0).and_then(Value::as_u64).unwrap_or(slot);
+            json!({
+                "blockhash": BLOCKHASH,
+                "previousBlockhash": BLOCKHASH,
+                "parentSlot": block.saturating_sub(1),
+                "blockHeight": block,
+                "blockTime": unix_secs(),
+                "transactions": []
+            })
+        }
+        "getSignaturesForAddress" | "getProgramAccounts" | "getRecentPerformanceSamples" => json!([]),
+        "sendTransaction" => json!("1111111111111111111111111111111111111111111111111111111111111111"),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    use crate::sweep::{self, SweepStep};
+    use crate::{cli_settings, Args, MethodConfig};
+
+    async fn mock(defaults: Behavior) -> MockServer {
+        start(MockOptions {
+            defaults,
+            script: MockScript::default(),
+            seed: 1,
+        })
+        .await
+        .unwrap()
+    }
+
+    // Секунда нагрузки обычными воркерами инструмента, без пауз между запросами
+    async fn load(mock: &MockServer, workers: usize) -> SweepStep {
+        let args = Args::parse_from(["solana-rpc-stress-test", "--url", &mock.url, "--duration", "1", "--timeout-ms", "0"]);
+        let settings = cli_settings(&args).unwrap();
+        let methods: Vec<MethodConfig> = vec![serde_json::from_value(json!({ "method": "getSlot", "workers": workers })).unwrap()];
+        mock.mark_load_start();
+        sweep::run_step(&methods, &settings, workers).await
+    }
+
+    fn served(mock: &MockServer) -> (u64, u64) {
+        let counters = &mock.server.counters;
+        (counters.http_requests.load(Ordering::Relaxed), counters.errors.load(Ordering::Relaxed))
+    }
+
+    fn client_errors(step: &SweepStep) -> u64 {
+        (step.error_percent * step.requests as f64 / 100.0).round() as u64
+    }
+
+    #[tokio::test]
+    async fn short_load_succeeds_and_matches_the_server_count() {
+        let mock = mock(Behavior::default()).await;
+        let step = load(&mock, 2).await;
+        assert!(step.requests > 0);
+        assert_eq!(step.error_percent, 0.0);
+        assert_eq!(served(&mock), (step.requests, 0));
+        assert_eq!(mock.server.counters.calls.load(Ordering::Relaxed), step.requests);
+    }
+
+    #[tokio::test]
+    async fn injected_errors_are_exactly_the_configured_share() {
+        for error in [MockError::Rpc, MockError::Unavailable] {
+            let mock = mock(Behavior {
+                error_rate: Some(25.0),
+                error: Some(error),
+                ..Default::default()
+            })
+            .await;
+            let step = load(&mock, 2).await;
+            let (requests, errors) = served(&mock);
+            assert_eq!(requests, step.requests, "{:?}", error);
+            assert_eq!(errors, requests / 4, "{:?}", error);
+            assert_eq!(client_errors(&step), errors, "{:?}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn latency_bounds_the_request_count() {
+        let mock = mock(Behavior {
+            latency_ms: Some(20),
+            ..Default::default()
+        })
+        .await;
+        let step = load(&mock, 2).await;
+        // Два воркера по 20 мс на запрос: не больше 50 запросов в секунду на воркер
+        assert!(step.requests <= 2 * 51, "{} requests", step.requests);
+        assert!(step.requests >= 20, "{} requests", step.requests);
+        assert!(step.p50_ms.unwrap() >= 20.0);
+        assert_eq!(served(&mock), (step.requests, 0));
+    }
+
+    #[tokio::test]
+    async fn slow_responses_are_counted() {
+        let behavior = Behavior {
+            slow_rate: Some(50.0),
+            slow_ms: Some(1),
+            ..Default::default()
+        };
+        let mock = mock(behavior).await;
+        let step = load(&mock, 1).await;
+        let slow = mock.server.counters.slow.load(Ordering::Relaxed);
+        assert!(slow > 0 && slow < step.requests, "{} slow of {}", slow, step.requests);
+    }
+}
@@ -0,0 +1,92 @@
+// Разбор сетевых ошибок по причине: DNS, отказ в соединении, сброс, TLS и прочие. Общий счетчик
+// "Network errors" не говорит, куда смотреть, а здесь каждая причина указывает на свое: резолвер,
+// закрытый порт или упавший процесс, балансировщик, сертификаты и версии TLS
+
+use serde::Serialize;
+use std::error::Error;
+use std::io::ErrorKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetworkErrorKind {
+    Dns,
+    Refused,
+    Reset, // RST, broken pipe, соединение закрыто без ответа
+    Tls,
+    Other,
+}
+
+impl NetworkErrorKind {
+    // Категория в последних ошибках (--last-errors)
+    pub fn label(self) -> &'static str {
+        match self {
+            NetworkErrorKind::Dns => "dns",
+            NetworkErrorKind::Refused => "refused",
+            NetworkErrorKind::Reset => "reset",
+            NetworkErrorKind::Tls => "tls",
+            NetworkErrorKind::Other => "network",
+        }
+    }
+}
+
+// Подстроки текста ошибки TLS: native-tls не дает добраться до ошибки openssl через source(),
+// а без feature tls-info других признаков нет
+const TLS_MARKERS: [&str; 4] = ["SSL routines", "certificate", "handshake", "tls"];
+
+// Причина по цепочке ошибок reqwest -> hyper -> io / openssl
+pub fn classify(e: &reqwest::Error) -> NetworkErrorKind {
+    let mut source: Option<&(dyn Error + 'static)> = Some(e);
+    let mut text = String::new();
+    while let Some(error) = source {
+        let message = error.to_string();
+        // Ошибка резолвера hyper: ConnectError "dns error"
+        if message.starts_with("dns error") {
+            return NetworkErrorKind::Dns;
+        }
+        if error.downcast_ref::<hyper::Error>().is_some_and(hyper::Error::is_incomplete_message) {
+            return NetworkErrorKind::Reset;
+        }
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            match io.kind() {
+                ErrorKind::ConnectionRefused => return NetworkErrorKind::Refused,
+                ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe | ErrorKind::UnexpectedEof => {
+                    return NetworkErrorKind::Reset
+                }
+                _ => {}
+            }
+        }
+        #[cfg(feature = "tls-info")]
+        if error.is::<openssl::ssl::Error>() || error.is::<openssl::error::ErrorStack>() {
+            return NetworkErrorKind::Tls;
+        }
+        text.push_str(&message);
+        text.push('\n');
+        source = error.source();
+    }
+    if TLS_MARKERS.iter().any(|marker| text.contains(marker)) {
+        NetworkErrorKind::Tls
+    } else {
+        NetworkErrorKind::Other
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct NetworkErrors {
+    pub dns: u64,
+    pub refused: u64,
+    pub reset: u64,
+    pub tls: u64,
+    pub other: u64,
+}
+
+impl NetworkErrors {
+    pub fn record(&mut self, kind: NetworkErrorKind) {
+        let counter = match kind {
+            NetworkErrorKind::Dns => &mut self.dns,
+            NetworkErrorKind::Refused => &mut self.refused,
+            NetworkErrorKind::Reset => &mut self.reset,
+            NetworkErrorKind::Tls => &mut self.tls,
+            NetworkErrorKind::Other => &mut self.other,
+        };
+        *counter += 1;
+    }
+}
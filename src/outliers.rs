@@ -0,0 +1,149 @@
+// Выбросы латентности (--outlier-ms): запросы дольше порога записываются в JSONL файл целиком -
+// метод, params, время, статус и заголовки ответа. По файлу после прогона видно, какие слоты
+// и аккаунты раз за разом дают хвост p99
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::redact::Redaction;
+
+pub const DEFAULT_FILE: &str = "outliers.jsonl";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OutlierSettings {
+    pub threshold_ms: u64,
+    pub file: String,
+}
+
+// Запрос, превысивший порог
+pub struct Outlier<'a> {
+    pub at_secs: f64, // от старта теста
+    pub started_utc: String, // начало и конец запроса по UTC
+    pub ended_utc: String,
+    pub worker_id: usize,
+    pub correlation_id: Option<&'a str>, // ID из --correlation-header
+    pub url: &'a str,
+    pub api_key: Option<&'a str>, // подпись ключа из пула
+    pub method: &'a str,
+    pub params: &'a [Value],
+    pub batch_size: usize,
+    pub request_id: u64, // для батча - ID первого запроса
+    pub latency_micros: u64,
+    pub decode_micros: u64,
+    pub outcome: String, // ok, rpc error, id mismatch или текст ошибки
+    pub response: Option<&'a (u16, HeaderMap)>, // статус и заголовки (None - ответа не было)
+}
+
+// Строка файла выбросов
+#[derive(Serialize)]
+struct Record<'a> {
+    at_secs: f64,
+    started_utc: &'a str,
+    ended_utc: &'a str,
+    worker: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    correlation_id: Option<&'a str>,
+    url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+    method: &'a str,
+    params: &'a [Value],
+    #[serde(skip_serializing_if = "is_single")]
+    batch_size: usize,
+    request_id: u64,
+    latency_ms: f64,
+    decode_ms: f64,
+    outcome: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    headers: BTreeMap<&'a str, &'a str>,
+}
+
+fn is_single(batch_size: &usize) -> bool {
+    *batch_size <= 1
+}
+
+pub struct OutlierLog {
+    settings: OutlierSettings,
+    redaction: Redaction, // --redact: файл можно приложить к тикету провайдера
+    file: Mutex<File>,
+    captured: AtomicU64,
+}
+
+impl OutlierLog {
+    // Файл перезаписывается: выбросы прошлого прогона к этому не относятся
+    pub fn create(settings: &OutlierSettings, redaction: Redaction) -> Result<Self, String> {
+        let file = File::create(&settings.file)
+            .map_err(|e| format!("Failed to create outliers file {}: {}", settings.file, e))?;
+        Ok(Self {
+            settings: settings.clone(),
+            redaction,
+            file: Mutex::new(file),
+            captured: AtomicU64::new(0),
+        })
+    }
+
+    pub fn threshold_micros(&self) -> u64 {
+        self.settings.threshold_ms * 1000
+    }
+
+    // Выбросы редки по определению, поэтому пишем сразу, без буфера: файл полон даже после Ctrl+C
+    pub fn record(&self, outlier: Outlier) {
+        let url = self.redaction.url(outlier.url);
+        let params = self.redaction.params(outlier.method, outlier.params);
+        let outcome = self.redaction.text(&outlier.outcome);
+        let record = Record {
+            at_secs: (outlier.at_secs * 1000.0).round() / 1000.0,
+            started_utc: &outlier.started_utc,
+            ended_utc: &outlier.ended_utc,
+            worker: outlier.worker_id,
+            correlation_id: outlier.correlation_id,
+            url: &url,
+            api_key: outlier.api_key,
+            method: outlier.method,
+            params: &params,
+            batch_size: outlier.batch_size,
+            request_id: outlier.request_id,
+            latency_ms: outlier.latency_micros as f64 / 1000.0,
+            decode_ms: outlier.decode_micros as f64 / 1000.0,
+            outcome: &outcome,
+            status: outlier.response.map(|(status, _)| *status),
+            headers: outlier
+                .response
+                .map(|(_, headers)| {
+                    headers
+                        .iter()
+                        .filter_map(|(name, value)| Some((name.as_str(), self.redaction.header(name.as_str(), value.to_str().ok()?))))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        let Ok(line) = serde_json::to_string(&record) else {
+            return;
+        };
+        if writeln!(self.file.lock().unwrap(), "{}", line).is_ok() {
+            self.captured.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn summarize(&self) -> OutlierSummary {
+        OutlierSummary {
+            threshold_ms: self.settings.threshold_ms,
+            file: self.settings.file.clone(),
+            captured: self.captured.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct OutlierSummary {
+    pub threshold_ms: u64,
+    pub file: String,
+    pub captured: u64,
+}
@@ -0,0 +1,180 @@
+// Перебор параметра запроса (подкоманда param-sweep): нагрузка из конфига идет по очереди с каждым значением
+// одного поля из секции [param_sweep] - transactionDetails у getBlock, размер батча, число аккаунтов в вызове.
+// Каждое значение - отдельный этап со своей статистикой, итог - одна таблица для сравнения
+//
+// [param_sweep]
+// field = "params.1.transactionDetails"   # или "batch"
+// values = ["full", "accounts", "signatures", "none"]
+// methods = ["getBlock"]                  # необязательно: по умолчанию все методы
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::sweep::{self, SweepStep};
+use crate::{secrets, MethodConfig, WorkerSettings};
+
+const LABEL_MAX_CHARS: usize = 40;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ParamSweepConfig {
+    pub field: String,
+    pub values: Vec<Value>,
+    #[serde(default)]
+    pub methods: Vec<String>, // имена или alias методов; пусто - все
+}
+
+// Поле метода: batch или путь внутри params (индексы массивов и ключи объектов через точку)
+enum Field {
+    Batch,
+    Params(Vec<String>),
+}
+
+fn parse_field(field: &str) -> Result<Field, String> {
+    match field.split('.').collect::<Vec<_>>().as_slice() {
+        ["batch"] => Ok(Field::Batch),
+        ["params", index, rest @ ..] if index.parse::<usize>().is_ok() => {
+            Ok(Field::Params(std::iter::once(*index).chain(rest.iter().copied()).map(str::to_string).collect()))
+        }
+        _ => Err(format!(
+            "[param_sweep] field must be \"batch\" or \"params.<index>[.<key>...]\", got \"{}\"",
+            field
+        )),
+    }
+}
+
+fn targets(method: &MethodConfig, config: &ParamSweepConfig) -> bool {
+    config.methods.is_empty()
+        || config.methods.iter().any(|name| *name == method.method || method.alias.as_deref() == Some(name.as_str()))
+}
+
+pub fn validate(config: &ParamSweepConfig, methods: &[MethodConfig]) -> Result<(), String> {
+    if config.values.is_empty() {
+        return Err("[param_sweep] values must not be empty".to_string());
+    }
+    if let Field::Batch = parse_field(&config.field)? {
+        if let Some(value) = config.values.iter().find(|value| value.as_u64().is_none_or(|size| size == 0)) {
+            return Err(format!("[param_sweep] batch values must be positive integers, got {}", value));
+        }
+    }
+    if !methods.iter().any(|method| targets(method, config)) {
+        return Err(format!("[param_sweep] methods {:?} match none of the configured methods", config.methods));
+    }
+    Ok(())
+}
+
+// Записывает value по пути, создавая недостающие объекты и дополняя массивы null
+fn set_path(target: &mut Value, path: &[String], value: Value) {
+    let Some((key, rest)) = path.split_first() else {
+        *target = value;
+        return;
+    };
+    let index = key.parse::<usize>().ok();
+    if index.is_some() && target.is_null() {
+        *target = Value::Array(Vec::new());
+    }
+    match (target, index) {
+        (Value::Array(items), Some(index)) => {
+            if items.len() <= index {
+                items.resize(index + 1, Value::Null);
+            }
+            set_path(&mut items[index], rest, value);
+        }
+        (target, _) => {
+            if !target.is_object() {
+                *target = Value::Object(serde_json::Map::new());
+            }
+            if let Value::Object(map) = target {
+                set_path(map.entry(key.clone()).or_insert(Value::Null), rest, value);
+            }
+        }
+    }
+}
+
+// Методы с одним значением поля
+fn variant(methods: &[MethodConfig], config: &ParamSweepConfig, value: &Value) -> Result<Vec<MethodConfig>, String> {
+    let field = parse_field(&config.field)?;
+    Ok(methods
+        .iter()
+        .map(|method| {
+            let mut method = method.clone();
+            if targets(&method, config) {
+                match &field {
+                    Field::Batch => method.batch = value.as_u64().map(|size| size as usize),
+                    Field::Params(path) => {
+                        let mut params = Value::Array(method.params.take().unwrap_or_default());
+                        set_path(&mut params, path, value.clone());
+                        method.params = match params {
+                            Value::Array(params) => Some(params),
+                            _ => None,
+                        };
+                    }
+                }
+            }
+            method
+        })
+        .collect())
+}
+
+// Подпись значения в таблице: строка как есть, массив - числом элементов (список аккаунтов), иначе JSON
+fn label(value: &Value) -> String {
+    let label = match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => format!("[{} items]", items.len()),
+        other => other.to_string(),
+    };
+    label.chars().take(LABEL_MAX_CHARS).collect()
+}
+
+// settings.duration - длительность одного этапа; воркеры методов - как в конфиге
+pub async fn run(
+    methods: &[MethodConfig],
+    settings: &WorkerSettings,
+    config: &ParamSweepConfig,
+    quiet: bool,
+) -> Result<Vec<(String, SweepStep)>, String> {
+    let workers = methods.iter().map(|method| method.workers.max(1)).sum();
+    let mut results = Vec::new();
+    for value in &config.values {
+        let label = label(value);
+        if !quiet {
+            println!("{} = {}: {}s of load...", config.field, label, settings.duration.as_secs());
+        }
+        let step = sweep::run_step(&variant(methods, config, value)?, settings, workers).await;
+        results.push((label, step));
+    }
+    Ok(results)
+}
+
+pub fn print_result(url: &str, field: &str, results: &[(String, SweepStep)]) {
+    let ms = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |ms| format!("{:.2}", ms));
+    let width = results.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0).max(field.len());
+    println!("\n=== Parameter Sweep: {} ===", secrets::redact(url));
+    println!(
+        "\n{:<width$}  {:>8}  {:>9}  {:>9}  {:>7}  {:>9}  {:>9}  {:>9}  {:>9}",
+        field, "Requests", "Req/s", "OK req/s", "Errors%", "Avg ms", "p50 ms", "p95 ms", "p99 ms"
+    );
+    for (label, step) in results {
+        println!(
+            "{:<width$}  {:>8}  {:>9.1}  {:>9.1}  {:>7.1}  {:>9}  {:>9}  {:>9}  {:>9}",
+            label,
+            step.requests,
+            step.rps,
+            step.successful_rps,
+            step.error_percent,
+            ms(step.average_ms),
+            ms(step.p50_ms),
+            ms(step.p95_ms),
+            ms(step.p99_ms)
+        );
+    }
+    if let Some((label, step)) = results.iter().max_by(|a, b| a.1.successful_rps.total_cmp(&b.1.successful_rps)) {
+        println!("\nHighest throughput: {} ({:.1} successful req/s)", label, step.successful_rps);
+    }
+    let fastest = results
+        .iter()
+        .filter_map(|(label, step)| Some((label, step.p95_ms?)))
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((label, p95)) = fastest {
+        println!("Lowest p95: {} ({:.2} ms)", label, p95);
+    }
+}
@@ -0,0 +1,96 @@
+// Перцентили латентности с доверительным интервалом. p99 из 300 ответов - это третий с конца ответ, и
+// следующий такой же прогон легко покажет другое число. Интервал строится без предположений о
+// распределении, по рангам порядковых статистик (нормальное приближение биномиального распределения).
+// Если верхняя граница интервала выходит за число ответов, перцентиль помечается как недостоверный,
+// а в отчете - сколько ответов для него нужно
+
+use serde::Serialize;
+
+pub const DEFAULT_CONFIDENCE: f64 = 95.0;
+const REPORTED: [(&str, f64); 5] = [("p50", 0.50), ("p90", 0.90), ("p95", 0.95), ("p99", 0.99), ("p99.9", 0.999)];
+
+#[derive(Serialize, Debug)]
+pub struct PercentileSummary {
+    pub name: &'static str,
+    pub value_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lower_ms: Option<f64>, // границы доверительного интервала; None - ответов не хватает
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upper_ms: Option<f64>,
+    pub reliable: bool,
+    pub required_samples: u64, // минимум ответов для интервала с заданным доверием
+}
+
+#[derive(Serialize, Debug)]
+pub struct PercentileReport {
+    pub confidence_percent: f64,
+    pub samples: u64,
+    pub percentiles: Vec<PercentileSummary>,
+}
+
+// Квантиль стандартного нормального распределения для двустороннего интервала
+// (Abramowitz & Stegun 26.2.23, погрешность меньше 5e-4)
+fn z_score(confidence_percent: f64) -> f64 {
+    let p = (1.0 - confidence_percent / 100.0) / 2.0;
+    let t = (-2.0 * p.ln()).sqrt();
+    t - (2.515517 + 0.802853 * t + 0.010328 * t * t) / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t)
+}
+
+// Ранги (с 1) границ интервала для квантиля q из n ответов
+fn rank_bounds(n: u64, q: f64, z: f64) -> (u64, u64) {
+    let center = n as f64 * q;
+    let half = z * (center * (1.0 - q)).sqrt();
+    (((center - half).floor() as u64).max(1), (center + half).ceil() as u64 + 1)
+}
+
+fn required_samples(q: f64, z: f64) -> u64 {
+    let (mut low, mut high) = (1u64, 1u64 << 40);
+    while low < high {
+        let n = low + (high - low) / 2;
+        if rank_bounds(n, q, z).1 <= n {
+            high = n;
+        } else {
+            low = n + 1;
+        }
+    }
+    low
+}
+
+pub fn validate(confidence_percent: f64) -> Result<(), String> {
+    if !(50.0..100.0).contains(&confidence_percent) {
+        return Err(format!("--percentile-confidence must be at least 50 and below 100, got {}", confidence_percent));
+    }
+    Ok(())
+}
+
+// None - успешных ответов не было
+pub fn summarize(latencies_micros: &[u64], confidence_percent: f64) -> Option<PercentileReport> {
+    if latencies_micros.is_empty() {
+        return None;
+    }
+    let mut sorted = latencies_micros.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len() as u64;
+    let z = z_score(confidence_percent);
+    let ms = |rank: u64| sorted[(rank.clamp(1, n) - 1) as usize] as f64 / 1000.0;
+    let percentiles = REPORTED
+        .iter()
+        .map(|&(name, q)| {
+            let (lower, upper) = rank_bounds(n, q, z);
+            let reliable = upper <= n;
+            PercentileSummary {
+                name,
+                value_ms: ms((n as f64 * q).ceil() as u64),
+                lower_ms: reliable.then(|| ms(lower)),
+                upper_ms: reliable.then(|| ms(upper)),
+                reliable,
+                required_samples: required_samples(q, z),
+            }
+        })
+        .collect();
+    Some(PercentileReport {
+        confidence_percent,
+        samples: n,
+        percentiles,
+    })
+}
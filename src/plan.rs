@@ -0,0 +1,108 @@
+// План прогона (--export-plan / --run-plan): итоговый конфиг, в котором записано все, что прогон вычислил
+// сам - seed, params по умолчанию из каталога, слоты вместо заглушек, воркеры после лимитов тарифа,
+// этапы sweep. Конфиг с умолчаниями зависит от версии инструмента, а план повторяет прогон точно.
+// План - обычный конфиг с секцией [plan], секреты в нем остаются ссылками ${env:...} и ${file:...}
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::paramsweep::ParamSweepConfig;
+use crate::report::utc_millis;
+use crate::secrets;
+
+// Версия формата плана: растет при несовместимых изменениях
+const FORMAT: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stages {
+    Sweep,
+    ParamSweep,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlanHeader {
+    pub format: u32,
+    pub tool_version: String,
+    pub created_utc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stages: Option<Stages>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<usize>, // число воркеров на этапах sweep
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_duration: Option<u64>,
+}
+
+impl PlanHeader {
+    pub fn new(stages: Option<Stages>, steps: Vec<usize>, step_duration: Option<u64>) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_micros() as u64);
+        Self {
+            format: FORMAT,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_utc: utc_millis(now),
+            stages,
+            steps,
+            step_duration,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PlanSection<T> {
+    plan: T,
+}
+
+#[derive(Serialize)]
+struct ParamSweepSection<'a> {
+    param_sweep: &'a ParamSweepConfig,
+}
+
+// resolved_config - итоговый конфиг с подставленными секретами. true - в плане остались замаскированные
+// секреты (ключи пула без ссылок): перед --run-plan их нужно вписать
+pub fn write(path: &Path, header: &PlanHeader, resolved_config: &str, param_sweep: Option<&ParamSweepConfig>) -> Result<bool, String> {
+    let mut out = format!(
+        "# Test plan exported by solana-rpc-stress-test {} at {}; rerun it exactly with --run-plan\n\n",
+        header.tool_version, header.created_utc
+    );
+    out.push_str(resolved_config);
+    if let Some(param_sweep) = param_sweep {
+        let section = toml::to_string(&ParamSweepSection { param_sweep }).map_err(|e| format!("Failed to serialize the plan: {}", e))?;
+        out.push('\n');
+        out.push_str(&section);
+    }
+    let section = toml::to_string(&PlanSection { plan: header }).map_err(|e| format!("Failed to serialize the plan: {}", e))?;
+    out.push('\n');
+    out.push_str(&section);
+    let out = secrets::restore_references(&out);
+    fs::write(path, out.as_bytes()).map_err(|e| format!("Failed to write plan {}: {}", path.display(), e))?;
+    Ok(out.contains(secrets::MASK))
+}
+
+// Заголовок плана; сам конфиг читается обычной загрузкой конфига
+pub fn load(path: &Path) -> Result<PlanHeader, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read plan {}: {}", path.display(), e))?;
+    let section: PlanSection<Option<PlanHeader>> = toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let Some(header) = section.plan else {
+        return Err(format!("{}: not a test plan (no [plan] section); write one with --export-plan", path.display()));
+    };
+    if header.format > FORMAT {
+        return Err(format!(
+            "{}: plan format {} is newer than this version supports ({}); run it with solana-rpc-stress-test {}",
+            path.display(),
+            header.format,
+            FORMAT,
+            header.tool_version
+        ));
+    }
+    if content.contains(secrets::MASK) {
+        return Err(format!(
+            "{}: the plan has masked secrets ({}); replace them with the values or ${{env:NAME}} references",
+            path.display(),
+            secrets::MASK
+        ));
+    }
+    Ok(header)
+}
@@ -0,0 +1,248 @@
+// План теста перед стартом: ожидаемое число запросов, доля каждого метода и примерный трафик.
+// Оценка грубая: время ответа неизвестно до старта, поэтому берется типичное, а размеры ответов - по методу.
+// В терминале без --yes тест стартует только после подтверждения: случайный многочасовой прогон
+// против платного endpoint стоит денег
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use crate::scenario::{ScenarioConfig, ThinkTime};
+use crate::{httpget, secrets, JsonRpcRequest, MethodConfig, WorkerSettings};
+
+// Время ответа, на которое рассчитан план
+const ASSUMED_LATENCY_MS: f64 = 100.0;
+// HTTP заголовки запроса и ответа поверх тела JSON
+const REQUEST_HEADERS_BYTES: u64 = 250;
+const RESPONSE_HEADERS_BYTES: u64 = 200;
+// Бесконечный тест оценивается за час
+const INFINITE_TEST_SECS: f64 = 3600.0;
+
+// Типичный размер тела ответа (без сжатия) с параметрами по умолчанию
+fn typical_response_bytes(method: &str) -> u64 {
+    match method {
+        "getProgramAccounts" => 5_000_000,
+        "getBlock" => 1_500_000,
+        "getLatestBlock" => 1_500_100,
+        "getVoteAccounts" => 500_000,
+        "getClusterNodes" => 300_000,
+        "getSignaturesForAddress" | "getBlockProduction" => 200_000,
+        "getSupply" => 100_000,
+        "getTokenAccountsByOwner" | "getTokenLargestAccounts" => 20_000,
+        "getMultipleAccounts" | "getBlocks" | "getBlocksWithLimit" => 10_000,
+        "getTransaction" | "getRecentPerformanceSamples" | "getLargestAccounts" | "getLeaderSchedule" => 5_000,
+        "getAccountInfo" | "getTokenAccountBalance" => 1_000,
+        _ => 300,
+    }
+}
+
+#[derive(Debug)]
+pub struct PlanLine {
+    pub name: String,
+    pub workers: usize,
+    pub requests: f64, // JSON-RPC запросы (в батче - каждый элемент)
+    pub sent_bytes: f64,
+    pub received_bytes: f64,
+    pub credits: f64,
+}
+
+#[derive(Debug)]
+pub struct TestPlan {
+    pub lines: Vec<PlanLine>,
+    pub secs: f64,
+    pub infinite: bool,
+    pub limited_by: Vec<String>, // что урезало оценку: --max-rps, бюджет
+}
+
+impl TestPlan {
+    fn total(&self, field: impl Fn(&PlanLine) -> f64) -> f64 {
+        self.lines.iter().map(field).sum()
+    }
+}
+
+fn body_bytes(method: &str, params: &[serde_json::Value], id: u64) -> u64 {
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id,
+        method: method.to_string(),
+        params: params.to_vec(),
+    };
+    serde_json::to_vec(&request).map_or(0, |body| body.len() as u64)
+}
+
+fn think_time_ms(think_time: Option<ThinkTime>) -> f64 {
+    match think_time {
+        Some(ThinkTime::Fixed(ms)) => ms as f64,
+        Some(ThinkTime::Range([from, to])) => (from + to) as f64 / 2.0,
+        None => 0.0,
+    }
+}
+
+pub fn estimate(methods: &[MethodConfig], scenarios: &[ScenarioConfig], settings: &WorkerSettings) -> TestPlan {
+    let infinite = settings.duration.is_zero();
+    let secs = if infinite { INFINITE_TEST_SECS } else { settings.duration.as_secs_f64() };
+    // Пауза воркера между запросами (итерациями сценария)
+    let pause_ms = settings.timeout_ms as f64 + settings.jitter_ms as f64 / 2.0;
+    // --pace-factor добавляет к паузе долю времени ответа
+    let pace_ms = settings.pace_factor.unwrap_or(0.0) * ASSUMED_LATENCY_MS;
+    let id = settings.ids.start;
+    let mut lines = Vec::new();
+    for method in methods {
+        let batch = method.batch.unwrap_or(1).max(1);
+        // getLatestBlock - два запроса на цикл (getSlot и getBlock)
+        let calls = if method.method == "getLatestBlock" { 2 } else { batch };
+        // Метод с фазой работает только в ее окне
+        let phase = method.phase();
+        let active_secs = (phase.end.map_or(secs, |end| end.as_secs_f64().min(secs)) - phase.start.as_secs_f64()).max(0.0);
+        let cycles = method.workers as f64 * active_secs * 1000.0 / ((ASSUMED_LATENCY_MS + pace_ms) * calls.min(2) as f64 + pause_ms);
+        let params = method.params.clone().unwrap_or_default();
+        // У GET тела запроса нет
+        let body = if method.method == httpget::METHOD {
+            0
+        } else if batch > 1 {
+            body_bytes(&method.method, &params, id) * batch as u64 + batch as u64 + 1
+        } else {
+            body_bytes(&method.method, &params, id) * calls as u64
+        };
+        let cost = settings.credits.as_ref().map_or(0, |credits| credits.cost(&method.method));
+        lines.push(PlanLine {
+            name: method.label(),
+            workers: method.workers,
+            requests: cycles * calls as f64,
+            sent_bytes: cycles * (body + REQUEST_HEADERS_BYTES * calls.min(2) as u64) as f64,
+            received_bytes: cycles
+                * (typical_response_bytes(&method.method) * batch as u64 + RESPONSE_HEADERS_BYTES * calls.min(2) as u64)
+                    as f64,
+            credits: cycles * calls as f64 * cost as f64,
+        });
+    }
+    for scenario in scenarios {
+        let mut requests_per_iteration = 0.0;
+        let mut iteration_ms = pause_ms;
+        let mut sent = 0.0;
+        let mut received = 0.0;
+        let mut cost = 0.0;
+        for step in &scenario.steps {
+            // Циклы while считаются одним запросом: сколько раз условие выполнится, до старта неизвестно
+            let count = step.repeat.unwrap_or(1) as f64;
+            requests_per_iteration += count;
+            iteration_ms += count * (ASSUMED_LATENCY_MS * (1.0 + settings.pace_factor.unwrap_or(0.0)) + think_time_ms(step.think_time_ms));
+            sent += count * (body_bytes(&step.method, &step.params, id) + REQUEST_HEADERS_BYTES) as f64;
+            received += count * (typical_response_bytes(&step.method) + RESPONSE_HEADERS_BYTES) as f64;
+            cost += count * settings.credits.as_ref().map_or(0, |credits| credits.cost(&step.method)) as f64;
+        }
+        let iterations = scenario.workers as f64 * secs * 1000.0 / iteration_ms;
+        lines.push(PlanLine {
+            name: format!("scenario {}", scenario.name),
+            workers: scenario.workers,
+            requests: iterations * requests_per_iteration,
+            sent_bytes: iterations * sent,
+            received_bytes: iterations * received,
+            credits: iterations * cost,
+        });
+    }
+
+    let mut plan = TestPlan {
+        lines,
+        secs,
+        infinite,
+        limited_by: Vec::new(),
+    };
+    // Лимиты урезают все методы пропорционально
+    let mut scale = 1.0;
+    let requests = plan.total(|line| line.requests);
+    if let Some(max_rps) = settings.max_rps {
+        let limit = max_rps as f64 * secs;
+        if requests > limit {
+            scale = limit / requests;
+            plan.limited_by.push(format!("--max-rps {}", max_rps));
+        }
+    }
+    if let Some(budget) = &settings.budget {
+        let (spent, unit) = match settings.credits {
+            Some(_) => (plan.total(|line| line.credits), "credits"),
+            None => (requests, "requests"),
+        };
+        if spent * scale > budget.limit as f64 {
+            scale = budget.limit as f64 / spent;
+            plan.limited_by.push(format!("request budget {} {}", budget.limit, unit));
+        }
+    }
+    for line in &mut plan.lines {
+        line.requests *= scale;
+        line.sent_bytes *= scale;
+        line.received_bytes *= scale;
+        line.credits *= scale;
+    }
+    plan
+}
+
+fn format_count(value: f64) -> String {
+    match value {
+        v if v >= 1e9 => format!("{:.1}B", v / 1e9),
+        v if v >= 1e6 => format!("{:.1}M", v / 1e6),
+        v if v >= 1e4 => format!("{:.1}K", v / 1e3),
+        v => format!("{:.0}", v),
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    match bytes {
+        b if b >= 1e12 => format!("{:.1} TB", b / 1e12),
+        b if b >= 1e9 => format!("{:.1} GB", b / 1e9),
+        b if b >= 1e6 => format!("{:.1} MB", b / 1e6),
+        b if b >= 1e3 => format!("{:.1} KB", b / 1e3),
+        b => format!("{:.0} B", b),
+    }
+}
+
+pub fn print_plan(plan: &TestPlan, settings: &WorkerSettings) {
+    let period = if plan.infinite { "per hour (infinite test)".to_string() } else { format!("for {:.0} sec", plan.secs) };
+    println!("\n=== Test Plan: estimate {} ===", period);
+    println!("Endpoint: {}", secrets::redact(&settings.url));
+    let total = plan.total(|line| line.requests);
+    let name_width = plan.lines.iter().map(|line| line.name.len()).max().unwrap_or(0).max(6);
+    println!(
+        "  {:<name_width$}  {:>7}  {:>10}  {:>9}  {:>6}  {:>10}  {:>10}",
+        "Method", "Workers", "Requests", "Req/s", "Share", "Sent", "Received"
+    );
+    for line in &plan.lines {
+        println!(
+            "  {:<name_width$}  {:>7}  {:>10}  {:>9.1}  {:>5.1}%  {:>10}  {:>10}",
+            line.name,
+            line.workers,
+            format_count(line.requests),
+            line.requests / plan.secs,
+            if total > 0.0 { line.requests / total * 100.0 } else { 0.0 },
+            format_bytes(line.sent_bytes),
+            format_bytes(line.received_bytes)
+        );
+    }
+    println!(
+        "Total: ~{} JSON-RPC requests (~{:.1} req/s), ~{} sent, ~{} received",
+        format_count(total),
+        total / plan.secs,
+        format_bytes(plan.total(|line| line.sent_bytes)),
+        format_bytes(plan.total(|line| line.received_bytes))
+    );
+    if settings.credits.is_some() {
+        println!("Credits: ~{}", format_count(plan.total(|line| line.credits)));
+    }
+    if !plan.limited_by.is_empty() {
+        println!("Limited by: {}", plan.limited_by.join(", "));
+    }
+    println!(
+        "Assumes ~{} ms per response and typical uncompressed response sizes; a faster endpoint gets more requests",
+        ASSUMED_LATENCY_MS
+    );
+}
+
+// Подтверждение старта. Без терминала (скрипты, CI) не спрашиваем
+pub fn confirm() -> Result<bool, String> {
+    if !io::stdin().is_terminal() {
+        return Ok(true);
+    }
+    eprint!("Start the test? [y/N]: ");
+    io::stderr().flush().map_err(|e| e.to_string())?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).map_err(|e| e.to_string())?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
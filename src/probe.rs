@@ -0,0 +1,296 @@
+// Поиск лимитов endpoint (подкоманда limits): двоичным поиском находится наибольший принимаемый размер
+// тела запроса, длина батча и число аккаунтов в getMultipleAccounts. Провайдеры редко документируют
+// эти лимиты точно, а упираются в них первыми индексаторы и батчеры
+//
+// Размер растет удвоением от заведомо допустимого до первого отказа, затем граница уточняется делением
+// пополам; запросов - порядка двух логарифмов от верхней границы поиска
+
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::secrets;
+
+// Аккаунт для getMultipleAccounts и тела запроса: System Program есть на любом кластере
+const PROBE_ACCOUNT: &str = "11111111111111111111111111111111";
+// На 429 ждем и повторяем: лимит частоты - не отказ по размеру
+const RATE_LIMIT_RETRIES: usize = 5;
+const RATE_LIMIT_WAIT: Duration = Duration::from_secs(2);
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Probe {
+    /// Request body size in bytes
+    Body,
+    /// JSON-RPC requests in one batch
+    Batch,
+    /// Accounts in one getMultipleAccounts request
+    Accounts,
+}
+
+impl Probe {
+    pub const ALL: [Probe; 3] = [Probe::Body, Probe::Batch, Probe::Accounts];
+
+    fn label(self) -> &'static str {
+        match self {
+            Probe::Body => "Request body, bytes",
+            Probe::Batch => "Batch length",
+            Probe::Accounts => "getMultipleAccounts accounts",
+        }
+    }
+
+    // С какого размера начинается поиск: заведомо допустимый у любого endpoint
+    fn start(self) -> u64 {
+        match self {
+            Probe::Body => 1024,
+            Probe::Batch | Probe::Accounts => 1,
+        }
+    }
+
+    // Точность границы: тело - до килобайта, счетчики - точно
+    fn resolution(self) -> u64 {
+        match self {
+            Probe::Body => 1024,
+            Probe::Batch | Probe::Accounts => 1,
+        }
+    }
+
+    fn request(self, size: u64) -> Vec<u8> {
+        match self {
+            // getBalance с раздутым pubkey: ответ - ошибка params (-32602), если тело вообще принято
+            Probe::Body => {
+                let template = json!({ "jsonrpc": "2.0", "id": 1, "method": "getBalance", "params": [""] }).to_string();
+                let padding = (size as usize).saturating_sub(template.len()).max(1);
+                json!({ "jsonrpc": "2.0", "id": 1, "method": "getBalance", "params": ["1".repeat(padding)] })
+                    .to_string()
+                    .into_bytes()
+            }
+            Probe::Batch => {
+                let batch: Vec<Value> =
+                    (1..=size).map(|id| json!({ "jsonrpc": "2.0", "id": id, "method": "getSlot" })).collect();
+                serde_json::to_vec(&batch).unwrap_or_default()
+            }
+            // Пустой срез данных: ответ не растет с числом аккаунтов
+            Probe::Accounts => {
+                let accounts = vec![PROBE_ACCOUNT; size as usize];
+                let config = json!({ "encoding": "base64", "dataSlice": { "offset": 0, "length": 0 } });
+                json!({ "jsonrpc": "2.0", "id": 1, "method": "getMultipleAccounts", "params": [accounts, config] })
+                    .to_string()
+                    .into_bytes()
+            }
+        }
+    }
+}
+
+// Ответ на пробу: принят ли размер и почему нет
+struct Attempt {
+    accepted: bool,
+    detail: String,
+}
+
+impl Attempt {
+    fn rejected(detail: impl Into<String>) -> Self {
+        Self {
+            accepted: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+fn rpc_error(error: &Value) -> (i64, String) {
+    let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
+    let message = error.get("message").and_then(Value::as_str).unwrap_or_default().to_string();
+    (code, message)
+}
+
+// Ошибка размера: неправильный запрос, ошибка разбора или явное упоминание лимита в тексте
+fn size_error(code: i64, message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    code == -32600 || code == -32700 || ["too large", "too many", "exceed", "limit", "max"].iter().any(|word| message.contains(word))
+}
+
+fn judge(probe: Probe, size: u64, body: &Value) -> Attempt {
+    match probe {
+        // Тело принято, если на него пришел JSON-RPC ответ не об ошибке размера
+        Probe::Body => match body.get("error").map(rpc_error) {
+            Some((code, message)) if size_error(code, &message) => Attempt::rejected(format!("rpc {}: {}", code, message)),
+            _ if body.get("error").is_some() || body.get("result").is_some() => Attempt { accepted: true, detail: String::new() },
+            _ => Attempt::rejected("not a JSON-RPC response"),
+        },
+        // Батч принят, если на каждый запрос пришел результат
+        Probe::Batch => match body {
+            Value::Array(responses) => {
+                if let Some(error) = responses.iter().find_map(|response| response.get("error")) {
+                    let (code, message) = rpc_error(error);
+                    return Attempt::rejected(format!("rpc {}: {}", code, message));
+                }
+                let results = responses.iter().filter(|response| response.get("result").is_some()).count();
+                if results as u64 == size {
+                    Attempt { accepted: true, detail: String::new() }
+                } else {
+                    Attempt::rejected(format!("{} results for {} requests", results, size))
+                }
+            }
+            other => match other.get("error").map(rpc_error) {
+                Some((code, message)) => Attempt::rejected(format!("rpc {}: {}", code, message)),
+                None => Attempt::rejected("not a batch response"),
+            },
+        },
+        Probe::Accounts => match body.get("error").map(rpc_error) {
+            Some((code, message)) => Attempt::rejected(format!("rpc {}: {}", code, message)),
+            None if body.get("result").is_some() => Attempt { accepted: true, detail: String::new() },
+            None => Attempt::rejected("not a JSON-RPC response"),
+        },
+    }
+}
+
+async fn attempt(client: &reqwest::Client, url: &str, probe: Probe, size: u64) -> Attempt {
+    let body = probe.request(size);
+    for _ in 0..RATE_LIMIT_RETRIES {
+        let response = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone())
+            .send()
+            .await;
+        let response = match response {
+            Ok(response) => response,
+            Err(e) if e.is_timeout() => return Attempt::rejected("timeout"),
+            Err(_) => return Attempt::rejected("connection closed"),
+        };
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            sleep(RATE_LIMIT_WAIT).await;
+            continue;
+        }
+        let text = match response.bytes().await {
+            Ok(text) => text,
+            Err(_) => return Attempt::rejected("connection closed while reading the response"),
+        };
+        if !status.is_success() {
+            return Attempt::rejected(format!("HTTP {}", status.as_u16()));
+        }
+        return match serde_json::from_slice::<Value>(&text) {
+            Ok(value) => judge(probe, size, &value),
+            Err(_) => Attempt::rejected("not a JSON-RPC response"),
+        };
+    }
+    Attempt::rejected("HTTP 429 after retries")
+}
+
+#[derive(Debug)]
+pub struct ProbeResult {
+    pub probe: Probe,
+    pub max_accepted: Option<u64>,
+    pub first_rejected: Option<(u64, String)>, // None - отказа не было до верхней границы поиска
+    pub requests: usize,
+}
+
+pub struct LimitsOptions {
+    pub url: String,
+    pub probes: Vec<Probe>,
+    pub max_body_kb: u64,
+    pub max_batch: u64,
+    pub max_accounts: u64,
+    pub http_timeout: Duration,
+    pub pause: Duration,
+}
+
+impl LimitsOptions {
+    fn upper_bound(&self, probe: Probe) -> u64 {
+        match probe {
+            Probe::Body => self.max_body_kb * 1024,
+            Probe::Batch => self.max_batch,
+            Probe::Accounts => self.max_accounts,
+        }
+        .max(probe.start())
+    }
+}
+
+async fn search(client: &reqwest::Client, options: &LimitsOptions, probe: Probe) -> ProbeResult {
+    let upper = options.upper_bound(probe);
+    let mut requests = 0;
+    let mut try_size = |size: u64| {
+        requests += 1;
+        async move {
+            sleep(options.pause).await;
+            attempt(client, &options.url, probe, size).await
+        }
+    };
+
+    let start = probe.start();
+    let first = try_size(start).await;
+    if !first.accepted {
+        return ProbeResult {
+            probe,
+            max_accepted: None,
+            first_rejected: Some((start, first.detail)),
+            requests: 1,
+        };
+    }
+    // Удвоение до первого отказа или верхней границы
+    let mut accepted = start;
+    let mut rejected: Option<(u64, String)> = None;
+    while accepted < upper {
+        let size = (accepted * 2).min(upper);
+        let result = try_size(size).await;
+        if result.accepted {
+            accepted = size;
+        } else {
+            rejected = Some((size, result.detail));
+            break;
+        }
+    }
+    // Деление пополам между последним принятым и первым отказом
+    while let Some((rejected_size, _)) = &rejected {
+        if rejected_size - accepted <= probe.resolution() {
+            break;
+        }
+        let size = accepted + (rejected_size - accepted) / 2;
+        let result = try_size(size).await;
+        if result.accepted {
+            accepted = size;
+        } else {
+            rejected = Some((size, result.detail));
+        }
+    }
+    ProbeResult {
+        probe,
+        max_accepted: Some(accepted),
+        first_rejected: rejected,
+        requests,
+    }
+}
+
+pub async fn run(options: LimitsOptions) -> Result<Vec<ProbeResult>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(options.http_timeout)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let mut results = Vec::new();
+    for &probe in &options.probes {
+        println!("Probing: {} (search bound {})...", probe.label(), options.upper_bound(probe));
+        results.push(search(&client, &options, probe).await);
+    }
+    Ok(results)
+}
+
+pub fn print_results(url: &str, results: &[ProbeResult]) {
+    println!("\n=== Limits: {} ===", secrets::redact(url));
+    println!("{:<28}  {:>12}  {:>14}  {:>8}  Rejection", "Limit", "Max accepted", "First rejected", "Requests");
+    for result in results {
+        let max_accepted = result.max_accepted.map_or_else(|| "-".to_string(), |size| size.to_string());
+        let (first_rejected, reason) = match &result.first_rejected {
+            Some((size, reason)) => (size.to_string(), reason.clone()),
+            // Отказа не было: лимит не ниже верхней границы поиска
+            None => ("-".to_string(), "none up to the search bound".to_string()),
+        };
+        println!(
+            "{:<28}  {:>12}  {:>14}  {:>8}  {}",
+            result.probe.label(),
+            max_accepted,
+            first_rejected,
+            result.requests,
+            reason
+        );
+    }
+}
@@ -0,0 +1,241 @@
+// Встроенные профили нагрузки (--profile): готовые смеси методов с params,
+// чтобы получить реалистичную нагрузку без собственного конфига.
+// Используются только аккаунты, существующие в любом кластере, поэтому профили работают и на devnet/localnet.
+// Исключение - das: ID ассетов и владельцев по умолчанию из mainnet, свои берутся из CSV фидеров
+
+use serde_json::{json, Value};
+
+use crate::catalog::{self, FIRST_AVAILABLE_BLOCK, LATEST_SLOT};
+use crate::feeder::{FeederConfig, FeederStrategy};
+use crate::scenario::{ScenarioConfig, StepConfig};
+use crate::MethodConfig;
+
+const CLOCK_SYSVAR: &str = "SysvarC1ock11111111111111111111111111111111";
+const RENT_SYSVAR: &str = "SysvarRent111111111111111111111111111111111";
+const VOTE_PROGRAM: &str = "Vote111111111111111111111111111111111111111";
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+// Самые большие аккаунты, которые есть в любом кластере: SlotHistory ~128 KiB, SlotHashes ~20 KiB, StakeHistory ~16 KiB
+const SLOT_HISTORY_SYSVAR: &str = "SysvarS1otHistory11111111111111111111111111";
+const SLOT_HASHES_SYSVAR: &str = "SysvarS1otHashes111111111111111111111111111";
+const STAKE_HISTORY_SYSVAR: &str = "SysvarStakeHistory1111111111111111111111111";
+// Переменные ID профиля das и их значения без фидера - примеры из документации DAS API (mainnet)
+const ID_VARS: [(&str, &str); 2] = [
+    ("asset", "F9Lw3ki3hJ7PF9HQXsBzoY8GyE6sPoEZZdXJBsTTD2rk"),
+    ("owner", "86xCnPeV69n6t3DnyGvkKobf9FdN2H9oiVDdaMpo2MMY"),
+];
+
+// CSV фидеры профиля (--das-assets, --das-owners): файл с заголовком asset или owner, по ID в строке
+#[derive(Clone, Debug, Default)]
+pub struct ProfileFeeders {
+    pub assets: Option<String>,
+    pub owners: Option<String>,
+}
+
+impl ProfileFeeders {
+    fn file(&self, var: &str) -> Option<&String> {
+        match var {
+            "asset" => self.assets.as_ref(),
+            "owner" => self.owners.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// dApp frontend: account reads, balances, blockhash
+    ReadHeavy,
+    /// Indexer: fresh blocks and signature history
+    Indexer,
+    /// Wallet: balances, token accounts, blockhash, recent signatures
+    Wallet,
+    /// Archival node: oldest blocks and deep signature history
+    Archival,
+    /// Validator dashboards: epoch progress, block production, supply, inflation
+    Dashboard,
+    /// Large accounts: getAccountInfo and getMultipleAccounts with base64 vs base64+zstd
+    BigAccounts,
+    /// NFT apps on DAS API: getAsset, getAssetsByOwner, searchAssets (mainnet)
+    Das,
+}
+
+impl Profile {
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::ReadHeavy => "read-heavy",
+            Profile::Indexer => "indexer",
+            Profile::Wallet => "wallet",
+            Profile::Archival => "archival",
+            Profile::Dashboard => "dashboard",
+            Profile::BigAccounts => "big-accounts",
+            Profile::Das => "das",
+        }
+    }
+
+    // Смесь методов: (метод, доля воркеров, params). None - params по умолчанию из каталога
+    fn mix(self) -> Vec<(&'static str, usize, Option<Vec<Value>>)> {
+        let finalized = json!({ "commitment": "finalized" });
+        let base64 = json!({ "commitment": "finalized", "encoding": "base64" });
+        match self {
+            Profile::ReadHeavy => vec![
+                ("getAccountInfo", 4, None),
+                ("getMultipleAccounts", 2, None),
+                ("getBalance", 2, None),
+                ("getLatestBlockhash", 1, None),
+                ("getSlot", 1, None),
+            ],
+            Profile::Indexer => vec![
+                ("getLatestBlock", 3, Some(vec![catalog::block_options()])),
+                (
+                    "getSignaturesForAddress",
+                    2,
+                    Some(vec![json!(VOTE_PROGRAM), json!({ "commitment": "finalized", "limit": 100 })]),
+                ),
+                ("getMultipleAccounts", 1, Some(vec![json!([CLOCK_SYSVAR, RENT_SYSVAR]), base64])),
+                ("getSlot", 1, None),
+            ],
+            Profile::Wallet => vec![
+                ("getBalance", 3, None),
+                (
+                    "getTokenAccountsByOwner",
+                    2,
+                    Some(vec![
+                        json!(CLOCK_SYSVAR),
+                        json!({ "programId": TOKEN_PROGRAM }),
+                        json!({ "commitment": "finalized", "encoding": "jsonParsed" }),
+                    ]),
+                ),
+                ("getLatestBlockhash", 2, None),
+                (
+                    "getSignaturesForAddress",
+                    1,
+                    Some(vec![json!(VOTE_PROGRAM), json!({ "commitment": "finalized", "limit": 10 })]),
+                ),
+                ("getMinimumBalanceForRentExemption", 1, None),
+            ],
+            Profile::Archival => vec![
+                ("getBlock", 3, Some(vec![json!(FIRST_AVAILABLE_BLOCK), catalog::block_options()])),
+                ("getBlockTime", 1, Some(vec![json!(FIRST_AVAILABLE_BLOCK)])),
+                ("getBlocksWithLimit", 1, Some(vec![json!(FIRST_AVAILABLE_BLOCK), json!(100), finalized])),
+                (
+                    "getSignaturesForAddress",
+                    2,
+                    Some(vec![json!(VOTE_PROGRAM), json!({ "commitment": "finalized", "limit": 1000 })]),
+                ),
+                ("getBlock", 1, Some(vec![json!(LATEST_SLOT), catalog::block_options()])),
+            ],
+            // Панели мониторинга опрашивают эти методы раз в несколько секунд; getBlockProduction без range -
+            // текущая эпоха по всем валидаторам, самый тяжелый запрос смеси
+            Profile::Dashboard => vec![
+                ("getEpochInfo", 3, None),
+                ("getBlockProduction", 1, Some(vec![finalized.clone()])),
+                ("getSupply", 1, None),
+                ("getInflationRate", 1, Some(Vec::new())),
+            ],
+            // Одни и те же аккаунты в двух кодировках: отчет сравнивает их по размеру ответа и декодированию
+            Profile::BigAccounts => {
+                let encoded = |encoding: &str| json!({ "commitment": "finalized", "encoding": encoding });
+                let big = json!([SLOT_HISTORY_SYSVAR, SLOT_HASHES_SYSVAR, STAKE_HISTORY_SYSVAR]);
+                vec![
+                    ("getAccountInfo", 2, Some(vec![json!(SLOT_HISTORY_SYSVAR), encoded("base64")])),
+                    ("getAccountInfo", 2, Some(vec![json!(SLOT_HISTORY_SYSVAR), encoded("base64+zstd")])),
+                    ("getMultipleAccounts", 1, Some(vec![big.clone(), encoded("base64")])),
+                    ("getMultipleAccounts", 1, Some(vec![big, encoded("base64+zstd")])),
+                ]
+            }
+            // NFT приложения: карточка ассета, коллекция кошелька постранично, поиск сжатых NFT владельца
+            Profile::Das => {
+                let (asset, owner) = (placeholder("asset"), placeholder("owner"));
+                vec![
+                    ("getAsset", 4, Some(vec![json!({ "id": asset })])),
+                    ("getAssetsByOwner", 2, Some(vec![json!({ "ownerAddress": owner, "page": 1, "limit": 100 })])),
+                    (
+                        "searchAssets",
+                        1,
+                        Some(vec![json!({ "ownerAddress": owner, "compressed": true, "page": 1, "limit": 100 })]),
+                    ),
+                ]
+            }
+        }
+    }
+
+    // Методы профиля; --workers умножает долю каждого метода
+    pub fn methods(self, workers: usize, batch_size: usize) -> Vec<MethodConfig> {
+        self.plan(workers, batch_size, &ProfileFeeders::default()).0
+    }
+
+    // Методы и сценарии профиля: метод, params которого берут ID из фидера, становится сценарием из одного шага
+    // (фидеры есть только у сценариев); остальные переменные заменяются ID по умолчанию
+    pub fn plan(self, workers: usize, batch_size: usize, feeders: &ProfileFeeders) -> (Vec<MethodConfig>, Vec<ScenarioConfig>) {
+        let mut methods = Vec::new();
+        let mut scenarios = Vec::new();
+        for (method, share, params) in self.mix() {
+            let workers = share * workers.max(1);
+            let mut params = params.or_else(|| catalog::default_params(method));
+            let fed = ID_VARS
+                .into_iter()
+                .find(|(name, _)| params.iter().flatten().any(|param| uses_var(param, name)))
+                .and_then(|(name, _)| feeders.file(name));
+            if let (Some(file), Some(params)) = (fed, params.clone()) {
+                scenarios.push(ScenarioConfig {
+                    name: method.to_string(),
+                    workers,
+                    feeder: Some(FeederConfig {
+                        file: file.clone(),
+                        strategy: FeederStrategy::Random,
+                    }),
+                    steps: vec![StepConfig {
+                        method: method.to_string(),
+                        params,
+                        extract: Default::default(),
+                        repeat: None,
+                        while_condition: None,
+                        max_loops: None,
+                        skip_if: None,
+                        think_time_ms: None,
+                    }],
+                });
+                continue;
+            }
+            for (name, default) in ID_VARS {
+                params.iter_mut().flatten().for_each(|param| fill_var(param, name, default));
+            }
+            methods.push(MethodConfig {
+                method: method.to_string(),
+                alias: None,
+                params,
+                workers,
+                batch: Some(batch_size),
+                start_offset: None,
+                end_offset: None,
+                duration: None,
+                path: None,
+                expected_status: None,
+            });
+        }
+        (methods, scenarios)
+    }
+}
+
+// ${name}: так же переменные записываются в params шагов сценария
+fn placeholder(name: &str) -> String {
+    format!("${{{}}}", name)
+}
+
+fn uses_var(value: &Value, name: &str) -> bool {
+    match value {
+        Value::String(text) => *text == placeholder(name),
+        Value::Array(items) => items.iter().any(|item| uses_var(item, name)),
+        Value::Object(map) => map.values().any(|item| uses_var(item, name)),
+        _ => false,
+    }
+}
+
+fn fill_var(value: &mut Value, name: &str, id: &str) {
+    match value {
+        Value::String(text) if *text == placeholder(name) => *value = json!(id),
+        Value::Array(items) => items.iter_mut().for_each(|item| fill_var(item, name, id)),
+        Value::Object(map) => map.values_mut().for_each(|item| fill_var(item, name, id)),
+        _ => {}
+    }
+}
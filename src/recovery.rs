@@ -0,0 +1,134 @@
+// Проверка восстановления (--recovery-check): редкие пробные getSlot до нагрузки (базовый уровень)
+// и после нее. Endpoint восстановился, если в конце пробной фазы латентность и доля ошибок
+// вернулись к базовому уровню; момент восстановления ищется по окнам пробной фазы
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::{send_rpc_request, HeaderCapture, Stats};
+
+const PROBE_METHOD: &str = "getSlot";
+const PROBES_PER_SEC: u64 = 2;
+pub const BASELINE_SECS: u64 = 10;
+const WINDOW_SECS: u64 = 5;
+
+// Допуск к базовому уровню: p50 не больше чем в 1.5 раза (плюс 5 мс на шум),
+// доля ошибок не больше чем на 5 процентных пунктов
+const LATENCY_FACTOR: f64 = 1.5;
+const LATENCY_SLACK_MS: f64 = 5.0;
+const ERROR_RATE_SLACK_PERCENT: f64 = 5.0;
+
+// Результат одной пробы: время от начала фазы и латентность (None - ошибка)
+struct Probe {
+    at_secs: u64,
+    latency_micros: Option<u64>,
+}
+
+// Пробы с постоянной частотой; медленный ответ не накапливает очередь - следующая проба идет сразу
+async fn probe_phase(client: &reqwest::Client, url: &str, duration: Duration) -> Vec<Probe> {
+    let interval = Duration::from_millis(1000 / PROBES_PER_SEC);
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+    let mut probes = Vec::new();
+    let started = Instant::now();
+    let mut next = started;
+    while started.elapsed() < duration {
+        sleep(next.saturating_duration_since(Instant::now())).await;
+        next += interval;
+        let request_start = Instant::now();
+        let ok = send_rpc_request(client, url, PROBE_METHOD, Vec::new(), probes.len() as u64 + 1, &mut capture)
+            .await
+            .is_ok_and(|response| response.error.is_none());
+        probes.push(Probe {
+            at_secs: request_start.duration_since(started).as_secs(),
+            latency_micros: ok.then(|| capture.network_micros(request_start)),
+        });
+    }
+    probes
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PhaseSummary {
+    pub probes: u64,
+    pub errors: u64,
+    pub error_rate_percent: f64,
+    pub p50_ms: Option<f64>, // None - ни одной успешной пробы
+    pub p95_ms: Option<f64>,
+}
+
+fn summarize(probes: &[Probe]) -> PhaseSummary {
+    let mut latencies: Vec<u64> = probes.iter().filter_map(|probe| probe.latency_micros).collect();
+    latencies.sort_unstable();
+    let percentile = |p: f64| {
+        let index = ((latencies.len() as f64 * p).ceil() as usize).saturating_sub(1);
+        (!latencies.is_empty()).then(|| latencies[index.min(latencies.len() - 1)] as f64 / 1000.0)
+    };
+    let errors = (probes.len() - latencies.len()) as u64;
+    PhaseSummary {
+        probes: probes.len() as u64,
+        errors,
+        error_rate_percent: if probes.is_empty() {
+            0.0
+        } else {
+            errors as f64 / probes.len() as f64 * 100.0
+        },
+        p50_ms: percentile(0.50),
+        p95_ms: percentile(0.95),
+    }
+}
+
+// Базовый уровень, измеренный до нагрузки
+pub struct Baseline {
+    phase: PhaseSummary,
+}
+
+pub async fn baseline(client: &reqwest::Client, url: &str) -> Baseline {
+    Baseline {
+        phase: summarize(&probe_phase(client, url, Duration::from_secs(BASELINE_SECS)).await),
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct RecoverySummary {
+    pub baseline: PhaseSummary,
+    pub after: PhaseSummary, // вся пробная фаза после нагрузки
+    pub duration_secs: u64,
+    pub recovered: Option<bool>, // None - базовый уровень не измерен (все пробы до нагрузки неудачны)
+    pub recovered_after_secs: Option<u64>, // от конца нагрузки: начало первого окна, после которого все окна в норме
+}
+
+impl Baseline {
+    fn within(&self, phase: &PhaseSummary) -> Option<bool> {
+        let baseline_p50 = self.phase.p50_ms?;
+        let latency_ok = phase
+            .p50_ms
+            .is_some_and(|p50| p50 <= baseline_p50 * LATENCY_FACTOR + LATENCY_SLACK_MS);
+        Some(latency_ok && phase.error_rate_percent <= self.phase.error_rate_percent + ERROR_RATE_SLACK_PERCENT)
+    }
+
+    // Пробная фаза после нагрузки и сравнение с базовым уровнем по окнам WINDOW_SECS
+    pub async fn check(self, client: &reqwest::Client, url: &str, duration: Duration) -> RecoverySummary {
+        let probes = probe_phase(client, url, duration).await;
+        let windows: Vec<(u64, Option<bool>)> = probes
+            .chunk_by(|a, b| a.at_secs / WINDOW_SECS == b.at_secs / WINDOW_SECS)
+            .map(|window| (window[0].at_secs / WINDOW_SECS * WINDOW_SECS, self.within(&summarize(window))))
+            .collect();
+        let recovered = windows.last().and_then(|&(_, ok)| ok);
+        let recovered_after_secs = match recovered {
+            Some(true) => windows
+                .iter()
+                .rev()
+                .take_while(|&&(_, ok)| ok == Some(true))
+                .last()
+                .map(|&(start, _)| start),
+            _ => None,
+        };
+        RecoverySummary {
+            baseline: self.phase,
+            after: summarize(&probes),
+            duration_secs: duration.as_secs(),
+            recovered,
+            recovered_after_secs,
+        }
+    }
+}
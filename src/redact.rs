@@ -0,0 +1,162 @@
+// Правила редактирования захваченных данных (--redact): файл выбросов, отладочный вывод и последние ошибки
+// можно приложить к тикету провайдера, не раскрывая транзакции и ключи. Известные секреты (ключи пула,
+// ${env:...}) маскируются всегда (secrets.rs); правило keys маскирует и то, что похоже на ключ
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+
+const MASK: &str = "***";
+// Методы, первый параметр которых - подписанная транзакция
+const TRANSACTION_METHODS: [&str; 2] = ["sendTransaction", "simulateTransaction"];
+// Параметр запроса или заголовок с таким словом в имени считается ключом
+const KEY_WORDS: [&str; 5] = ["key", "token", "secret", "auth", "cookie"];
+// Сегмент пути URL такой длины из букв и цифр - ключ в пути (/v2/<key>, /<token>/)
+const PATH_KEY_MIN_LEN: usize = 20;
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactRule {
+    /// Drop transaction payloads from params and responses
+    Transactions,
+    /// Mask API keys and tokens in URLs and headers
+    Keys,
+}
+
+impl RedactRule {
+    pub fn name(self) -> &'static str {
+        match self {
+            RedactRule::Transactions => "transactions",
+            RedactRule::Keys => "keys",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Redaction {
+    pub transactions: bool,
+    pub keys: bool,
+}
+
+impl Redaction {
+    pub fn new(rules: &[RedactRule]) -> Self {
+        Self {
+            transactions: rules.contains(&RedactRule::Transactions),
+            keys: rules.contains(&RedactRule::Keys),
+        }
+    }
+
+    pub fn rules(&self) -> Vec<RedactRule> {
+        let mut rules = Vec::new();
+        if self.transactions {
+            rules.push(RedactRule::Transactions);
+        }
+        if self.keys {
+            rules.push(RedactRule::Keys);
+        }
+        rules
+    }
+
+    // params запроса: транзакция заменяется ее размером
+    pub fn params<'a>(&self, method: &str, params: &'a [Value]) -> Cow<'a, [Value]> {
+        if !self.transactions || !TRANSACTION_METHODS.contains(&method) {
+            return Cow::Borrowed(params);
+        }
+        let mut params = params.to_vec();
+        if let Some(Value::String(transaction)) = params.first() {
+            params[0] = Value::String(format!("<redacted transaction, {} chars>", transaction.len()));
+        }
+        Cow::Owned(params)
+    }
+
+    // Тело ответа: transaction и transactions (getBlock, getTransaction) заменяются пометкой
+    pub fn body(&self, body: &mut Value) {
+        if !self.transactions {
+            return;
+        }
+        match body {
+            Value::Object(object) => {
+                for (name, value) in object.iter_mut() {
+                    match (name.as_str(), &*value) {
+                        ("transactions", Value::Array(transactions)) => {
+                            *value = Value::String(format!("<redacted {} transactions>", transactions.len()));
+                        }
+                        ("transaction", Value::Null) => {}
+                        ("transaction", _) => *value = Value::String("<redacted transaction>".to_string()),
+                        _ => self.body(value),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.body(item)),
+            _ => {}
+        }
+    }
+
+    // URL: значения параметров-ключей и длинные сегменты пути
+    pub fn url<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        if !self.keys {
+            return Cow::Borrowed(url);
+        }
+        let Ok(mut parsed) = reqwest::Url::parse(url) else {
+            return Cow::Borrowed(url);
+        };
+        if parsed.password().is_some() {
+            let _ = parsed.set_password(Some(MASK));
+        }
+        let path = parsed.path_segments().map(|segments| {
+            segments.map(|segment| if looks_like_key(segment) { MASK } else { segment }).collect::<Vec<_>>().join("/")
+        });
+        if let Some(path) = path {
+            parsed.set_path(&path);
+        }
+        if parsed.query().is_some() {
+            let pairs: Vec<(String, String)> = parsed
+                .query_pairs()
+                .map(|(name, value)| {
+                    let value = if is_key_name(&name) { MASK.to_string() } else { value.into_owned() };
+                    (name.into_owned(), value)
+                })
+                .collect();
+            parsed.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+        Cow::Owned(parsed.to_string())
+    }
+
+    // Заголовок: значение ключа, токена или cookie маскируется
+    pub fn header<'a>(&self, name: &str, value: &'a str) -> &'a str {
+        if self.keys && is_key_name(name) {
+            MASK
+        } else {
+            value
+        }
+    }
+
+    // Произвольный текст (ошибка reqwest содержит URL запроса): маскируются URL внутри него
+    pub fn text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !self.keys || !text.contains("://") {
+            return Cow::Borrowed(text);
+        }
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = [rest.find("http://"), rest.find("https://")].into_iter().flatten().min() {
+            let end = rest[start..].find(|c: char| c.is_whitespace() || c == ')').map_or(rest.len(), |end| start + end);
+            out.push_str(&rest[..start]);
+            out.push_str(&self.url(&rest[start..end]));
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        Cow::Owned(out)
+    }
+}
+
+fn is_key_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    KEY_WORDS.iter().any(|word| name.contains(word))
+}
+
+fn looks_like_key(segment: &str) -> bool {
+    segment.len() >= PATH_KEY_MIN_LEN
+        && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && segment.chars().any(|c| c.is_ascii_digit())
+        && segment.chars().any(|c| c.is_ascii_alphabetic())
+}
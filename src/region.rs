@@ -0,0 +1,218 @@
+// Адреса endpoint (--endpoint-ips): IP, в которые хост резолвится перед тестом, и IP, с которых реально
+// пришли ответы. Anycast и GeoDNS от прогона к прогону отправляют запросы в разные регионы, и без этого
+// результаты прогонов несравнимы. С --geo-lookup адреса дополняются ASN и местоположением
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+// ipinfo.io отвечает и без токена (с дневным лимитом); {ip} заменяется на адрес
+pub const DEFAULT_GEO_URL: &str = "https://ipinfo.io/{ip}/json";
+
+const GEO_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug)]
+pub struct AddressCheck {
+    pub geo_url: Option<String>, // шаблон URL сервиса геоданных
+}
+
+impl AddressCheck {
+    // Геоданные включают и сами адреса
+    pub fn new(enabled: bool, geo_url: Option<String>) -> Option<Self> {
+        (enabled || geo_url.is_some()).then_some(Self { geo_url })
+    }
+}
+
+// Ответы с одного IP: время до заголовков ответа (без чтения тела)
+#[derive(Default, Clone, Copy, Debug)]
+pub struct AddressCounters {
+    pub responses: u64,
+    pub sum_micros: u64,
+    pub max_micros: u64,
+}
+
+impl AddressCounters {
+    pub fn record(&mut self, micros: u64) {
+        self.responses += 1;
+        self.sum_micros += micros;
+        self.max_micros = self.max_micros.max(micros);
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct GeoInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anycast: Option<bool>,
+}
+
+impl GeoInfo {
+    // "AS13335 Cloudflare, Inc., Brisbane, Queensland, AU, anycast"
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<&str> = [&self.asn, &self.org, &self.city, &self.region, &self.country]
+            .into_iter()
+            .filter_map(|part| part.as_deref())
+            .collect();
+        if self.anycast == Some(true) {
+            parts.push("anycast");
+        }
+        parts.join(", ")
+    }
+}
+
+// Адреса перед тестом
+#[derive(Debug)]
+pub struct ResolvedEndpoint {
+    pub host: String,
+    pub ips: Vec<IpAddr>,
+    pub error: Option<String>, // хост не резолвится локально (например, запросы идут через прокси)
+    pub geo: BTreeMap<IpAddr, GeoInfo>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AddressSummary {
+    pub ip: IpAddr,
+    pub resolved: bool, // был среди адресов хоста перед тестом
+    pub responses: u64,
+    pub share_percent: f64,
+    pub average_ms: f64, // до заголовков ответа
+    pub max_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geo: Option<GeoInfo>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EndpointAddresses {
+    pub host: String,
+    pub resolved: Vec<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_error: Option<String>,
+    pub used: Vec<AddressSummary>,
+}
+
+// Адреса хоста URL (IP в URL - он сам)
+pub async fn resolve(url: &str) -> ResolvedEndpoint {
+    let parsed = reqwest::Url::parse(url).ok();
+    let host = parsed
+        .as_ref()
+        .and_then(|url| url.host_str())
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']').to_string())
+        .unwrap_or_default();
+    let port = parsed.as_ref().and_then(|url| url.port_or_known_default()).unwrap_or(443);
+    let (ips, error) = match tokio::net::lookup_host((host.as_str(), port)).await {
+        Ok(addrs) => {
+            let mut ips: Vec<IpAddr> = addrs.map(|addr| addr.ip()).collect();
+            ips.sort();
+            ips.dedup();
+            (ips, None)
+        }
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    ResolvedEndpoint {
+        host,
+        ips,
+        error,
+        geo: BTreeMap::new(),
+    }
+}
+
+// Частные и служебные адреса во внешних сервисах не найти
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !(ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified()),
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            !(ip.is_loopback() || ip.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+// Ответ ipinfo.io, ipapi.co и похожих сервисов: ASN отдельным полем или в начале org ("AS13335 Cloudflare, Inc.")
+fn parse_geo(body: &Value) -> GeoInfo {
+    let text = |key: &str| body.get(key).and_then(Value::as_str).filter(|s| !s.is_empty()).map(str::to_string);
+    let mut asn = text("asn");
+    let mut org = text("org");
+    if let Some(full) = org.clone() {
+        if let Some((prefix, name)) = full.split_once(' ').filter(|(prefix, _)| prefix.starts_with("AS")) {
+            asn.get_or_insert_with(|| prefix.to_string());
+            org = Some(name.to_string());
+        }
+    }
+    GeoInfo {
+        asn,
+        org,
+        city: text("city"),
+        region: text("region"),
+        country: text("country"),
+        anycast: body.get("anycast").and_then(Value::as_bool),
+    }
+}
+
+// Геоданные публичных адресов, которых еще нет в geo. Отдельный клиент без заголовков и ключей endpoint:
+// сторонний сервис получает только IP
+pub async fn lookup_geo(url_template: &str, ips: &[IpAddr], geo: &mut BTreeMap<IpAddr, GeoInfo>) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(GEO_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let mut errors = Vec::new();
+    for &ip in ips {
+        if geo.contains_key(&ip) || !is_public(ip) {
+            continue;
+        }
+        let url = url_template.replace("{ip}", &ip.to_string());
+        let response = client.get(&url).send().await.and_then(|response| response.error_for_status());
+        match response {
+            Ok(response) => match response.json::<Value>().await {
+                Ok(body) => {
+                    geo.insert(ip, parse_geo(&body));
+                }
+                Err(e) => errors.push(format!("{}: {}", ip, e)),
+            },
+            Err(e) => errors.push(format!("{}: {}", ip, e)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Geo lookup failed for {}", errors.join("; ")))
+    }
+}
+
+pub fn summarize(endpoint: ResolvedEndpoint, counters: BTreeMap<IpAddr, AddressCounters>) -> EndpointAddresses {
+    let total: u64 = counters.values().map(|counters| counters.responses).sum();
+    let mut used: Vec<AddressSummary> = counters
+        .into_iter()
+        .map(|(ip, counters)| AddressSummary {
+            ip,
+            resolved: endpoint.ips.contains(&ip),
+            responses: counters.responses,
+            share_percent: if total > 0 {
+                counters.responses as f64 / total as f64 * 100.0
+            } else {
+                0.0
+            },
+            average_ms: counters.sum_micros as f64 / counters.responses.max(1) as f64 / 1000.0,
+            max_ms: counters.max_micros as f64 / 1000.0,
+            geo: endpoint.geo.get(&ip).cloned(),
+        })
+        .collect();
+    used.sort_by_key(|address| std::cmp::Reverse(address.responses));
+    EndpointAddresses {
+        host: endpoint.host,
+        resolved: endpoint.ips,
+        resolve_error: endpoint.error,
+        used,
+    }
+}
@@ -0,0 +1,1713 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::accounts::{self, AccountReadSummary};
+use crate::affinity::GroupSummary;
+use crate::aggregator::{BucketClock, HeaderSample, StatsState};
+use crate::apdex::{ApdexSummary, ApdexThresholds};
+use crate::autotune::TuneResult;
+use crate::breaker::BreakerTrip;
+use crate::changepoints::{self, LatencyShift};
+use crate::compression::AcceptEncoding;
+use crate::discovery::{NodeStats, NodeSummary};
+use crate::errorlog::ErrorLogSummary;
+use crate::fingerprint::EndpointFingerprint;
+use crate::scenario::ScenarioSummary;
+use crate::i18n::{texts, Lang, Texts};
+use crate::dnsrefresh::DnsRefreshSummary;
+use crate::environment::ClientEnvironment;
+use crate::incidents::Incident;
+use crate::neterror::NetworkErrors;
+use crate::keys::KeySummary;
+use crate::clock::ClockSummary;
+use crate::consistency::{self, ConsistencySummary};
+use crate::outliers::OutlierSummary;
+use crate::percentiles::PercentileReport;
+use crate::recovery::{PhaseSummary, RecoverySummary};
+use crate::sidechannel::{self, PollSummary};
+use crate::snapshot::SnapshotSummary;
+use crate::region::EndpointAddresses;
+use crate::{format_tags, Stats};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SummaryFormat {
+    Full,
+    Compact,
+    Oneline,
+}
+
+// Итог прогона: прошел ли он пороги и какие пороги нарушены
+#[derive(Serialize, Debug)]
+pub struct Verdict {
+    pub passed: bool,
+    pub violations: Vec<String>,
+    pub checks: Vec<VerdictCheck>, // все проверенные пороги, в том числе пройденные
+}
+
+#[derive(Serialize, Debug)]
+pub struct VerdictCheck {
+    pub name: String,
+    pub passed: bool,
+    pub violation: String, // текст нарушения, если порог не пройден
+}
+
+// Итоговые метрики прогона: из них строятся и консольный отчет, и файлы в --out-dir
+#[derive(Serialize, Debug)]
+pub struct Summary {
+    pub tags: BTreeMap<String, String>,
+    pub endpoint: Option<EndpointFingerprint>, // getVersion / getGenesisHash / getIdentity до старта
+    pub environment: Option<ClientEnvironment>, // версии, ОС, CPU и лимиты клиента
+    pub correlation: Option<CorrelationSummary>, // только с --correlation-header
+    pub clock: Option<ClockSummary>, // только с --clock-check
+    pub started_utc: String,
+    pub finished_utc: String,
+    pub elapsed_secs: f64,
+    pub total_requests: u64,
+    pub successful_requests: u64,
+    pub success_rate: f64,
+    pub errors: ErrorSummary,
+    pub rolling: Option<RollingSummary>, // None - прогон короче одного окна
+    pub periods: Option<PeriodReport>, // только с --summary-window
+    pub batch: Option<BatchSummary>,
+    pub timeouts: Option<TimeoutSummary>,
+    pub near_timeout_successes: u64,
+    pub connections: Option<ConnectionSummary>, // None - ни одного ответа и обрыва соединения
+    pub worker_crashes: u64,
+    pub parse_disabled: bool, // --no-parse: RPC ошибки и несовпадения ID не обнаруживаются
+    pub headers: BTreeMap<String, Vec<HeaderPoint>>,
+    pub credits: Option<CreditSummary>,
+    pub budget: Option<BudgetSummary>, // только с --request-budget
+    pub response_sizes: Option<ResponseSizeSummary>, // только с --accept-encoding
+    pub request_compression: Option<RequestCompressionSummary>, // только с --gzip-requests
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub account_reads: Vec<AccountReadSummary>, // getAccountInfo и getMultipleAccounts по кодировке запроса
+    pub deadline: Option<DeadlineSummary>, // только с --deadline-header
+    pub recovery: Option<RecoverySummary>, // только с --recovery-check
+    pub snapshot: Option<SnapshotSummary>, // только с --snapshot-downloads
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub side_channel: Vec<PollSummary>, // только с --poll-url
+    pub addresses: Option<EndpointAddresses>, // только с --endpoint-ips
+    pub dns_refresh: Option<DnsRefreshSummary>, // только с --dns-refresh
+    pub breaker: Option<BreakerSummary>, // только с --breaker-error-rate
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub incidents: Vec<Incident>, // всплески ошибок над фоном
+    pub latency: LatencySummary, // сеть и endpoint, без декодирования на клиенте
+    pub decode: Option<DecodeSummary>,
+    pub control: Option<ControlSummary>, // только если были служебные запросы (getLatestBlock)
+    pub outliers: Option<OutlierSummary>, // только с --outlier-ms
+    pub consistency: Option<ConsistencySummary>, // только с --hash-responses
+    pub auto_tune: Vec<TuneResult>,
+    pub apdex: Option<ApdexReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<NodeSummary>, // только если запросы шли на несколько endpoint
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<KeySummary>, // только с пулом API ключей
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub affinity: Vec<GroupSummary>, // только с --affinity: сначала группа с привязкой
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scenarios: Vec<ScenarioSummary>,
+    // Диапазон выданных ID: по нему прогон можно найти в логах прокси или дампе трафика
+    pub request_ids: Option<RequestIdRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_errors: Option<ErrorLogSummary>, // последние ошибки воркеров (--last-errors), None - ошибок не было
+    #[serde(skip)]
+    pub latencies_micros: Vec<u64>,
+    #[serde(skip)]
+    pub latency_starts_unix_micros: Vec<u64>, // начало каждого запроса из latencies_micros
+}
+
+#[derive(Serialize, Debug)]
+pub struct ErrorSummary {
+    pub http: BTreeMap<String, u64>,
+    pub http_timeouts: u64,
+    pub connect_timeouts: u64,
+    pub json_parse_errors: u64,
+    pub network_errors: u64,
+    pub network: NetworkErrors, // network_errors по причине
+    pub socket_exhaustion: u64, // лимиты сокетов клиента, не сбои сети
+    pub rpc_errors: u64,
+    pub id_mismatches: u64,
+}
+
+// Предупреждение о stale keep-alive: не меньше 5 ошибок на соединениях из пула и их доля втрое выше, чем на новых
+const STALE_KEEP_ALIVE_MIN_ERRORS: u64 = 5;
+const STALE_KEEP_ALIVE_RATIO: f64 = 3.0;
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole > 0 {
+        part as f64 / whole as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+// Соединения прогона: открытые (по первому ответу на них), переиспользованные и оборванные.
+// Частые новые соединения и TLS рукопожатия объясняют всплески латентности
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ConnectionSummary {
+    pub opened: u64,
+    pub reused: u64, // ответов по уже открытому соединению
+    pub tls_handshakes: u64,
+    pub closed_by_peer: u64, // endpoint закрыл соединение, не ответив на запрос
+    pub resets: u64, // соединение сброшено (RST, broken pipe)
+    pub errors_on_reused: u64, // сетевые ошибки запросов, ушедших по соединению из пула
+    pub errors_on_new: u64, // по только что открытому соединению
+}
+
+impl ConnectionSummary {
+    // Доля ответов по переиспользованным соединениям
+    // Доля сетевых ошибок среди запросов по переиспользованным и по новым соединениям
+    pub fn error_rate_on_reused(&self) -> f64 {
+        percent(self.errors_on_reused, self.reused + self.errors_on_reused)
+    }
+
+    pub fn error_rate_on_new(&self) -> f64 {
+        percent(self.errors_on_new, self.opened + self.errors_on_new)
+    }
+
+    // На соединениях из пула ошибки заметно чаще, чем на новых: endpoint или балансировщик закрывает
+    // простаивающие keep-alive соединения раньше клиента, сам endpoint при этом доступен
+    pub fn stale_keep_alive(&self) -> bool {
+        self.errors_on_reused >= STALE_KEEP_ALIVE_MIN_ERRORS
+            && self.error_rate_on_reused() > self.error_rate_on_new() * STALE_KEEP_ALIVE_RATIO
+    }
+
+    pub fn reuse_percent(&self) -> f64 {
+        let responses = self.opened + self.reused;
+        if responses > 0 {
+            self.reused as f64 / responses as f64 * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+// Скользящее окно по завершенным секундам прогона: худшие и лучшие участки,
+// которые теряются в средних за весь прогон
+#[derive(Serialize, Debug)]
+pub struct RollingSummary {
+    pub window_secs: u64,
+    pub peak_rps: f64,
+    pub lowest_rps: f64,
+    pub worst_error_rate_percent: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PeriodReport {
+    pub period_secs: u64,
+    pub periods: Vec<PeriodSummary>,
+}
+
+// Итоги одного периода --summary-window; последний период может быть короче (length_secs)
+#[derive(Serialize, Debug)]
+pub struct PeriodSummary {
+    pub start_secs: u64,
+    pub start_utc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_unix: Option<u64>, // только с --align-buckets: граница периода по часам (первый период начат до старта)
+    pub length_secs: f64,
+    pub requests: u64,
+    pub rps: f64,
+    pub error_rate_percent: f64,
+    pub p95_ms: Option<f64>, // None - в периоде не было успешных ответов
+}
+
+// Предохранитель: порог, пауза и все срабатывания за прогон
+#[derive(Serialize, Debug)]
+pub struct BreakerSummary {
+    pub error_rate_percent: f64,
+    pub window_secs: u64,
+    pub cooldown_secs: u64,
+    pub trips: Vec<BreakerTrip>,
+}
+
+// Сколько ждали запросы, завершившиеся таймаутом
+#[derive(Serialize, Debug)]
+pub struct TimeoutSummary {
+    pub count: u64,
+    pub min_wait_ms: f64,
+    pub avg_wait_ms: f64,
+    pub max_wait_ms: f64,
+    pub histogram: Vec<HistogramBucket>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HistogramBucket {
+    pub le_ms: Option<u64>, // None - последний бакет без верхней границы
+    pub count: u64,
+}
+
+// Верхние границы бакетов гистограммы ожидания таймаута
+const TIMEOUT_BUCKETS_MS: &[u64] = &[100, 1_000, 5_000, 10_000, 30_000, 60_000];
+
+fn timeout_summary(mut waits: Vec<u64>) -> Option<TimeoutSummary> {
+    if waits.is_empty() {
+        return None;
+    }
+    waits.sort_unstable();
+    let mut histogram: Vec<HistogramBucket> = TIMEOUT_BUCKETS_MS
+        .iter()
+        .map(|&le| HistogramBucket { le_ms: Some(le), count: 0 })
+        .chain(std::iter::once(HistogramBucket { le_ms: None, count: 0 }))
+        .collect();
+    for &wait in &waits {
+        let ms = wait / 1000;
+        let index = TIMEOUT_BUCKETS_MS
+            .iter()
+            .position(|&le| ms <= le)
+            .unwrap_or(TIMEOUT_BUCKETS_MS.len());
+        histogram[index].count += 1;
+    }
+    let sum: u64 = waits.iter().sum();
+    Some(TimeoutSummary {
+        count: waits.len() as u64,
+        min_wait_ms: waits[0] as f64 / 1000.0,
+        avg_wait_ms: sum as f64 / waits.len() as f64 / 1000.0,
+        max_wait_ms: waits[waits.len() - 1] as f64 / 1000.0,
+        histogram,
+    })
+}
+
+#[derive(Serialize, Debug)]
+pub struct BatchSummary {
+    pub batches: u64,
+    pub missing_responses: u64,
+    pub duplicate_responses: u64,
+    pub unexpected_ids: u64,
+    pub out_of_order_batches: u64,
+}
+
+// Значения заголовка за интервал: min/max для числовых значений, иначе последнее
+#[derive(Serialize, Debug)]
+pub struct HeaderPoint {
+    pub interval_start_secs: u64,
+    pub interval_start_utc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_start_unix: Option<u64>, // только с --align-buckets
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub last: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CreditSummary {
+    pub total: u64,
+    pub per_second: f64,
+    pub projected_30_days: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ApdexReport {
+    pub thresholds: ApdexThresholds,
+    pub methods: Vec<ApdexSummary>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RequestIdRange {
+    pub first: u64,
+    pub last: u64,
+}
+
+// Размеры тел ответов: на проводе (как пришли) и после распаковки
+#[derive(Serialize, Debug)]
+pub struct ResponseSizeSummary {
+    pub accept_encoding: AcceptEncoding,
+    pub responses: u64,
+    pub wire_bytes: u64,
+    pub decoded_bytes: u64,
+    pub average_wire_bytes: f64,
+    pub average_decoded_bytes: f64,
+    pub compression_ratio: f64, // decoded / wire
+}
+
+// Сжатие тел запросов (--gzip-requests): rejected - ответы 415 или JSON-RPC ошибка разбора (-32700)
+#[derive(Serialize, Debug)]
+pub struct RequestCompressionSummary {
+    pub requests: u64,
+    pub raw_bytes: u64,
+    pub sent_bytes: u64,
+    pub compression_ratio: f64, // raw / sent
+    pub rejected: u64,
+}
+
+// ID корреляции (--correlation-header): ID запросов прогона - <run_id>-<воркер>-<номер запроса воркера>
+#[derive(Serialize, Debug)]
+pub struct CorrelationSummary {
+    pub header: String,
+    pub run_id: String,
+}
+
+// Объявленный дедлайн (--deadline-header): сколько успешных ответов пришло позже него
+#[derive(Serialize, Debug)]
+pub struct DeadlineSummary {
+    pub header: String,
+    pub deadline_ms: u64,
+    pub late_responses: u64,
+    pub late_percent: f64, // от успешных ответов
+}
+
+// Расход --request-budget: exhausted_after_secs - когда бюджета не хватило на очередной запрос
+#[derive(Serialize, Debug)]
+pub struct BudgetSummary {
+    pub unit: &'static str, // credits или requests
+    pub limit: u64,
+    pub spent: u64,
+    pub exhausted_after_secs: Option<f64>,
+    pub projected_full_duration: Option<f64>, // сколько ушло бы за всю заданную длительность
+}
+
+#[derive(Serialize, Debug)]
+pub struct LatencySummary {
+    pub average_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub stddev_ms: f64,
+    pub iqr_ms: f64, // p75 - p25
+    pub jitter_ms: f64, // средняя разница латентности соседних по времени отправки запросов
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub shifts: Vec<LatencyShift>, // смены режима латентности за прогон
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentiles: Option<PercentileReport>, // None - успешных ответов не было
+}
+
+// Распаковка и разбор JSON ответов на клиенте
+#[derive(Serialize, Debug)]
+pub struct DecodeSummary {
+    pub average_ms: f64,
+    pub max_ms: f64,
+    pub share_percent: f64, // доля в полном времени запроса (сеть + декодирование)
+}
+
+// Служебные запросы воркеров: идут к тому же endpoint, но не входят в запросы, ошибки и латентность нагрузки
+#[derive(Serialize, Debug)]
+pub struct ControlSummary {
+    pub requests: u64,
+    pub errors: u64,
+    pub average_ms: f64,
+}
+
+// Разброс латентности: стандартное отклонение, межквартильный размах и джиттер (RFC 3550: среднее
+// |разницы| между соседними запросами; соседние - по времени отправки, а не по порядку ответов)
+fn spread_ms(times: &[u64], starts: &[u64], average_ms: f64) -> (f64, f64, f64) {
+    if times.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+    let variance = times.iter().map(|&t| (t as f64 / 1000.0 - average_ms).powi(2)).sum::<f64>() / times.len() as f64;
+    let mut sorted = times.to_vec();
+    sorted.sort_unstable();
+    let quartile = |q: f64| sorted[((sorted.len() as f64 * q).ceil() as usize).saturating_sub(1)] as f64 / 1000.0;
+    let mut by_start: Vec<(u64, u64)> = starts.iter().copied().zip(times.iter().copied()).collect();
+    by_start.sort_unstable();
+    let jitter = if by_start.len() > 1 {
+        let sum: u64 = by_start.windows(2).map(|pair| pair[0].1.abs_diff(pair[1].1)).sum();
+        sum as f64 / (by_start.len() - 1) as f64 / 1000.0
+    } else {
+        0.0
+    };
+    (variance.sqrt(), quartile(0.75) - quartile(0.25), jitter)
+}
+
+impl Stats {
+    // Собирает итоговые метрики из состояния, забранного у агрегатора
+    pub fn summarize(&self, state: StatsState, tags: &BTreeMap<String, String>) -> Summary {
+        let total = state.total_requests;
+        let successful = state.successful_requests;
+        let elapsed_secs = self.started_at.elapsed().as_secs_f64();
+
+        let times = state.response_times;
+
+        let average_ms = if !times.is_empty() {
+            let sum: u64 = times.iter().sum();
+            (sum as f64 / times.len() as f64) / 1000.0 // конвертируем в миллисекунды
+        } else {
+            0.0
+        };
+        let min_ms = times.iter().min().map(|&t| t as f64 / 1000.0).unwrap_or(0.0);
+        let max_ms = times.iter().max().map(|&t| t as f64 / 1000.0).unwrap_or(0.0);
+        let (stddev_ms, iqr_ms, jitter_ms) = spread_ms(&times, &state.response_starts, average_ms);
+
+        let decode_times = state.decode_times;
+        let decode = (!decode_times.is_empty()).then(|| {
+            let average = decode_times.iter().sum::<u64>() as f64 / decode_times.len() as f64 / 1000.0;
+            DecodeSummary {
+                average_ms: average,
+                max_ms: decode_times.iter().max().copied().unwrap_or(0) as f64 / 1000.0,
+                share_percent: if average + average_ms > 0.0 {
+                    average / (average + average_ms) * 100.0
+                } else {
+                    0.0
+                },
+            }
+        });
+
+        let control = (state.control_requests > 0).then(|| ControlSummary {
+            requests: state.control_requests,
+            errors: state.control_errors,
+            average_ms: state.control_micros as f64 / state.control_requests as f64 / 1000.0,
+        });
+
+        let success_rate = if total > 0 {
+            (successful as f64 / total as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        let batch = (state.batches > 0).then_some(BatchSummary {
+            batches: state.batches,
+            missing_responses: state.batch_missing,
+            duplicate_responses: state.batch_duplicates,
+            unexpected_ids: state.batch_unexpected,
+            out_of_order_batches: state.batch_out_of_order,
+        });
+
+        let credits_used = state.credits_used;
+        let credits = (credits_used > 0).then(|| {
+            let per_second = credits_used as f64 / elapsed_secs.max(0.001);
+            CreditSummary {
+                total: credits_used,
+                per_second,
+                projected_30_days: per_second * 30.0 * 24.0 * 3600.0,
+            }
+        });
+
+        Summary {
+            tags: tags.clone(),
+            endpoint: None,
+            environment: None,
+            correlation: None,
+            clock: None,
+            started_utc: self.clock.utc(0.0),
+            finished_utc: self.clock.utc(elapsed_secs),
+            elapsed_secs,
+            total_requests: total,
+            successful_requests: successful,
+            success_rate,
+            errors: ErrorSummary {
+                http: state.http_errors,
+                http_timeouts: state.http_timeouts,
+                connect_timeouts: state.connect_timeouts,
+                json_parse_errors: state.json_parse_errors,
+                network_errors: state.network_errors,
+                network: state.network_kinds,
+                socket_exhaustion: state.socket_exhaustion_errors,
+                rpc_errors: state.rpc_errors,
+                id_mismatches: state.id_mismatches,
+            },
+            rolling: state.rolling,
+            periods: None,
+            batch,
+            timeouts: timeout_summary(state.timeout_waits),
+            near_timeout_successes: state.near_timeout_successes,
+            connections: Some(state.connections.clone())
+                .filter(|c| c.opened + c.reused + c.errors_on_reused + c.errors_on_new > 0),
+            worker_crashes: state.worker_crashes,
+            parse_disabled: false,
+            headers: header_trajectory(state.header_samples, &self.clock),
+            credits,
+            budget: None,
+            response_sizes: None,
+            request_compression: None,
+            account_reads: accounts::summarize(state.account_reads),
+            deadline: None,
+            recovery: None,
+            snapshot: None,
+            side_channel: sidechannel::summarize(state.polls, &self.clock),
+            addresses: None,
+            dns_refresh: None,
+            consistency: None,
+            outliers: None,
+            breaker: None,
+            incidents: state.incidents,
+            latency: LatencySummary {
+                average_ms,
+                min_ms,
+                max_ms,
+                stddev_ms,
+                iqr_ms,
+                jitter_ms,
+                shifts: changepoints::detect(&times, &state.response_starts, &self.clock),
+                percentiles: None,
+            },
+            decode,
+            control,
+            auto_tune: Vec::new(),
+            apdex: None,
+            nodes: node_summaries(&state.nodes),
+            api_keys: state.api_keys.values().map(|counters| counters.summarize()).collect(),
+            affinity: state
+                .affinity
+                .iter()
+                .rev()
+                .map(|(pinned, counters)| counters.summarize(*pinned))
+                .collect(),
+            request_ids: None,
+            last_errors: None,
+            scenarios: state.scenarios.iter().map(|(name, counters)| counters.summarize(name)).collect(),
+            latencies_micros: times,
+            latency_starts_unix_micros: state.response_starts.iter().map(|&micros| self.clock.unix_micros(micros)).collect(),
+        }
+    }
+}
+
+// Сводка по endpoint нужна только при fan-out на несколько нод
+fn node_summaries(nodes: &BTreeMap<String, NodeStats>) -> Vec<NodeSummary> {
+    if nodes.len() < 2 {
+        return Vec::new();
+    }
+    nodes.iter().map(|(url, counters)| counters.summarize(url)).collect()
+}
+
+// Значения заголовка по интервалам: начало интервала от старта и по часам (--align-buckets)
+type HeaderIntervals = BTreeMap<(u64, Option<u64>), Vec<String>>;
+
+// Траектория захваченных заголовков: для каждого интервала диапазон значений по всем воркерам
+fn header_trajectory(samples: Vec<HeaderSample>, clock: &BucketClock) -> BTreeMap<String, Vec<HeaderPoint>> {
+    let mut by_header: BTreeMap<String, HeaderIntervals> = BTreeMap::new();
+    for sample in samples {
+        by_header
+            .entry(sample.name)
+            .or_default()
+            .entry((sample.interval_start_secs, sample.interval_start_unix))
+            .or_default()
+            .push(sample.value);
+    }
+
+    by_header
+        .into_iter()
+        .map(|(name, intervals)| {
+            let points = intervals
+                .into_iter()
+                .map(|((start, start_unix), values)| {
+                    let numbers: Vec<f64> = values.iter().filter_map(|v| v.trim().parse().ok()).collect();
+                    let numeric = !numbers.is_empty() && numbers.len() == values.len();
+                    HeaderPoint {
+                        interval_start_secs: start,
+                        interval_start_utc: match start_unix {
+                            Some(unix) => utc_millis(unix * 1_000_000),
+                            None => clock.utc(start as f64),
+                        },
+                        interval_start_unix: start_unix,
+                        min: numeric.then(|| numbers.iter().cloned().fold(f64::INFINITY, f64::min)),
+                        max: numeric.then(|| numbers.iter().cloned().fold(f64::NEG_INFINITY, f64::max)),
+                        last: values.last().cloned().unwrap_or_default(),
+                    }
+                })
+                .collect();
+            (name, points)
+        })
+        .collect()
+}
+
+impl Summary {
+    pub fn render(&self, lang: Lang) -> String {
+        let mut out = String::new();
+        // Запись в String не может завершиться ошибкой
+        let _ = self.write_text(&mut out, texts(lang));
+        out
+    }
+
+    fn write_text(&self, out: &mut String, t: &Texts) -> std::fmt::Result {
+        writeln!(out, "\n{}", t.statistics_title)?;
+        if !self.tags.is_empty() {
+            writeln!(out, "{}: {}", t.tags, format_tags(&self.tags))?;
+        }
+        writeln!(out, "{}: {} - {}", t.run_time_utc, self.started_utc, self.finished_utc)?;
+        if let Some(environment) = &self.environment {
+            writeln!(out, "{}: {}", t.client_environment, environment.describe(t))?;
+        }
+        if let Some(endpoint) = &self.endpoint {
+            let unknown = || "-".to_string();
+            writeln!(
+                out,
+                "{}: solana-core {}, feature-set {}",
+                t.endpoint_version,
+                endpoint.solana_core().unwrap_or("-"),
+                endpoint.feature_set().map_or_else(unknown, |f| f.to_string())
+            )?;
+            writeln!(out, "{}: {}", t.genesis_hash, endpoint.genesis_hash.as_deref().unwrap_or("-"))?;
+            writeln!(out, "{}: {}", t.node_identity, endpoint.identity.as_deref().unwrap_or("-"))?;
+        }
+        if let Some(correlation) = &self.correlation {
+            writeln!(out, "{} ({}): {}-*", t.correlation_ids, correlation.header, correlation.run_id)?;
+        }
+        writeln!(out, "{}: {}", t.total_requests, self.total_requests)?;
+        writeln!(out, "{}: {} ({:.2}%)", t.successful, self.successful_requests, self.success_rate)?;
+        if let Some(rolling) = &self.rolling {
+            writeln!(
+                out,
+                "{} ({} s): {} {:.1} req/s, {} {:.1} req/s, {} {:.2}%",
+                t.rolling_window,
+                rolling.window_secs,
+                t.peak,
+                rolling.peak_rps,
+                t.lowest,
+                rolling.lowest_rps,
+                t.worst_error_rate,
+                rolling.worst_error_rate_percent
+            )?;
+        }
+        writeln!(out, "\n{}:", t.errors)?;
+
+        // Выводим HTTP ошибки по каждому статусу
+        for (error_name, count) in &self.errors.http {
+            writeln!(out, "  {}: {}", error_name, count)?;
+        }
+        writeln!(out, "  {}: {}", t.http_timeouts, self.errors.http_timeouts)?;
+        writeln!(out, "  {}: {}", t.connect_timeouts, self.errors.connect_timeouts)?;
+        writeln!(out, "  {}: {}", t.json_parse_errors, self.errors.json_parse_errors)?;
+        writeln!(out, "  {}: {}", t.network_errors, self.errors.network_errors)?;
+        if self.errors.network_errors > 0 {
+            let network = &self.errors.network;
+            writeln!(
+                out,
+                "    DNS: {}, {}: {}, {}: {}, TLS: {}, {}: {}",
+                network.dns,
+                t.connection_refused,
+                network.refused,
+                t.connection_reset,
+                network.reset,
+                network.tls,
+                t.other_lower,
+                network.other
+            )?;
+        }
+        if self.errors.socket_exhaustion > 0 {
+            writeln!(out, "  {}: {}", t.socket_exhaustion_errors, self.errors.socket_exhaustion)?;
+        }
+        writeln!(out, "  {}: {}", t.rpc_errors, self.errors.rpc_errors)?;
+        writeln!(out, "  {}: {}", t.id_mismatches, self.errors.id_mismatches)?;
+
+        if !self.incidents.is_empty() {
+            writeln!(out, "\n{}:", t.error_incidents)?;
+            for incident in &self.incidents {
+                // Время UTC без даты и миллисекунд
+                let time = |utc: &str| utc.get(11..19).unwrap_or_default().to_string();
+                writeln!(
+                    out,
+                    "  [{:.0}s - {:.0}s, {} - {} UTC] {} {}, {} {:.2}% ({} {:.2}%), {} {} ({})",
+                    incident.start_secs,
+                    incident.end_secs,
+                    time(&incident.start_utc),
+                    time(&incident.end_utc),
+                    incident.requests,
+                    t.requests_lower,
+                    t.errors_lower,
+                    incident.error_rate_percent,
+                    t.baseline,
+                    incident.baseline_error_rate_percent,
+                    t.mostly,
+                    incident.dominant_error,
+                    incident.dominant_errors
+                )?;
+            }
+        }
+
+        if let Some(periods) = &self.periods {
+            writeln!(out, "\n{} ({} s):", t.summary_by_period, periods.period_secs)?;
+            for period in &periods.periods {
+                let p95 = period.p95_ms.map_or_else(|| "-".to_string(), |ms| format!("{:.2} ms", ms));
+                // С --align-buckets - границы периода по часам UTC
+                let range = match period.start_unix {
+                    Some(unix) => format!("{} - {} UTC", clock_time(unix), clock_time(unix + periods.period_secs)),
+                    None => format!("{}s - {:.0}s", period.start_secs, period.start_secs as f64 + period.length_secs),
+                };
+                writeln!(
+                    out,
+                    "  [{}] {} {}, {:.1} req/s, {} {:.2}%, p95 {}",
+                    range,
+                    period.requests,
+                    t.requests_lower,
+                    period.rps,
+                    t.errors_lower,
+                    period.error_rate_percent,
+                    p95
+                )?;
+            }
+        }
+
+        if let Some(connections) = &self.connections {
+            writeln!(out, "\n{}:", t.connections)?;
+            writeln!(out, "  {}: {}", t.connections_opened, connections.opened)?;
+            writeln!(
+                out,
+                "  {}: {} ({:.2}% {})",
+                t.connections_reused,
+                connections.reused,
+                connections.reuse_percent(),
+                t.of_responses
+            )?;
+            if connections.tls_handshakes > 0 {
+                writeln!(out, "  {}: {}", t.tls_handshakes, connections.tls_handshakes)?;
+            }
+            writeln!(out, "  {}: {}", t.connections_closed_by_peer, connections.closed_by_peer)?;
+            writeln!(out, "  {}: {}", t.connection_resets, connections.resets)?;
+            writeln!(
+                out,
+                "  {}: {} ({:.2}% {})",
+                t.errors_on_reused,
+                connections.errors_on_reused,
+                connections.error_rate_on_reused(),
+                t.of_requests_on_them
+            )?;
+            writeln!(
+                out,
+                "  {}: {} ({:.2}% {})",
+                t.errors_on_new,
+                connections.errors_on_new,
+                connections.error_rate_on_new(),
+                t.of_requests_on_them
+            )?;
+        }
+
+        if let Some(batch) = &self.batch {
+            writeln!(out, "\n{} ({} {}):", t.batch_anomalies, batch.batches, t.batches)?;
+            writeln!(out, "  {}: {}", t.missing_responses, batch.missing_responses)?;
+            writeln!(out, "  {}: {}", t.duplicate_responses, batch.duplicate_responses)?;
+            writeln!(out, "  {}: {}", t.unexpected_ids, batch.unexpected_ids)?;
+            writeln!(out, "  {}: {}", t.out_of_order_batches, batch.out_of_order_batches)?;
+        }
+
+        if let Some(timeouts) = &self.timeouts {
+            writeln!(out, "\n{} ({}):", t.timeout_waits, timeouts.count)?;
+            writeln!(
+                out,
+                "  {} {:.2} ms, {} {:.2} ms, {} {:.2} ms",
+                t.minimum, timeouts.min_wait_ms, t.average, timeouts.avg_wait_ms, t.maximum, timeouts.max_wait_ms
+            )?;
+            for bucket in timeouts.histogram.iter().filter(|b| b.count > 0) {
+                match bucket.le_ms {
+                    Some(le) => writeln!(out, "  <= {} ms: {}", le, bucket.count)?,
+                    None => writeln!(out, "  > {} ms: {}", TIMEOUT_BUCKETS_MS[TIMEOUT_BUCKETS_MS.len() - 1], bucket.count)?,
+                }
+            }
+        }
+        if self.near_timeout_successes > 0 {
+            writeln!(
+                out,
+                "\n{}: {} {}",
+                t.warning, self.near_timeout_successes, t.near_timeout_warning
+            )?;
+        }
+        if self.worker_crashes > 0 {
+            writeln!(
+                out,
+                "\n{}: {} {}",
+                t.warning, self.worker_crashes, t.worker_crashes_warning
+            )?;
+        }
+        if self.parse_disabled {
+            writeln!(out, "\n{}: {}", t.warning, t.parse_disabled_warning)?;
+        }
+        if self.connections.as_ref().is_some_and(ConnectionSummary::stale_keep_alive) {
+            writeln!(out, "\n{}: {}", t.warning, t.stale_keep_alive_warning)?;
+        }
+        if self.clock.as_ref().is_some_and(|clock| clock.date_skewed() || clock.block_time_skewed()) {
+            writeln!(out, "\n{}: {}", t.warning, t.clock_skew_warning)?;
+        }
+
+        if !self.headers.is_empty() {
+            writeln!(out, "\n{}:", t.response_headers)?;
+            for (name, points) in &self.headers {
+                writeln!(out, "  {}:", name)?;
+                for point in points {
+                    let start = match point.interval_start_unix {
+                        Some(unix) => clock_time(unix),
+                        None => format!("{}s", point.interval_start_secs),
+                    };
+                    match (point.min, point.max) {
+                        (Some(min), Some(max)) if min != max => writeln!(out, "    [{}] {} - {}", start, min, max)?,
+                        (Some(min), _) => writeln!(out, "    [{}] {}", start, min)?,
+                        _ => writeln!(out, "    [{}] {}", start, point.last)?,
+                    }
+                }
+            }
+        }
+
+        if let Some(credits) = &self.credits {
+            writeln!(out, "\n{}:", t.credits)?;
+            writeln!(out, "  {}: {}", t.total, credits.total)?;
+            writeln!(out, "  {}: {:.2}", t.per_second, credits.per_second)?;
+            writeln!(out, "  {}: {:.0}", t.projected_30_days, credits.projected_30_days)?;
+        }
+
+        if let Some(breaker) = &self.breaker {
+            writeln!(
+                out,
+                "\n{} ({} >= {:.1}% / {} s, {} {} s): {} {}",
+                t.circuit_breaker,
+                t.error_rate,
+                breaker.error_rate_percent,
+                breaker.window_secs,
+                t.cooldown,
+                breaker.cooldown_secs,
+                t.trips,
+                breaker.trips.len()
+            )?;
+            for trip in &breaker.trips {
+                writeln!(
+                    out,
+                    "  [{}s] {} {:.2}%, {} {}",
+                    trip.at_secs, t.error_rate, trip.error_rate_percent, t.requests_in_window, trip.window_requests
+                )?;
+            }
+        }
+
+        if let Some(sizes) = &self.response_sizes {
+            writeln!(
+                out,
+                "\n{} (Accept-Encoding: {}):",
+                t.response_sizes,
+                sizes.accept_encoding.header_value().unwrap_or("none")
+            )?;
+            writeln!(out, "  {}: {}", t.responses, sizes.responses)?;
+            writeln!(
+                out,
+                "  {}: {} ({:.0} {})",
+                t.wire_size,
+                format_bytes(sizes.wire_bytes),
+                sizes.average_wire_bytes,
+                t.bytes_per_response
+            )?;
+            writeln!(
+                out,
+                "  {}: {} ({:.0} {})",
+                t.decoded_size,
+                format_bytes(sizes.decoded_bytes),
+                sizes.average_decoded_bytes,
+                t.bytes_per_response
+            )?;
+            writeln!(out, "  {}: {:.2}x", t.compression_ratio, sizes.compression_ratio)?;
+        }
+
+        if !self.account_reads.is_empty() {
+            writeln!(out, "\n{}:", t.account_reads)?;
+            for reads in &self.account_reads {
+                writeln!(
+                    out,
+                    "  {}: {} {}, {} {:.2} ms",
+                    reads.encoding, t.responses, reads.responses, t.average, reads.average_ms
+                )?;
+                writeln!(
+                    out,
+                    "    {}: {:.0} B, {}: {:.0} B ({})",
+                    t.wire_size, reads.average_wire_bytes, t.decoded_size, reads.average_decoded_bytes, t.bytes_per_response
+                )?;
+                write!(out, "    {}: {:.0} B", t.account_data, reads.average_data_bytes)?;
+                if let Some(size) = reads.average_account_bytes {
+                    write!(out, ", {}: {:.0} B", t.account_size, size)?;
+                }
+                writeln!(out)?;
+                writeln!(out, "    {}: {:.3} ms", t.decode_on_client, reads.average_decode_ms)?;
+            }
+        }
+
+        if let Some(clock) = &self.clock {
+            writeln!(out, "\n{}:", t.clock_check)?;
+            match (clock.date_offset_ms, clock.date_uncertainty_ms) {
+                (Some(offset), Some(uncertainty)) => writeln!(
+                    out,
+                    "  HTTP Date: {:+.0} ms (± {:.0} ms), {}",
+                    offset, uncertainty, t.endpoint_clock_relative
+                )?,
+                _ => writeln!(out, "  HTTP Date: {}", t.no_date_header)?,
+            }
+            if let Some(slot) = clock.block_slot {
+                match clock.block_time_offset_secs {
+                    Some(offset) => writeln!(
+                        out,
+                        "  {} ({} {}): {:+} s, {}",
+                        t.block_time, t.slot, slot, offset, t.endpoint_clock_relative
+                    )?,
+                    None => writeln!(out, "  {} ({} {}): -", t.block_time, t.slot, slot)?,
+                }
+            }
+        }
+
+        if let Some(deadline) = &self.deadline {
+            writeln!(out, "\n{} ({}: {} ms):", t.declared_deadline, deadline.header, deadline.deadline_ms)?;
+            writeln!(
+                out,
+                "  {}: {} ({:.2}% {})",
+                t.late_responses, deadline.late_responses, deadline.late_percent, t.of_successful
+            )?;
+        }
+
+        if let Some(compression) = &self.request_compression {
+            writeln!(out, "\n{} (Content-Encoding: gzip):", t.request_compression)?;
+            writeln!(
+                out,
+                "  {}: {}, {} -> {} ({:.2}x)",
+                t.requests,
+                compression.requests,
+                format_bytes(compression.raw_bytes),
+                format_bytes(compression.sent_bytes),
+                compression.compression_ratio
+            )?;
+            let verdict = if compression.rejected == 0 {
+                t.compression_accepted
+            } else if compression.rejected >= compression.requests {
+                t.compression_rejected
+            } else {
+                t.compression_partially_rejected
+            };
+            writeln!(out, "  {}: {} ({} {})", t.endpoint, verdict, compression.rejected, t.rejected_responses)?;
+        }
+
+        if let Some(budget) = &self.budget {
+            let unit = if budget.unit == "credits" { t.budget_credits } else { t.budget_requests };
+            writeln!(out, "\n{} ({}):", t.request_budget, unit)?;
+            writeln!(out, "  {}: {}", t.limit, budget.limit)?;
+            writeln!(out, "  {}: {}", t.spent, budget.spent)?;
+            if let Some(secs) = budget.exhausted_after_secs {
+                writeln!(out, "  {}: {:.2} s", t.exhausted_after, secs)?;
+            }
+            if let Some(projected) = budget.projected_full_duration {
+                writeln!(out, "  {}: {:.0}", t.projected_full_duration, projected)?;
+            }
+        }
+
+        if let Some(apdex) = &self.apdex {
+            writeln!(
+                out,
+                "\nApdex (T = {} ms, F = {} ms):",
+                apdex.thresholds.satisfied_ms, apdex.thresholds.tolerating_ms
+            )?;
+            for method in &apdex.methods {
+                writeln!(
+                    out,
+                    "  {}: {:.3} ({} {}, {} {}, {} {})",
+                    method.method,
+                    method.score,
+                    t.satisfied,
+                    method.satisfied,
+                    t.tolerating,
+                    method.tolerating,
+                    t.frustrated,
+                    method.frustrated
+                )?;
+            }
+        }
+
+        if !self.scenarios.is_empty() {
+            writeln!(out, "\n{}:", t.scenarios)?;
+            for scenario in &self.scenarios {
+                writeln!(
+                    out,
+                    "  {}: {} {}, {} {}, {} {}",
+                    scenario.name,
+                    scenario.started,
+                    t.iterations,
+                    scenario.completed,
+                    t.completed,
+                    scenario.failed,
+                    t.failed
+                )?;
+                for failures in &scenario.failed_at {
+                    writeln!(
+                        out,
+                        "    {} {} ({}): {}",
+                        t.failed_at_step, failures.step, failures.method, failures.count
+                    )?;
+                }
+                if let Some(latency) = &scenario.latency {
+                    writeln!(
+                        out,
+                        "    {}: {} {:.2} ms, p50 {:.2} ms, p95 {:.2} ms, p99 {:.2} ms, {} {:.2} ms",
+                        t.iteration_latency,
+                        t.average,
+                        latency.average_ms,
+                        latency.p50_ms,
+                        latency.p95_ms,
+                        latency.p99_ms,
+                        t.maximum,
+                        latency.max_ms
+                    )?;
+                }
+            }
+        }
+
+        if let Some(addresses) = &self.addresses {
+            writeln!(out, "\n{} ({}):", t.endpoint_addresses, addresses.host)?;
+            let resolved: Vec<String> = addresses.resolved.iter().map(|ip| ip.to_string()).collect();
+            match &addresses.resolve_error {
+                Some(error) => writeln!(out, "  {}: - ({})", t.resolved_before_test, error)?,
+                None => writeln!(out, "  {}: {}", t.resolved_before_test, resolved.join(", "))?,
+            }
+            for address in &addresses.used {
+                let not_resolved = if address.resolved { String::new() } else { format!(" ({})", t.not_resolved_before_test) };
+                writeln!(
+                    out,
+                    "  {}{}: {} {} ({:.2}%), {}: {} {:.2} ms, {} {:.2} ms",
+                    address.ip,
+                    not_resolved,
+                    address.responses,
+                    t.responses_lower,
+                    address.share_percent,
+                    t.time_to_headers,
+                    t.average,
+                    address.average_ms,
+                    t.maximum,
+                    address.max_ms
+                )?;
+                if let Some(geo) = &address.geo {
+                    writeln!(out, "    {}", geo.describe())?;
+                }
+            }
+        }
+
+        if let Some(dns) = &self.dns_refresh {
+            let list = |addresses: &[std::net::IpAddr]| addresses.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ");
+            writeln!(
+                out,
+                "\n{} ({}, {} {} s): {}, {} {}",
+                t.dns_refresh,
+                dns.host,
+                t.every_secs,
+                dns.interval_secs,
+                t.count(dns.lookups, &t.lookups),
+                dns.failures,
+                t.failed
+            )?;
+            writeln!(out, "  {}: {}", t.initial_answer, list(&dns.initial))?;
+            if dns.changes.is_empty() {
+                writeln!(out, "  {}", t.answer_unchanged)?;
+            }
+            for change in &dns.changes {
+                writeln!(out, "  {} (+{:.0} s): {}", change.at_utc, change.at_secs, list(&change.addresses))?;
+            }
+        }
+
+        if let Some(consistency) = &self.consistency {
+            writeln!(
+                out,
+                "\n{}: {} {}, {} {}",
+                t.response_consistency,
+                consistency.requests.len(),
+                t.distinct_requests,
+                consistency.inconsistent,
+                t.with_several_bodies
+            )?;
+            for request in consistency.requests.iter().take(consistency::REPORT_LIMIT) {
+                writeln!(
+                    out,
+                    "  {}: {} {}, {} {}, {} {}, {} {:.1}%",
+                    request.request,
+                    request.responses,
+                    t.responses_lower,
+                    request.distinct_bodies,
+                    t.distinct_bodies,
+                    request.changes,
+                    t.body_changes,
+                    t.most_common_body,
+                    request.most_common_percent
+                )?;
+            }
+            if consistency.requests.len() > consistency::REPORT_LIMIT {
+                writeln!(out, "  ... {} {}", consistency.requests.len() - consistency::REPORT_LIMIT, t.more_in_json)?;
+            }
+        }
+
+        if !self.nodes.is_empty() {
+            writeln!(out, "\n{}:", t.nodes)?;
+            for node in &self.nodes {
+                writeln!(
+                    out,
+                    "  {}: {}/{} {} ({:.2}%), {} {:.2} ms, {} {:.2} ms",
+                    node.url,
+                    node.successful,
+                    node.requests,
+                    t.ok,
+                    node.success_rate,
+                    t.average,
+                    node.average_ms,
+                    t.maximum,
+                    node.max_ms
+                )?;
+            }
+        }
+
+        if !self.api_keys.is_empty() {
+            writeln!(out, "\n{}:", t.api_keys)?;
+            for key in &self.api_keys {
+                writeln!(
+                    out,
+                    "  {}: {}/{} {} ({:.2}%), {} {:.2} ms, {} {:.2} ms",
+                    key.key,
+                    key.successful,
+                    key.requests,
+                    t.ok,
+                    key.success_rate,
+                    t.average,
+                    key.average_ms,
+                    t.maximum,
+                    key.max_ms
+                )?;
+            }
+        }
+
+        if !self.affinity.is_empty() {
+            writeln!(out, "\n{}:", t.session_affinity)?;
+            for group in &self.affinity {
+                write!(
+                    out,
+                    "  {}: {}/{} {} ({:.2}%), {} {:.2} ms, {} {:.2} ms",
+                    if group.pinned { t.with_affinity } else { t.without_affinity },
+                    group.successful,
+                    group.requests,
+                    t.ok,
+                    group.success_rate,
+                    t.average,
+                    group.average_ms,
+                    t.maximum,
+                    group.max_ms
+                )?;
+                if group.pinned {
+                    write!(out, ", {} {}, {} {}", t.sessions_pinned, group.pinned_sessions, t.reassigned, group.reassignments)?;
+                }
+                writeln!(out)?;
+            }
+        }
+
+        if !self.auto_tune.is_empty() {
+            writeln!(out, "\n{}:", t.auto_tune)?;
+            for result in &self.auto_tune {
+                match (result.optimal_workers, result.p95_ms) {
+                    (Some(workers), Some(p95)) => writeln!(
+                        out,
+                        "  {}: {} (p95 {:.2} ms, {} {:.2} ms)",
+                        result.method, t.count(workers as u64, &t.workers), p95, t.target, result.target_p95_ms
+                    )?,
+                    _ => writeln!(
+                        out,
+                        "  {}: {} {:.2} ms",
+                        result.method, t.target_not_reached, result.target_p95_ms
+                    )?,
+                }
+            }
+        }
+
+        if let Some(recovery) = &self.recovery {
+            writeln!(out, "\n{}:", t.recovery_check)?;
+            let phase_line = |phase: &PhaseSummary| {
+                let ms = |value: Option<f64>| value.map_or("-".to_string(), |ms| format!("{:.2} ms", ms));
+                format!(
+                    "p50 {}, p95 {}, {} {:.2}% ({}/{})",
+                    ms(phase.p50_ms),
+                    ms(phase.p95_ms),
+                    t.errors_lower,
+                    phase.error_rate_percent,
+                    phase.errors,
+                    phase.probes
+                )
+            };
+            writeln!(out, "  {}: {}", t.recovery_before, phase_line(&recovery.baseline))?;
+            writeln!(
+                out,
+                "  {} ({} s): {}",
+                t.recovery_after,
+                recovery.duration_secs,
+                phase_line(&recovery.after)
+            )?;
+            match (recovery.recovered, recovery.recovered_after_secs) {
+                (Some(true), Some(secs)) => writeln!(out, "  {}: {} {} s", t.recovered, t.after, secs)?,
+                (Some(true), None) => writeln!(out, "  {}", t.recovered)?,
+                (Some(false), _) => writeln!(out, "  {}", t.not_recovered)?,
+                (None, _) => writeln!(out, "  {}", t.baseline_unavailable)?,
+            }
+        }
+
+        if let Some(snapshot) = &self.snapshot {
+            writeln!(out, "\n{}:", t.snapshot_downloads)?;
+            for file in &snapshot.files {
+                match (&file.error, file.size_bytes) {
+                    (Some(error), _) => writeln!(out, "  {}: {} ({})", file.path, t.unavailable, error)?,
+                    (None, Some(size)) => writeln!(
+                        out,
+                        "  {}: {} ({:.1} MB)",
+                        file.path,
+                        file.url.as_deref().unwrap_or_default(),
+                        size as f64 / 1024.0 / 1024.0
+                    )?,
+                    (None, None) => {
+                        writeln!(out, "  {}: {} ({})", file.path, file.url.as_deref().unwrap_or_default(), t.no_range_support)?
+                    }
+                }
+            }
+            writeln!(
+                out,
+                "  {}: {:.1} MB, {:.2} MB/s ({} x {} MB), {} {}, {} {}",
+                t.downloaded,
+                snapshot.bytes as f64 / 1024.0 / 1024.0,
+                snapshot.mb_per_sec,
+                snapshot.workers,
+                snapshot.range_mb,
+                t.requests_lower,
+                snapshot.requests,
+                t.errors_lower,
+                snapshot.errors
+            )?;
+            write!(out, "  {}: {}", t.complete_files, snapshot.completed_files)?;
+            if let Some(ttfb) = snapshot.time_to_first_byte_ms {
+                write!(out, ", {} {:.2} ms", t.time_to_first_byte, ttfb)?;
+            }
+            writeln!(out)?;
+        }
+
+        if !self.side_channel.is_empty() {
+            writeln!(out, "\n{}:", t.side_channel)?;
+            for url in &self.side_channel {
+                writeln!(
+                    out,
+                    "  {}: {}, {} {}, {} {:.2} ms",
+                    url.url, t.count(url.polls as u64, &t.polls), url.errors, t.errors_lower, t.average_lower, url.average_ms
+                )?;
+                for point in &url.points {
+                    write!(out, "    [{:.0}s] ", point.at_secs)?;
+                    if let Some(status) = point.status {
+                        write!(out, "HTTP {}, ", status)?;
+                    }
+                    write!(out, "{:.2} ms", point.ms)?;
+                    let metrics: Vec<String> = point.metrics.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+                    match (&point.error, &point.text) {
+                        (Some(error), _) => write!(out, ": {}", error)?,
+                        (None, Some(text)) if !text.is_empty() => write!(out, ": {}", text)?,
+                        _ if !metrics.is_empty() => write!(out, ": {}", metrics.join(", "))?,
+                        _ => {}
+                    }
+                    if let Some(client) = &point.client {
+                        write!(out, " | {:.1} req/s, {} {:.2}%", client.rps, t.errors_lower, client.error_rate_percent)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+        }
+
+        writeln!(out, "\n{}:", t.latency)?;
+        writeln!(out, "  {}: {:.2} ms", t.average, self.latency.average_ms)?;
+        if !self.latencies_micros.is_empty() {
+            writeln!(out, "  {}: {:.2} ms", t.minimum, self.latency.min_ms)?;
+            writeln!(out, "  {}: {:.2} ms", t.maximum, self.latency.max_ms)?;
+            writeln!(out, "  {}: {:.2} ms", t.standard_deviation, self.latency.stddev_ms)?;
+            writeln!(out, "  {}: {:.2} ms", t.interquartile_range, self.latency.iqr_ms)?;
+            writeln!(out, "  {}: {:.2} ms", t.jitter, self.latency.jitter_ms)?;
+        }
+        if !self.latency.shifts.is_empty() {
+            writeln!(out, "  {}:", t.latency_shifts)?;
+            for shift in &self.latency.shifts {
+                writeln!(
+                    out,
+                    "    {} {}s ({} UTC): {:.2} ms -> {:.2} ms ({:+.0}%)",
+                    t.at_time,
+                    shift.at_secs,
+                    shift.at_utc.get(11..19).unwrap_or_default(),
+                    shift.before_ms,
+                    shift.after_ms,
+                    shift.change_percent
+                )?;
+            }
+        }
+        if let Some(report) = &self.latency.percentiles {
+            writeln!(out, "  {} ({}% {}):", t.percentiles, report.confidence_percent, t.confidence_interval)?;
+            let rps = self.successful_requests as f64 / self.elapsed_secs.max(0.001);
+            for percentile in &report.percentiles {
+                match (percentile.lower_ms, percentile.upper_ms) {
+                    (Some(lower), Some(upper)) => writeln!(
+                        out,
+                        "    {}: {:.2} ms ({:.2} - {:.2})",
+                        percentile.name, percentile.value_ms, lower, upper
+                    )?,
+                    // Точное число здесь вводит в заблуждение: округляем и пишем, сколько нужно ответов
+                    _ => {
+                        write!(
+                            out,
+                            "    {}: ~{:.0} ms, {}: {} {} {}",
+                            percentile.name,
+                            percentile.value_ms,
+                            t.low_confidence,
+                            report.samples,
+                            t.responses_needs,
+                            percentile.required_samples
+                        )?;
+                        if rps > 0.0 {
+                            let required = percentile.required_samples as f64;
+                            write!(
+                                out,
+                                " (~{:.0} s {} {:.1} req/s {} {:.1} req/s {} {:.0} s)",
+                                required / rps,
+                                t.at_rate,
+                                rps,
+                                t.or_lower,
+                                required / self.elapsed_secs.max(0.001),
+                                t.over_time,
+                                self.elapsed_secs
+                            )?;
+                        }
+                        writeln!(out)?;
+                    }
+                }
+            }
+        }
+        if let Some(decode) = &self.decode {
+            writeln!(
+                out,
+                "  {}: {} {:.2} ms, {} {:.2} ms ({:.1}% {})",
+                t.client_decode,
+                t.average,
+                decode.average_ms,
+                t.maximum,
+                decode.max_ms,
+                decode.share_percent,
+                t.of_request_time
+            )?;
+        }
+        if let Some(control) = &self.control {
+            writeln!(
+                out,
+                "  {}: {}, {} {}, {} {:.2} ms",
+                t.control_requests,
+                control.requests,
+                t.errors_lower,
+                control.errors,
+                t.average.to_lowercase(),
+                control.average_ms
+            )?;
+        }
+        if let Some(outliers) = &self.outliers {
+            writeln!(
+                out,
+                "  {} (> {} ms): {}, {} {}",
+                t.outliers, outliers.threshold_ms, outliers.captured, t.written_to, outliers.file
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn verdict(&self, min_success_rate: f64) -> Verdict {
+        let mut checks = vec![VerdictCheck {
+            name: "successful requests > 0".to_string(),
+            passed: self.successful_requests > 0,
+            violation: "no successful requests".to_string(),
+        }];
+        if min_success_rate > 0.0 {
+            checks.push(VerdictCheck {
+                name: format!("success rate >= {:.2}%", min_success_rate),
+                passed: self.success_rate >= min_success_rate,
+                violation: format!("success rate {:.2}% < {:.2}%", self.success_rate, min_success_rate),
+            });
+        }
+        let violations: Vec<String> = checks
+            .iter()
+            .filter(|check| !check.passed)
+            .map(|check| check.violation.clone())
+            .collect();
+        Verdict {
+            passed: violations.is_empty(),
+            violations,
+            checks,
+        }
+    }
+
+    pub fn error_count(&self) -> u64 {
+        self.total_requests - self.successful_requests
+    }
+
+    // Краткий отчет: несколько строк с главными метриками
+    fn render_compact(&self, verdict: &Verdict, lang: Lang) -> String {
+        let t = texts(lang);
+        let mut out = String::new();
+        let _ = writeln!(out, "\n{}", t.statistics_title);
+        if !self.tags.is_empty() {
+            let _ = writeln!(out, "{}: {}", t.tags, format_tags(&self.tags));
+        }
+        let _ = writeln!(
+            out,
+            "{}: {}, {}: {} ({:.2}%), {}: {}",
+            t.requests,
+            self.total_requests,
+            t.ok,
+            self.successful_requests,
+            self.success_rate,
+            t.failed,
+            self.error_count()
+        );
+        let _ = writeln!(
+            out,
+            "{}: {} {:.2} ms, {} {:.2} ms, {} {:.2} ms",
+            t.latency,
+            t.average,
+            self.latency.average_ms,
+            t.minimum,
+            self.latency.min_ms,
+            t.maximum,
+            self.latency.max_ms
+        );
+        let _ = writeln!(out, "{}: {}", t.verdict, self.render_oneline(verdict));
+        out
+    }
+
+    // Одна строка с вердиктом для скриптов
+    pub fn render_oneline(&self, verdict: &Verdict) -> String {
+        let mut line = format!(
+            "{} total={} ok={:.2}% errors={} avg={:.2}ms max={:.2}ms",
+            if verdict.passed { "PASS" } else { "FAIL" },
+            self.total_requests,
+            self.success_rate,
+            self.error_count(),
+            self.latency.average_ms,
+            self.latency.max_ms
+        );
+        if !verdict.violations.is_empty() {
+            let _ = write!(line, " ({})", verdict.violations.join("; "));
+        }
+        line
+    }
+
+    // Строка вердикта остается на английском: ее разбирают скрипты
+    pub fn print(&self, format: SummaryFormat, verdict: &Verdict, lang: Lang) {
+        match format {
+            SummaryFormat::Full => {
+                print!("{}", self.render(lang));
+                println!("\n{}: {}", texts(lang).verdict, self.render_oneline(verdict));
+                print!("{}", self.render_last_errors(lang));
+            }
+            SummaryFormat::Compact => {
+                print!("{}", self.render_compact(verdict, lang));
+                print!("{}", self.render_last_errors(lang));
+            }
+            SummaryFormat::Oneline => println!("{}", self.render_oneline(verdict)),
+        }
+    }
+
+    // Последние ошибки воркеров - после отчета, чтобы их было видно в конце вывода
+    fn render_last_errors(&self, lang: Lang) -> String {
+        let t = texts(lang);
+        let mut out = String::new();
+        let Some(log) = &self.last_errors else {
+            return out;
+        };
+        let _ = writeln!(out, "\n{} ({} {} {}):", t.last_errors, log.entries.len(), t.of, log.total);
+        for entry in &log.entries {
+            let _ = writeln!(
+                out,
+                "  [{:>8.3}s] {} {:<3} {:<15} {}",
+                entry.at_secs,
+                t.worker,
+                entry.worker,
+                entry.category,
+                entry.message
+            );
+        }
+        out
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 30 => format!("{:.2} GiB", b as f64 / (1u64 << 30) as f64),
+        b if b >= 1 << 20 => format!("{:.2} MiB", b as f64 / (1u64 << 20) as f64),
+        b if b >= 1 << 10 => format!("{:.2} KiB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
+
+// Текущее время UTC в виде 20261015-103726 (для имен каталогов прогонов)
+fn utc_timestamp() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let rem = secs % 86_400;
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+// Unix время в виде 2026-10-15 10:37 UTC (для списка прогонов в истории)
+pub fn format_utc(secs: u64) -> String {
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let rem = secs % 86_400;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, rem / 3600, (rem % 3600) / 60)
+}
+
+// Unix время в микросекундах в виде 2026-10-15T10:37:00.125Z (время событий в JSON и CSV)
+pub fn utc_millis(unix_micros: u64) -> String {
+    let secs = unix_micros / 1_000_000;
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60,
+        unix_micros % 1_000_000 / 1000
+    )
+}
+
+// Время суток UTC из Unix времени: 10:37:00 (границы выровненных корзин)
+fn clock_time(secs: u64) -> String {
+    let rem = secs % 86_400;
+    format!("{:02}:{:02}:{:02}", rem / 3600, (rem % 3600) / 60, rem % 60)
+}
+
+// Преобразование числа дней от 1970-01-01 в дату (алгоритм civil_from_days)
+fn civil_from_days(days: u64) -> (i64, i64, i64) {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Файл с вердиктом для оркестрации: pass/fail, нарушенные пороги и ключевые метрики
+pub fn write_verdict_file(path: &Path, summary: &Summary, verdict: &Verdict) -> Result<(), Box<dyn std::error::Error>> {
+    let content = serde_json::json!({
+        "passed": verdict.passed,
+        "violations": verdict.violations,
+        "tags": summary.tags,
+        "environment": summary.environment,
+        "metrics": {
+            "total_requests": summary.total_requests,
+            "successful_requests": summary.successful_requests,
+            "success_rate": summary.success_rate,
+            "errors": summary.error_count(),
+            "average_latency_ms": summary.latency.average_ms,
+            "max_latency_ms": summary.latency.max_ms,
+            "stddev_latency_ms": summary.latency.stddev_ms,
+            "latency_iqr_ms": summary.latency.iqr_ms,
+            "latency_jitter_ms": summary.latency.jitter_ms,
+            "elapsed_secs": summary.elapsed_secs,
+            "started_utc": summary.started_utc,
+            "finished_utc": summary.finished_utc,
+        },
+    });
+    fs::write(path, serde_json::to_string_pretty(&content)?)?;
+    Ok(())
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// JUnit XML для CI (Jenkins, GitLab): каждый порог вердикта - отдельный тест, теги - свойства набора,
+// строка вердикта - в system-out
+pub fn write_junit_file(path: &Path, summary: &Summary, verdict: &Verdict) -> Result<(), Box<dyn std::error::Error>> {
+    let failures = verdict.checks.iter().filter(|check| !check.passed).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"solana-rpc-stress-test\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        verdict.checks.len(),
+        failures,
+        summary.elapsed_secs
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"thresholds\" tests=\"{}\" failures=\"{}\" errors=\"0\" time=\"{:.3}\">",
+        verdict.checks.len(),
+        failures,
+        summary.elapsed_secs
+    );
+    if !summary.tags.is_empty() {
+        xml.push_str("    <properties>\n");
+        for (key, value) in &summary.tags {
+            let _ = writeln!(
+                xml,
+                "      <property name=\"{}\" value=\"{}\"/>",
+                xml_escape(key),
+                xml_escape(value)
+            );
+        }
+        xml.push_str("    </properties>\n");
+    }
+    for check in &verdict.checks {
+        let name = xml_escape(&check.name);
+        if check.passed {
+            let _ = writeln!(xml, "    <testcase classname=\"solana-rpc-stress-test\" name=\"{}\"/>", name);
+        } else {
+            let _ = writeln!(xml, "    <testcase classname=\"solana-rpc-stress-test\" name=\"{}\">", name);
+            let _ = writeln!(
+                xml,
+                "      <failure type=\"threshold\" message=\"{}\"/>",
+                xml_escape(&check.violation)
+            );
+            xml.push_str("    </testcase>\n");
+        }
+    }
+    let _ = writeln!(xml, "    <system-out>{}</system-out>", xml_escape(&summary.render_oneline(verdict)));
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    fs::write(path, xml)?;
+    Ok(())
+}
+
+// Создает каталог прогона <out_dir>/run-<timestamp>
+pub fn create_run_dir(out_dir: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let base = Path::new(out_dir).join(format!("run-{}", utc_timestamp()));
+    let mut dir = base.clone();
+    let mut suffix = 1;
+    while dir.exists() {
+        dir = PathBuf::from(format!("{}-{}", base.display(), suffix));
+        suffix += 1;
+    }
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Сохраняет артефакты прогона: итоговый конфиг, вердикт (JSON и JUnit XML), JSON-отчет, CSV с временами ответов и текстовый отчет
+pub fn write_run_artifacts(
+    dir: &Path,
+    resolved_config: &str,
+    summary: &Summary,
+    verdict: &Verdict,
+    lang: Lang,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(dir.join("config.toml"), resolved_config)?;
+    write_verdict_file(&dir.join("verdict.json"), summary, verdict)?;
+    write_junit_file(&dir.join("junit.xml"), summary, verdict)?;
+    fs::write(dir.join("report.json"), serde_json::to_string_pretty(summary)?)?;
+    fs::write(dir.join("summary.txt"), summary.render(lang))?;
+
+    // Теги добавляются колонками, чтобы CSV разных прогонов можно было объединять
+    let mut csv = String::new();
+    for key in summary.tags.keys() {
+        csv.push_str(key);
+        csv.push(',');
+    }
+    // Начало и конец запроса по UTC - для сопоставления с логами сервера
+    csv.push_str("latency_ms,start_utc,end_utc\n");
+    let tag_values: String = summary.tags.values().map(|v| format!("{},", v)).collect();
+    for (micros, start) in summary.latencies_micros.iter().zip(&summary.latency_starts_unix_micros) {
+        let _ = writeln!(
+            csv,
+            "{}{:.3},{},{}",
+            tag_values,
+            *micros as f64 / 1000.0,
+            utc_millis(*start),
+            utc_millis(start + micros)
+        );
+    }
+    fs::write(dir.join("latencies.csv"), csv)?;
+    Ok(())
+}
@@ -0,0 +1,700 @@
+// Сценарии: последовательность шагов-запросов, где значения из ответов предыдущих шагов
+// подставляются в params следующих через переменные ${name}
+
+use crossbeam::queue::SegQueue;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::aggregator::WorkerCounters;
+use crate::apdex::{ApdexThresholds, MethodApdex};
+use crate::feeder::{Feeder, FeederConfig};
+use crate::limits::Pacer;
+use crate::{
+    charge, deadline_micros, keyed_clients, random_seed, send_and_record, HeaderCapture, RequestContext, ResponseParser, Rng, Stats,
+    WorkerSettings,
+};
+
+// Ограничение числа повторов шага с while по умолчанию, чтобы условие-ошибка не зациклило воркер
+const DEFAULT_MAX_LOOPS: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScenarioConfig {
+    pub name: String,
+    pub workers: usize,
+    // CSV файл, столбцы которого становятся переменными итерации
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feeder: Option<FeederConfig>,
+    pub steps: Vec<StepConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StepConfig {
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+    // Имя переменной -> путь в ответе, например "$.result.transactions[0].transaction.signatures[0]"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extract: BTreeMap<String, String>,
+    // Выполнить шаг N раз подряд
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<usize>,
+    // Повторять шаг, пока условие на его ответе выполняется (например, "$.result not_empty")
+    #[serde(rename = "while", default, skip_serializing_if = "Option::is_none")]
+    pub while_condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_loops: Option<usize>,
+    // Пропустить шаг, если условие выполняется (например, "${sig} missing")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_if: Option<String>,
+    // Пауза после каждого запроса шага: think_time_ms = 200 или think_time_ms = [100, 500]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub think_time_ms: Option<ThinkTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum ThinkTime {
+    Fixed(u64),
+    Range([u64; 2]),
+}
+
+impl ThinkTime {
+    fn pick(self, rng: &mut Rng) -> Duration {
+        let ms = match self {
+            ThinkTime::Fixed(ms) => ms,
+            ThinkTime::Range([from, to]) => from.min(to) + rng.up_to(from.abs_diff(to)),
+        };
+        Duration::from_millis(ms)
+    }
+}
+
+// Итерации сценария как единицы измерения (аналог "транзакций" JMeter)
+pub struct ScenarioStats {
+    started: AtomicU64,
+    completed: AtomicU64,
+    failed_at: Vec<AtomicU64>, // по индексу шага
+    methods: Vec<String>,
+    durations: SegQueue<u64>, // микросекунды завершенных итераций
+}
+
+impl ScenarioStats {
+    pub fn new(steps: &[StepConfig]) -> Self {
+        Self {
+            started: AtomicU64::new(0),
+            completed: AtomicU64::new(0),
+            failed_at: steps.iter().map(|_| AtomicU64::new(0)).collect(),
+            methods: steps.iter().map(|step| step.method.clone()).collect(),
+            durations: SegQueue::new(),
+        }
+    }
+
+    fn record_started(&self) {
+        self.started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_completed(&self, duration_micros: u64) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        self.durations.push(duration_micros);
+    }
+
+    fn record_failed(&self, step_index: usize) {
+        self.failed_at[step_index].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        *self.started.get_mut() += other.started.into_inner();
+        *self.completed.get_mut() += other.completed.into_inner();
+        for (failed, other) in self.failed_at.iter_mut().zip(other.failed_at) {
+            *failed.get_mut() += other.into_inner();
+        }
+        while let Some(duration) = other.durations.pop() {
+            self.durations.push(duration);
+        }
+    }
+
+    // Сводка по сценарию (очередь длительностей при этом опустошается)
+    pub fn summarize(&self, name: &str) -> ScenarioSummary {
+        let failed_at: Vec<StepFailures> = self
+            .failed_at
+            .iter()
+            .zip(&self.methods)
+            .enumerate()
+            .map(|(index, (count, method))| StepFailures {
+                step: index + 1,
+                method: method.clone(),
+                count: count.load(Ordering::Relaxed),
+            })
+            .filter(|failures| failures.count > 0)
+            .collect();
+
+        let mut durations = Vec::new();
+        while let Some(duration) = self.durations.pop() {
+            durations.push(duration);
+        }
+        durations.sort_unstable();
+        let percentile = |p: f64| {
+            let index = ((durations.len() as f64 * p).ceil() as usize).saturating_sub(1);
+            durations[index.min(durations.len() - 1)] as f64 / 1000.0
+        };
+        let latency = (!durations.is_empty()).then(|| IterationLatency {
+            average_ms: durations.iter().sum::<u64>() as f64 / durations.len() as f64 / 1000.0,
+            p50_ms: percentile(0.50),
+            p95_ms: percentile(0.95),
+            p99_ms: percentile(0.99),
+            max_ms: percentile(1.0),
+        });
+
+        ScenarioSummary {
+            name: name.to_string(),
+            started: self.started.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            failed: failed_at.iter().map(|failures| failures.count).sum(),
+            failed_at,
+            latency,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ScenarioSummary {
+    pub name: String,
+    pub started: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub failed_at: Vec<StepFailures>,
+    pub latency: Option<IterationLatency>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StepFailures {
+    pub step: usize,
+    pub method: String,
+    pub count: u64,
+}
+
+// Длительность итерации целиком: все шаги, повторы и think time
+#[derive(Serialize, Debug)]
+pub struct IterationLatency {
+    pub average_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(i64), // отрицательный - с конца массива
+}
+
+// Разбирает путь вида $.a.b[0].c (корень - весь JSON-RPC ответ)
+fn parse_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| format!("path '{}' must start with '$'", path))?;
+    let mut segments = Vec::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                while let Some(&next) = chars.peek() {
+                    if next == '.' || next == '[' {
+                        break;
+                    }
+                    key.push(next);
+                    chars.next();
+                }
+                if key.is_empty() {
+                    return Err(format!("empty key in path '{}'", path));
+                }
+                segments.push(PathSegment::Key(key));
+            }
+            '[' => {
+                let mut index = String::new();
+                for next in chars.by_ref() {
+                    if next == ']' {
+                        break;
+                    }
+                    index.push(next);
+                }
+                let index = index
+                    .parse()
+                    .map_err(|_| format!("invalid index '[{}]' in path '{}'", index, path))?;
+                segments.push(PathSegment::Index(index));
+            }
+            _ => return Err(format!("unexpected '{}' in path '{}'", c, path)),
+        }
+    }
+    Ok(segments)
+}
+
+fn extract(value: &Value, path: &[PathSegment]) -> Option<Value> {
+    let mut current = value;
+    for segment in path {
+        current = match segment {
+            PathSegment::Key(key) => current.get(key)?,
+            PathSegment::Index(index) => {
+                let items = current.as_array()?;
+                let index = if *index < 0 { items.len() as i64 + index } else { *index };
+                items.get(usize::try_from(index).ok()?)?
+            }
+        };
+    }
+    (!current.is_null()).then(|| current.clone())
+}
+
+#[derive(Debug)]
+enum Operand {
+    Path(Vec<PathSegment>), // путь в последнем ответе сценария
+    Var(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Exists,
+    Missing,
+    Empty,
+    NotEmpty,
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+// Условие вида "<$.path | ${var}> <op> [значение]", например "$.result not_empty" или "${slot} > 100"
+#[derive(Debug)]
+struct Condition {
+    operand: Operand,
+    op: Op,
+    value: Value,
+}
+
+impl Condition {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut parts = text.trim().splitn(3, char::is_whitespace);
+        let operand = parts.next().unwrap_or_default();
+        let op = parts.next().ok_or_else(|| format!("condition '{}' has no operator", text))?;
+        let operand = match operand.strip_prefix("${").and_then(|rest| rest.strip_suffix('}')) {
+            Some(name) => Operand::Var(name.to_string()),
+            None => Operand::Path(parse_path(operand)?),
+        };
+        let op = match op {
+            "exists" => Op::Exists,
+            "missing" => Op::Missing,
+            "empty" => Op::Empty,
+            "not_empty" => Op::NotEmpty,
+            "==" => Op::Eq,
+            "!=" => Op::Ne,
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            other => return Err(format!("unknown operator '{}' in condition '{}'", other, text)),
+        };
+        let value = match parts.next().map(str::trim) {
+            // Значение - JSON литерал, иначе строка как есть
+            Some(raw) => serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string())),
+            None if matches!(op, Op::Exists | Op::Missing | Op::Empty | Op::NotEmpty) => Value::Null,
+            None => return Err(format!("condition '{}' needs a value to compare with", text)),
+        };
+        Ok(Self { operand, op, value })
+    }
+
+    fn holds(&self, vars: &BTreeMap<String, Value>, last_response: &Value) -> bool {
+        let actual = match &self.operand {
+            Operand::Path(path) => extract(last_response, path),
+            Operand::Var(name) => vars.get(name).cloned(),
+        };
+        let is_empty = |value: &Value| match value {
+            Value::Null => true,
+            Value::String(s) => s.is_empty(),
+            Value::Array(items) => items.is_empty(),
+            Value::Object(map) => map.is_empty(),
+            _ => false,
+        };
+        let compare = |actual: &Value| match (actual.as_f64(), self.value.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => None,
+        };
+        match (self.op, &actual) {
+            (Op::Exists, actual) => actual.is_some(),
+            (Op::Missing, actual) => actual.is_none(),
+            (Op::Empty, actual) => actual.as_ref().is_none_or(is_empty),
+            (Op::NotEmpty, actual) => actual.as_ref().is_some_and(|v| !is_empty(v)),
+            (Op::Eq, actual) => actual.as_ref() == Some(&self.value),
+            (Op::Ne, actual) => actual.as_ref() != Some(&self.value),
+            (_, None) => false,
+            (Op::Gt, Some(actual)) => compare(actual).is_some_and(|o| o.is_gt()),
+            (Op::Ge, Some(actual)) => compare(actual).is_some_and(|o| o.is_ge()),
+            (Op::Lt, Some(actual)) => compare(actual).is_some_and(|o| o.is_lt()),
+            (Op::Le, Some(actual)) => compare(actual).is_some_and(|o| o.is_le()),
+        }
+    }
+
+    fn var(&self) -> Option<&str> {
+        match &self.operand {
+            Operand::Var(name) => Some(name),
+            Operand::Path(_) => None,
+        }
+    }
+}
+
+// Имена переменных ${name}, упомянутых в значении
+fn referenced_vars(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find("${") {
+                match rest[start + 2..].find('}') {
+                    Some(end) => {
+                        out.push(rest[start + 2..start + 2 + end].to_string());
+                        rest = &rest[start + 3 + end..];
+                    }
+                    None => break,
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| referenced_vars(item, out)),
+        Value::Object(map) => map.values().for_each(|item| referenced_vars(item, out)),
+        _ => {}
+    }
+}
+
+// Подставляет переменные: "${name}" целиком заменяется значением любого типа,
+// вхождение внутри строки - строковым представлением
+fn substitute(value: &Value, vars: &BTreeMap<String, Value>) -> Value {
+    match value {
+        Value::String(s) => {
+            if let Some(name) = s.strip_prefix("${").and_then(|rest| rest.strip_suffix('}')) {
+                if let Some(var) = vars.get(name) {
+                    return var.clone();
+                }
+            }
+            let mut result = s.clone();
+            for (name, var) in vars {
+                let text = match var {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                result = result.replace(&format!("${{{}}}", name), &text);
+            }
+            Value::String(result)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| substitute(item, vars)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), substitute(item, vars)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+// Проверяет пути извлечения и то, что каждая переменная извлекается раньше, чем используется
+// Загружает CSV фидеры сценариев (по одному на сценарий, если задан)
+pub fn load_feeders(scenarios: &[ScenarioConfig], base_dir: &Path) -> Result<Vec<Option<Arc<Feeder>>>, String> {
+    scenarios
+        .iter()
+        .map(|scenario| match &scenario.feeder {
+            Some(config) => Feeder::load(config, base_dir)
+                .map(|feeder| Some(Arc::new(feeder)))
+                .map_err(|e| format!("scenario '{}': {}", scenario.name, e)),
+            None => Ok(None),
+        })
+        .collect()
+}
+
+pub fn validate(scenarios: &[ScenarioConfig], feeders: &[Option<Arc<Feeder>>]) -> Result<(), String> {
+    for (scenario, feeder) in scenarios.iter().zip(feeders) {
+        if scenario.steps.is_empty() {
+            return Err(format!("scenario '{}' has no steps", scenario.name));
+        }
+        // Столбцы фидера доступны с первого шага
+        let mut known: Vec<&str> = feeder
+            .iter()
+            .flat_map(|feeder| feeder.columns.iter().map(String::as_str))
+            .collect();
+        for (index, step) in scenario.steps.iter().enumerate() {
+            let mut used = Vec::new();
+            step.params.iter().for_each(|param| referenced_vars(param, &mut used));
+            if let Some(name) = used.iter().find(|name| !known.contains(&name.as_str())) {
+                return Err(format!(
+                    "scenario '{}' step {} ({}): variable '{}' is not extracted by an earlier step",
+                    scenario.name,
+                    index + 1,
+                    step.method,
+                    name
+                ));
+            }
+            let fail = |e: String| format!("scenario '{}' step {} ({}): {}", scenario.name, index + 1, step.method, e);
+            if step.repeat.is_some() && step.while_condition.is_some() {
+                return Err(fail("use either repeat or while, not both".to_string()));
+            }
+            // skip_if проверяется до шага, поэтому видит только переменные предыдущих шагов
+            if let Some(text) = &step.skip_if {
+                let condition = Condition::parse(text).map_err(fail)?;
+                if let Some(name) = condition.var().filter(|name| !known.contains(name)) {
+                    return Err(fail(format!("variable '{}' is not extracted by an earlier step", name)));
+                }
+            }
+            for (name, path) in &step.extract {
+                parse_path(path).map_err(fail)?;
+                known.push(name);
+            }
+            if let Some(text) = &step.while_condition {
+                let condition = Condition::parse(text).map_err(fail)?;
+                if let Some(name) = condition.var().filter(|name| !known.contains(name)) {
+                    return Err(fail(format!("variable '{}' is not extracted by this or an earlier step", name)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Шаг с заранее разобранными путями и условиями (validate уже проверил их при старте)
+struct PreparedStep {
+    step: StepConfig,
+    extract: Vec<(String, Vec<PathSegment>)>,
+    while_condition: Option<Condition>,
+    skip_if: Option<Condition>,
+}
+
+impl PreparedStep {
+    fn new(step: StepConfig) -> Self {
+        let extract = step
+            .extract
+            .iter()
+            .filter_map(|(name, path)| parse_path(path).ok().map(|segments| (name.clone(), segments)))
+            .collect();
+        let while_condition = step.while_condition.as_deref().and_then(|text| Condition::parse(text).ok());
+        let skip_if = step.skip_if.as_deref().and_then(|text| Condition::parse(text).ok());
+        Self {
+            step,
+            extract,
+            while_condition,
+            skip_if,
+        }
+    }
+}
+
+// Запрос итерации для --dry-run: переменные фидера подставлены, переменные из ответов остаются ${name}
+pub struct PlannedStep {
+    pub method: String,
+    pub params: Vec<Value>,
+    pub think_time: Duration,
+}
+
+// Запросы одной итерации в порядке отправки, с тем же расходом rng, что у воркера (keys - число ключей воркера).
+// Ответов нет, поэтому skip_if не проверяется, а шаг с while показан одним запросом.
+// None - строки фидера закончились
+pub fn plan_iteration(scenario: &ScenarioConfig, feeder: Option<&Feeder>, keys: usize, rng: &mut Rng) -> Option<Vec<PlannedStep>> {
+    rng.up_to(keys.max(1) as u64 - 1);
+    let vars = match feeder {
+        Some(feeder) => feeder.next(rng)?,
+        None => BTreeMap::new(),
+    };
+    let mut planned = Vec::new();
+    for step in &scenario.steps {
+        let requests = match &step.while_condition {
+            Some(_) => 1,
+            None => step.repeat.unwrap_or(1),
+        };
+        for _ in 0..requests {
+            planned.push(PlannedStep {
+                method: step.method.clone(),
+                params: step.params.iter().map(|param| substitute(param, &vars)).collect(),
+                think_time: step.think_time_ms.map_or(Duration::ZERO, |think_time| think_time.pick(rng)),
+            });
+        }
+    }
+    Some(planned)
+}
+
+// Выполняет запросы шагов сценария и учитывает их в статистике
+struct StepRunner<'a> {
+    ctx: RequestContext<'a>,
+    settings: &'a WorkerSettings,
+    scenario_name: &'a str,
+    capture: HeaderCapture,
+    apdex: Vec<Option<(ApdexThresholds, WorkerCounters<MethodApdex>)>>,
+    debug_rng: Rng, // выборка --debug-sample, отдельно от потока --seed
+}
+
+impl StepRunner<'_> {
+    // Один запрос шага. Возвращает ответ и признак того, что все переменные извлечены;
+    // None - запрос завершился ошибкой
+    async fn call(
+        &mut self,
+        index: usize,
+        prepared: &PreparedStep,
+        request_id: u64,
+        vars: &mut BTreeMap<String, Value>,
+    ) -> Option<(Value, bool)> {
+        let step = &prepared.step;
+        let settings = self.settings;
+        // Итерация доходит до конца и после окончания теста, поэтому без ограничения ожидания
+        self.ctx.stats.rate_limit_wait(1, None).await;
+        if !charge(settings.credits.as_deref(), settings.budget.as_ref(), self.ctx.stats, &step.method, 1) {
+            return None;
+        }
+        self.ctx.node.record_attempts(1);
+        if let Some(key) = self.ctx.key {
+            key.record_attempts(1);
+        }
+        self.capture.record_group_attempts(1);
+        if let Some((_, counters)) = &self.apdex[index] {
+            counters.record_attempts(1);
+        }
+        self.ctx.debug = settings.debug.as_ref().is_some_and(|filter| filter.admits(&step.method, &mut self.debug_rng));
+
+        let params = step.params.iter().map(|param| substitute(param, vars)).collect();
+        let request_start = Instant::now();
+        let (response, response_time_micros) =
+            send_and_record(&self.ctx, &step.method, params, request_id, request_start, &mut self.capture).await?;
+        if let Some((thresholds, counters)) = &self.apdex[index] {
+            counters.record_success(thresholds, response_time_micros, 1);
+        }
+
+        let response = serde_json::to_value(&response).unwrap_or_default();
+        let mut extracted = true;
+        for (name, path) in &prepared.extract {
+            match extract(&response, path) {
+                Some(value) => {
+                    vars.insert(name.clone(), value);
+                }
+                None => {
+                    if self.ctx.debug {
+                        println!(
+                            "[Worker {}] Scenario '{}' step {}: nothing to extract for '{}'",
+                            self.ctx.worker_id,
+                            self.scenario_name,
+                            index + 1,
+                            name
+                        );
+                    }
+                    extracted = false;
+                }
+            }
+        }
+        Some((response, extracted))
+    }
+}
+
+// Воркер сценария: шаги выполняются по порядку, с учетом skip_if, repeat и while;
+// с фидером каждая итерация начинается с переменных из очередной строки CSV.
+// При ошибке запроса или неудачном извлечении (вне while) оставшиеся шаги итерации пропускаются
+pub async fn scenario_worker(
+    worker_id: usize,
+    scenario: ScenarioConfig,
+    feeder: Option<Arc<Feeder>>,
+    settings: WorkerSettings,
+    stats: Stats,
+) {
+    let iterations = stats.scenario_stats(&scenario);
+    let steps: Vec<PreparedStep> = scenario.steps.into_iter().map(PreparedStep::new).collect();
+
+    let clients = keyed_clients(&settings, &stats, worker_id);
+    let node = stats.node_stats(&settings.url);
+    let mut runner = StepRunner {
+        ctx: RequestContext {
+            transport: &clients[0].transport,
+            stats: &stats,
+            node: &node,
+            key: clients[0].key.as_deref(),
+            worker_id,
+            debug: false, // решается для каждого шага
+            near_timeout_micros: settings.http_timeout.as_micros() as u64 * 9 / 10,
+            deadline_micros: deadline_micros(settings.deadline.as_ref()),
+            // Шагам нужен result для извлечения переменных
+            parser: ResponseParser::Serde,
+        },
+        settings: &settings,
+        scenario_name: &scenario.name,
+        capture: HeaderCapture::new(settings.capture_headers.clone(), settings.header_interval_secs, stats.clone())
+            .with_gzip_requests(settings.gzip_requests)
+            .with_correlation(settings.correlation.as_ref(), worker_id)
+            .with_affinity(settings.affinity.as_ref(), worker_id),
+        apdex: steps
+            .iter()
+            .map(|prepared| settings.apdex.map(|thresholds| (thresholds, stats.method_apdex(&prepared.step.method))))
+            .collect(),
+        debug_rng: Rng::new(random_seed().wrapping_add(worker_id as u64)),
+    };
+
+    let mut rng = Rng::new(settings.seed.wrapping_add(worker_id as u64));
+    let mut pacer = Pacer::new(settings.pace_factor);
+    while stats.running() {
+        // Бюджет исчерпан: новых итераций не начинаем
+        if stats.budget_exhausted() {
+            break;
+        }
+        if stats.breaker_wait().await {
+            continue;
+        }
+        // При ротации ключей на каждый запрос вся итерация идет с одним ключом, как у реального клиента
+        let keyed = &clients[rng.up_to(clients.len() as u64 - 1) as usize];
+        runner.ctx.transport = &keyed.transport;
+        runner.ctx.key = keyed.key.as_deref();
+        let mut vars: BTreeMap<String, Value> = match &feeder {
+            Some(feeder) => match feeder.next(&mut rng) {
+                Some(row) => row,
+                None => break, // строки фидера (strategy = "once") закончились
+            },
+            None => BTreeMap::new(),
+        };
+        let iteration_start = Instant::now();
+        iterations.record_started();
+        let mut failed_at = None;
+        let mut last_response = Value::Null;
+        'steps: for (index, prepared) in steps.iter().enumerate() {
+            if prepared.skip_if.as_ref().is_some_and(|c| c.holds(&vars, &last_response)) {
+                continue;
+            }
+            let max_loops = match &prepared.while_condition {
+                Some(_) => prepared.step.max_loops.unwrap_or(DEFAULT_MAX_LOOPS),
+                None => prepared.step.repeat.unwrap_or(1),
+            };
+            for _ in 0..max_loops {
+                let request_id = settings.ids.next();
+                let Some((response, extracted)) = runner.call(index, prepared, request_id, &mut vars).await else {
+                    failed_at = Some(index);
+                    break 'steps;
+                };
+                last_response = response;
+                if let Some(think_time) = prepared.step.think_time_ms {
+                    sleep(think_time.pick(&mut rng)).await;
+                }
+                match &prepared.while_condition {
+                    // Конец пагинации: ответ без данных для извлечения или условие больше не выполняется
+                    Some(condition) if !extracted || !condition.holds(&vars, &last_response) => break,
+                    Some(_) => {}
+                    None if !extracted => {
+                        failed_at = Some(index);
+                        break 'steps;
+                    }
+                    None => {}
+                }
+            }
+        }
+        match failed_at {
+            // Итерация прервана исчерпанием бюджета, а не ошибкой шага
+            Some(_) if stats.budget_exhausted() => break,
+            Some(index) => iterations.record_failed(index),
+            None => iterations.record_completed(iteration_start.elapsed().as_micros() as u64),
+        }
+
+        // Адаптивная пауза считается по всей итерации
+        sleep(pacer.pause(iteration_start.elapsed(), settings.timeout_ms + rng.up_to(settings.jitter_ms))).await;
+    }
+}
@@ -0,0 +1,106 @@
+// Секреты в конфиге: ${env:NAME} и ${file:/path} в строковых значениях подставляются при загрузке,
+// чтобы API ключи не хранились в самих конфигах. Подставленные значения запоминаются
+// и заменяются на *** в выводе настроек и в сохраненном итоговом конфиге, а в плане прогона - обратно на ссылки
+
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+// Значение секрета и ссылка, из которой оно подставлено (None - ключ пула и прочие секреты без ссылки)
+static SECRETS: Mutex<Vec<(String, Option<String>)>> = Mutex::new(Vec::new());
+
+pub const MASK: &str = "***";
+
+// Подставляет секреты во все строки значения (рекурсивно по массивам и таблицам)
+pub fn resolve(value: &mut toml::Value) -> Result<(), String> {
+    match value {
+        toml::Value::String(text) => {
+            if let Some(resolved) = resolve_str(text)? {
+                *text = resolved;
+            }
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                resolve(item)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                resolve(item)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// None - в строке нет ссылок на секреты
+fn resolve_str(text: &str) -> Result<Option<String>, String> {
+    if !text.contains("${env:") && !text.contains("${file:") {
+        return Ok(None);
+    }
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let reference = &rest[start + 2..];
+        let Some(end) = reference.find('}') else {
+            break;
+        };
+        let secret = match reference[..end].split_once(':') {
+            Some(("env", name)) => {
+                std::env::var(name).map_err(|_| format!("Environment variable {} is not set (${{env:{}}})", name, name))?
+            }
+            Some(("file", path)) => std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read secret file {}: {}", path, e))?
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+            // Не секрет (например, переменная сценария ${sig}) - оставляем как есть
+            _ => {
+                out.push_str(&rest[..start + 2 + end + 1]);
+                rest = &reference[end + 1..];
+                continue;
+            }
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(&secret);
+        if !secret.is_empty() {
+            SECRETS.lock().unwrap().push((secret, Some(rest[start..start + 2 + end + 1].to_string())));
+        }
+        rest = &reference[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(Some(out))
+}
+
+// Значение, которое нельзя показывать в выводе (например, ключи из пула API ключей)
+pub fn remember(secret: String) {
+    if !secret.is_empty() {
+        SECRETS.lock().unwrap().push((secret, None));
+    }
+}
+
+// Заменяет значения подставленных секретов на ***
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let secrets = SECRETS.lock().unwrap();
+    if !secrets.iter().any(|(secret, _)| text.contains(secret.as_str())) {
+        return Cow::Borrowed(text);
+    }
+    let mut redacted = text.to_string();
+    for (secret, _) in secrets.iter() {
+        redacted = redacted.replace(secret.as_str(), MASK);
+    }
+    Cow::Owned(redacted)
+}
+
+// Возвращает вместо подставленных секретов их ссылки ${env:NAME} и ${file:/path}: файл с ними можно
+// запустить снова, не раскрывая ключей. Секреты без ссылки заменяются на ***
+pub fn restore_references(text: &str) -> Cow<'_, str> {
+    let secrets = SECRETS.lock().unwrap();
+    if !secrets.iter().any(|(secret, _)| text.contains(secret.as_str())) {
+        return Cow::Borrowed(text);
+    }
+    let mut restored = text.to_string();
+    for (secret, reference) in secrets.iter() {
+        restored = restored.replace(secret.as_str(), reference.as_deref().unwrap_or(MASK));
+    }
+    Cow::Owned(restored)
+}
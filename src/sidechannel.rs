@@ -0,0 +1,239 @@
+// Опрос служебных URL (--poll-url): все время теста раз в --poll-interval секунд запрашиваются обычные HTTP
+// адреса ноды рядом с RPC - /health, /metrics и т.п. Ответ сохраняется вместе с тем, что в этот момент видел
+// клиент (запросов в секунду и доля ошибок за скользящее окно), поэтому по отчету видно, совпадает ли
+// "behind" в /health или рост очереди в метриках с падением пропускной способности
+//
+// Текстовый ответ сохраняется как есть (обрезанным), ответ в формате Prometheus - числами метрик
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::aggregator::{BucketClock, StatEvent};
+use crate::{secrets, Stats};
+
+pub const DEFAULT_INTERVAL_SECS: u64 = 5;
+const TEXT_MAX_CHARS: usize = 120;
+// Без --poll-metric в отчет попадают только менявшиеся за тест метрики, не больше стольких
+const MAX_METRICS: usize = 12;
+
+#[derive(Clone, Debug)]
+pub struct PollSettings {
+    pub urls: Vec<String>,
+    pub interval: Duration,
+    pub metrics: Vec<String>, // префиксы метрик Prometheus; пусто - все
+}
+
+impl PollSettings {
+    pub fn new(urls: Vec<String>, interval_secs: u64, metrics: Vec<String>) -> Result<Option<Self>, String> {
+        if urls.is_empty() {
+            return Ok(None);
+        }
+        if interval_secs == 0 {
+            return Err("--poll-interval must be at least 1".to_string());
+        }
+        Ok(Some(Self {
+            urls,
+            interval: Duration::from_secs(interval_secs),
+            metrics,
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PollBody {
+    Text(String),
+    Metrics(BTreeMap<String, f64>), // метрика с метками -> значение
+}
+
+// Клиентская картина в момент опроса: закрытые секунды скользящего окна агрегатора
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct ClientWindow {
+    pub window_secs: u64,
+    pub rps: f64,
+    pub error_rate_percent: f64,
+}
+
+pub struct PollSample {
+    pub url: String,
+    pub at_secs: f64, // от начала теста
+    pub status: Option<u16>,
+    pub micros: u64,
+    pub error: Option<String>,
+    pub body: Option<PollBody>,
+    pub client: Option<ClientWindow>, // заполняет агрегатор; None - еще ни одной закрытой секунды
+}
+
+// Разбор текстового формата Prometheus: `имя{метки} значение [время]`. None - тело не в этом формате
+fn parse_metrics(body: &str, prefixes: &[String]) -> Option<BTreeMap<String, f64>> {
+    let mut metrics = BTreeMap::new();
+    for line in body.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let (name, rest) = match line.find('}') {
+            Some(end) => line.split_at(end + 1),
+            None => line.split_once(char::is_whitespace)?,
+        };
+        let value: f64 = rest.split_whitespace().next()?.parse().ok()?;
+        if prefixes.is_empty() || prefixes.iter().any(|prefix| name.starts_with(prefix.as_str())) {
+            metrics.insert(name.to_string(), value);
+        }
+    }
+    (!body.trim().is_empty()).then_some(metrics)
+}
+
+async fn poll(client: &reqwest::Client, url: &reqwest::Url, settings: &PollSettings, started_at: Instant) -> PollSample {
+    let start = Instant::now();
+    let mut sample = PollSample {
+        url: secrets::redact(url.as_str()).into_owned(),
+        at_secs: started_at.elapsed().as_secs_f64(),
+        status: None,
+        micros: 0,
+        error: None,
+        body: None,
+        client: None,
+    };
+    let body = match client.get(url.clone()).send().await {
+        Ok(response) => {
+            sample.status = Some(response.status().as_u16());
+            response.text().await
+        }
+        Err(e) => Err(e),
+    };
+    sample.micros = start.elapsed().as_micros() as u64;
+    match body {
+        Ok(body) => {
+            sample.body = Some(match parse_metrics(&body, &settings.metrics) {
+                Some(metrics) => PollBody::Metrics(metrics),
+                // Одной строкой: в отчете ответ стоит в строке опроса
+                None => PollBody::Text(body.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(TEXT_MAX_CHARS).collect()),
+            })
+        }
+        Err(e) if e.is_timeout() => sample.error = Some("timeout".to_string()),
+        Err(e) => sample.error = Some(e.to_string()),
+    }
+    sample
+}
+
+// Запускает опрос; путь с / в начале отсчитывается от хоста endpoint. Задача завершается с концом теста
+pub fn spawn(settings: PollSettings, base_url: &str, http_timeout: Duration, stats: Stats) -> Result<tokio::task::JoinHandle<()>, String> {
+    let urls = settings
+        .urls
+        .iter()
+        .map(|url| {
+            reqwest::Url::parse(base_url)
+                .and_then(|base| base.join(url))
+                .map_err(|e| format!("--poll-url {}: invalid URL: {}", url, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let client = reqwest::Client::builder()
+        .timeout(http_timeout.min(settings.interval))
+        .build()
+        .map_err(|e| format!("Failed to build poll HTTP client: {}", e))?;
+    Ok(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(settings.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // Первый опрос - через интервал: к нему в окне агрегатора уже есть закрытые секунды
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !stats.running() {
+                break;
+            }
+            for url in &urls {
+                let sample = poll(&client, url, &settings, stats.started_at).await;
+                stats.send(StatEvent::Poll(sample));
+            }
+        }
+    }))
+}
+
+#[derive(Serialize, Debug)]
+pub struct PollPoint {
+    pub at_secs: f64,
+    pub at_utc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientWindow>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PollSummary {
+    pub url: String,
+    pub polls: usize,
+    pub errors: usize, // без ответа или не 2xx
+    pub average_ms: f64,
+    pub points: Vec<PollPoint>,
+}
+
+// Метрики, которые стоит показать: все, если их немного, иначе менявшиеся за тест
+fn shown_metrics(samples: &[&PollSample]) -> Vec<String> {
+    let mut values: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for sample in samples {
+        if let Some(PollBody::Metrics(metrics)) = &sample.body {
+            for (name, value) in metrics {
+                values.entry(name).or_default().push(*value);
+            }
+        }
+    }
+    if values.len() <= MAX_METRICS {
+        return values.keys().map(|name| name.to_string()).collect();
+    }
+    values
+        .into_iter()
+        .filter(|(_, values)| values.iter().any(|value| *value != values[0]))
+        .map(|(name, _)| name.to_string())
+        .take(MAX_METRICS)
+        .collect()
+}
+
+pub fn summarize(samples: Vec<PollSample>, clock: &BucketClock) -> Vec<PollSummary> {
+    let mut by_url: BTreeMap<String, Vec<&PollSample>> = BTreeMap::new();
+    for sample in &samples {
+        by_url.entry(sample.url.clone()).or_default().push(sample);
+    }
+    by_url
+        .into_iter()
+        .map(|(url, samples)| {
+            let shown = shown_metrics(&samples);
+            let points: Vec<PollPoint> = samples
+                .iter()
+                .map(|sample| PollPoint {
+                    at_secs: sample.at_secs,
+                    at_utc: clock.utc(sample.at_secs),
+                    status: sample.status,
+                    ms: sample.micros as f64 / 1000.0,
+                    error: sample.error.clone(),
+                    text: match &sample.body {
+                        Some(PollBody::Text(text)) => Some(text.clone()),
+                        _ => None,
+                    },
+                    metrics: match &sample.body {
+                        Some(PollBody::Metrics(metrics)) => {
+                            shown.iter().filter_map(|name| Some((name.clone(), *metrics.get(name)?))).collect()
+                        }
+                        _ => BTreeMap::new(),
+                    },
+                    client: sample.client,
+                })
+                .collect();
+            let errors = samples
+                .iter()
+                .filter(|sample| sample.error.is_some() || !sample.status.is_some_and(|status| (200..300).contains(&status)))
+                .count();
+            PollSummary {
+                url,
+                polls: points.len(),
+                errors,
+                average_ms: points.iter().map(|point| point.ms).sum::<f64>() / points.len().max(1) as f64,
+                points,
+            }
+        })
+        .collect()
+}
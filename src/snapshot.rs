@@ -0,0 +1,278 @@
+// Загрузка снапшотов (--snapshot-downloads): параллельно с RPC нагрузкой воркеры скачивают снапшоты ноды
+// (/snapshot.tar.bz2, /incremental-snapshot.tar.bz2) кусками через Range запросы. Раздача снапшотов
+// конкурирует с RPC за диск и канал, поэтому проверять их нужно вместе: в отчете - пропускная способность
+// загрузки рядом с латентностью RPC
+//
+// Все воркеры качают общий цикл кусков: файлы по очереди, каждый от начала до конца, затем заново.
+// Если сервер не поддерживает Range, файл целиком - один кусок
+
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{secrets, Stats};
+
+pub const DEFAULT_PATHS: [&str; 2] = ["/snapshot.tar.bz2", "/incremental-snapshot.tar.bz2"];
+pub const DEFAULT_RANGE_MB: u64 = 16;
+
+#[derive(Clone, Debug)]
+pub struct SnapshotSettings {
+    pub workers: usize,
+    pub paths: Vec<String>,
+    pub range_mb: u64,
+}
+
+impl SnapshotSettings {
+    pub fn new(workers: Option<usize>, paths: Vec<String>, range_mb: u64) -> Result<Option<Self>, String> {
+        let Some(workers) = workers.filter(|&workers| workers > 0) else {
+            return Ok(None);
+        };
+        if range_mb == 0 {
+            return Err("--snapshot-range-mb must be at least 1".to_string());
+        }
+        let paths = if paths.is_empty() { DEFAULT_PATHS.iter().map(|path| path.to_string()).collect() } else { paths };
+        Ok(Some(Self { workers, paths, range_mb }))
+    }
+
+    fn range_bytes(&self) -> u64 {
+        self.range_mb * 1024 * 1024
+    }
+}
+
+// Файл снапшота после первого запроса: нода отвечает редиректом на файл с номером слота в имени
+struct SnapshotFile {
+    path: String,
+    url: Option<reqwest::Url>,   // None - файл недоступен
+    size: Option<u64>,           // None - сервер не поддерживает Range
+    error: Option<String>,
+    chunks: u64,
+}
+
+#[derive(Default)]
+pub struct SnapshotStats {
+    files: std::sync::Mutex<Vec<FileSummary>>,
+    requests: AtomicU64,
+    errors: AtomicU64,
+    bytes: AtomicU64,
+    completed: AtomicU64, // скачанных последних кусков файлов - полных проходов по файлу
+    first_byte_micros: AtomicU64,
+    first_bytes: AtomicU64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FileSummary {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    pub ranges: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SnapshotSummary {
+    pub workers: usize,
+    pub range_mb: u64,
+    pub files: Vec<FileSummary>,
+    pub requests: u64,
+    pub errors: u64,
+    pub bytes: u64,
+    pub mb_per_sec: f64,
+    pub completed_files: u64,
+    pub time_to_first_byte_ms: Option<f64>,
+}
+
+impl SnapshotStats {
+    // elapsed_secs - длительность прогона: загрузка идет все время теста
+    pub fn summarize(&self, settings: &SnapshotSettings, elapsed_secs: f64) -> SnapshotSummary {
+        let bytes = self.bytes.load(Ordering::Relaxed);
+        let first_bytes = self.first_bytes.load(Ordering::Relaxed);
+        SnapshotSummary {
+            workers: settings.workers,
+            range_mb: settings.range_mb,
+            files: self.files.lock().unwrap().clone(),
+            requests: self.requests.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            bytes,
+            mb_per_sec: if elapsed_secs > 0.0 { bytes as f64 / 1024.0 / 1024.0 / elapsed_secs } else { 0.0 },
+            completed_files: self.completed.load(Ordering::Relaxed),
+            time_to_first_byte_ms: (first_bytes > 0)
+                .then(|| self.first_byte_micros.load(Ordering::Relaxed) as f64 / first_bytes as f64 / 1000.0),
+        }
+    }
+}
+
+// Первый запрос - один байт: из Content-Range виден размер, из итогового URL - имя файла после редиректа
+async fn resolve(client: &reqwest::Client, base: &str, path: &str, timeout: Duration) -> SnapshotFile {
+    let unavailable = |error: String| SnapshotFile {
+        path: path.to_string(),
+        url: None,
+        size: None,
+        error: Some(error),
+        chunks: 0,
+    };
+    let url = match reqwest::Url::parse(base).and_then(|base| base.join(path)) {
+        Ok(url) => url,
+        Err(e) => return unavailable(format!("invalid URL: {}", e)),
+    };
+    let response = match tokio::time::timeout(timeout, client.get(url).header(RANGE, "bytes=0-0").send()).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => return unavailable(e.to_string()),
+        Err(_) => return unavailable("timeout".to_string()),
+    };
+    let url = response.url().clone();
+    let size = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit('/').next()?.parse::<u64>().ok());
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT if size.is_some() => SnapshotFile {
+            path: path.to_string(),
+            url: Some(url),
+            size,
+            error: None,
+            chunks: 0,
+        },
+        // Range не поддерживается: тело - весь файл, его не читаем
+        StatusCode::OK => SnapshotFile {
+            path: path.to_string(),
+            url: Some(url),
+            size: None,
+            error: None,
+            chunks: 1,
+        },
+        status => unavailable(format!("HTTP {}", status.as_u16())),
+    }
+}
+
+// Запускает загрузку: сначала находит файлы, затем запускает воркеры. Задача завершается с концом теста
+pub fn spawn(
+    settings: SnapshotSettings,
+    base_url: String,
+    http_timeout: Duration,
+    stats: Stats,
+) -> Result<(tokio::task::JoinHandle<()>, Arc<SnapshotStats>), String> {
+    // Без общего таймаута: ответ без Range - весь снапшот; таймаут - на каждую порцию тела
+    let client = reqwest::Client::builder()
+        .connect_timeout(http_timeout)
+        .build()
+        .map_err(|e| format!("Failed to build snapshot HTTP client: {}", e))?;
+    let counters = Arc::new(SnapshotStats::default());
+    let snapshot_stats = counters.clone();
+    let handle = tokio::spawn(async move {
+        let range = settings.range_bytes();
+        let mut files = Vec::new();
+        for path in &settings.paths {
+            let mut file = resolve(&client, &base_url, path, http_timeout).await;
+            if let Some(size) = file.size {
+                file.chunks = size.div_ceil(range).max(1);
+            }
+            files.push(file);
+        }
+        *counters.files.lock().unwrap() = files
+            .iter()
+            .map(|file| FileSummary {
+                path: file.path.clone(),
+                url: file.url.as_ref().map(|url| secrets::redact(url.as_str()).into_owned()),
+                size_bytes: file.size,
+                ranges: file.size.is_some(),
+                error: file.error.clone(),
+            })
+            .collect();
+        files.retain(|file| file.url.is_some());
+        if files.is_empty() {
+            return;
+        }
+
+        let files = Arc::new(files);
+        let cycle: u64 = files.iter().map(|file| file.chunks).sum();
+        let next_chunk = Arc::new(AtomicU64::new(0));
+        let workers: Vec<_> = (0..settings.workers)
+            .map(|_| {
+                let (client, files, next_chunk, counters, stats) =
+                    (client.clone(), files.clone(), next_chunk.clone(), counters.clone(), stats.clone());
+                tokio::spawn(async move {
+                    while stats.running() {
+                        let (file, chunk) = locate(&files, next_chunk.fetch_add(1, Ordering::Relaxed) % cycle);
+                        download(&client, file, chunk, range, http_timeout, &counters, &stats).await;
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            let _ = worker.await;
+        }
+    });
+    Ok((handle, snapshot_stats))
+}
+
+// Номер куска в цикле -> файл и кусок в нем
+fn locate(files: &[SnapshotFile], mut index: u64) -> (&SnapshotFile, u64) {
+    for file in files {
+        if index < file.chunks {
+            return (file, index);
+        }
+        index -= file.chunks;
+    }
+    (&files[0], 0)
+}
+
+async fn download(
+    client: &reqwest::Client,
+    file: &SnapshotFile,
+    chunk: u64,
+    range: u64,
+    timeout: Duration,
+    counters: &SnapshotStats,
+    stats: &Stats,
+) {
+    let Some(url) = file.url.clone() else {
+        return;
+    };
+    counters.requests.fetch_add(1, Ordering::Relaxed);
+    let mut request = client.get(url);
+    let expected = match file.size {
+        Some(size) => {
+            let start = chunk * range;
+            let end = (start + range).min(size) - 1;
+            request = request.header(RANGE, format!("bytes={}-{}", start, end));
+            StatusCode::PARTIAL_CONTENT
+        }
+        None => StatusCode::OK,
+    };
+    let request_start = Instant::now();
+    let mut response = match tokio::time::timeout(timeout, request.send()).await {
+        Ok(Ok(response)) if response.status() == expected => response,
+        _ => {
+            counters.errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    };
+    counters.first_byte_micros.fetch_add(request_start.elapsed().as_micros() as u64, Ordering::Relaxed);
+    counters.first_bytes.fetch_add(1, Ordering::Relaxed);
+    // Байты учитываются по мере прихода: к концу теста недокачанный кусок тоже входит в пропускную способность
+    loop {
+        match tokio::time::timeout(timeout, response.chunk()).await {
+            Ok(Ok(Some(bytes))) => {
+                counters.bytes.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+                if !stats.running() {
+                    return;
+                }
+            }
+            Ok(Ok(None)) => break,
+            _ => {
+                counters.errors.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+    if chunk + 1 == file.chunks {
+        counters.completed.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,102 @@
+// Лимиты сокетов до старта: каждый воркер держит свое соединение, и при тысячах воркеров упирается в
+// ulimit -n (открытые файлы) или в диапазон эфемерных портов раньше, чем в endpoint. Мягкий лимит
+// открытых файлов поднимается до нужного (не выше жесткого), об остальном - предупреждение. Ошибки
+// исчерпания сокетов под нагрузкой считаются отдельно от сетевых: это проблема клиента, а не endpoint
+
+use std::os::raw::c_int;
+
+// Сверх соединений воркеров: служебные запросы, DNS, файлы отчетов и выбросов, стандартные потоки
+const RESERVE_FDS: u64 = 64;
+const PORT_RANGE_FILE: &str = "/proc/sys/net/ipv4/ip_local_port_range";
+
+#[cfg(target_os = "linux")]
+const RLIMIT_NOFILE: c_int = 7;
+#[cfg(not(target_os = "linux"))]
+const RLIMIT_NOFILE: c_int = 8; // macOS и BSD
+
+// rlim_t - 64 бита на Linux x86_64/aarch64 и macOS
+#[repr(C)]
+struct RLimit {
+    current: u64,
+    max: u64,
+}
+
+extern "C" {
+    fn getrlimit(resource: c_int, limit: *mut RLimit) -> c_int;
+    fn setrlimit(resource: c_int, limit: *const RLimit) -> c_int;
+}
+
+fn open_files_limit() -> Option<RLimit> {
+    let mut limit = RLimit { current: 0, max: 0 };
+    // SAFETY: getrlimit только заполняет переданную структуру
+    (unsafe { getrlimit(RLIMIT_NOFILE, &mut limit) } == 0).then_some(limit)
+}
+
+// Число эфемерных портов из /proc (None - не Linux или файл недоступен)
+fn ephemeral_ports() -> Option<u64> {
+    let range = std::fs::read_to_string(PORT_RANGE_FILE).ok()?;
+    let mut bounds = range.split_whitespace().map(|bound| bound.parse::<u64>());
+    let (low, high) = (bounds.next()?.ok()?, bounds.next()?.ok()?);
+    Some(high.saturating_sub(low) + 1)
+}
+
+pub struct Preflight {
+    pub notes: Vec<String>, // что изменено (поднятый лимит)
+    pub warnings: Vec<String>,
+}
+
+// Проверка перед стартом для наибольшего числа одновременных воркеров
+pub fn preflight(workers: usize) -> Preflight {
+    let needed = workers as u64 + RESERVE_FDS;
+    let mut preflight = Preflight {
+        notes: Vec::new(),
+        warnings: Vec::new(),
+    };
+    if let Some(limit) = open_files_limit() {
+        let mut current = limit.current;
+        if current < needed && limit.max > current {
+            let raised = RLimit {
+                current: needed.min(limit.max),
+                max: limit.max,
+            };
+            // SAFETY: структура живет дольше вызова; жесткий лимит не меняется
+            if unsafe { setrlimit(RLIMIT_NOFILE, &raised) } == 0 {
+                preflight.notes.push(format!("Raised the open files limit (ulimit -n) from {} to {}", current, raised.current));
+                current = raised.current;
+            }
+        }
+        if current < needed {
+            preflight.warnings.push(format!(
+                "{} workers need about {} open files, but the limit (ulimit -n) is {} (hard limit {}); \
+                 expect \"too many open files\" errors, raise the limit or lower the worker count",
+                workers, needed, current, limit.max
+            ));
+        }
+    }
+    if let Some(ports) = ephemeral_ports().filter(|&ports| ports < needed) {
+        preflight.warnings.push(format!(
+            "{} workers need about {} local ports, but only {} ephemeral ports are available ({}); \
+             expect \"address not available\" errors",
+            workers, needed, ports, PORT_RANGE_FILE
+        ));
+    }
+    preflight
+}
+
+// Исчерпание сокетов клиента в цепочке ошибки запроса: описание для последних ошибок или None
+pub fn exhaustion(e: &reqwest::Error) -> Option<&'static str> {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(e);
+    while let Some(error) = source {
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            // EMFILE и ENFILE одинаковы на Linux и macOS
+            match io.raw_os_error() {
+                Some(24) => return Some("too many open files (ulimit -n)"),
+                Some(23) => return Some("system file table is full"),
+                _ if io.kind() == std::io::ErrorKind::AddrNotAvailable => return Some("no free ephemeral ports"),
+                _ => {}
+            }
+        }
+        source = error.source();
+    }
+    None
+}
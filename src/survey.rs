@@ -0,0 +1,133 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::{send_rpc_request, HeaderCapture, Stats};
+
+// Результат опроса одного endpoint
+#[derive(Debug)]
+pub struct EndpointProbe {
+    pub url: String,
+    pub version: Option<String>,
+    pub healthy: bool,
+    pub slot: Option<u64>,
+    pub average_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub errors: usize,
+}
+
+// Опрашивает endpoint: getVersion, getHealth и probes раз getSlot (по нему меряем латентность)
+async fn probe_endpoint(url: String, probes: usize, http_timeout: Duration) -> EndpointProbe {
+    let mut result = EndpointProbe {
+        url,
+        version: None,
+        healthy: false,
+        slot: None,
+        average_ms: None,
+        max_ms: None,
+        errors: 0,
+    };
+    let client = match reqwest::Client::builder().timeout(http_timeout).build() {
+        Ok(client) => client,
+        Err(_) => {
+            result.errors += 1;
+            return result;
+        }
+    };
+    let mut capture = HeaderCapture::new(Vec::new(), 0, Stats::new());
+
+    match send_rpc_request(&client, &result.url, "getVersion", Vec::new(), 1, &mut capture).await {
+        Ok(response) => {
+            result.version = response
+                .result
+                .as_ref()
+                .and_then(|r| r.get("solana-core"))
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+        }
+        Err(_) => result.errors += 1,
+    }
+
+    match send_rpc_request(&client, &result.url, "getHealth", Vec::new(), 2, &mut capture).await {
+        Ok(response) => result.healthy = response.error.is_none(),
+        Err(_) => result.errors += 1,
+    }
+
+    let mut latencies = Vec::new();
+    for i in 0..probes {
+        let started = Instant::now();
+        match send_rpc_request(&client, &result.url, "getSlot", Vec::new(), 3 + i as u64, &mut capture).await {
+            Ok(response) if response.error.is_none() => {
+                latencies.push(started.elapsed().as_secs_f64() * 1000.0);
+                if let Some(slot) = response.result.and_then(|r| r.as_u64()) {
+                    result.slot = Some(result.slot.map_or(slot, |s| s.max(slot)));
+                }
+            }
+            _ => result.errors += 1,
+        }
+    }
+    if !latencies.is_empty() {
+        result.average_ms = Some(latencies.iter().sum::<f64>() / latencies.len() as f64);
+        result.max_ms = latencies.iter().cloned().reduce(f64::max);
+    }
+    result
+}
+
+// Опрашивает все endpoint (не больше concurrency одновременно) и сортирует:
+// сначала здоровые, затем по отставанию от максимального слота и по средней латентности
+pub async fn run(urls: Vec<String>, probes: usize, concurrency: usize, http_timeout: Duration) -> Vec<EndpointProbe> {
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let handles: Vec<_> = urls
+        .into_iter()
+        .map(|url| {
+            let semaphore = semaphore.clone();
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                probe_endpoint(url, probes, http_timeout).await
+            })
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for handle in handles {
+        if let Ok(probe) = handle.await {
+            results.push(probe);
+        }
+    }
+
+    let max_slot = results.iter().filter_map(|p| p.slot).max().unwrap_or(0);
+    results.sort_by(|a, b| {
+        let lag = |p: &EndpointProbe| p.slot.map_or(u64::MAX, |s| max_slot - s);
+        let latency = |p: &EndpointProbe| p.average_ms.unwrap_or(f64::MAX);
+        b.healthy
+            .cmp(&a.healthy)
+            .then(lag(a).cmp(&lag(b)))
+            .then(latency(a).total_cmp(&latency(b)))
+    });
+    results
+}
+
+pub fn print_table(results: &[EndpointProbe]) {
+    let max_slot = results.iter().filter_map(|p| p.slot).max().unwrap_or(0);
+    let url_width = results.iter().map(|p| p.url.len()).max().unwrap_or(0).max(3);
+    println!("=== Endpoint Survey ===");
+    println!(
+        "{:>3}  {:<url_width$}  {:<7}  {:<10}  {:>12}  {:>6}  {:>9}  {:>9}  {:>6}",
+        "#", "URL", "Health", "Version", "Slot", "Lag", "Avg ms", "Max ms", "Errors"
+    );
+    for (rank, probe) in results.iter().enumerate() {
+        let dash = || "-".to_string();
+        println!(
+            "{:>3}  {:<url_width$}  {:<7}  {:<10}  {:>12}  {:>6}  {:>9}  {:>9}  {:>6}",
+            rank + 1,
+            probe.url,
+            if probe.healthy { "ok" } else { "FAIL" },
+            probe.version.as_deref().unwrap_or("-"),
+            probe.slot.map_or_else(dash, |s| s.to_string()),
+            probe.slot.map_or_else(dash, |s| (max_slot - s).to_string()),
+            probe.average_ms.map_or_else(dash, |ms| format!("{:.2}", ms)),
+            probe.max_ms.map_or_else(dash, |ms| format!("{:.2}", ms)),
+            probe.errors
+        );
+    }
+}
@@ -0,0 +1,160 @@
+// Кривая масштабируемости (подкоманда sweep): та же нагрузка, что задана флагами или конфигом, идет этапами
+// с разным числом воркеров (1, 2, 4, ... 256 по умолчанию), каждый этап - короткий отдельный прогон со своей
+// статистикой. По таблице видно, где пропускная способность перестает расти, а латентность начинает
+
+use std::time::Instant;
+
+use crate::{secrets, spawn_supervised, worker, MethodConfig, Stats, WorkerSettings};
+
+pub const DEFAULT_STEPS: [usize; 9] = [1, 2, 4, 8, 16, 32, 64, 128, 256];
+// Рост пропускной способности меньше этого (в процентах к предыдущему этапу) - насыщение
+const SATURATION_GAIN_PERCENT: f64 = 10.0;
+
+#[derive(Debug)]
+pub struct SweepStep {
+    pub workers: usize,
+    pub requests: u64,
+    pub rps: f64,
+    pub successful_rps: f64,
+    pub error_percent: f64,
+    pub average_ms: Option<f64>,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+}
+
+// Нагрузка для перебора: методы с постоянным числом воркеров на весь этап
+pub fn check_workload(methods: &[MethodConfig], has_scenarios: bool, auto_tune: bool) -> Result<(), String> {
+    if auto_tune || has_scenarios {
+        return Err("sweeps run methods with fixed worker counts: not compatible with --auto-tune and scenarios".to_string());
+    }
+    if methods.iter().any(|method| method.start_offset.is_some() || method.end_offset.is_some() || method.duration.is_some()) {
+        return Err("sweeps do not support method phases (start_offset, end_offset, duration)".to_string());
+    }
+    Ok(())
+}
+
+// Воркеры этапа делятся между методами пропорционально их workers (метод с малой долей может не получить
+// ни одного); остаток - методам с наибольшей дробной частью
+fn distribute(methods: &[MethodConfig], workers: usize) -> Vec<usize> {
+    let total: usize = methods.iter().map(|method| method.workers.max(1)).sum();
+    let exact: Vec<f64> = methods.iter().map(|method| (workers * method.workers.max(1)) as f64 / total as f64).collect();
+    let mut counts: Vec<usize> = exact.iter().map(|share| share.floor() as usize).collect();
+    let mut order: Vec<usize> = (0..methods.len()).collect();
+    order.sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    for &i in order.iter().take(workers - counts.iter().sum::<usize>()) {
+        counts[i] += 1;
+    }
+    counts
+}
+
+fn percentile_ms(sorted: &[u64], p: f64) -> Option<f64> {
+    let index = ((sorted.len() as f64 * p).ceil() as usize).saturating_sub(1);
+    (!sorted.is_empty()).then(|| sorted[index.min(sorted.len() - 1)] as f64 / 1000.0)
+}
+
+pub async fn run_step(methods: &[MethodConfig], settings: &WorkerSettings, workers: usize) -> SweepStep {
+    let stats = Stats::with_breaker(None, None, false).with_duration(settings.duration);
+    let started = Instant::now();
+    let mut handles = Vec::new();
+    let mut worker_id = 0;
+    for (method_config, count) in methods.iter().zip(distribute(methods, workers)) {
+        for _ in 0..count {
+            let id = worker_id;
+            let method_config = method_config.clone();
+            let worker_stats = stats.clone();
+            handles.push(spawn_supervised(id, settings.clone(), stats.clone(), move |settings| {
+                worker(id, method_config.clone(), settings, worker_stats.clone(), None)
+            }));
+            worker_id += 1;
+        }
+    }
+    for handle in handles {
+        let _ = handle.await;
+    }
+    let state = stats.collect().await;
+    let elapsed = started.elapsed().as_secs_f64().max(0.001);
+    let mut latencies = state.response_times;
+    latencies.sort_unstable();
+    SweepStep {
+        workers,
+        requests: state.total_requests,
+        rps: state.total_requests as f64 / elapsed,
+        successful_rps: state.successful_requests as f64 / elapsed,
+        error_percent: if state.total_requests > 0 {
+            (state.total_requests - state.successful_requests) as f64 / state.total_requests as f64 * 100.0
+        } else {
+            0.0
+        },
+        average_ms: (!latencies.is_empty()).then(|| latencies.iter().sum::<u64>() as f64 / latencies.len() as f64 / 1000.0),
+        p50_ms: percentile_ms(&latencies, 0.50),
+        p95_ms: percentile_ms(&latencies, 0.95),
+        p99_ms: percentile_ms(&latencies, 0.99),
+    }
+}
+
+// Этапы идут по очереди; settings.duration - длительность одного этапа
+pub async fn run(methods: &[MethodConfig], settings: &WorkerSettings, steps: &[usize], quiet: bool) -> Vec<SweepStep> {
+    let mut results = Vec::new();
+    for &workers in steps {
+        if !quiet {
+            println!("Workers {}: {}s of load...", workers, settings.duration.as_secs());
+        }
+        results.push(run_step(methods, settings, workers).await);
+    }
+    results
+}
+
+pub fn print_result(url: &str, results: &[SweepStep]) {
+    let ms = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |ms| format!("{:.2}", ms));
+    println!("\n=== Worker Sweep: {} ===", secrets::redact(url));
+    println!(
+        "\n{:>7}  {:>8}  {:>9}  {:>9}  {:>7}  {:>9}  {:>9}  {:>9}  {:>9}  {:>8}",
+        "Workers", "Requests", "Req/s", "OK req/s", "Errors%", "Avg ms", "p50 ms", "p95 ms", "p99 ms", "Δ OK/s"
+    );
+    let mut previous: Option<&SweepStep> = None;
+    for step in results {
+        let gain = previous
+            .filter(|previous| previous.successful_rps > 0.0)
+            .map_or_else(|| "-".to_string(), |previous| format!("{:+.0}%", (step.successful_rps / previous.successful_rps - 1.0) * 100.0));
+        println!(
+            "{:>7}  {:>8}  {:>9.1}  {:>9.1}  {:>7.1}  {:>9}  {:>9}  {:>9}  {:>9}  {:>8}",
+            step.workers,
+            step.requests,
+            step.rps,
+            step.successful_rps,
+            step.error_percent,
+            ms(step.average_ms),
+            ms(step.p50_ms),
+            ms(step.p95_ms),
+            ms(step.p99_ms),
+            gain
+        );
+        previous = Some(step);
+    }
+
+    let Some(peak) = results.iter().max_by(|a, b| a.successful_rps.total_cmp(&b.successful_rps)) else {
+        return;
+    };
+    println!(
+        "\nPeak throughput: {:.1} successful req/s at {} workers (p95 {} ms)",
+        peak.successful_rps,
+        peak.workers,
+        ms(peak.p95_ms)
+    );
+    // Насыщение - первый этап, после которого добавление воркеров почти не прибавляет успешных ответов
+    let saturation = results.windows(2).find(|pair| {
+        pair[0].successful_rps > 0.0 && (pair[1].successful_rps / pair[0].successful_rps - 1.0) * 100.0 < SATURATION_GAIN_PERCENT
+    });
+    match saturation {
+        Some(pair) => println!(
+            "Saturation: going from {} to {} workers added less than {:.0}% throughput, p95 {} -> {} ms",
+            pair[0].workers,
+            pair[1].workers,
+            SATURATION_GAIN_PERCENT,
+            ms(pair[0].p95_ms),
+            ms(pair[1].p95_ms)
+        ),
+        None => println!("Saturation: not reached, throughput kept growing up to {} workers", results[results.len() - 1].workers),
+    }
+}
@@ -0,0 +1,233 @@
+// Тикет для поддержки провайдера (--output-ticket): один текстовый файл, который можно приложить к обращению
+// как есть - окружение клиента, настройки прогона, итоги, хронология ошибок с correlation ID и образцы
+// неудачных запросов со статусом и заголовками ответа. Все проходит через --redact и маскирование секретов
+
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::i18n::{texts, Lang};
+use crate::outliers::Outlier;
+use crate::redact::Redaction;
+use crate::report::Summary;
+use crate::secrets;
+
+// Образцы: первые неудачные запросы (начало проблемы) и последние (ее состояние к концу теста)
+const FIRST_SAMPLES: usize = 5;
+const LAST_SAMPLES: usize = 10;
+const PARAMS_MAX_CHARS: usize = 300;
+
+struct FailureSample {
+    at_utc: String, // конец запроса
+    worker: usize,
+    correlation_id: Option<String>,
+    api_key: Option<String>,
+    method: String,
+    params: String,
+    batch_size: usize,
+    request_id: u64,
+    latency_ms: f64,
+    outcome: String,
+    status: Option<u16>,
+    headers: Vec<(String, String)>,
+}
+
+pub struct FailureSamples {
+    redaction: Redaction,
+    first: Mutex<Vec<FailureSample>>,
+    last: Mutex<VecDeque<FailureSample>>,
+    total: AtomicU64,
+}
+
+impl FailureSamples {
+    pub fn new(redaction: Redaction) -> Self {
+        Self {
+            redaction,
+            first: Mutex::new(Vec::with_capacity(FIRST_SAMPLES)),
+            last: Mutex::new(VecDeque::with_capacity(LAST_SAMPLES)),
+            total: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, failure: Outlier) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        let params = Value::Array(self.redaction.params(failure.method, failure.params).into_owned()).to_string();
+        let sample = FailureSample {
+            at_utc: failure.ended_utc,
+            worker: failure.worker_id,
+            correlation_id: failure.correlation_id.map(str::to_string),
+            api_key: failure.api_key.map(str::to_string),
+            method: failure.method.to_string(),
+            params: truncate(&params, PARAMS_MAX_CHARS),
+            batch_size: failure.batch_size,
+            request_id: failure.request_id,
+            latency_ms: failure.latency_micros as f64 / 1000.0,
+            outcome: secrets::redact(&self.redaction.text(&failure.outcome)).into_owned(),
+            status: failure.response.map(|(status, _)| *status),
+            headers: failure
+                .response
+                .map(|(_, headers)| {
+                    headers
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            let value = self.redaction.header(name.as_str(), value.to_str().ok()?);
+                            Some((name.to_string(), secrets::redact(value).into_owned()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        let mut first = self.first.lock().unwrap();
+        if first.len() < FIRST_SAMPLES {
+            first.push(sample);
+            return;
+        }
+        drop(first);
+        let mut last = self.last.lock().unwrap();
+        if last.len() == LAST_SAMPLES {
+            last.pop_front();
+        }
+        last.push_back(sample);
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+// Имя хоста без внешних зависимостей: переменная окружения или /etc/hostname
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+pub struct Ticket<'a> {
+    pub url: &'a str,
+    pub user_agent: &'a str,
+    pub resolved_config: &'a str, // уже с замаскированными секретами
+    pub summary: &'a Summary,
+    pub verdict_line: &'a str,
+    pub samples: Option<&'a FailureSamples>,
+    pub redaction: Redaction,
+}
+
+// Разделы тикета на английском: его читает поддержка провайдера, независимо от --lang
+pub fn render(ticket: &Ticket) -> String {
+    let summary = ticket.summary;
+    let mut out = String::new();
+    let _ = writeln!(out, "=== RPC Endpoint Support Ticket ===\n");
+    let _ = writeln!(out, "Endpoint: {}", secrets::redact(&ticket.redaction.url(ticket.url)));
+    let _ = writeln!(out, "Test window (UTC): {} - {} ({:.1}s)", summary.started_utc, summary.finished_utc, summary.elapsed_secs);
+    let _ = writeln!(
+        out,
+        "Requests: {}, errors: {} ({:.2}% success)",
+        summary.total_requests,
+        summary.error_count(),
+        summary.success_rate
+    );
+    if let Some(correlation) = &summary.correlation {
+        let _ = writeln!(
+            out,
+            "Correlation: every request carries {} = {}-<worker>-<n>; search your logs for run ID {}",
+            correlation.header, correlation.run_id, correlation.run_id
+        );
+    }
+    if let Some(ids) = &summary.request_ids {
+        let _ = writeln!(out, "JSON-RPC request IDs: {}..{}", ids.first, ids.last);
+    }
+    let _ = writeln!(out, "Verdict: {}", ticket.verdict_line);
+
+    let _ = writeln!(out, "\n--- Client Environment ---");
+    if let Some(environment) = &summary.environment {
+        let _ = writeln!(out, "Client: {}", environment.describe(texts(Lang::En)));
+    }
+    let _ = writeln!(out, "Host: {}", hostname());
+    let _ = writeln!(out, "User-Agent: {}", ticket.user_agent);
+    let command: Vec<String> = std::env::args().collect();
+    let _ = writeln!(out, "Command: {}", secrets::redact(&ticket.redaction.text(&command.join(" "))));
+
+    let _ = writeln!(out, "\n--- Error Timeline ---");
+    if summary.incidents.is_empty() && summary.last_errors.is_none() {
+        let _ = writeln!(out, "No errors recorded.");
+    }
+    for incident in &summary.incidents {
+        let _ = writeln!(
+            out,
+            "{} - {}: error burst, {} of {} requests failed ({:.1}%, baseline {:.1}%), mostly {} ({})",
+            incident.start_utc,
+            incident.end_utc,
+            incident.errors,
+            incident.requests,
+            incident.error_rate_percent,
+            incident.baseline_error_rate_percent,
+            incident.dominant_error,
+            incident.dominant_errors
+        );
+    }
+    if let Some(log) = &summary.last_errors {
+        let _ = writeln!(out, "Last {} of {} errors:", log.entries.len(), log.total);
+        for entry in &log.entries {
+            let _ = writeln!(out, "  {} worker {} {}: {}", entry.at_utc, entry.worker, entry.category, entry.message);
+        }
+    }
+
+    if let Some(samples) = ticket.samples {
+        let first = samples.first.lock().unwrap();
+        let last = samples.last.lock().unwrap();
+        let total = samples.total.load(Ordering::Relaxed);
+        let _ = writeln!(out, "\n--- Sample Failing Requests ({} of {}) ---", first.len() + last.len(), total);
+        if total == 0 {
+            let _ = writeln!(out, "No failing requests.");
+        }
+        for (i, sample) in first.iter().chain(last.iter()).enumerate() {
+            if i == first.len() && total > (first.len() + last.len()) as u64 {
+                let _ = writeln!(out, "\n[... {} more ...]", total - (first.len() + last.len()) as u64);
+            }
+            render_sample(&mut out, sample);
+        }
+    }
+
+    let _ = writeln!(out, "\n--- Run Summary ---");
+    out.push_str(&summary.render(Lang::En));
+
+    let _ = writeln!(out, "\n--- Settings ---");
+    out.push_str(ticket.resolved_config);
+    out
+}
+
+fn render_sample(out: &mut String, sample: &FailureSample) {
+    let _ = write!(out, "\n{} worker {} {} id {}", sample.at_utc, sample.worker, sample.method, sample.request_id);
+    if sample.batch_size > 1 {
+        let _ = write!(out, " (batch of {})", sample.batch_size);
+    }
+    let _ = writeln!(out);
+    if let Some(id) = &sample.correlation_id {
+        let _ = writeln!(out, "  correlation ID: {}", id);
+    }
+    if let Some(key) = &sample.api_key {
+        let _ = writeln!(out, "  API key: {}", key);
+    }
+    let _ = writeln!(out, "  params: {}", sample.params);
+    let _ = writeln!(out, "  outcome: {} after {:.2} ms", sample.outcome, sample.latency_ms);
+    if let Some(status) = sample.status {
+        let _ = writeln!(out, "  HTTP status: {}", status);
+    }
+    for (name, value) in &sample.headers {
+        let _ = writeln!(out, "  {}: {}", name, value);
+    }
+}
+
+pub fn write(path: &Path, ticket: &Ticket) -> Result<(), String> {
+    fs::write(path, render(ticket)).map_err(|e| format!("Failed to write ticket {}: {}", path.display(), e))
+}
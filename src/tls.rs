@@ -0,0 +1,100 @@
+// Параметры TLS соединения с endpoint для предварительной диагностики (--ping).
+// Без feature tls-info (openssl) показывается только то, что endpoint использует TLS
+
+#[cfg(feature = "tls-info")]
+use openssl::asn1::Asn1Time;
+#[cfg(feature = "tls-info")]
+use openssl::nid::Nid;
+#[cfg(feature = "tls-info")]
+use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+#[cfg(feature = "tls-info")]
+use openssl::x509::X509NameRef;
+#[cfg(feature = "tls-info")]
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+pub struct TlsInfo {
+    pub version: String,
+    pub cipher: String,
+    pub alpn: Option<String>,
+    pub subject: String,
+    pub issuer: String,
+    pub not_after: String,
+    pub days_left: Option<i32>,
+    pub verified: bool,
+}
+
+#[cfg(feature = "tls-info")]
+fn name_to_string(name: &X509NameRef) -> String {
+    let preferred = [Nid::COMMONNAME, Nid::ORGANIZATIONNAME];
+    let parts: Vec<String> = name
+        .entries()
+        .filter(|entry| preferred.contains(&entry.object().nid()))
+        .filter_map(|entry| {
+            let key = entry.object().nid().short_name().ok()?;
+            let value = entry.data().to_string().ok()?;
+            Some(format!("{}={}", key, value))
+        })
+        .collect();
+    parts.join(", ")
+}
+
+// Открывает отдельное TLS соединение и возвращает согласованные параметры.
+// None - endpoint без TLS (http://)
+#[cfg(feature = "tls-info")]
+pub fn probe(url: &str, timeout: Duration) -> Result<Option<TlsInfo>, String> {
+    let url = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    if url.scheme() != "https" {
+        return Ok(None);
+    }
+    let host = url.host_str().ok_or("URL has no host")?;
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let address = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("No address for {}", host))?;
+    let stream = TcpStream::connect_timeout(&address, timeout).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+
+    // Проверку сертификата не прерываем: просроченный сертификат - как раз то, что надо показать
+    let mut builder = SslConnector::builder(SslMethod::tls()).map_err(|e| e.to_string())?;
+    builder.set_verify(SslVerifyMode::NONE);
+    builder
+        .set_alpn_protos(b"\x02h2\x08http/1.1")
+        .map_err(|e| e.to_string())?;
+    let connector = builder.build();
+    let tls = connector
+        .connect(host, stream)
+        .map_err(|e| format!("TLS handshake failed: {}", e))?;
+
+    let ssl = tls.ssl();
+    let certificate = ssl.peer_certificate();
+    let days_left = certificate.as_ref().and_then(|cert| {
+        let now = Asn1Time::days_from_now(0).ok()?;
+        now.diff(cert.not_after()).ok().map(|diff| diff.days)
+    });
+    Ok(Some(TlsInfo {
+        version: ssl.version_str().to_string(),
+        cipher: ssl.current_cipher().map(|c| c.name().to_string()).unwrap_or_default(),
+        alpn: ssl
+            .selected_alpn_protocol()
+            .map(|p| String::from_utf8_lossy(p).into_owned()),
+        subject: certificate.as_ref().map(|c| name_to_string(c.subject_name())).unwrap_or_default(),
+        issuer: certificate.as_ref().map(|c| name_to_string(c.issuer_name())).unwrap_or_default(),
+        not_after: certificate.as_ref().map(|c| c.not_after().to_string()).unwrap_or_default(),
+        days_left,
+        verified: ssl.verify_result() == openssl::x509::X509VerifyResult::OK,
+    }))
+}
+
+#[cfg(not(feature = "tls-info"))]
+pub fn probe(url: &str, _timeout: Duration) -> Result<Option<TlsInfo>, String> {
+    let url = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    if url.scheme() != "https" {
+        return Ok(None);
+    }
+    Err("TLS details are not available: rebuild with --features tls-info".to_string())
+}
@@ -0,0 +1,102 @@
+// Транспорт воркера: как JSON-RPC запросы доходят до endpoint и как возвращаются ответы. Цикл воркера,
+// сценарии и учет статистики (send_and_record, send_raw_and_record) работают через этот трейт, поэтому
+// новый протокол (WebSocket, gRPC) - это новая реализация Transport, а не еще одна копия воркера.
+// Пока есть только HTTP: POST тела JSON-RPC
+//
+// Заголовки ответа, размеры тел и соединения транспорт отдает в HeaderCapture воркера, как и раньше
+
+use serde::Serialize;
+
+use crate::dnsrefresh::ClientRefresh;
+use crate::{post_json, read_json, HeaderCapture, JsonRpcRequest, JsonRpcResponse, JsonRpcResponseHead, RequestError, ResponseParser};
+
+pub trait Transport {
+    // Адрес endpoint для отчета и файла выбросов
+    fn endpoint(&self) -> &str;
+
+    // Одиночный запрос; parser - как разбирать ответ (--parser)
+    async fn call(
+        &self,
+        request: &JsonRpcRequest,
+        capture: &mut HeaderCapture,
+        parser: ResponseParser,
+    ) -> Result<JsonRpcResponse, RequestError>;
+
+    // Батч: ответы в том порядке, в каком пришли
+    async fn call_batch(
+        &self,
+        requests: &[JsonRpcRequest],
+        capture: &mut HeaderCapture,
+        parser: ResponseParser,
+    ) -> Result<Vec<JsonRpcResponse>, RequestError>;
+
+    // --no-parse: ответ читается и отбрасывается, итог - только статус
+    async fn send_raw<T: Serialize + ?Sized + Sync>(
+        &self,
+        body: &T,
+        capture: &mut HeaderCapture,
+    ) -> Result<reqwest::StatusCode, RequestError>;
+}
+
+// JSON-RPC поверх HTTP POST: клиент воркера (с ключом из пула) и его URL
+pub struct HttpTransport {
+    pub client: reqwest::Client,
+    pub url: String,
+    pub refresh: Option<ClientRefresh>, // --dns-refresh: клиент пересоздается после смены ответа DNS
+}
+
+impl HttpTransport {
+    pub fn client(&self) -> reqwest::Client {
+        match &self.refresh {
+            Some(refresh) => refresh.client(),
+            None => self.client.clone(),
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn endpoint(&self) -> &str {
+        &self.url
+    }
+
+    async fn call(
+        &self,
+        request: &JsonRpcRequest,
+        capture: &mut HeaderCapture,
+        parser: ResponseParser,
+    ) -> Result<JsonRpcResponse, RequestError> {
+        let response = post_json(&self.client(), &self.url, request, capture).await?;
+        match parser {
+            ResponseParser::Serde => read_json(response, capture).await,
+            ResponseParser::Fast => read_json::<JsonRpcResponseHead>(response, capture).await.map(Into::into),
+        }
+    }
+
+    async fn call_batch(
+        &self,
+        requests: &[JsonRpcRequest],
+        capture: &mut HeaderCapture,
+        parser: ResponseParser,
+    ) -> Result<Vec<JsonRpcResponse>, RequestError> {
+        let response = post_json(&self.client(), &self.url, requests, capture).await?;
+        match parser {
+            ResponseParser::Serde => read_json(response, capture).await,
+            ResponseParser::Fast => {
+                let heads: Vec<JsonRpcResponseHead> = read_json(response, capture).await?;
+                Ok(heads.into_iter().map(Into::into).collect())
+            }
+        }
+    }
+
+    async fn send_raw<T: Serialize + ?Sized + Sync>(
+        &self,
+        body: &T,
+        capture: &mut HeaderCapture,
+    ) -> Result<reqwest::StatusCode, RequestError> {
+        let response = post_json(&self.client(), &self.url, body, capture).await?;
+        capture.observe(&response);
+        let status = response.status();
+        response.bytes().await?;
+        Ok(status)
+    }
+}
@@ -0,0 +1,271 @@
+// Тренд метрики по истории прогонов (подкоманда trend): временной ряд по прошлым прогонам одного endpoint
+// и регрессии двух видов - скачок (прогон сильно хуже нескольких предыдущих) и постепенная деградация
+// (значимый наклон линейного тренда по всему ряду)
+
+use crate::history::{self, RunRecord};
+use crate::report;
+
+// Прогон считается скачком, если он хуже среднего предыдущих на Z_THRESHOLD стандартных отклонений
+const Z_THRESHOLD: f64 = 3.0;
+// Нижняя граница отклонения в долях среднего: у очень стабильных прогонов шум меньше реального разброса endpoint
+const MIN_RELATIVE_DEVIATION: f64 = 0.02;
+// Меньше предыдущих прогонов - базы для сравнения нет
+const MIN_BASELINE_RUNS: usize = 5;
+// Меньше прогонов - наклон тренда не оценивается
+const MIN_TREND_RUNS: usize = 6;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    P50,
+    P95,
+    P99,
+    Avg,
+    Max,
+    Rps,
+    SuccessRate,
+}
+
+impl Metric {
+    fn label(self) -> &'static str {
+        match self {
+            Metric::P50 => "p50 ms",
+            Metric::P95 => "p95 ms",
+            Metric::P99 => "p99 ms",
+            Metric::Avg => "Avg ms",
+            Metric::Max => "Max ms",
+            Metric::Rps => "RPS",
+            Metric::SuccessRate => "Success %",
+        }
+    }
+
+    fn value(self, run: &RunRecord) -> Option<f64> {
+        match self {
+            Metric::P50 => run.p50_ms,
+            Metric::P95 => run.p95_ms,
+            Metric::P99 => run.p99_ms,
+            Metric::Avg => Some(run.average_ms),
+            Metric::Max => Some(run.max_ms),
+            Metric::Rps => Some(run.rps),
+            Metric::SuccessRate => Some(run.success_rate),
+        }
+    }
+
+    // 1 - рост метрики это ухудшение (латентность), -1 - падение (RPS, доля успешных)
+    fn worse_direction(self) -> f64 {
+        match self {
+            Metric::Rps | Metric::SuccessRate => -1.0,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TrendPoint {
+    pub run: RunRecord,
+    pub value: f64,
+    pub baseline: Option<(f64, f64)>, // среднее и отклонение предыдущих прогонов
+    pub z: Option<f64>,               // отклонение от базы в сторону ухудшения (положительное - хуже)
+    pub regression: bool,
+}
+
+#[derive(Debug)]
+pub struct TrendLine {
+    pub first: f64, // значение линии тренда на первом и последнем прогоне
+    pub last: f64,
+    pub t: f64, // t-статистика наклона
+    pub significant: bool,
+    pub degrading: bool, // значимый наклон в сторону ухудшения
+}
+
+#[derive(Debug)]
+pub struct TrendReport {
+    pub endpoint: String,
+    pub metric: Metric,
+    pub points: Vec<TrendPoint>,
+    pub line: Option<TrendLine>,
+}
+
+impl TrendReport {
+    // Регрессия в последнем прогоне или постепенная деградация по всему ряду
+    pub fn regressed(&self) -> bool {
+        self.points.last().is_some_and(|point| point.regression) || self.line.as_ref().is_some_and(|line| line.degrading)
+    }
+}
+
+fn mean_and_deviation(values: &[f64]) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1).max(1) as f64;
+    (mean, variance.sqrt())
+}
+
+// Критическое значение t для двустороннего уровня 5%
+fn t_critical(df: usize) -> f64 {
+    match df {
+        0 | 1 => 12.71,
+        2 => 4.30,
+        3 => 3.18,
+        4 => 2.78,
+        5 => 2.57,
+        6 => 2.45,
+        7 => 2.36,
+        8 => 2.31,
+        9 => 2.26,
+        10..=11 => 2.23,
+        12..=14 => 2.18,
+        15..=19 => 2.13,
+        20..=29 => 2.09,
+        30..=59 => 2.04,
+        _ => 1.98,
+    }
+}
+
+// Линейная регрессия метрики по номеру прогона в ряду
+fn fit_line(values: &[f64], direction: f64) -> Option<TrendLine> {
+    let n = values.len();
+    if n < MIN_TREND_RUNS {
+        return None;
+    }
+    let mean_x = (n - 1) as f64 / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n as f64;
+    let sxx: f64 = (0..n).map(|i| (i as f64 - mean_x).powi(2)).sum();
+    let sxy: f64 = values.iter().enumerate().map(|(i, y)| (i as f64 - mean_x) * (y - mean_y)).sum();
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let residuals: f64 = values
+        .iter()
+        .enumerate()
+        .map(|(i, y)| (y - (intercept + slope * i as f64)).powi(2))
+        .sum();
+    let standard_error = (residuals / (n - 2) as f64 / sxx).sqrt();
+    let t = if standard_error > 0.0 {
+        slope / standard_error
+    } else if slope == 0.0 {
+        0.0
+    } else {
+        f64::INFINITY.copysign(slope)
+    };
+    let significant = t.abs() > t_critical(n - 2);
+    Some(TrendLine {
+        first: intercept,
+        last: intercept + slope * (n - 1) as f64,
+        t,
+        significant,
+        degrading: significant && slope * direction > 0.0,
+    })
+}
+
+pub fn analyze(runs: Vec<RunRecord>, metric: Metric, baseline_runs: usize) -> Result<TrendReport, String> {
+    let endpoint = runs.last().map(|run| run.endpoint.clone()).unwrap_or_default();
+    let direction = metric.worse_direction();
+    let mut points: Vec<TrendPoint> = Vec::new();
+    for run in runs {
+        let Some(value) = metric.value(&run) else {
+            continue;
+        };
+        let previous: Vec<f64> = points.iter().rev().take(baseline_runs).map(|point| point.value).collect();
+        let baseline = (previous.len() >= MIN_BASELINE_RUNS).then(|| mean_and_deviation(&previous));
+        let z = baseline.map(|(mean, deviation)| {
+            let deviation = deviation.max(mean.abs() * MIN_RELATIVE_DEVIATION);
+            if deviation > 0.0 {
+                (value - mean) / deviation * direction
+            } else {
+                0.0
+            }
+        });
+        points.push(TrendPoint {
+            run,
+            value,
+            baseline,
+            z,
+            regression: z.is_some_and(|z| z > Z_THRESHOLD),
+        });
+    }
+    if points.is_empty() {
+        return Err(format!("No runs with {} in history", metric.label()));
+    }
+    let values: Vec<f64> = points.iter().map(|point| point.value).collect();
+    let line = fit_line(&values, direction);
+    Ok(TrendReport {
+        endpoint,
+        metric,
+        points,
+        line,
+    })
+}
+
+// Прогоны одного endpoint: точное совпадение URL или единственный endpoint, содержащий url
+pub fn load(db: &str, url: &str, limit: usize) -> Result<Vec<RunRecord>, String> {
+    let runs = history::list(db, Some(url), usize::MAX)?;
+    let mut endpoints: Vec<&str> = runs.iter().map(|run| run.endpoint.as_str()).collect();
+    endpoints.sort_unstable();
+    endpoints.dedup();
+    let endpoint = match endpoints.as_slice() {
+        [] => return Err(format!("No runs for {} in {}", url, db)),
+        [endpoint] => endpoint.to_string(),
+        several => match several.iter().find(|endpoint| **endpoint == url) {
+            Some(endpoint) => endpoint.to_string(),
+            None => return Err(format!("{} matches several endpoints: {}", url, several.join(", "))),
+        },
+    };
+    let mut runs: Vec<RunRecord> = runs.into_iter().filter(|run| run.endpoint == endpoint).collect();
+    let skip = runs.len().saturating_sub(limit);
+    runs.drain(..skip);
+    Ok(runs)
+}
+
+pub fn print_report(report: &TrendReport) {
+    let label = report.metric.label();
+    println!("=== Trend of {}: {} ({} runs) ===", label, report.endpoint, report.points.len());
+    println!(
+        "{:>5}  {:<16}  {:>10}  {:>18}  {:>7}  {:<10}  {:<20}  Workload / tags",
+        "#", "Started (UTC)", label, "Baseline", "z", "", ""
+    );
+    let max = report.points.iter().map(|point| point.value.abs()).fold(0.0, f64::max);
+    for point in &report.points {
+        let baseline = point
+            .baseline
+            .map_or_else(|| "-".to_string(), |(mean, deviation)| format!("{:.2} ± {:.2}", mean, deviation));
+        let tags = if point.run.tags.is_empty() { String::new() } else { format!(" [{}]", point.run.tags) };
+        // Полоса пропорциональна значению: форму ряда видно без графиков
+        let bar = if max > 0.0 { "#".repeat((point.value.abs() / max * 20.0).round() as usize) } else { String::new() };
+        println!(
+            "{:>5}  {:<16}  {:>10.2}  {:>18}  {:>7}  {:<10}  {:<20}  {}{}",
+            point.run.id,
+            report::format_utc(point.run.started_at),
+            point.value,
+            baseline,
+            point.z.map_or_else(|| "-".to_string(), |z| format!("{:+.1}", z)),
+            if point.regression { "REGRESSION" } else { "" },
+            bar,
+            point.run.workload,
+            tags
+        );
+    }
+    let jumps = report.points.iter().filter(|point| point.regression).count();
+    println!(
+        "\nJumps: {} run(s) worse than the average of the previous runs by more than {} standard deviations",
+        jumps, Z_THRESHOLD
+    );
+    match &report.line {
+        Some(line) => {
+            let change = if line.first != 0.0 { (line.last - line.first) / line.first.abs() * 100.0 } else { 0.0 };
+            let verdict = if line.degrading {
+                "significant degradation"
+            } else if line.significant {
+                "significant improvement"
+            } else {
+                "no significant trend"
+            };
+            println!(
+                "Trend: {:.2} -> {:.2} ({:+.1}%) over {} runs, t = {:.2}: {}",
+                line.first,
+                line.last,
+                change,
+                report.points.len(),
+                line.t,
+                verdict
+            );
+        }
+        None => println!("Trend: not estimated (fewer than {} runs)", MIN_TREND_RUNS),
+    }
+}